@@ -0,0 +1,166 @@
+//! Async Unzipper Module.
+//!
+//! This module provides an asynchronous front-end to the Unzipper, for use within a Tokio runtime.
+//! The zip file is read on the blocking thread pool of the runtime through `spawn_blocking`, so that opening
+//! the archive and decompressing entries never block the asynchronous tasks.
+//!
+//! This module is available with the `tokio` feature.
+
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::entry::EntryInfo;
+use crate::error::UnzipperError;
+use crate::options::UnzipperOptions;
+use crate::secret::Secret;
+use crate::unzipper::Unzipper;
+
+/// Struct that provides asynchronous access to a zip archive.
+///
+/// It can be cloned cheaply to be shared between tasks: the clones use the same Unzipper, their operations
+/// running concurrently on the blocking thread pool.
+#[derive(Clone)]
+pub struct AsyncUnzipper {
+    unzipper: Arc<Unzipper<File>>,
+    entries: Arc<Vec<EntryInfo>>,
+}
+
+impl AsyncUnzipper {
+    /// Opens the zip file at the specified path.
+    ///
+    /// # Arguments
+    /// * `filepath` - A reference to the path of the zip file to be opened.
+    ///
+    /// # Returns
+    /// A Result containing the AsyncUnzipper, or an error if the file could not be opened.
+    pub async fn open(filepath: &Path) -> Result<AsyncUnzipper, UnzipperError> {
+        AsyncUnzipper::open_with_options(filepath, UnzipperOptions::default()).await
+    }
+
+    /// Opens the zip file at the specified path, using the given options.
+    ///
+    /// # Arguments
+    /// * `filepath` - A reference to the path of the zip file to be opened.
+    /// * `options` - The options controlling how the zip file is read.
+    ///
+    /// # Returns
+    /// A Result containing the AsyncUnzipper, or an error if the file could not be opened.
+    pub async fn open_with_options(
+        filepath: &Path,
+        options: UnzipperOptions,
+    ) -> Result<AsyncUnzipper, UnzipperError> {
+        let filepath = filepath.to_path_buf();
+        let unzipper = spawn_blocking(move || Unzipper::with_options(&filepath, options)).await?;
+
+        // The listing is kept, so that it is available without waiting for other operations
+        let mut entries: Vec<EntryInfo> = unzipper.entries().collect();
+        entries.sort_by(|a, b| a.name().cmp(b.name()));
+
+        Ok(AsyncUnzipper {
+            unzipper: Arc::new(unzipper),
+            entries: Arc::new(entries),
+        })
+    }
+
+    /// Returns the file entries of the zip archive, sorted by name.
+    pub fn entries(&self) -> &[EntryInfo] {
+        &self.entries
+    }
+
+    /// Unzips a file from the archive into a bytes vector.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to unzip.
+    ///
+    /// # Returns
+    /// A Result containing the content of the file, or an error if the file is not found or decompression fails.
+    pub async fn get_file(&self, file_path: &str) -> Result<Vec<u8>, UnzipperError> {
+        let file_path = file_path.to_string();
+        self.run(move |unzipper| unzipper.get_file(&file_path))
+            .await
+    }
+
+    /// Unzips an encrypted file from the archive into a bytes vector.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to unzip.
+    /// * `password` - The password used to decrypt the entry. It is ignored if the entry is not encrypted.
+    ///
+    /// # Returns
+    /// A Result containing the content of the file, or an error if the file is not found, the password is wrong
+    /// or decompression fails.
+    pub async fn get_file_with_password(
+        &self,
+        file_path: &str,
+        password: &[u8],
+    ) -> Result<Vec<u8>, UnzipperError> {
+        let file_path = file_path.to_string();
+        let password = Secret::from(password); // wiped once the blocking operation is done
+        self.run(move |unzipper| unzipper.get_file_with_password(&file_path, password.as_bytes()))
+            .await
+    }
+
+    /// Extracts every entry of the zip archive into the destination directory.
+    ///
+    /// # Arguments
+    /// * `dest` - A reference to the path of the directory where the entries will be written.
+    ///
+    /// # Returns
+    /// A Result indicating success or an error if an entry could not be extracted. See `Unzipper::extract_all()`.
+    pub async fn extract_all(&self, dest: &Path) -> Result<(), UnzipperError> {
+        let dest: PathBuf = dest.to_path_buf();
+        self.run(move |unzipper| unzipper.extract_all(&dest)).await
+    }
+
+    /// Runs an operation on the Unzipper on the blocking thread pool.
+    async fn run<T: Send + 'static>(
+        &self,
+        operation: impl FnOnce(&Unzipper<File>) -> Result<T, UnzipperError> + Send + 'static,
+    ) -> Result<T, UnzipperError> {
+        let unzipper = self.unzipper.clone();
+        spawn_blocking(move || operation(&unzipper)).await
+    }
+}
+
+/// Runs a blocking function on the blocking thread pool of the Tokio runtime.
+async fn spawn_blocking<T: Send + 'static>(
+    function: impl FnOnce() -> Result<T, UnzipperError> + Send + 'static,
+) -> Result<T, UnzipperError> {
+    tokio::task::spawn_blocking(function)
+        .await
+        .map_err(|error| UnzipperError::Io(std::io::Error::other(error)))?
+}
+
+#[cfg(test)]
+mod async_unzipper_tests {
+    use super::*;
+
+    use test_support::unit_test::UnitTest;
+
+    #[tokio::test]
+    async fn test_async_unzipper_get_file() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+
+        let unzipper = AsyncUnzipper::open(&file).await.unwrap();
+        assert_eq!(unzipper.entries().len(), 86);
+        assert!(unzipper
+            .entries()
+            .windows(2)
+            .all(|pair| pair[0].name() < pair[1].name()));
+
+        // Clones share the same zip file
+        let clone = unzipper.clone();
+        let (mimetype, content) =
+            tokio::join!(unzipper.get_file("mimetype"), clone.get_file("content.opf"));
+        assert_eq!(mimetype.unwrap(), b"application/epub+zip");
+        assert_eq!(content.unwrap().len(), 12472);
+
+        assert!(matches!(
+            unzipper.get_file("missing.txt").await,
+            Err(UnzipperError::NotFound(_))
+        ));
+        assert!(AsyncUnzipper::open(Path::new("missing.zip")).await.is_err());
+    }
+}
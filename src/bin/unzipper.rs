@@ -0,0 +1,181 @@
+//! Unzipper Command Line Tool.
+//!
+//! This binary exposes the library to the command line, to list, extract and test zip archives without
+//! writing Rust, or to pipe the content of an entry to other tools. It is built with the `cli` feature.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use unzipper::{EntryKind, Unzipper};
+
+const USAGE: &str = "Usage: unzipper <command> <archive> [arguments]
+
+Commands:
+  list <archive>                              List the entries of the archive
+  extract <archive> [-d <dir>] [<pattern>]    Extract the entries, or the ones matching a glob pattern
+  test <archive>                              Verify the CRC-32 of every entry
+  cat <archive> <path>                        Write the content of an entry to the standard output";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let stdout = std::io::stdout();
+    match run(&args, &mut stdout.lock()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(message) => {
+            eprintln!("unzipper: {message}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Runs a command.
+///
+/// # Arguments
+/// * `args` - The command line arguments, without the program name.
+/// * `out` - The writer receiving the output of the command.
+///
+/// # Returns
+/// A Result containing true if the command succeeded, false if some entries failed the test,
+/// or the error message if the command could not be run.
+fn run(args: &[String], out: &mut impl Write) -> Result<bool, String> {
+    let (command, archive) = match args {
+        [command, archive, ..] => (command.as_str(), Path::new(archive)),
+        _ => return Err(USAGE.to_string()),
+    };
+    let rest = &args[2..];
+    let unzipper =
+        Unzipper::new(archive).map_err(|error| format!("{}: {error}", archive.display()))?;
+
+    match command {
+        "list" => {
+            write!(out, "{}", unzipper.listing()).map_err(|error| error.to_string())?;
+            Ok(true)
+        }
+        "extract" => extract(&unzipper, rest, out),
+        "test" => test(&unzipper, archive, out),
+        "cat" => match rest {
+            // The content is streamed, never being held fully in memory
+            [path] => unzipper
+                .get_file_to(path, out)
+                .map(|_| true)
+                .map_err(|error| error.to_string()),
+            _ => Err(format!("Expected one entry path\n\n{USAGE}")),
+        },
+        _ => Err(format!("Unknown command: {command}\n\n{USAGE}")),
+    }
+}
+
+/// Extracts the entries of the archive, or the ones matching a glob pattern.
+fn extract(unzipper: &Unzipper, args: &[String], out: &mut impl Write) -> Result<bool, String> {
+    let mut dest = PathBuf::from(".");
+    let mut pattern = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-d" => match args.next() {
+                Some(dir) => dest = PathBuf::from(dir),
+                None => return Err(format!("Missing directory after -d\n\n{USAGE}")),
+            },
+            _ if pattern.is_none() => pattern = Some(arg.as_str()),
+            _ => return Err(format!("Unexpected argument: {arg}\n\n{USAGE}")),
+        }
+    }
+
+    let count = match pattern {
+        Some(pattern) => unzipper.extract_matching(pattern, &dest),
+        None => unzipper
+            .extract_all(&dest)
+            .map(|_| unzipper.entries().count()),
+    }
+    .map_err(|error| error.to_string())?;
+    writeln!(out, "{count} entries extracted to {}", dest.display())
+        .map_err(|error| error.to_string())?;
+    Ok(true)
+}
+
+/// Reads every entry of the archive, verifying its CRC-32, as done by `unzip -t`.
+fn test(unzipper: &Unzipper, archive: &Path, out: &mut impl Write) -> Result<bool, String> {
+    let names: Vec<String> = unzipper
+        .entries()
+        .filter(|entry| entry.kind() != EntryKind::Directory)
+        .map(|entry| entry.name().to_string())
+        .collect();
+
+    let mut errors = 0;
+    for name in &names {
+        let status = match unzipper.get_file_to(name, &mut std::io::sink()) {
+            Ok(_) => "OK".to_string(),
+            Err(error) => {
+                errors += 1;
+                error.to_string()
+            }
+        };
+        writeln!(out, "    testing: {name:<40} {status}").map_err(|error| error.to_string())?;
+    }
+
+    match errors {
+        0 => writeln!(out, "No errors detected in {}", archive.display()),
+        _ => writeln!(out, "{errors} errors detected in {}", archive.display()),
+    }
+    .map_err(|error| error.to_string())?;
+    Ok(errors == 0)
+}
+
+#[cfg(test)]
+mod unzipper_cli_tests {
+    use super::*;
+
+    use test_support::unit_test::UnitTest;
+
+    /// Runs a command, returning its result and its output.
+    fn run_command(args: &[&str]) -> (Result<bool, String>, String) {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let mut out = Vec::new();
+        let result = run(&args, &mut out);
+        (result, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_unzipper_cli() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let file = file.to_str().unwrap();
+
+        let (result, output) = run_command(&["list", file]);
+        assert_eq!(result, Ok(true));
+        assert_eq!(output.lines().count(), 90);
+
+        let (result, output) = run_command(&["test", file]);
+        assert_eq!(result, Ok(true));
+        assert!(output.contains("    testing: mimetype"));
+        assert!(output.ends_with(&format!("No errors detected in {file}\n")));
+
+        let dest = std::env::temp_dir().join("unzipper_cli");
+        let _ = std::fs::remove_dir_all(&dest);
+        let (result, output) =
+            run_command(&["extract", file, "-d", dest.to_str().unwrap(), "Images/*"]);
+        assert_eq!(result, Ok(true));
+        assert!(output.starts_with("3 entries extracted"));
+        assert!(dest.join("Images/spacer.gif").is_file());
+        std::fs::remove_dir_all(&dest).unwrap();
+
+        let (result, output) = run_command(&["cat", file, "META-INF/container.xml"]);
+        assert_eq!(result, Ok(true));
+        assert!(output.starts_with("<?xml"));
+        assert!(run_command(&["cat", file, "missing.txt"]).0.is_err());
+        assert!(run_command(&["cat", file]).0.is_err());
+
+        // Corrupted entries fail the test
+        let unit_test = UnitTest::new("unzipper_crc_check");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("corrupted.zip");
+        let (result, output) = run_command(&["test", file.to_str().unwrap()]);
+        assert_eq!(result, Ok(false));
+        assert!(output.contains("CRC-32 mismatch"));
+
+        assert!(run_command(&["list"]).0.is_err());
+        assert!(run_command(&["unknown", file.to_str().unwrap()]).0.is_err());
+        assert!(run_command(&["list", "missing.zip"]).0.is_err());
+    }
+}
@@ -0,0 +1,286 @@
+//! Cache Module.
+//!
+//! This module provides a block cache between the Unzipper and a slow source, such as a network storage
+//! or a spinning disk. The source is read by fixed-size blocks, the most recently used ones being kept,
+//! so that the repeated reads of the headers and of the entries read again do not reach the source.
+
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Read, Seek, SeekFrom};
+
+/// Default size of the blocks read from the source.
+const DEFAULT_BLOCK_SIZE: usize = 64 * 1024;
+
+/// Default number of blocks kept in the cache.
+const DEFAULT_CAPACITY: usize = 64;
+
+/// Block of the source kept in the cache.
+struct Block {
+    data: Vec<u8>,
+    last_used: u64, // value of the use counter when the block was last read
+}
+
+/// Reader keeping the least recently used blocks of its source in memory.
+///
+/// It wraps any `Read + Seek` source and is itself `Read + Seek`, so that it is given to
+/// `Unzipper::from_reader()` in place of the source.
+pub struct CachedReader<R: Read + Seek> {
+    source: R,
+    block_size: usize,
+    capacity: usize,
+    blocks: HashMap<u64, Block>, // by block index
+    uses: u64,                   // counter of the block reads, giving their recency
+    length: Option<u64>,         // of the source, once known
+    position: u64,               // current position in the stream
+    hits: u64,
+    misses: u64,
+}
+
+impl<R: Read + Seek> CachedReader<R> {
+    /// Creates a new CachedReader with blocks of 64 KiB, keeping 64 blocks at most.
+    ///
+    /// # Arguments
+    /// * `source` - The source read through the cache.
+    pub fn new(source: R) -> CachedReader<R> {
+        CachedReader {
+            source,
+            block_size: DEFAULT_BLOCK_SIZE,
+            capacity: DEFAULT_CAPACITY,
+            blocks: HashMap::new(),
+            uses: 0,
+            length: None,
+            position: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Sets the size of the blocks read from the source. The blocks already cached are dropped.
+    pub fn block_size(mut self, block_size: usize) -> CachedReader<R> {
+        self.block_size = block_size.max(1);
+        self.blocks.clear();
+        self
+    }
+
+    /// Sets the number of blocks kept in the cache, the least recently used ones being dropped first.
+    pub fn capacity(mut self, capacity: usize) -> CachedReader<R> {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Returns the number of reads served from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns the number of blocks read from the source.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Drops the blocks cached, such as after the source was modified.
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.length = None;
+    }
+
+    /// Returns the source, consuming the reader.
+    pub fn into_inner(self) -> R {
+        self.source
+    }
+
+    /// Returns the block of the given index, reading it from the source if not cached.
+    fn block(&mut self, index: u64) -> std::io::Result<&[u8]> {
+        self.uses += 1;
+        if self.blocks.contains_key(&index) {
+            self.hits += 1;
+        } else {
+            self.misses += 1;
+            let data = self.read_block(index)?;
+            if self.blocks.len() >= self.capacity {
+                self.evict();
+            }
+            self.blocks.insert(index, Block { data, last_used: 0 });
+        }
+
+        let uses = self.uses;
+        match self.blocks.get_mut(&index) {
+            Some(block) => {
+                block.last_used = uses;
+                Ok(&block.data)
+            }
+            None => Ok(&[]),
+        }
+    }
+
+    /// Reads the block of the given index from the source, shorter than the block size at the end
+    /// of the source.
+    fn read_block(&mut self, index: u64) -> std::io::Result<Vec<u8>> {
+        self.source
+            .seek(SeekFrom::Start(index * self.block_size as u64))?;
+        let mut data = vec![0u8; self.block_size];
+        let mut length = 0;
+        while length < data.len() {
+            match self.source.read(&mut data[length..]) {
+                Ok(0) => break,
+                Ok(count) => length += count,
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => return Err(error),
+            }
+        }
+        data.truncate(length);
+        Ok(data)
+    }
+
+    /// Drops the least recently used block.
+    fn evict(&mut self) {
+        if let Some(index) = self
+            .blocks
+            .iter()
+            .min_by_key(|(_, block)| block.last_used)
+            .map(|(index, _)| *index)
+        {
+            self.blocks.remove(&index);
+        }
+    }
+
+    /// Returns the length of the source, seeking to its end the first time.
+    fn length(&mut self) -> std::io::Result<u64> {
+        match self.length {
+            Some(length) => Ok(length),
+            None => {
+                let length = self.source.seek(SeekFrom::End(0))?;
+                self.length = Some(length);
+                Ok(length)
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> Read for CachedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let block_size = self.block_size as u64;
+        let offset = (self.position % block_size) as usize;
+        let block = self.block(self.position / block_size)?;
+        if offset >= block.len() {
+            // Past the end of the source, in its last block
+            return Ok(0);
+        }
+        let length = buf.len().min(block.len() - offset);
+        buf[..length].copy_from_slice(&block[offset..offset + length]);
+        self.position += length as u64;
+        Ok(length)
+    }
+}
+
+impl<R: Read + Seek> Seek for CachedReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(delta) => self.length()?.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        };
+        self.position =
+            position.ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Invalid seek position"))?;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use super::*;
+
+    use std::fs::File;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    /// Source counting the reads reaching it.
+    struct Counting(File, Arc<AtomicU64>);
+
+    impl Read for Counting {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.1.fetch_add(1, Ordering::Relaxed);
+            self.0.read(buf)
+        }
+    }
+
+    impl Seek for Counting {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.0.seek(pos)
+        }
+    }
+
+    #[test]
+    fn test_cache_reads() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let content = Unzipper::new(&file)
+            .unwrap()
+            .get_file("content.opf")
+            .unwrap();
+
+        let reads = Arc::new(AtomicU64::new(0));
+        let source = Counting(File::open(&file).unwrap(), reads.clone());
+        let unzipper = Unzipper::from_reader(CachedReader::new(source)).unwrap();
+        assert_eq!(unzipper.get_file("content.opf").unwrap(), content);
+
+        // Read again from the cache only
+        let count = reads.load(Ordering::Relaxed);
+        assert_eq!(unzipper.get_file("content.opf").unwrap(), content);
+        assert_eq!(reads.load(Ordering::Relaxed), count);
+
+        // With a single small block, the blocks are read again once evicted
+        let source = Counting(File::open(&file).unwrap(), reads.clone());
+        let reader = CachedReader::new(source).block_size(512).capacity(1);
+        let unzipper = Unzipper::from_reader(reader).unwrap();
+        let count = reads.load(Ordering::Relaxed);
+        assert_eq!(unzipper.get_file("content.opf").unwrap(), content);
+        assert_eq!(unzipper.get_file("content.opf").unwrap(), content);
+        assert!(reads.load(Ordering::Relaxed) > count + 2);
+    }
+
+    #[test]
+    fn test_cache_eviction() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut reader = CachedReader::new(std::io::Cursor::new(data))
+            .block_size(16)
+            .capacity(2);
+        let mut buf = [0u8; 4];
+
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [0, 1, 2, 3]);
+        reader.seek(SeekFrom::Start(20)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        reader.seek(SeekFrom::Start(4)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!((reader.hits(), reader.misses()), (1, 2));
+
+        // The block 1 is the least recently used one
+        reader.seek(SeekFrom::Start(40)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        reader.seek(SeekFrom::Start(8)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!((reader.hits(), reader.misses()), (2, 3));
+        reader.seek(SeekFrom::Start(16)).unwrap();
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [16, 17, 18, 19]);
+        assert_eq!((reader.hits(), reader.misses()), (2, 4));
+
+        reader.seek(SeekFrom::End(-2)).unwrap();
+        assert_eq!(reader.read(&mut buf).unwrap(), 2);
+        assert_eq!(buf[..2], [254, 255]);
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+
+        // A position past the end of the source, inside its short last block
+        let data: Vec<u8> = (0..250).collect();
+        let mut reader = CachedReader::new(std::io::Cursor::new(data)).block_size(16);
+        reader.seek(SeekFrom::Start(252)).unwrap();
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+    }
+}
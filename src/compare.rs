@@ -0,0 +1,187 @@
+//! Compare Module.
+//!
+//! This module provides the comparison of a zip archive with a directory on disk, such as the one where
+//! the archive was extracted, to check that an installation is up to date. The files are compared by size
+//! and CRC-32, using the values of the central directory, so that the entries are not decompressed.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use crate::crc32::Crc32;
+use crate::entry::EntryKind;
+use crate::error::UnzipperError;
+use crate::extract::safe_destination;
+use crate::unzipper::Unzipper;
+
+/// Struct that describes the differences between a zip archive and a directory.
+///
+/// The paths are the cleaned paths of the entries, relative to the directory, with `/` as separator.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DirDiff {
+    changed: Vec<String>,
+    missing: Vec<String>,
+    extra: Vec<String>,
+}
+
+impl DirDiff {
+    /// Returns the paths of the entries whose file on disk has another size, content or type.
+    pub fn changed(&self) -> &[String] {
+        &self.changed
+    }
+
+    /// Returns the paths of the entries without a file or directory on disk.
+    pub fn missing(&self) -> &[String] {
+        &self.missing
+    }
+
+    /// Returns the paths of the files and directories on disk that are not in the zip archive, sorted by name.
+    pub fn extra(&self) -> &[String] {
+        &self.extra
+    }
+
+    /// Returns true if the directory holds exactly the content of the zip archive.
+    pub fn is_up_to_date(&self) -> bool {
+        self.changed.is_empty() && self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+/// Computes the size and CRC-32 of a file on disk, reading it through the given buffer.
+fn file_crc32(path: &Path, buffer: &mut [u8]) -> Result<(u64, u32), UnzipperError> {
+    let mut file = File::open(path)?;
+    let mut crc32 = Crc32::new();
+    let mut size = 0;
+    loop {
+        let length = file.read(buffer)?;
+        if length == 0 {
+            return Ok((size, crc32.finalize()));
+        }
+        crc32.update(&buffer[..length]);
+        size += length as u64;
+    }
+}
+
+/// Collects the paths of the files and directories under a directory, without following symbolic links.
+///
+/// # Arguments
+/// * `dir` - The directory to walk.
+/// * `prefix` - The path of the directory relative to the root of the walk, empty for the root.
+/// * `paths` - Receives the paths relative to the root of the walk, with `/` as separator.
+fn walk_dir(dir: &Path, prefix: &str, paths: &mut Vec<String>) -> Result<(), UnzipperError> {
+    for dir_entry in fs::read_dir(dir)? {
+        let dir_entry = dir_entry?;
+        let name = dir_entry.file_name().to_string_lossy().into_owned();
+        let path = if prefix.is_empty() {
+            name
+        } else {
+            format!("{prefix}/{name}")
+        };
+        if dir_entry.file_type()?.is_dir() {
+            walk_dir(&dir_entry.path(), &path, paths)?;
+        }
+        paths.push(path);
+    }
+    Ok(())
+}
+
+impl<R: Read + Seek> Unzipper<R> {
+    /// Compares the zip archive with a directory on disk.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory to compare, such as the destination of a previous extraction.
+    ///
+    /// # Returns
+    /// A Result containing the DirDiff listing the entries that differ, by size or CRC-32, from their file
+    /// on disk, the entries missing from the directory, in the order of the central directory, and the files
+    /// and directories of the directory that are not in the zip archive. The content of a symbolic link is
+    /// its target path. Returns an error if the directory cannot be read.
+    pub fn compare_dir(&self, dir: &Path) -> Result<DirDiff, UnzipperError> {
+        let mut diff = DirDiff::default();
+        let mut buffer = vec![0u8; self.options.buffer_size];
+        for entry in self.entries() {
+            let Some(path) = safe_destination(dir, entry.name()) else {
+                diff.missing.push(entry.name().to_string());
+                continue;
+            };
+            let Ok(metadata) = fs::symlink_metadata(&path) else {
+                diff.missing.push(entry.name().to_string());
+                continue;
+            };
+
+            let same = match entry.kind() {
+                EntryKind::Directory => metadata.is_dir(),
+                EntryKind::File => {
+                    metadata.is_file()
+                        && metadata.len() == entry.size() as u64
+                        && file_crc32(&path, &mut buffer)? == (entry.size() as u64, entry.crc32())
+                }
+                EntryKind::Symlink => match fs::read_link(&path) {
+                    Ok(target) => {
+                        let target = target.to_string_lossy();
+                        let mut crc32 = Crc32::new();
+                        crc32.update(target.as_bytes());
+                        target.len() == entry.size() as usize && crc32.finalize() == entry.crc32()
+                    }
+                    Err(_) => false,
+                },
+            };
+            if !same {
+                diff.changed.push(entry.name().to_string());
+            }
+        }
+
+        // The directories implied by the entry paths are expected as well
+        let expected: HashSet<String> = self.walk().map(|node| node.path().to_string()).collect();
+        let mut on_disk = Vec::new();
+        walk_dir(dir, "", &mut on_disk)?;
+        on_disk.retain(|path| !expected.contains(path));
+        on_disk.sort();
+        diff.extra = on_disk;
+        Ok(diff)
+    }
+}
+
+#[cfg(test)]
+mod compare_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_compare_dir() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let dest = std::env::temp_dir().join("unzipper_compare_dir");
+        let _ = fs::remove_dir_all(&dest);
+        let unzipper = Unzipper::new(&file).unwrap();
+        unzipper.extract_all(&dest).unwrap();
+        assert!(unzipper.compare_dir(&dest).unwrap().is_up_to_date());
+
+        // Same size, other content
+        fs::write(dest.join("mimetype"), "application/epub+zi_").unwrap();
+        fs::write(dest.join("toc.ncx"), "truncated").unwrap();
+        fs::remove_file(dest.join("Images/spacer.gif")).unwrap();
+        fs::remove_dir_all(dest.join("Fonts")).unwrap();
+        fs::create_dir_all(dest.join("Notes/Drafts")).unwrap();
+        fs::write(dest.join("Notes/Drafts/todo.txt"), "todo").unwrap();
+
+        let diff = unzipper.compare_dir(&dest).unwrap();
+        assert!(!diff.is_up_to_date());
+        assert_eq!(diff.changed(), ["mimetype", "toc.ncx"]);
+        assert_eq!(diff.missing().len(), 8);
+        assert!(diff.missing().contains(&"Fonts".to_string()));
+        assert!(diff.missing().contains(&"Fonts/Swinging.ttf".to_string()));
+        assert!(diff.missing().contains(&"Images/spacer.gif".to_string()));
+        assert_eq!(
+            diff.extra(),
+            ["Notes", "Notes/Drafts", "Notes/Drafts/todo.txt"]
+        );
+
+        fs::remove_dir_all(&dest).unwrap();
+        assert!(unzipper.compare_dir(&dest).is_err());
+    }
+}
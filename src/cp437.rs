@@ -0,0 +1,67 @@
+//! CP437 Module.
+//!
+//! This module provides the decoding of file names stored with the IBM PC character set (code page 437),
+//! which is the encoding mandated by the PKWARE APPNOTE when the bit 11 of the general purpose bit flag is not set.
+//! Archives created by old Windows tools rely on it for non-ASCII names.
+
+/// Characters of the upper half (0x80 to 0xFF) of code page 437. The lower half is ASCII.
+const UPPER_HALF: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', 'É', 'æ', 'Æ',
+    'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', 'á', 'í', 'ó', 'ú', 'ñ', 'Ñ',
+    'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕',
+    '╣', '║', '╗', '╝', '╜', '╛', '┐', '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦',
+    '╠', '═', '╬', '╧', '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐',
+    '▀', 'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', '≡', '±',
+    '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{00a0}',
+];
+
+/// Decodes a byte string encoded with code page 437.
+///
+/// # Arguments
+/// * `bytes` - The encoded byte string.
+///
+/// # Returns
+/// The decoded string. Every byte value maps to a character, so the decoding never fails.
+pub(crate) fn decode(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&byte| {
+            if byte < 0x80 {
+                byte as char
+            } else {
+                UPPER_HALF[(byte - 0x80) as usize]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod cp437_tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_cp437_file_names() {
+        assert_eq!(decode(b"caf\x82.txt"), "café.txt");
+        assert_eq!(decode(b"\xb0\xdb\xff"), "░█\u{00a0}");
+
+        let unit_test = UnitTest::new("unzipper_file_names");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("encodings.zip");
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        let mut names: Vec<String> = unzipper.entries().map(|e| e.name().to_string()).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["bad\u{fffd}.txt", "café.txt", "plain.txt", "日本語.txt"]
+        );
+
+        // CP437 name, without the bit 11 set
+        assert_eq!(unzipper.get_file("café.txt").unwrap(), b"cp437\n");
+        // UTF-8 name, with the bit 11 set
+        assert_eq!(unzipper.get_file("日本語.txt").unwrap(), b"utf-8\n");
+    }
+}
@@ -0,0 +1,99 @@
+//! CRC-32 Module.
+//!
+//! This module provides the CRC-32 (IEEE 802.3) checksum used by the zip format to verify the integrity
+//! of the decompressed content of the file entries.
+//!
+//! The checksum of the content is computed by crc32fast, which uses the SIMD instructions of the processor
+//! when available (PCLMULQDQ on x86, the CRC instructions on aarch64), so that the verification does not
+//! dominate the extraction time. The lookup table is kept for the byte-wise updates of the ZipCrypto keys.
+
+const CRC32_POLYNOMIAL: u32 = 0xedb88320;
+
+const CRC32_TABLE: [u32; 256] = make_table();
+
+/// Builds the lookup table used to compute the CRC-32 one byte at a time.
+const fn make_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ CRC32_POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Updates a raw (not inverted) CRC-32 value with a single byte.
+#[inline]
+pub(crate) fn update_byte(crc: u32, byte: u8) -> u32 {
+    CRC32_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8)
+}
+
+/// Struct that computes a CRC-32 incrementally, as data becomes available.
+#[derive(Debug, Clone)]
+pub(crate) struct Crc32 {
+    hasher: crc32fast::Hasher,
+}
+
+impl Crc32 {
+    /// Creates a new Crc32 with no data processed.
+    pub(crate) fn new() -> Crc32 {
+        Crc32 {
+            hasher: crc32fast::Hasher::new(),
+        }
+    }
+
+    /// Adds the given bytes to the checksum.
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        self.hasher.update(bytes);
+    }
+
+    /// Returns the CRC-32 of the bytes processed so far.
+    pub(crate) fn finalize(&self) -> u32 {
+        self.hasher.clone().finalize()
+    }
+}
+
+#[cfg(test)]
+mod crc32_tests {
+    use super::*;
+
+    fn crc32(bytes: &[u8]) -> u32 {
+        let mut crc = Crc32::new();
+        crc.update(bytes);
+        crc.finalize()
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(crc32(b"application/epub+zip"), 0x2cab616f);
+    }
+
+    #[test]
+    fn test_crc32_matches_table() {
+        // Long enough for the SIMD implementations, updated in uneven parts
+        let bytes: Vec<u8> = (0..10_000u32).map(|i| (i * 7 + i / 13) as u8).collect();
+        let expected = !bytes
+            .iter()
+            .fold(0xffffffff, |crc, &byte| update_byte(crc, byte));
+
+        assert_eq!(crc32(&bytes), expected);
+        let mut crc = Crc32::new();
+        for part in bytes.chunks(333) {
+            crc.update(part);
+        }
+        assert_eq!(crc.finalize(), expected);
+        assert_eq!(crc.finalize(), expected);
+    }
+}
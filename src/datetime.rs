@@ -0,0 +1,237 @@
+//! Date and Time Module.
+//!
+//! This module provides the decoding of the MS-DOS date and time recorded for each file entry of a zip archive.
+//! MS-DOS timestamps have a 2-second resolution, cover the years 1980 to 2107, and carry no time zone:
+//! they are interpreted as UTC.
+//!
+//! More accurate timestamps may be found in the extra fields of the entries.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Struct that holds the timestamps of an entry found in its extra fields, more accurate than the MS-DOS date and time.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Timestamps {
+    #[cfg_attr(feature = "serde", serde(with = "serde_time"))]
+    pub(crate) modified: Option<SystemTime>,
+    #[cfg_attr(feature = "serde", serde(with = "serde_time"))]
+    pub(crate) accessed: Option<SystemTime>,
+    #[cfg_attr(feature = "serde", serde(with = "serde_time"))]
+    pub(crate) created: Option<SystemTime>,
+}
+
+/// Serialization of the timestamps as signed seconds and nanoseconds since the Unix epoch.
+///
+/// The serde format of SystemTime rejects the times before 1970, which the NTFS timestamps may hold.
+#[cfg(feature = "serde")]
+mod serde_time {
+    use std::time::{Duration, SystemTime};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Time as the (floored) seconds since the Unix epoch, and the nanoseconds added to them.
+    #[derive(Serialize, Deserialize)]
+    struct UnixTime {
+        seconds: i64,
+        nanoseconds: u32,
+    }
+
+    pub(super) fn serialize<S: Serializer>(
+        time: &Option<SystemTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let unix_time = time.map(|time| {
+            let (seconds, nanoseconds) = super::unix_seconds(time);
+            UnixTime {
+                seconds,
+                nanoseconds,
+            }
+        });
+        unix_time.serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<SystemTime>, D::Error> {
+        let unix_time = Option::<UnixTime>::deserialize(deserializer)?;
+        Ok(unix_time.map(|unix_time| {
+            super::unix_time(unix_time.seconds) + Duration::from_nanos(unix_time.nanoseconds as u64)
+        }))
+    }
+}
+
+/// Returns the SystemTime of a Unix timestamp, in seconds since 1970-01-01 UTC.
+pub(crate) fn unix_time(seconds: i64) -> SystemTime {
+    if seconds >= 0 {
+        UNIX_EPOCH + Duration::from_secs(seconds as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(seconds.unsigned_abs())
+    }
+}
+
+/// Returns the (floored) seconds since the Unix epoch of a SystemTime, and the nanoseconds added to them.
+pub(crate) fn unix_seconds(time: SystemTime) -> (i64, u32) {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(after) => (after.as_secs() as i64, after.subsec_nanos()),
+        Err(error) => {
+            let before = error.duration();
+            let seconds = -(before.as_secs() as i64);
+            match before.subsec_nanos() {
+                0 => (seconds, 0),
+                nanos => (seconds - 1, 1_000_000_000 - nanos),
+            }
+        }
+    }
+}
+
+/// Returns the SystemTime of a Windows FILETIME, in 100-nanosecond intervals since 1601-01-01 UTC.
+pub(crate) fn file_time(intervals: u64) -> SystemTime {
+    const EPOCH_DIFFERENCE: Duration = Duration::from_secs(11644473600);
+
+    let since_1601 = Duration::new(
+        intervals / 10_000_000,
+        (intervals % 10_000_000) as u32 * 100,
+    );
+    if since_1601 >= EPOCH_DIFFERENCE {
+        UNIX_EPOCH + (since_1601 - EPOCH_DIFFERENCE)
+    } else {
+        UNIX_EPOCH - (EPOCH_DIFFERENCE - since_1601)
+    }
+}
+
+/// Struct that holds the components of an MS-DOS date and time.
+//
+// date: bits 0-4 day (1-31), bits 5-8 month (1-12), bits 9-15 years since 1980
+// time: bits 0-4 seconds / 2, bits 5-10 minutes, bits 11-15 hours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DosDateTime {
+    pub(crate) year: u16,
+    pub(crate) month: u8,
+    pub(crate) day: u8,
+    pub(crate) hour: u8,
+    pub(crate) minute: u8,
+    pub(crate) second: u8,
+}
+
+impl DosDateTime {
+    /// Decodes an MS-DOS date and time.
+    ///
+    /// # Arguments
+    /// * `date` - The date in MS-DOS format.
+    /// * `time` - The time in MS-DOS format.
+    ///
+    /// # Returns
+    /// The date and time components, or None if they are out of range (e.g. a zero date, written by some tools).
+    pub(crate) fn new(date: u16, time: u16) -> Option<DosDateTime> {
+        let date_time = DosDateTime {
+            year: 1980 + (date >> 9),
+            month: ((date >> 5) & 0x0f) as u8,
+            day: (date & 0x1f) as u8,
+            hour: (time >> 11) as u8,
+            minute: ((time >> 5) & 0x3f) as u8,
+            second: ((time & 0x1f) * 2) as u8,
+        };
+
+        if !(1..=12).contains(&date_time.month)
+            || date_time.day == 0
+            || date_time.day > days_in_month(date_time.year, date_time.month)
+            || date_time.hour > 23
+            || date_time.minute > 59
+            || date_time.second > 59
+        {
+            return None;
+        }
+        Some(date_time)
+    }
+
+    /// Returns the date and time as a SystemTime, interpreting it as UTC.
+    pub(crate) fn to_system_time(self) -> SystemTime {
+        let days = days_from_civil(self.year, self.month, self.day);
+        let seconds =
+            days * 86400 + self.hour as u64 * 3600 + self.minute as u64 * 60 + self.second as u64;
+        UNIX_EPOCH + Duration::from_secs(seconds)
+    }
+
+    /// Returns the date and time as a chrono NaiveDateTime.
+    #[cfg(feature = "chrono")]
+    pub(crate) fn to_naive_date_time(self) -> Option<chrono::NaiveDateTime> {
+        chrono::NaiveDate::from_ymd_opt(self.year as i32, self.month as u32, self.day as u32)?
+            .and_hms_opt(self.hour as u32, self.minute as u32, self.second as u32)
+    }
+}
+
+/// Returns the number of days in the given month.
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns true if the given year is a leap year.
+fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+/// Returns the number of days between 1970-01-01 and the given date, which must not be before 1970.
+fn days_from_civil(year: u16, month: u8, day: u8) -> u64 {
+    // Years start in March, so that the leap day is the last day of the year
+    let year = if month <= 2 { year - 1 } else { year } as u64;
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let month = month as u64;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day as u64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod datetime_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_datetime_dos_to_system_time() {
+        // 1980-01-01 00:00:00, the MS-DOS epoch
+        let epoch = DosDateTime::new(0x0021, 0).unwrap();
+        assert_eq!(
+            epoch.to_system_time(),
+            UNIX_EPOCH + Duration::from_secs(315532800)
+        );
+
+        // 2024-02-29 23:59:58
+        let leap_day = DosDateTime::new((44 << 9) | (2 << 5) | 29, (23 << 11) | (59 << 5) | 29);
+        assert_eq!(
+            leap_day.unwrap().to_system_time(),
+            UNIX_EPOCH + Duration::from_secs(1709251198)
+        );
+
+        assert_eq!(DosDateTime::new(0, 0), None);
+        assert_eq!(DosDateTime::new((43 << 9) | (2 << 5) | 29, 0), None);
+
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let entry = unzipper
+            .entries()
+            .find(|entry| entry.name() == "mimetype")
+            .unwrap();
+
+        // 2020-11-26 16:32:40
+        assert_eq!(
+            entry.modified(),
+            Some(UNIX_EPOCH + Duration::from_secs(1606408360))
+        );
+        #[cfg(feature = "chrono")]
+        assert_eq!(
+            entry.modified_date_time().unwrap().to_string(),
+            "2020-11-26 16:32:40"
+        );
+    }
+}
@@ -0,0 +1,853 @@
+//! Decompressor Module.
+//!
+//! This module provides the decompression of the entry data, for each supported compression method.
+//! A decompressor is fed with chunks of compressed data as they are read from the zip file, and produces
+//! the decompressed data in the buffer given by the caller, keeping its state between calls.
+//!
+//! DEFLATE (8) is always available. DEFLATE64 (9) requires the `deflate64` feature,
+//! BZIP2 (12) the `bzip2` feature and XZ (95) the `xz` feature. Applications can register their own
+//! decompressors for the other methods, or to replace a built-in one.
+//!
+//! DEFLATE is inflated by miniz_oxide, in pure Rust. With the `flate2` feature, it is inflated by flate2
+//! with its zlib-rs backend instead, and with the `zlib-ng` feature, by flate2 with the zlib-ng C library,
+//! trading portability for throughput. With the `libdeflate` feature, the DEFLATE entries of known size
+//! up to 64 MiB are inflated at once by libdeflate, which is faster than the streaming backends for whole entries.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+#[cfg(not(feature = "flate2"))]
+use miniz_oxide::inflate::stream::{inflate, InflateState};
+#[cfg(not(feature = "flate2"))]
+use miniz_oxide::{DataFormat, MZError, MZFlush, MZStatus};
+
+use crate::error::UnzipperError;
+
+/// Result of a decompression step.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecompressResult {
+    /// The number of bytes consumed from the compressed input.
+    pub bytes_consumed: usize,
+    /// The number of bytes written to the decompressed output.
+    pub bytes_written: usize,
+    /// True once the end of the compressed stream has been reached.
+    pub finished: bool,
+}
+
+/// Trait implemented by the decompressors of the compression methods.
+///
+/// A new decompressor is created for each entry read. Besides the built-in ones, decompressors are registered
+/// with `UnzipperOptions::decompressor()` or `Unzipper::register_decompressor()`.
+pub trait Decompressor: Send {
+    /// Decompresses a chunk of compressed data.
+    ///
+    /// # Arguments
+    /// * `input` - The compressed data available. It may be only partially consumed. It is empty once all
+    ///   the compressed data of the entry has been given, the decompressor then having to finish the stream.
+    /// * `output` - The buffer receiving the decompressed data.
+    ///
+    /// # Returns
+    /// A Result containing the number of bytes consumed and written, or an error if the compressed data is invalid.
+    fn decompress(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<DecompressResult, UnzipperError>;
+}
+
+/// Function creating the decompressor of an entry.
+type Factory = Arc<dyn Fn() -> Box<dyn Decompressor> + Send + Sync>;
+
+/// Largest number of inflate states kept for reuse.
+const MAX_SPARE_INFLATE_STATES: usize = 8;
+
+/// Struct that holds the decompressors registered by the application, by compression method.
+#[derive(Clone, Default)]
+pub(crate) struct DecompressorRegistry {
+    factories: HashMap<u16, Factory>,
+    inflate_states: InflatePool,
+}
+
+/// Trait implemented by the inflate backends of the DEFLATE decompressor, selected by the cargo features.
+trait Inflate: Send {
+    /// Creates the state of a new raw DEFLATE stream.
+    fn new() -> Self;
+
+    /// Resets the state for a new stream.
+    fn reset(&mut self);
+
+    /// Inflates a chunk of compressed data. See `Decompressor::decompress()`.
+    fn inflate(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<DecompressResult, UnzipperError>;
+}
+
+/// Inflate backend of the DEFLATE decompressor.
+#[cfg(not(feature = "flate2"))]
+type Inflater = MinizInflater;
+#[cfg(feature = "flate2")]
+type Inflater = Flate2Inflater;
+
+/// Inflate backend based on miniz_oxide.
+#[cfg(not(feature = "flate2"))]
+struct MinizInflater(Box<InflateState>);
+
+#[cfg(not(feature = "flate2"))]
+impl Inflate for MinizInflater {
+    fn new() -> MinizInflater {
+        MinizInflater(Box::new(InflateState::new(DataFormat::Raw)))
+    }
+
+    fn reset(&mut self) {
+        self.0.reset(DataFormat::Raw);
+    }
+
+    fn inflate(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<DecompressResult, UnzipperError> {
+        let stream_result = inflate(&mut self.0, input, output, MZFlush::None);
+
+        let finished = match stream_result.status {
+            Ok(MZStatus::StreamEnd) => true,
+            Ok(_) | Err(MZError::Buf) => false,
+            Err(_) => {
+                return Err(UnzipperError::Decompression(
+                    "Invalid compressed data".to_string(),
+                ));
+            }
+        };
+
+        Ok(DecompressResult {
+            bytes_consumed: stream_result.bytes_consumed,
+            bytes_written: stream_result.bytes_written,
+            finished,
+        })
+    }
+}
+
+/// Inflate backend based on flate2.
+#[cfg(feature = "flate2")]
+struct Flate2Inflater(flate2::Decompress);
+
+#[cfg(feature = "flate2")]
+impl Inflate for Flate2Inflater {
+    fn new() -> Flate2Inflater {
+        Flate2Inflater(flate2::Decompress::new(false))
+    }
+
+    fn reset(&mut self) {
+        self.0.reset(false);
+    }
+
+    fn inflate(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<DecompressResult, UnzipperError> {
+        let total_in = self.0.total_in();
+        let total_out = self.0.total_out();
+
+        let status = self
+            .0
+            .decompress(input, output, flate2::FlushDecompress::None)
+            .map_err(|_| UnzipperError::Decompression("Invalid compressed data".to_string()))?;
+
+        Ok(DecompressResult {
+            bytes_consumed: (self.0.total_in() - total_in) as usize,
+            bytes_written: (self.0.total_out() - total_out) as usize,
+            finished: status == flate2::Status::StreamEnd,
+        })
+    }
+}
+
+/// Pool of the inflate states of the DEFLATE decompressors dropped, reused by the next ones
+/// instead of allocating a new state for each entry.
+#[derive(Clone, Default)]
+pub(crate) struct InflatePool {
+    states: Arc<Mutex<Vec<Inflater>>>,
+}
+
+impl InflatePool {
+    /// Returns a spare inflate state, reset for a new stream, or a new one if none is left.
+    fn take(&self) -> Inflater {
+        match self.states.lock().ok().and_then(|mut states| states.pop()) {
+            Some(mut state) => {
+                state.reset();
+                state
+            }
+            None => Inflater::new(),
+        }
+    }
+
+    /// Keeps an inflate state for reuse, unless enough of them are already kept.
+    fn put(&self, state: Inflater) {
+        if let Ok(mut states) = self.states.lock() {
+            if states.len() < MAX_SPARE_INFLATE_STATES {
+                states.push(state);
+            }
+        }
+    }
+
+    /// Returns the number of spare inflate states.
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.states.lock().map_or(0, |states| states.len())
+    }
+}
+
+impl fmt::Debug for DecompressorRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut methods: Vec<&u16> = self.factories.keys().collect();
+        methods.sort();
+        f.debug_struct("DecompressorRegistry")
+            .field("methods", &methods)
+            .finish()
+    }
+}
+
+impl DecompressorRegistry {
+    /// Registers the decompressor of a compression method, replacing any decompressor of the method.
+    ///
+    /// # Arguments
+    /// * `method` - The compression method, as recorded in the zip archive.
+    /// * `factory` - The function creating a decompressor, called for each entry read.
+    pub(crate) fn register<D, F>(&mut self, method: u16, factory: F)
+    where
+        D: Decompressor + 'static,
+        F: Fn() -> D + Send + Sync + 'static,
+    {
+        self.factories
+            .insert(method, Arc::new(move || Box::new(factory())));
+    }
+
+    /// Returns true if entries compressed with the given method can be read.
+    pub(crate) fn is_supported(&self, method: u16) -> bool {
+        self.factories.contains_key(&method) || is_built_in(method)
+    }
+
+    /// Creates the decompressor of the given compression method, the registered one first.
+    ///
+    /// # Arguments
+    /// * `method` - The compression method of the entry.
+    /// * `size` - The uncompressed size of the entry, 0 when it is not known.
+    ///
+    /// # Returns
+    /// A Result containing the decompressor, None for stored (not compressed) entries,
+    /// or an error if the method is not supported.
+    pub(crate) fn new_decompressor(
+        &self,
+        method: u16,
+        size: u64,
+    ) -> Result<Option<Box<dyn Decompressor>>, UnzipperError> {
+        match self.factories.get(&method) {
+            Some(factory) => Ok(Some(factory())),
+            None => new_built_in(method, size, &self.inflate_states),
+        }
+    }
+}
+
+/// Returns true if the given method has a built-in decompressor.
+fn is_built_in(method: u16) -> bool {
+    match method {
+        0 | 8 => true,
+        #[cfg(feature = "deflate64")]
+        9 => true,
+        #[cfg(feature = "bzip2")]
+        12 => true,
+        #[cfg(feature = "xz")]
+        95 => true,
+        _ => false,
+    }
+}
+
+/// Creates the built-in decompressor of the given compression method.
+///
+/// # Arguments
+/// * `method` - The compression method of the entry.
+/// * `size` - The uncompressed size of the entry, 0 when it is not known.
+/// * `inflate_states` - The inflate states reused by the DEFLATE decompressor.
+///
+/// # Returns
+/// A Result containing the decompressor, None for stored (not compressed) entries,
+/// or an error if the method has no built-in decompressor.
+#[cfg_attr(not(feature = "libdeflate"), allow(unused_variables))]
+fn new_built_in(
+    method: u16,
+    size: u64,
+    inflate_states: &InflatePool,
+) -> Result<Option<Box<dyn Decompressor>>, UnzipperError> {
+    match method {
+        0 => Ok(None),
+        #[cfg(feature = "libdeflate")]
+        8 if size > 0 && size <= LIBDEFLATE_MAX_SIZE as u64 => Ok(Some(Box::new(LibDeflate::new(
+            size as usize,
+            inflate_states.clone(),
+        )))),
+        8 => Ok(Some(Box::new(Deflate::new(inflate_states.clone())))),
+        #[cfg(feature = "deflate64")]
+        9 => Ok(Some(Box::new(Deflate64::new()))),
+        #[cfg(feature = "bzip2")]
+        12 => Ok(Some(Box::new(Bzip2::new()))),
+        #[cfg(feature = "xz")]
+        95 => Ok(Some(Box::new(Xz::new()?))),
+        _ => Err(UnzipperError::UnsupportedMethod(method)),
+    }
+}
+
+/// DEFLATE decompressor, based on the inflate backend selected by the cargo features.
+///
+/// Its inflate state is taken from the pool on the first call, and given back to the pool when it is dropped.
+struct Deflate {
+    state: Option<Inflater>,
+    pool: InflatePool,
+}
+
+impl Deflate {
+    fn new(pool: InflatePool) -> Deflate {
+        Deflate { state: None, pool }
+    }
+}
+
+impl Drop for Deflate {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.take() {
+            self.pool.put(state);
+        }
+    }
+}
+
+impl Decompressor for Deflate {
+    fn decompress(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<DecompressResult, UnzipperError> {
+        self.state
+            .get_or_insert_with(|| self.pool.take())
+            .inflate(input, output)
+    }
+}
+
+/// Largest uncompressed size of the entries inflated at once by libdeflate, the larger ones being
+/// decompressed as a stream.
+#[cfg(feature = "libdeflate")]
+const LIBDEFLATE_MAX_SIZE: usize = 64 * 1024 * 1024;
+
+/// DEFLATE decompressor inflating the whole entry at once, based on libdeflate.
+///
+/// The compressed data is gathered until its end, then inflated in a single call into a buffer of the size
+/// of the entry, served by the next calls. Should the data inflate to more than the size of the entry, it is
+/// inflated again as a stream, leaving the size mismatch to be handled as the options require.
+#[cfg(feature = "libdeflate")]
+struct LibDeflate {
+    size: usize,
+    input: Vec<u8>,          // compressed data gathered
+    input_pos: usize,        // next compressed byte given to the streaming decompressor
+    output: Option<Vec<u8>>, // inflated data, once all the compressed data has been gathered
+    output_pos: usize,       // next inflated byte to serve
+    stream: Option<Deflate>, // streaming decompressor, used when the entry size is wrong
+    pool: InflatePool,
+}
+
+#[cfg(feature = "libdeflate")]
+impl LibDeflate {
+    fn new(size: usize, pool: InflatePool) -> LibDeflate {
+        LibDeflate {
+            size,
+            input: Vec::new(),
+            input_pos: 0,
+            output: None,
+            output_pos: 0,
+            stream: None,
+            pool,
+        }
+    }
+
+    /// Inflates the compressed data gathered, as a stream, until some data is written or the end is reached.
+    fn inflate_stream(&mut self, output: &mut [u8]) -> Result<DecompressResult, UnzipperError> {
+        let stream = self
+            .stream
+            .get_or_insert_with(|| Deflate::new(self.pool.clone()));
+        loop {
+            let result = stream.decompress(&self.input[self.input_pos..], output)?;
+            self.input_pos += result.bytes_consumed;
+            if result.finished || result.bytes_written > 0 || result.bytes_consumed == 0 {
+                // The caller has no more compressed data to give
+                return Ok(DecompressResult {
+                    bytes_consumed: 0,
+                    ..result
+                });
+            }
+        }
+    }
+}
+
+#[cfg(feature = "libdeflate")]
+impl Decompressor for LibDeflate {
+    fn decompress(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<DecompressResult, UnzipperError> {
+        if self.stream.is_some() {
+            return self.inflate_stream(output);
+        }
+        if self.output.is_none() {
+            if !input.is_empty() {
+                self.input.extend_from_slice(input);
+                return Ok(DecompressResult {
+                    bytes_consumed: input.len(),
+                    bytes_written: 0,
+                    finished: false,
+                });
+            }
+
+            let mut inflated = vec![0u8; self.size];
+            match libdeflater::Decompressor::new().deflate_decompress(&self.input, &mut inflated) {
+                Ok(length) => {
+                    inflated.truncate(length);
+                    self.output = Some(inflated);
+                }
+                Err(libdeflater::DecompressionError::InsufficientSpace) => {
+                    return self.inflate_stream(output);
+                }
+                Err(libdeflater::DecompressionError::BadData) => {
+                    return Err(UnzipperError::Decompression(
+                        "Invalid compressed data".to_string(),
+                    ));
+                }
+            }
+        }
+
+        let inflated = self.output.as_deref().unwrap_or_default();
+        let length = output.len().min(inflated.len() - self.output_pos);
+        output[..length].copy_from_slice(&inflated[self.output_pos..self.output_pos + length]);
+        self.output_pos += length;
+        Ok(DecompressResult {
+            bytes_consumed: 0,
+            bytes_written: length,
+            finished: self.output_pos == inflated.len(),
+        })
+    }
+}
+
+/// DEFLATE64 decompressor, based on the deflate64 crate.
+#[cfg(feature = "deflate64")]
+struct Deflate64 {
+    state: Box<deflate64::InflaterManaged>,
+}
+
+#[cfg(feature = "deflate64")]
+impl Deflate64 {
+    fn new() -> Deflate64 {
+        Deflate64 {
+            state: Box::new(deflate64::InflaterManaged::new()),
+        }
+    }
+}
+
+#[cfg(feature = "deflate64")]
+impl Decompressor for Deflate64 {
+    fn decompress(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<DecompressResult, UnzipperError> {
+        let result = self.state.inflate(input, output);
+        if result.data_error {
+            return Err(UnzipperError::Decompression(
+                "Invalid DEFLATE64 compressed data".to_string(),
+            ));
+        }
+
+        Ok(DecompressResult {
+            bytes_consumed: result.bytes_consumed,
+            bytes_written: result.bytes_written,
+            finished: self.state.finished(),
+        })
+    }
+}
+
+/// BZIP2 decompressor, based on the bzip2 crate.
+#[cfg(feature = "bzip2")]
+struct Bzip2 {
+    state: bzip2::Decompress,
+}
+
+#[cfg(feature = "bzip2")]
+impl Bzip2 {
+    fn new() -> Bzip2 {
+        Bzip2 {
+            state: bzip2::Decompress::new(false),
+        }
+    }
+}
+
+#[cfg(feature = "bzip2")]
+impl Decompressor for Bzip2 {
+    fn decompress(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<DecompressResult, UnzipperError> {
+        let total_in = self.state.total_in();
+        let total_out = self.state.total_out();
+
+        let status = self.state.decompress(input, output).map_err(|error| {
+            UnzipperError::Decompression(format!("Invalid bzip2 compressed data: {error}"))
+        })?;
+
+        Ok(DecompressResult {
+            bytes_consumed: (self.state.total_in() - total_in) as usize,
+            bytes_written: (self.state.total_out() - total_out) as usize,
+            finished: status == bzip2::Status::StreamEnd,
+        })
+    }
+}
+
+/// XZ decompressor, based on the liblzma crate.
+#[cfg(feature = "xz")]
+struct Xz {
+    state: liblzma::stream::Stream,
+}
+
+#[cfg(feature = "xz")]
+impl Xz {
+    fn new() -> Result<Xz, UnzipperError> {
+        let state = liblzma::stream::Stream::new_stream_decoder(u64::MAX, 0).map_err(|error| {
+            UnzipperError::Decompression(format!("Unable to initialize the XZ decoder: {error}"))
+        })?;
+        Ok(Xz { state })
+    }
+}
+
+#[cfg(feature = "xz")]
+impl Decompressor for Xz {
+    fn decompress(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<DecompressResult, UnzipperError> {
+        let total_in = self.state.total_in();
+        let total_out = self.state.total_out();
+
+        let status = self
+            .state
+            .process(input, output, liblzma::stream::Action::Run)
+            .map_err(|error| {
+                UnzipperError::Decompression(format!("Invalid XZ compressed data: {error}"))
+            })?;
+
+        Ok(DecompressResult {
+            bytes_consumed: (self.state.total_in() - total_in) as usize,
+            bytes_written: (self.state.total_out() - total_out) as usize,
+            finished: status == liblzma::stream::Status::StreamEnd,
+        })
+    }
+}
+
+#[cfg(test)]
+mod decompressor_tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "bzip2")]
+    fn test_decompressor_bzip2() {
+        use std::path::PathBuf;
+
+        use crate::unzipper::Unzipper;
+        use test_support::unit_test::UnitTest;
+
+        let unit_test = UnitTest::new("unzipper_bzip2");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("bzip2.zip");
+        let expected =
+            std::fs::read(PathBuf::from(unit_test.test_case_folder()).join("words.txt")).unwrap();
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert!(unzipper.entries().all(|entry| entry.method() == 12));
+        assert_eq!(
+            unzipper.get_file("short.txt").unwrap(),
+            b"Short text compressed with BZIP2.\n"
+        );
+        // Spans several 16 KiB chunks of compressed data
+        assert_eq!(unzipper.get_file("words.txt").unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "deflate64")]
+    fn test_decompressor_deflate64() {
+        use std::path::PathBuf;
+
+        use crate::unzipper::Unzipper;
+        use test_support::unit_test::UnitTest;
+
+        let unit_test = UnitTest::new("unzipper_deflate64");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("deflate64.zip");
+        let expected =
+            std::fs::read(PathBuf::from(unit_test.test_case_folder()).join("large.txt")).unwrap();
+
+        // The entry uses distances beyond 32 KiB and the 16-bit extra length of DEFLATE64
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert_eq!(unzipper.entries().next().unwrap().method(), 9);
+        assert_eq!(unzipper.get_file("large.txt").unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "xz")]
+    fn test_decompressor_xz() {
+        use std::path::PathBuf;
+
+        use crate::unzipper::Unzipper;
+        use test_support::unit_test::UnitTest;
+
+        let unit_test = UnitTest::new("unzipper_xz");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("xz.zip");
+        let expected =
+            std::fs::read(PathBuf::from(unit_test.test_case_folder()).join("large.txt")).unwrap();
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert!(unzipper.entries().all(|entry| entry.method() == 95));
+        assert_eq!(
+            unzipper.get_file("short.txt").unwrap(),
+            b"Short text compressed with XZ.\n"
+        );
+        // Spans several 16 KiB chunks of compressed data
+        assert_eq!(unzipper.get_file("large.txt").unwrap(), expected);
+    }
+
+    #[test]
+    fn test_decompressor_unsupported_method() {
+        let decompressors = DecompressorRegistry::default();
+        assert!(decompressors.is_supported(8));
+        assert!(!decompressors.is_supported(14));
+        assert!(matches!(
+            decompressors.new_decompressor(14, 0),
+            Err(UnzipperError::UnsupportedMethod(14))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "libdeflate")]
+    fn test_decompressor_libdeflate() {
+        use std::path::PathBuf;
+
+        use crate::unzipper::Unzipper;
+        use test_support::unit_test::UnitTest;
+
+        /// Decompresses the given data by chunks of 100 bytes, as the entry readers do.
+        fn decompress(decompressor: &mut dyn Decompressor, data: &[u8]) -> Vec<u8> {
+            let mut content = Vec::new();
+            let mut output = [0u8; 1000];
+            let mut chunks = data.chunks(100).chain(std::iter::repeat(&[][..]));
+            let mut input = chunks.next().unwrap_or_default();
+            loop {
+                let result = decompressor.decompress(input, &mut output).unwrap();
+                content.extend_from_slice(&output[..result.bytes_written]);
+                input = &input[result.bytes_consumed..];
+                if input.is_empty() {
+                    input = chunks.next().unwrap_or_default();
+                }
+                if result.finished {
+                    return content;
+                }
+            }
+        }
+
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let raw = unzipper.get_raw("content.opf").unwrap();
+        let expected = unzipper.get_file("content.opf").unwrap();
+
+        let pool = InflatePool::default();
+        for size in [expected.len(), expected.len() + 100, expected.len() - 100] {
+            let mut decompressor = LibDeflate::new(size, pool.clone());
+            assert_eq!(decompress(&mut decompressor, raw.data()), expected);
+            // Only the entry whose size is too small is inflated again as a stream
+            assert_eq!(decompressor.stream.is_some(), size < expected.len());
+        }
+
+        let mut decompressor = LibDeflate::new(expected.len(), pool);
+        decompressor.decompress(&[0xff; 10], &mut []).unwrap();
+        assert!(matches!(
+            decompressor.decompress(&[], &mut [0u8; 10]),
+            Err(UnzipperError::Decompression(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(not(feature = "libdeflate"))]
+    fn test_decompressor_inflate_states_reused() {
+        use std::path::PathBuf;
+
+        use crate::unzipper::Unzipper;
+        use test_support::unit_test::UnitTest;
+
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let expected = Unzipper::new(&file).unwrap();
+        let unzipper = Unzipper::new(&file).unwrap();
+        let inflate_states = unzipper.options.decompressors.inflate_states.clone();
+        assert_eq!(inflate_states.len(), 0);
+
+        // A single state is used by the entries read one after the other
+        let names: Vec<String> = unzipper
+            .entries()
+            .filter(|entry| entry.method() == 8)
+            .map(|entry| entry.name().to_string())
+            .collect();
+        assert!(names.len() > 10);
+        for name in &names {
+            let content = unzipper.get_file(name).unwrap();
+            assert_eq!(content, expected.get_file(name).unwrap());
+            assert_eq!(inflate_states.len(), 1);
+        }
+
+        // A state given back in the middle of a stream is reset
+        let mut reader = unzipper.entry_reader("content.opf").unwrap();
+        std::io::Read::read_exact(&mut reader, &mut [0u8; 10]).unwrap();
+        assert_eq!(inflate_states.len(), 0);
+        drop(reader);
+        assert_eq!(inflate_states.len(), 1);
+        assert_eq!(
+            unzipper.get_file("content.opf").unwrap(),
+            expected.get_file("content.opf").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_decompressor_inflate_backend() {
+        use std::path::PathBuf;
+
+        use crate::unzipper::Unzipper;
+        use test_support::unit_test::UnitTest;
+
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let raw = unzipper.get_raw("content.opf").unwrap();
+        let expected = unzipper.get_file("content.opf").unwrap();
+
+        // The state is reset between the streams, the output being given by chunks of 100 bytes
+        let mut inflater = Inflater::new();
+        for _ in 0..2 {
+            let mut content = Vec::new();
+            let mut input = raw.data();
+            let mut output = [0u8; 100];
+            loop {
+                let result = inflater.inflate(input, &mut output).unwrap();
+                input = &input[result.bytes_consumed..];
+                content.extend_from_slice(&output[..result.bytes_written]);
+                if result.finished {
+                    break;
+                }
+            }
+            assert_eq!(content, expected);
+            inflater.reset();
+        }
+
+        assert!(matches!(
+            inflater.inflate(&[0xff; 10], &mut [0u8; 100]),
+            Err(UnzipperError::Decompression(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "zlib-ng")]
+    fn test_decompressor_zlib_ng() {
+        use std::path::PathBuf;
+
+        use crate::unzipper::Unzipper;
+        use test_support::unit_test::UnitTest;
+
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        // The zlib-ng feature selects the flate2 backend, flate2 preferring zlib-ng over zlib-rs
+        let new_inflater: fn() -> Flate2Inflater = Inflater::new;
+        let raw = unzipper.get_raw("content.opf").unwrap();
+        let expected = unzipper.get_file("content.opf").unwrap();
+        let mut output = vec![0u8; expected.len()];
+        let result = new_inflater().inflate(raw.data(), &mut output).unwrap();
+        assert!(result.finished);
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_decompressor_inflate_pool() {
+        let pool = InflatePool::default();
+        assert_eq!(pool.len(), 0);
+        let state = pool.take();
+        pool.put(state);
+        assert_eq!(pool.len(), 1);
+        for _ in 0..MAX_SPARE_INFLATE_STATES + 2 {
+            pool.put(Inflater::new());
+        }
+        assert_eq!(pool.len(), MAX_SPARE_INFLATE_STATES);
+    }
+
+    /// Decompressor of a method inverting the bits of the content.
+    struct Inverted;
+
+    impl Decompressor for Inverted {
+        fn decompress(
+            &mut self,
+            input: &[u8],
+            output: &mut [u8],
+        ) -> Result<DecompressResult, UnzipperError> {
+            let length = input.len().min(output.len());
+            for (output, input) in output.iter_mut().zip(input) {
+                *output = !input;
+            }
+            Ok(DecompressResult {
+                bytes_consumed: length,
+                bytes_written: length,
+                finished: input.is_empty(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_decompressor_registered() {
+        use std::path::PathBuf;
+
+        use crate::error::UnsupportedFeature;
+        use crate::options::UnzipperOptions;
+        use crate::unzipper::Unzipper;
+        use test_support::unit_test::UnitTest;
+
+        let unit_test = UnitTest::new("unzipper_unsupported");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("custom.zip");
+        let expected = b"Content compressed with a custom method.\n";
+
+        let mut unzipper = Unzipper::new(&file).unwrap();
+        assert_eq!(
+            unzipper
+                .metadata("custom.txt")
+                .unwrap()
+                .unsupported_features(),
+            [UnsupportedFeature::CompressionMethod(200)]
+        );
+        assert!(matches!(
+            unzipper.get_file("custom.txt"),
+            Err(UnzipperError::Unsupported { .. })
+        ));
+        unzipper.register_decompressor(200, || Inverted);
+        assert!(unzipper
+            .metadata("custom.txt")
+            .unwrap()
+            .unsupported_features()
+            .is_empty());
+        assert_eq!(unzipper.get_file("custom.txt").unwrap(), expected);
+
+        let options = UnzipperOptions::new().decompressor(200, || Inverted);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        assert_eq!(unzipper.get_file("custom.txt").unwrap(), expected);
+    }
+}
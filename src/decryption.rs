@@ -0,0 +1,252 @@
+//! Decryption Module.
+//!
+//! This module provides the extension point through which applications decrypt the entries of their own
+//! encryption schemes, such as DRM wrappers or corporate encryption. A provider recognizes the entries it
+//! handles and creates their decryptor, while the Unzipper reads the header preceding the encrypted data,
+//! the encrypted data itself and the trailer following it. The data decrypted is then decompressed as usual.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::entry::EntryInfo;
+use crate::error::UnzipperError;
+
+/// Trait implemented by the decryptors of the entries, created by a DecryptionProvider for each entry read.
+pub trait Decryptor: Send {
+    /// Decrypts a chunk of encrypted data in place. The chunks are given in order.
+    ///
+    /// # Arguments
+    /// * `data` - The encrypted data, replaced by the decrypted data.
+    fn decrypt(&mut self, data: &mut [u8]);
+
+    /// Called once all the encrypted data has been decrypted, to verify the trailer of the entry.
+    ///
+    /// # Arguments
+    /// * `trailer` - The bytes following the encrypted data, as many as given by the provider `trailer_size()`.
+    ///
+    /// # Returns
+    /// A Result indicating success, or an error such as `UnzipperError::AuthenticationFailed`
+    /// if the data was tampered with.
+    fn finish(&mut self, trailer: &[u8]) -> Result<(), UnzipperError> {
+        let _ = trailer;
+        Ok(())
+    }
+}
+
+/// Trait implemented by the providers of an encryption scheme.
+///
+/// A provider is registered with `UnzipperOptions::decryption_provider()` or
+/// `Unzipper::register_decryption_provider()`. It is consulted for each entry read, before the built-in
+/// ZipCrypto and WinZip AES schemes, whether or not the entry has its encryption flag set.
+pub trait DecryptionProvider: Send + Sync {
+    /// Returns true if the provider decrypts the entry.
+    fn handles(&self, entry: &EntryInfo) -> bool;
+
+    /// Returns the number of bytes preceding the encrypted data of the entry, such as a salt or a key check.
+    fn header_size(&self, entry: &EntryInfo) -> usize {
+        let _ = entry;
+        0
+    }
+
+    /// Returns the number of bytes following the encrypted data of the entry, such as an authentication code.
+    fn trailer_size(&self, entry: &EntryInfo) -> usize {
+        let _ = entry;
+        0
+    }
+
+    /// Creates the decryptor of an entry.
+    ///
+    /// # Arguments
+    /// * `entry` - The entry to decrypt.
+    /// * `header` - The bytes preceding the encrypted data, as many as given by `header_size()`.
+    /// * `password` - The password given to read the entry, if any.
+    ///
+    /// # Returns
+    /// A Result containing the decryptor, or an error such as `UnzipperError::PasswordRequired`
+    /// or `UnzipperError::InvalidPassword`.
+    fn new_decryptor(
+        &self,
+        entry: &EntryInfo,
+        header: &[u8],
+        password: Option<&[u8]>,
+    ) -> Result<Box<dyn Decryptor>, UnzipperError>;
+}
+
+/// Struct that holds the decryption providers registered by the application, in the order of registration.
+#[derive(Clone, Default)]
+pub(crate) struct DecryptionProviders {
+    providers: Vec<Arc<dyn DecryptionProvider>>,
+}
+
+impl fmt::Debug for DecryptionProviders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecryptionProviders")
+            .field("count", &self.providers.len())
+            .finish()
+    }
+}
+
+impl DecryptionProviders {
+    /// Registers a decryption provider, consulted after the ones previously registered.
+    pub(crate) fn register(&mut self, provider: impl DecryptionProvider + 'static) {
+        self.providers.push(Arc::new(provider));
+    }
+
+    /// Returns the first provider handling the entry, if any.
+    pub(crate) fn find(&self, entry: &EntryInfo) -> Option<Arc<dyn DecryptionProvider>> {
+        self.providers
+            .iter()
+            .find(|provider| provider.handles(entry))
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod decryption_tests {
+    use super::*;
+
+    use std::io::{Read, Seek, SeekFrom};
+    use std::path::PathBuf;
+
+    use crate::options::UnzipperOptions;
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    /// Scheme XORing the data with the first byte of the password, checked by the last byte of the header.
+    struct XorProvider;
+
+    struct XorDecryptor {
+        key: u8,
+    }
+
+    impl Decryptor for XorDecryptor {
+        fn decrypt(&mut self, data: &mut [u8]) {
+            data.iter_mut().for_each(|byte| *byte ^= self.key);
+        }
+
+        fn finish(&mut self, trailer: &[u8]) -> Result<(), UnzipperError> {
+            match trailer {
+                b"END" => Ok(()),
+                _ => Err(UnzipperError::AuthenticationFailed("trailer".to_string())),
+            }
+        }
+    }
+
+    impl DecryptionProvider for XorProvider {
+        fn handles(&self, entry: &EntryInfo) -> bool {
+            entry.is_encrypted() && entry.name().starts_with("secret")
+        }
+
+        fn header_size(&self, _entry: &EntryInfo) -> usize {
+            4
+        }
+
+        fn trailer_size(&self, _entry: &EntryInfo) -> usize {
+            3
+        }
+
+        fn new_decryptor(
+            &self,
+            entry: &EntryInfo,
+            header: &[u8],
+            password: Option<&[u8]>,
+        ) -> Result<Box<dyn Decryptor>, UnzipperError> {
+            let key = match password {
+                Some([key, ..]) => *key,
+                _ => return Err(UnzipperError::PasswordRequired(entry.name().to_string())),
+            };
+            if header != [b'X', b'O', b'R', key] {
+                return Err(UnzipperError::InvalidPassword(entry.name().to_string()));
+            }
+            Ok(Box::new(XorDecryptor { key }))
+        }
+    }
+
+    /// Same scheme, applied to the entries whose encryption flag is not set, taking the key from the header.
+    struct UnflaggedXorProvider;
+
+    impl DecryptionProvider for UnflaggedXorProvider {
+        fn handles(&self, entry: &EntryInfo) -> bool {
+            entry.name().starts_with("secret")
+        }
+
+        fn header_size(&self, _entry: &EntryInfo) -> usize {
+            4
+        }
+
+        fn trailer_size(&self, _entry: &EntryInfo) -> usize {
+            3
+        }
+
+        fn new_decryptor(
+            &self,
+            _entry: &EntryInfo,
+            header: &[u8],
+            _password: Option<&[u8]>,
+        ) -> Result<Box<dyn Decryptor>, UnzipperError> {
+            Ok(Box::new(XorDecryptor { key: header[3] }))
+        }
+    }
+
+    #[test]
+    fn test_decryption_provider() {
+        let unit_test = UnitTest::new("unzipper_unsupported");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("custom_encryption.zip");
+        let expected = b"Content encrypted with a custom scheme.\n";
+
+        // Without the provider, the entry is taken as encrypted with ZipCrypto
+        let mut unzipper = Unzipper::new(&file).unwrap();
+        assert!(unzipper.get_file_with_password("secret.txt", b"Z").is_err());
+
+        unzipper.register_decryption_provider(XorProvider);
+        assert_eq!(
+            unzipper.get_file_with_password("secret.txt", b"Z").unwrap(),
+            expected
+        );
+        assert!(matches!(
+            unzipper.get_file("secret.txt"),
+            Err(UnzipperError::PasswordRequired(_))
+        ));
+        assert!(matches!(
+            unzipper.get_file_with_password("secret.txt", b"Y"),
+            Err(UnzipperError::InvalidPassword(_))
+        ));
+        assert_eq!(
+            unzipper.get_file("public.txt").unwrap(),
+            b"Public content.\n"
+        );
+
+        let options = UnzipperOptions::new().decryption_provider(XorProvider);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        let mut reader = unzipper
+            .entry_reader_with_password("secret.txt", b"Z")
+            .unwrap();
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).unwrap();
+        assert_eq!(content, expected);
+
+        // A stored entry handled by a provider is decrypted even if its encryption flag is not set
+        let mut bytes = std::fs::read(&file).unwrap();
+        bytes[6] &= !0x01;
+        let central_pos = bytes.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+        bytes[central_pos + 8] &= !0x01;
+        let options = UnzipperOptions::new().decryption_provider(UnflaggedXorProvider);
+        let unzipper =
+            Unzipper::from_reader_with_options(std::io::Cursor::new(bytes), options).unwrap();
+        assert_eq!(
+            unzipper.read_range("secret.txt", 8, 9).unwrap(),
+            &expected[8..17]
+        );
+        assert_eq!(unzipper.get_file("secret.txt").unwrap(), expected);
+
+        // Also once its data has been read to the end, the cipher being dropped
+        let mut reader = unzipper.seekable_entry_reader("secret.txt").unwrap();
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).unwrap();
+        assert_eq!(content, expected);
+        reader.seek(SeekFrom::Start(8)).unwrap();
+        let mut chunk = [0u8; 9];
+        reader.read_exact(&mut chunk).unwrap();
+        assert_eq!(chunk, expected[8..17]);
+    }
+}
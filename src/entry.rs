@@ -0,0 +1,298 @@
+//! Entry Module.
+//!
+//! This module provides the public description of the file entries found in a zip archive.
+//! It allows callers to build their own listings of the archive content without extracting anything.
+
+use std::time::SystemTime;
+
+use crate::datetime::{DosDateTime, Timestamps};
+use crate::error::UnsupportedFeature;
+use crate::options::UnzipperOptions;
+use crate::unzipper::FileEntry;
+
+/// Enum that describes the kind of a file entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EntryKind {
+    /// A regular file.
+    File,
+    /// A directory.
+    Directory,
+    /// A symbolic link. Its data is the path of the link target.
+    Symlink,
+}
+
+/// Enum that describes the order in which the file entries are iterated by `Unzipper::entries_in_order()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EntryOrder {
+    /// The order of the central directory, as given by `Unzipper::entries()`.
+    #[default]
+    Archive,
+    /// The order of the paths, compared character by character.
+    Name,
+    /// The natural order of the paths, in which the numbers are compared by value, so that `page2.png`
+    /// comes before `page10.png`, as expected for the pages of comic book archives.
+    Natural,
+}
+
+/// Struct that describes a file entry of a zip archive, as recorded in its central directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntryInfo {
+    name: String,
+    compressed_size: u32,
+    size: u32,
+    method: u16,
+    crc32: u32,
+    offset: u32,
+    flags: u16,
+    made_by: u16,
+    external_attributes: u32,
+    last_mod_time: u16,
+    last_mod_date: u16,
+    timestamps: Timestamps,
+    unix_mode: Option<u32>,
+    kind: EntryKind,
+    unsupported_features: Vec<UnsupportedFeature>,
+}
+
+impl EntryInfo {
+    /// Creates a new EntryInfo from a central directory file entry.
+    ///
+    /// # Arguments
+    /// * `name` - The cleaned path of the entry in the zip archive.
+    /// * `file_entry` - The file entry as read from the central directory.
+    /// * `options` - The options of the Unzipper, whose decompressors and decryption providers
+    ///   support the entries they handle.
+    pub(crate) fn new(name: &str, file_entry: &FileEntry, options: &UnzipperOptions) -> EntryInfo {
+        let mut info = EntryInfo {
+            name: name.to_string(),
+            compressed_size: file_entry.compressed_size,
+            size: file_entry.size,
+            method: file_entry.method,
+            crc32: file_entry.crc32,
+            offset: file_entry.start_pos,
+            flags: file_entry.flags,
+            made_by: file_entry.made_by,
+            external_attributes: file_entry.external_attr,
+            last_mod_time: file_entry.last_mod_time,
+            last_mod_date: file_entry.last_mod_date,
+            timestamps: file_entry.timestamps,
+            unix_mode: file_entry.unix_mode(),
+            kind: file_entry.kind(),
+            unsupported_features: Vec::new(),
+        };
+        let decrypted = options.decryption_providers.find(&info).is_some();
+        info.unsupported_features =
+            file_entry.unsupported_features(&options.decompressors, decrypted);
+        info
+    }
+
+    /// Returns the cleaned path of the entry in the zip archive.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the size of the entry data in the zip file.
+    pub fn compressed_size(&self) -> u32 {
+        self.compressed_size
+    }
+
+    /// Returns the size of the entry once decompressed.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the compression method of the entry (0 = not compressed, 8 = DEFLATE).
+    ///
+    /// For entries encrypted with WinZip AES, the method of the data once decrypted is returned.
+    pub fn method(&self) -> u16 {
+        self.method
+    }
+
+    /// Returns the CRC-32 of the decompressed data, as recorded in the central directory.
+    ///
+    /// It is 0 for entries encrypted with WinZip AES AE-2, which do not record it.
+    pub fn crc32(&self) -> u32 {
+        self.crc32
+    }
+
+    /// Returns the position of the entry local header in the zip file.
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Returns the general purpose bit flag of the entry.
+    pub fn flags(&self) -> u16 {
+        self.flags
+    }
+
+    /// Returns the "version made by" field of the entry. Its upper byte identifies the host system
+    /// (0 = MS-DOS, 3 = Unix, 10 = NTFS, 19 = OS X), which gives the meaning of the external attributes.
+    pub fn version_made_by(&self) -> u16 {
+        self.made_by
+    }
+
+    /// Returns the external attributes of the entry, whose meaning depends on the host system.
+    ///
+    /// The lower byte holds the MS-DOS attributes, and for Unix hosts, the upper 16 bits hold the Unix mode.
+    pub fn external_attributes(&self) -> u32 {
+        self.external_attributes
+    }
+
+    /// Returns true if the entry is encrypted, and then requires a password to be read.
+    pub fn is_encrypted(&self) -> bool {
+        self.flags & 0x0001 != 0
+    }
+
+    /// Returns the capabilities needed to read the entry that are not available, such as its compression
+    /// method or encryption. The entry can be read, given its password if encrypted, when it is empty.
+    pub fn unsupported_features(&self) -> &[UnsupportedFeature] {
+        &self.unsupported_features
+    }
+
+    /// Returns true if the CRC-32 and sizes of the entry are stored in a data descriptor following its data,
+    /// as done by streaming writers. The values of the central directory are used to read such entries.
+    pub fn has_data_descriptor(&self) -> bool {
+        self.flags & 0x0008 != 0
+    }
+
+    /// Returns the last modification time of the entry.
+    ///
+    /// # Returns
+    /// The modification time of the NTFS or extended timestamp extra fields when present. Otherwise, the MS-DOS date
+    /// and time of the entry, interpreted as UTC, with a resolution of 2 seconds. None if it is invalid.
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.timestamps.modified.or_else(|| {
+            DosDateTime::new(self.last_mod_date, self.last_mod_time)
+                .map(DosDateTime::to_system_time)
+        })
+    }
+
+    /// Returns the last access time of the entry, if recorded in its extra fields.
+    pub fn accessed(&self) -> Option<SystemTime> {
+        self.timestamps.accessed
+    }
+
+    /// Returns the creation time of the entry, if recorded in its extra fields.
+    pub fn created(&self) -> Option<SystemTime> {
+        self.timestamps.created
+    }
+
+    /// Returns the last modification date and time of the entry, as recorded in the zip archive.
+    ///
+    /// # Returns
+    /// The MS-DOS date and time of the entry, without time zone, or None if it is invalid.
+    ///
+    /// This method is available with the `chrono` feature.
+    #[cfg(feature = "chrono")]
+    pub fn modified_date_time(&self) -> Option<chrono::NaiveDateTime> {
+        DosDateTime::new(self.last_mod_date, self.last_mod_time)?.to_naive_date_time()
+    }
+
+    /// Returns the MS-DOS date and time of the entry, or None if it is invalid.
+    pub(crate) fn dos_date_time(&self) -> Option<DosDateTime> {
+        DosDateTime::new(self.last_mod_date, self.last_mod_time)
+    }
+
+    /// Returns the Unix mode of the entry, holding its file type and permission bits (e.g. 0o100755).
+    ///
+    /// # Returns
+    /// The mode stored in the external attributes, or None if the entry was not made on a Unix system.
+    pub fn unix_mode(&self) -> Option<u32> {
+        self.unix_mode
+    }
+
+    /// Returns the kind of the entry: regular file, directory or symbolic link.
+    ///
+    /// The kind is given by the Unix file type of the entry. Otherwise, entries whose name ends with '/'
+    /// or having the MS-DOS directory attribute are directories.
+    pub fn kind(&self) -> EntryKind {
+        self.kind
+    }
+
+    /// Returns true if the entry is a directory. Its name is given without the trailing '/'.
+    pub fn is_dir(&self) -> bool {
+        self.kind == EntryKind::Directory
+    }
+}
+
+/// Struct that holds the still-compressed data of a file entry, as returned by `Unzipper::get_raw()`.
+///
+/// The data can be re-served as is (e.g. deflate data to an HTTP client accepting it), or copied to another
+/// archive without being decompressed and compressed again, using the method and CRC-32 of the entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawEntry {
+    info: EntryInfo,
+    data: Vec<u8>,
+}
+
+impl RawEntry {
+    /// Creates a new RawEntry from the description of an entry and its data.
+    pub(crate) fn new(info: EntryInfo, data: Vec<u8>) -> RawEntry {
+        RawEntry { info, data }
+    }
+
+    /// Returns the description of the entry, as recorded in the central directory.
+    pub fn info(&self) -> &EntryInfo {
+        &self.info
+    }
+
+    /// Returns the compressed data of the entry. For an encrypted entry, it is still encrypted,
+    /// and starts with the encryption header.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consumes the RawEntry, returning its compressed data.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Returns the compression method of the data (0 = not compressed, 8 = DEFLATE).
+    pub fn method(&self) -> u16 {
+        self.info.method
+    }
+
+    /// Returns the CRC-32 of the decompressed data, as recorded in the central directory.
+    pub fn crc32(&self) -> u32 {
+        self.info.crc32
+    }
+
+    /// Returns the size of the data once decompressed.
+    pub fn size(&self) -> u32 {
+        self.info.size
+    }
+}
+
+#[cfg(test)]
+mod entry_tests {
+    #[cfg(feature = "json")]
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_entry_serde() {
+        use std::path::PathBuf;
+
+        use crate::unzipper::Unzipper;
+        use test_support::unit_test::UnitTest;
+
+        let unit_test = UnitTest::new("unzipper_timestamps");
+        for name in ["extended_timestamp.zip", "ntfs_timestamp.zip"] {
+            let file = PathBuf::from(unit_test.test_case_folder()).join(name);
+            let unzipper = Unzipper::new(&file).unwrap();
+            for entry in unzipper.entries() {
+                let json = serde_json::to_string(&entry).unwrap();
+                let restored: EntryInfo = serde_json::from_str(&json).unwrap();
+                assert_eq!(restored, entry);
+                assert_eq!(restored.modified(), entry.modified());
+            }
+        }
+
+        let json = serde_json::to_string(&EntryKind::Symlink).unwrap();
+        assert_eq!(json, "\"Symlink\"");
+    }
+}
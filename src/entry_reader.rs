@@ -0,0 +1,838 @@
+//! Entry Reader Module.
+//!
+//! This module provides a streaming reader over the content of a single file entry of a zip archive.
+//! The entry data is read from the archive in chunks and decompressed on the fly, so that large entries
+//! can be processed without holding them fully in memory.
+
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::sync::atomic::Ordering;
+
+use crate::crc32::Crc32;
+use crate::decompressor::Decompressor;
+use crate::decryption::{DecryptionProvider, Decryptor};
+use crate::entry::EntryInfo;
+use crate::error::UnzipperError;
+use crate::options::ParseMode;
+use crate::secret::{wipe, Secret};
+use crate::unzipper::{FileEntry, Unzipper};
+use crate::warning::Warning;
+#[cfg(feature = "crypto")]
+use crate::winzip_aes::{WinZipAes, AUTHENTICATION_CODE_SIZE, PASSWORD_VERIFIER_SIZE};
+use crate::zipcrypto::{ZipCrypto, ENCRYPTION_HEADER_SIZE};
+
+/// Largest capacity reserved ahead for the whole content of an entry, the rest growing as the data is read.
+const MAX_PREALLOCATION: usize = 1024 * 1024;
+
+/// Cipher used to decrypt the data of an encrypted entry.
+enum Cipher {
+    ZipCrypto(ZipCrypto),
+    #[cfg(feature = "crypto")]
+    WinZipAes(Box<WinZipAes>),
+    Custom {
+        decryptor: Box<dyn Decryptor>,
+        trailer_size: usize,
+    },
+}
+
+/// Struct that provides streaming access to the decompressed content of a file entry.
+///
+/// It is obtained through `Unzipper::entry_reader()` and implements `std::io::Read`.
+/// The compressed data is read from the archive in chunks of the buffer size of the options as the reader
+/// is consumed.
+/// Unless disabled in the options, the CRC-32 of the data is verified once the end of the entry is reached,
+/// and an `UnzipperError::CrcMismatch` is returned as the inner error if it does not match the one stored in the archive.
+/// When a progress receiver is installed in the Unzipper, it is notified as the data is decompressed.
+/// When the cancellation token of the options is set, the next read returns `UnzipperError::Cancelled`.
+/// The decompression limits of the options are verified as the data is decompressed, and an
+/// `UnzipperError::LimitExceeded` is returned as the inner error as soon as one of them is exceeded.
+pub struct EntryReader<'a, R: Read + Seek = File> {
+    unzipper: &'a Unzipper<R>,
+    file_path: String,
+    position: usize,  // next compressed byte to read in the zip file
+    remaining: usize, // compressed bytes not yet read from the zip file
+    size: usize,      // once decompressed
+    crc32: u32,       // expected CRC-32 of the decompressed data
+    crc: Option<Crc32>,
+    cipher: Option<Cipher>,
+    decompressor: Option<Box<dyn Decompressor>>,
+    buffer: Vec<u8>,
+    buffer_pos: usize,
+    buffer_len: usize,
+    finished: bool,
+    progress_entry: Option<EntryInfo>, // reported to the progress receiver, if any
+    compressed_size: u64,              // in the zip file, including any encryption header
+    bytes_decompressed: u64,
+    data_pos: usize, // of the entry data in the zip file, including any encryption header
+    file_entry: FileEntry, // as read from the central directory, to restart the reading
+    password: Option<Secret>, // to restart the reading of an encrypted entry
+    encrypted: bool, // by a cipher or a provider, the buffer then holding decrypted data to be wiped
+}
+
+/// Cipher of an encrypted entry, with the sizes of the data surrounding the encrypted data.
+struct OpenedCipher {
+    cipher: Cipher,
+    header_size: usize,  // before the encrypted data
+    trailer_size: usize, // after the encrypted data
+}
+
+impl<'a, R: Read + Seek> EntryReader<'a, R> {
+    /// Creates a new EntryReader for the entry data located at the specified position.
+    ///
+    /// # Arguments
+    /// * `unzipper` - The Unzipper owning the zip file, with the file entry already opened.
+    /// * `file_path` - The cleaned path of the entry in the zip archive.
+    /// * `position` - The position of the entry data in the zip file.
+    /// * `file_entry` - The file entry as read from the central directory.
+    /// * `password` - The password used to decrypt the entry, if it is encrypted.
+    ///
+    /// # Returns
+    /// A Result containing the EntryReader, or an error if the entry is encrypted and the password is missing or wrong.
+    pub(crate) fn new(
+        unzipper: &'a Unzipper<R>,
+        file_path: &str,
+        position: usize,
+        file_entry: &FileEntry,
+        password: Option<&[u8]>,
+    ) -> Result<EntryReader<'a, R>, UnzipperError> {
+        let data_pos = position;
+        let mut position = position;
+        let mut remaining = file_entry.compressed_size as usize;
+
+        let info = EntryInfo::new(file_path, file_entry, &unzipper.options);
+        let cipher = if let Some(cipher) =
+            Self::open_cipher(unzipper, &info, file_entry, password, position)?
+        {
+            position += cipher.header_size;
+            remaining = remaining
+                .checked_sub(cipher.header_size + cipher.trailer_size)
+                .ok_or_else(|| {
+                    UnzipperError::InvalidArchive(format!(
+                        "Encrypted entry is too small: {file_path}"
+                    ))
+                })?;
+            Some(cipher.cipher)
+        } else {
+            None
+        };
+        let encrypted = cipher.is_some();
+
+        let decompressor = unzipper
+            .options
+            .decompressors
+            .new_decompressor(file_entry.method, file_entry.size as u64)?;
+        let mut crc32 = file_entry.crc32;
+
+        // The CRC-32 is not stored for WinZip AES entries of the AE-2 kind
+        let crc = if unzipper.options.verify_crc
+            && file_entry
+                .aes
+                .is_none_or(|aes_extra| aes_extra.version != 2)
+        {
+            Some(Crc32::new())
+        } else {
+            None
+        };
+
+        // With a data descriptor (bit 3), the CRC-32 it contains must match the one of the central directory,
+        // and is used instead when the central directory does not provide it
+        if crc.is_some() && file_entry.flags & 0x0008 != 0 {
+            let descriptor = unzipper
+                .read_data_descriptor(data_pos + file_entry.compressed_size as usize, file_entry)?;
+            if crc32 == 0 {
+                crc32 = descriptor.crc32;
+            } else if descriptor.crc32 != crc32 {
+                // The lenient mode keeps the CRC-32 of the central directory
+                unzipper.tolerate_warning(
+                    Warning::new(
+                        Some(file_path),
+                        "Data descriptor does not match the central directory",
+                    ),
+                    ParseMode::Lenient,
+                )?;
+            }
+        }
+
+        let progress_entry = match unzipper.lock_progress() {
+            Some(mut progress) => {
+                progress.entry_started(&info);
+                Some(info)
+            }
+            None => None,
+        };
+
+        Ok(EntryReader {
+            unzipper,
+            file_path: file_path.to_string(),
+            position,
+            remaining,
+            size: file_entry.size as usize,
+            crc32,
+            crc,
+            cipher,
+            decompressor,
+            buffer: Vec::new(),
+            buffer_pos: 0,
+            buffer_len: 0,
+            finished: false,
+            progress_entry,
+            compressed_size: file_entry.compressed_size as u64,
+            bytes_decompressed: 0,
+            data_pos,
+            file_entry: file_entry.clone(),
+            password: password.map(Secret::from),
+            encrypted,
+        })
+    }
+
+    /// Checks the password of an entry, without reading its data.
+    ///
+    /// # Arguments
+    /// * `unzipper` - The Unzipper owning the zip file.
+    /// * `file_path` - The cleaned path of the entry in the zip archive.
+    /// * `position` - The position of the entry data in the zip file.
+    /// * `file_entry` - The file entry as read from the central directory.
+    /// * `password` - The password to check, if any.
+    ///
+    /// # Returns
+    /// A Result indicating success, also for an entry that is not encrypted, or an error if the password
+    /// is missing or wrong.
+    pub(crate) fn check_password(
+        unzipper: &Unzipper<R>,
+        file_path: &str,
+        position: usize,
+        file_entry: &FileEntry,
+        password: Option<&[u8]>,
+    ) -> Result<(), UnzipperError> {
+        let info = EntryInfo::new(file_path, file_entry, &unzipper.options);
+        Self::open_cipher(unzipper, &info, file_entry, password, position).map(|_| ())
+    }
+
+    /// Opens the cipher of an entry, with the decryption provider handling it or the scheme of the entry.
+    ///
+    /// # Returns
+    /// A Result containing the cipher, None if the entry is not encrypted, or an error if the password
+    /// is missing or wrong.
+    fn open_cipher(
+        unzipper: &Unzipper<R>,
+        info: &EntryInfo,
+        file_entry: &FileEntry,
+        password: Option<&[u8]>,
+        position: usize,
+    ) -> Result<Option<OpenedCipher>, UnzipperError> {
+        let file_path = info.name();
+        if let Some(provider) = unzipper.options.decryption_providers.find(info) {
+            return Self::open_custom(unzipper, info, provider.as_ref(), password, position)
+                .map(Some);
+        }
+        if file_entry.flags & 0x0001 == 0 {
+            return Ok(None);
+        }
+        let password = match password {
+            Some(password) => password,
+            None => return Err(UnzipperError::PasswordRequired(file_path.to_string())),
+        };
+        let cipher = if file_entry.aes.is_some() {
+            Self::open_winzip_aes(unzipper, file_path, file_entry, password, position)?
+        } else {
+            Self::open_zipcrypto(unzipper, file_path, file_entry, password, position)?
+        };
+        Ok(Some(cipher))
+    }
+
+    /// Reads the ZipCrypto encryption header of an entry and checks the password against it.
+    ///
+    /// # Returns
+    /// A Result containing the cipher, or an error if the password is wrong.
+    fn open_zipcrypto(
+        unzipper: &Unzipper<R>,
+        file_path: &str,
+        file_entry: &FileEntry,
+        password: &[u8],
+        position: usize,
+    ) -> Result<OpenedCipher, UnzipperError> {
+        let mut header = [0u8; ENCRYPTION_HEADER_SIZE];
+        unzipper.get_data(&mut header, position, "encryption header")?;
+
+        // With a data descriptor (bit 3), the check byte comes from the modification time
+        let check_byte = if file_entry.flags & 0x0008 != 0 {
+            (file_entry.last_mod_time >> 8) as u8
+        } else {
+            (file_entry.crc32 >> 24) as u8
+        };
+
+        let mut cipher = ZipCrypto::new(password);
+        if !cipher.check_header(&header, check_byte) {
+            return Err(UnzipperError::InvalidPassword(file_path.to_string()));
+        }
+        Ok(OpenedCipher {
+            cipher: Cipher::ZipCrypto(cipher),
+            header_size: ENCRYPTION_HEADER_SIZE,
+            trailer_size: 0,
+        })
+    }
+
+    /// Reads the header of an entry handled by a decryption provider and creates its decryptor.
+    ///
+    /// # Returns
+    /// A Result containing the cipher, or the error of the provider.
+    fn open_custom(
+        unzipper: &Unzipper<R>,
+        info: &EntryInfo,
+        provider: &dyn DecryptionProvider,
+        password: Option<&[u8]>,
+        position: usize,
+    ) -> Result<OpenedCipher, UnzipperError> {
+        let header_size = provider.header_size(info);
+        let trailer_size = provider.trailer_size(info);
+        if header_size + trailer_size > info.compressed_size() as usize {
+            return Err(UnzipperError::InvalidArchive(format!(
+                "Encrypted entry is too small: {}",
+                info.name()
+            )));
+        }
+        let mut header = vec![0u8; header_size];
+        unzipper.get_data(&mut header, position, "encryption header")?;
+        let decryptor = provider.new_decryptor(info, &header, password)?;
+        Ok(OpenedCipher {
+            cipher: Cipher::Custom {
+                decryptor,
+                trailer_size,
+            },
+            header_size,
+            trailer_size,
+        })
+    }
+
+    /// Reads the salt and password verifier of a WinZip AES entry and derives the decryption keys.
+    ///
+    /// # Returns
+    /// A Result containing the cipher, or an error if the password is wrong or if the `crypto` feature is disabled.
+    #[cfg(feature = "crypto")]
+    fn open_winzip_aes(
+        unzipper: &Unzipper<R>,
+        file_path: &str,
+        file_entry: &FileEntry,
+        password: &[u8],
+        position: usize,
+    ) -> Result<OpenedCipher, UnzipperError> {
+        let aes_extra = match file_entry.aes {
+            Some(ref aes_extra) => aes_extra,
+            None => return Err(UnzipperError::UnsupportedMethod(crate::extra::AES_METHOD)),
+        };
+        let salt_length = aes_extra.salt_length();
+        let header_size = salt_length + PASSWORD_VERIFIER_SIZE;
+        let mut header = [0u8; 16 + PASSWORD_VERIFIER_SIZE];
+        unzipper.get_data(&mut header[..header_size], position, "encryption header")?;
+        let (salt, verifier) = header[..header_size].split_at(salt_length);
+        let verifier = verifier.try_into().unwrap_or(&[0; PASSWORD_VERIFIER_SIZE]);
+
+        match WinZipAes::new(password, aes_extra, salt, verifier) {
+            Some(cipher) => Ok(OpenedCipher {
+                cipher: Cipher::WinZipAes(Box::new(cipher)),
+                header_size,
+                trailer_size: AUTHENTICATION_CODE_SIZE,
+            }),
+            None => Err(UnzipperError::InvalidPassword(file_path.to_string())),
+        }
+    }
+
+    /// Reads the salt and password verifier of a WinZip AES entry and derives the decryption keys.
+    ///
+    /// # Returns
+    /// A Result containing the cipher, or an error if the password is wrong or if the `crypto` feature is disabled.
+    #[cfg(not(feature = "crypto"))]
+    fn open_winzip_aes(
+        _unzipper: &Unzipper<R>,
+        file_path: &str,
+        _file_entry: &FileEntry,
+        _password: &[u8],
+        _position: usize,
+    ) -> Result<OpenedCipher, UnzipperError> {
+        Err(UnzipperError::Unsupported {
+            file_path: file_path.to_string(),
+            features: vec![crate::error::UnsupportedFeature::AesEncryption],
+        })
+    }
+
+    /// Returns the size of the entry once decompressed, as recorded in the central directory.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the capacity to reserve for the whole content of the entry.
+    ///
+    /// The size recorded in the central directory is bounded by the entry size limit and by `MAX_PREALLOCATION`,
+    /// as a crafted size would otherwise allocate up to 4 GiB before any data is decompressed.
+    pub(crate) fn output_capacity(&self) -> usize {
+        let max_entry_size = self.unzipper.options.max_entry_size.unwrap_or(u64::MAX);
+        self.size
+            .min(usize::try_from(max_entry_size).unwrap_or(usize::MAX))
+            .min(MAX_PREALLOCATION)
+    }
+
+    /// Restarts the reading at the start of the entry content.
+    ///
+    /// # Returns
+    /// A Result indicating success, or an error if the entry could not be opened again.
+    pub(crate) fn rewind(&mut self) -> Result<(), UnzipperError> {
+        // A new reader provides the initial state, its cipher, decompressor and progress entry being taken
+        let mut reader = EntryReader::new(
+            self.unzipper,
+            &self.file_path,
+            self.data_pos,
+            &self.file_entry,
+            self.password.as_ref().map(Secret::as_bytes),
+        )?;
+        self.position = reader.position;
+        self.remaining = reader.remaining;
+        self.crc32 = reader.crc32;
+        self.crc = reader.crc.take();
+        self.cipher = reader.cipher.take();
+        self.decompressor = reader.decompressor.take();
+        self.progress_entry = reader.progress_entry.take();
+        self.buffer_pos = 0;
+        self.buffer_len = 0;
+        self.finished = false;
+        self.bytes_decompressed = 0;
+        Ok(())
+    }
+
+    /// Moves the reading to a position of the content of a stored entry that is neither encrypted nor handled
+    /// by a decryption provider, without reading the data before it.
+    ///
+    /// The CRC-32 of the entry is not verified once the reading has been moved, as the data skipped is not read.
+    ///
+    /// # Arguments
+    /// * `offset` - The position in the entry content, bounded by the size of the entry.
+    ///
+    /// # Returns
+    /// True if the reading was moved, false if the entry is compressed or encrypted.
+    pub(crate) fn seek_stored(&mut self, offset: u64) -> bool {
+        // The cipher is dropped once the data is read, the entry still being encrypted
+        if self.decompressor.is_some() || self.encrypted || self.file_entry.flags & 0x0001 != 0 {
+            return false;
+        }
+        let offset = offset.min(self.compressed_size) as usize;
+        self.position = self.data_pos + offset;
+        self.remaining = self.compressed_size as usize - offset;
+        self.crc = None;
+        self.finished = false;
+        self.bytes_decompressed = offset as u64;
+        true
+    }
+
+    /// Verifies the CRC-32 of the decompressed data, once the end of the entry has been reached.
+    fn check_crc(&self) -> Result<(), UnzipperError> {
+        match self.crc {
+            Some(ref crc) if crc.finalize() != self.crc32 => Err(UnzipperError::CrcMismatch {
+                file_path: self.file_path.clone(),
+                expected: self.crc32,
+                actual: crc.finalize(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Verifies the decompressed size against the one recorded in the central directory, once the end
+    /// of the entry has been reached.
+    fn check_size(&mut self) -> Result<(), UnzipperError> {
+        if self.bytes_decompressed == self.size as u64 {
+            return Ok(());
+        }
+        self.unzipper.tolerate_warning(
+            Warning::new(
+                Some(&self.file_path),
+                "Decompressed size does not match the central directory",
+            ),
+            ParseMode::Standard,
+        )
+    }
+
+    /// Verifies the decompression limits of the options, once `length` more bytes have been decompressed.
+    fn check_limits(&self, length: usize) -> Result<(), UnzipperError> {
+        let options = &self.unzipper.options;
+        let exceeded = |limit: &'static str| {
+            Err(UnzipperError::LimitExceeded {
+                file_path: self.file_path.clone(),
+                limit,
+            })
+        };
+
+        if options
+            .max_entry_size
+            .is_some_and(|max| self.bytes_decompressed > max)
+        {
+            return exceeded("entry size");
+        }
+        if options.max_compression_ratio.is_some_and(|ratio| {
+            self.bytes_decompressed > ratio as u64 * self.compressed_size.max(1)
+        }) {
+            return exceeded("compression ratio");
+        }
+
+        let total = self
+            .unzipper
+            .total_decompressed
+            .fetch_add(length as u64, Ordering::Relaxed)
+            + length as u64;
+        if options.max_total_size.is_some_and(|max| total > max) {
+            return exceeded("total size");
+        }
+        Ok(())
+    }
+
+    /// Reads raw entry data from the zip file into the provided buffer.
+    ///
+    /// # Returns
+    /// The number of bytes read, limited by the amount of entry data remaining in the zip file.
+    fn read_raw(&mut self, buf: &mut [u8]) -> Result<usize, UnzipperError> {
+        let length = std::cmp::min(buf.len(), self.remaining);
+        if length > 0 {
+            self.unzipper
+                .get_data(&mut buf[..length], self.position, "file data")?;
+            match self.cipher {
+                Some(Cipher::ZipCrypto(ref mut cipher)) => cipher.decrypt(&mut buf[..length]),
+                #[cfg(feature = "crypto")]
+                Some(Cipher::WinZipAes(ref mut cipher)) => cipher.decrypt(&mut buf[..length]),
+                Some(Cipher::Custom {
+                    ref mut decryptor, ..
+                }) => decryptor.decrypt(&mut buf[..length]),
+                None => {}
+            }
+            self.position += length;
+            self.remaining -= length;
+        }
+        if self.remaining == 0 {
+            self.check_trailer()?;
+        }
+        Ok(length)
+    }
+
+    /// Verifies the trailer of an encrypted entry, such as the authentication code of a WinZip AES entry,
+    /// once all its encrypted data has been read.
+    fn check_trailer(&mut self) -> Result<(), UnzipperError> {
+        match self.cipher.take() {
+            #[cfg(feature = "crypto")]
+            Some(Cipher::WinZipAes(cipher)) => {
+                let mut code = [0u8; AUTHENTICATION_CODE_SIZE];
+                self.unzipper
+                    .get_data(&mut code, self.position, "authentication code")?;
+                if !cipher.verify(&code) {
+                    return Err(UnzipperError::AuthenticationFailed(self.file_path.clone()));
+                }
+            }
+            Some(Cipher::Custom {
+                mut decryptor,
+                trailer_size,
+            }) => {
+                let mut trailer = vec![0u8; trailer_size];
+                self.unzipper
+                    .get_data(&mut trailer, self.position, "encryption trailer")?;
+                decryptor.finish(&trailer)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Exchanges the internal buffer of the reader with the given one, so that readers opened one after
+    /// the other share a single buffer instead of allocating their own.
+    pub(crate) fn swap_buffer(&mut self, buffer: &mut Vec<u8>) {
+        if self.encrypted {
+            wipe(&mut self.buffer);
+        }
+        std::mem::swap(&mut self.buffer, buffer);
+    }
+
+    /// Refills the internal buffer with the next chunk of compressed data.
+    fn fill_buffer(&mut self) -> Result<(), UnzipperError> {
+        // The buffer is only allocated once data has to be read through it
+        let mut buffer = std::mem::take(&mut self.buffer);
+        if buffer.is_empty() {
+            buffer.resize(self.unzipper.options.buffer_size, 0);
+        }
+        let result = self.read_raw(&mut buffer);
+        self.buffer = buffer;
+        self.buffer_len = result?;
+        self.buffer_pos = 0;
+        Ok(())
+    }
+
+    /// Decompresses entry data into the provided buffer, reading compressed chunks as required.
+    fn read_compressed(&mut self, buf: &mut [u8]) -> Result<usize, UnzipperError> {
+        loop {
+            if self.buffer_pos == self.buffer_len && self.remaining > 0 {
+                self.fill_buffer()?;
+            }
+
+            let decompressor = match self.decompressor {
+                Some(ref mut decompressor) => decompressor,
+                None => {
+                    return Err(UnzipperError::Decompression(
+                        "Decompression state not initialized".to_string(),
+                    ));
+                }
+            };
+
+            // Once all the compressed data has been given, the decompressor is called with no input to finish
+            let input = &self.buffer[self.buffer_pos..self.buffer_len];
+            let ended = input.is_empty();
+            let result = decompressor.decompress(input, buf)?;
+            self.buffer_pos += result.bytes_consumed;
+
+            if result.finished {
+                self.finished = true;
+                return Ok(result.bytes_written);
+            }
+            if result.bytes_written > 0 {
+                return Ok(result.bytes_written);
+            }
+            if ended {
+                return Err(UnzipperError::Decompression(
+                    "Compressed data is truncated".to_string(),
+                ));
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> Read for EntryReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.finished || buf.is_empty() {
+            return Ok(0);
+        }
+        self.unzipper.options.check_cancelled()?;
+
+        let length = if self.decompressor.is_some() {
+            self.read_compressed(buf)?
+        } else {
+            let length = self.read_raw(buf)?;
+            if length == 0 {
+                self.finished = true;
+            }
+            length
+        };
+
+        if let Some(ref mut crc) = self.crc {
+            crc.update(&buf[..length]);
+        }
+        self.bytes_decompressed += length as u64;
+        self.check_limits(length)?;
+        if self.finished {
+            self.check_crc()?;
+            self.check_size()?;
+        }
+
+        if let (Some(entry), Some(mut progress)) =
+            (&self.progress_entry, self.unzipper.lock_progress())
+        {
+            if length > 0 {
+                progress.bytes_decompressed(entry, self.bytes_decompressed);
+            }
+            if self.finished {
+                progress.entry_finished(entry);
+            }
+        }
+        Ok(length)
+    }
+}
+
+impl<R: Read + Seek> Drop for EntryReader<'_, R> {
+    fn drop(&mut self) {
+        if self.encrypted {
+            wipe(&mut self.buffer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod entry_reader_tests {
+    use super::*;
+
+    use std::io::Cursor;
+    use std::path::PathBuf;
+
+    use crate::options::UnzipperOptions;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_entry_reader_cancellation() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        let token = Arc::new(AtomicBool::new(false));
+        let file = PathBuf::from(UnitTest::new("unzipper_open_epub_file").test_case_folder())
+            .join("ebook.epub");
+        let options = UnzipperOptions::new().cancellation(token.clone());
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+
+        let mut reader = unzipper.entry_reader("content.opf").unwrap();
+        let mut buf = [0u8; 1024];
+        assert_eq!(reader.read(&mut buf).unwrap(), 1024);
+        token.store(true, Ordering::Relaxed);
+        let error = UnzipperError::from(reader.read(&mut buf).unwrap_err());
+        assert!(matches!(error, UnzipperError::Cancelled));
+        drop(reader);
+
+        assert!(matches!(
+            unzipper.get_file("mimetype"),
+            Err(UnzipperError::Cancelled)
+        ));
+        let dest = std::env::temp_dir().join("unzipper_entry_reader_cancellation");
+        assert!(matches!(
+            unzipper.extract_all(&dest),
+            Err(UnzipperError::Cancelled)
+        ));
+        let _ = std::fs::remove_dir_all(&dest);
+
+        token.store(false, Ordering::Relaxed);
+        assert!(unzipper.get_file("mimetype").is_ok());
+    }
+
+    #[test]
+    fn test_entry_reader_limits() {
+        let file =
+            PathBuf::from(UnitTest::new("unzipper_limits").test_case_folder()).join("bomb.zip");
+        let limit = |options: UnzipperOptions, file_path: &str| match Unzipper::with_options(
+            &file, options,
+        )
+        .unwrap()
+        .get_file(file_path)
+        {
+            Err(UnzipperError::LimitExceeded { limit, .. }) => Some(limit),
+            Ok(_) => None,
+            Err(error) => panic!("unexpected error: {error}"),
+        };
+
+        // 10 MiB of zeros, compressed more than 1000 times
+        assert_eq!(limit(UnzipperOptions::new(), "zeros.bin"), None);
+        let options = UnzipperOptions::new().max_entry_size(1024 * 1024);
+        assert_eq!(limit(options.clone(), "zeros.bin"), Some("entry size"));
+        assert_eq!(limit(options, "text.txt"), None);
+
+        let options = UnzipperOptions::new().max_compression_ratio(100);
+        assert_eq!(
+            limit(options.clone(), "zeros.bin"),
+            Some("compression ratio")
+        );
+        assert_eq!(limit(options, "text.txt"), None);
+
+        // The total is counted over every entry read by the Unzipper
+        let options = UnzipperOptions::new().max_total_size(1500);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        assert!(unzipper.get_file("text.txt").is_ok());
+        assert!(matches!(
+            unzipper.get_file("text.txt"),
+            Err(UnzipperError::LimitExceeded {
+                limit: "total size",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_entry_reader_output_capacity() {
+        let file =
+            PathBuf::from(UnitTest::new("unzipper_limits").test_case_folder()).join("bomb.zip");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let text = unzipper.get_file("text.txt").unwrap();
+        assert_eq!(
+            unzipper.entry_reader("text.txt").unwrap().output_capacity(),
+            text.len()
+        );
+
+        // A declared size far above the limit is not reserved ahead
+        let mut bytes = std::fs::read(&file).unwrap();
+        let central_pos = bytes.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+        for pos in central_pos..bytes.len() - 4 {
+            if &bytes[pos..pos + 4] == b"PK\x01\x02" {
+                bytes[pos + 24..pos + 28].copy_from_slice(&u32::MAX.to_le_bytes());
+            }
+        }
+        let unzipper = Unzipper::from_vec(bytes.clone()).unwrap();
+        let reader = unzipper.entry_reader("text.txt").unwrap();
+        assert_eq!(reader.size(), u32::MAX as usize);
+        assert_eq!(reader.output_capacity(), MAX_PREALLOCATION);
+
+        let options = UnzipperOptions::new().max_entry_size(1024);
+        let unzipper = Unzipper::from_reader_with_options(Cursor::new(bytes), options).unwrap();
+        let reader = unzipper.entry_reader("text.txt").unwrap();
+        assert_eq!(reader.output_capacity(), 1024);
+        assert_eq!(unzipper.get_file("text.txt").unwrap(), text);
+    }
+
+    #[test]
+    fn test_entry_reader_crc_check() {
+        let unit_test = UnitTest::new("unzipper_crc_check");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("corrupted.zip");
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert!(unzipper.get_file("deflated.txt").is_ok());
+
+        let error = unzipper.get_file("stored.txt").unwrap_err();
+        assert!(matches!(
+            error,
+            UnzipperError::CrcMismatch {
+                expected: 0x6d93c138,
+                actual: 0x28c3debf,
+                ..
+            }
+        ));
+
+        let options = UnzipperOptions::new().verify_crc(false);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        assert!(unzipper.get_file("stored.txt").is_ok());
+
+        // Every entry of a sound archive passes the check
+        let file = PathBuf::from(UnitTest::new("unzipper_open_epub_file").test_case_folder())
+            .join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let file_paths: Vec<String> = unzipper.entries().map(|e| e.name().to_string()).collect();
+        for file_path in file_paths {
+            assert!(unzipper.get_file(&file_path).is_ok(), "{file_path}");
+        }
+    }
+
+    #[test]
+    fn test_entry_reader_data_descriptor() {
+        let unit_test = UnitTest::new("unzipper_data_descriptor");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("streamed.zip");
+        let deflated = "Deflated entry written by a streaming writer.\n".repeat(100);
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert!(unzipper.entries().all(|entry| entry.has_data_descriptor()));
+        assert_eq!(
+            unzipper.get_file("stored.txt").unwrap(),
+            b"Stored entry written by a streaming writer.\n"
+        );
+        assert_eq!(
+            unzipper.get_file("deflated.txt").unwrap(),
+            deflated.as_bytes()
+        );
+
+        // Without CRC-32 in the central directory, the one of the data descriptor is verified
+        let mut bytes = std::fs::read(&file).unwrap();
+        let central_pos = bytes.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+        for pos in central_pos..bytes.len() - 4 {
+            if &bytes[pos..pos + 4] == b"PK\x01\x02" {
+                bytes[pos + 16..pos + 20].fill(0);
+            }
+        }
+        let unzipper = Unzipper::from_vec(bytes.clone()).unwrap();
+        assert_eq!(
+            unzipper.get_file("deflated.txt").unwrap(),
+            deflated.as_bytes()
+        );
+
+        bytes[30 + "stored.txt".len()] ^= 0x01;
+        let unzipper = Unzipper::from_vec(bytes).unwrap();
+        assert!(matches!(
+            unzipper.get_file("stored.txt"),
+            Err(UnzipperError::CrcMismatch {
+                expected: 0x16dd88cb,
+                ..
+            })
+        ));
+    }
+}
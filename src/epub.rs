@@ -0,0 +1,180 @@
+//! EPUB Module.
+//!
+//! This module provides the helpers of the EPUB Open Container Format (OCF): the check of the `mimetype`
+//! entry, which must come first, stored and with the `application/epub+zip` content, and the discovery
+//! of the package document (OPF) through the rootfiles of `META-INF/container.xml`.
+
+use std::io::{Read, Seek};
+
+use crate::error::UnzipperError;
+use crate::unzipper::Unzipper;
+
+/// Content of the `mimetype` entry of an EPUB.
+const EPUB_MIMETYPE: &[u8] = b"application/epub+zip";
+
+/// Path of the container file of an EPUB.
+const CONTAINER_PATH: &str = "META-INF/container.xml";
+
+/// Media type of the package document in the container file.
+const OPF_MEDIA_TYPE: &str = "application/oebps-package+xml";
+
+/// Returns the value of an attribute of an XML start tag.
+///
+/// # Arguments
+/// * `tag` - The content of the tag, between its name and its closing `>`.
+/// * `name` - The name of the attribute.
+///
+/// # Returns
+/// The value of the attribute with the predefined entities replaced, or None if the tag has no such attribute.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let mut rest = tag;
+    while let Some(equal) = rest.find('=') {
+        let attribute_name = rest[..equal].trim();
+        let value = rest[equal + 1..].trim_start();
+        let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+        let end = value[1..].find(quote)? + 1;
+        if attribute_name == name {
+            return Some(
+                value[1..end]
+                    .replace("&lt;", "<")
+                    .replace("&gt;", ">")
+                    .replace("&quot;", "\"")
+                    .replace("&apos;", "'")
+                    .replace("&amp;", "&"),
+            );
+        }
+        rest = &value[end + 1..];
+    }
+    None
+}
+
+/// Returns the paths of the package documents listed by a container file.
+///
+/// # Arguments
+/// * `container` - The content of `META-INF/container.xml`.
+///
+/// # Returns
+/// The `full-path` of the `rootfile` elements with the OPF media type, in document order.
+fn rootfiles(container: &str) -> Vec<String> {
+    container
+        .split("<rootfile")
+        .skip(1)
+        // Leaves out the enclosing rootfiles element
+        .filter(|tag| tag.starts_with(|c: char| c.is_whitespace() || c == '/' || c == '>'))
+        .filter_map(|tag| tag.split('>').next())
+        .filter(|tag| attribute(tag, "media-type").as_deref() == Some(OPF_MEDIA_TYPE))
+        .filter_map(|tag| attribute(tag, "full-path"))
+        .collect()
+}
+
+impl<R: Read + Seek> Unzipper<R> {
+    /// Checks the `mimetype` entry of an EPUB, as required by the Open Container Format.
+    ///
+    /// # Returns
+    /// A Result indicating success, or an `InvalidEpub` error if the `mimetype` entry is missing, is not the first
+    /// entry of the zip archive, is compressed or encrypted, or if its content is not `application/epub+zip`.
+    pub fn validate_epub_mimetype(&self) -> Result<(), UnzipperError> {
+        let invalid = |msg: &str| Err(UnzipperError::InvalidEpub(msg.to_string()));
+
+        let Some(entry) = self.metadata("mimetype") else {
+            return invalid("missing mimetype entry");
+        };
+        let first_start_pos = self
+            .parsed_entries()
+            .iter()
+            .map(|(_, file_entry)| file_entry.start_pos)
+            .min();
+        let is_first = self
+            .entries()
+            .next()
+            .is_some_and(|first| first.name() == "mimetype")
+            && first_start_pos == Some(self.parsed_entries()["mimetype"].start_pos);
+        if !is_first {
+            return invalid("mimetype is not the first entry");
+        }
+        if entry.method() != 0 {
+            return invalid("mimetype is compressed");
+        }
+        if entry.is_encrypted() {
+            return invalid("mimetype is encrypted");
+        }
+        if self.get_file("mimetype")? != EPUB_MIMETYPE {
+            return invalid("mimetype content is not application/epub+zip");
+        }
+        Ok(())
+    }
+
+    /// Returns the path of the package document (OPF) of an EPUB.
+    ///
+    /// # Returns
+    /// A Result containing the cleaned path of the first rootfile of `META-INF/container.xml` with the
+    /// `application/oebps-package+xml` media type, which is the default rendition of the publication,
+    /// or an `InvalidEpub` error if the container file is missing or lists no such rootfile.
+    pub fn epub_rootfile(&self) -> Result<String, UnzipperError> {
+        let container = match self.get_file(CONTAINER_PATH) {
+            Ok(container) => container,
+            Err(UnzipperError::NotFound(_)) => {
+                return Err(UnzipperError::InvalidEpub(format!(
+                    "missing {CONTAINER_PATH}"
+                )))
+            }
+            Err(error) => return Err(error),
+        };
+        rootfiles(&String::from_utf8_lossy(&container))
+            .first()
+            .map(|full_path| self.clean_file_path(full_path))
+            .ok_or_else(|| UnzipperError::InvalidEpub(format!("no rootfile in {CONTAINER_PATH}")))
+    }
+}
+
+#[cfg(test)]
+mod epub_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_epub_rootfiles() {
+        let container = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="EPUB/cover.pdf" media-type="application/pdf"/>
+    <rootfile media-type='application/oebps-package+xml' full-path='EPUB/R&amp;D.opf' />
+    <rootfile
+        full-path="EPUB/alt.opf"
+        media-type="application/oebps-package+xml"></rootfile>
+  </rootfiles>
+</container>"#;
+        assert_eq!(rootfiles(container), ["EPUB/R&D.opf", "EPUB/alt.opf"]);
+        assert!(rootfiles("<container><rootfiles/></container>").is_empty());
+    }
+
+    #[test]
+    fn test_epub_container() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert!(unzipper.validate_epub_mimetype().is_ok());
+        assert_eq!(unzipper.epub_rootfile().unwrap(), "content.opf");
+
+        let unit_test = UnitTest::new("unzipper_epub");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("invalid.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert!(matches!(
+            unzipper.validate_epub_mimetype(),
+            Err(UnzipperError::InvalidEpub(msg)) if msg == "mimetype is not the first entry"
+        ));
+        assert!(matches!(
+            unzipper.epub_rootfile(),
+            Err(UnzipperError::InvalidEpub(msg)) if msg == "no rootfile in META-INF/container.xml"
+        ));
+
+        let unit_test = UnitTest::new("unzipper_symlinks");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("symlinks.zip");
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert!(unzipper.validate_epub_mimetype().is_err());
+        assert!(unzipper.epub_rootfile().is_err());
+    }
+}
@@ -0,0 +1,225 @@
+//! Error Module.
+//!
+//! This module provides the error type returned by the Unzipper operations.
+//! Each kind of failure has its own variant, so that callers can handle them programmatically.
+
+use core::fmt;
+
+/// Enum that describes a capability of the zip format needed to read an entry and missing from this crate,
+/// or disabled by its cargo features.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnsupportedFeature {
+    /// A compression method that is not supported, or whose cargo feature is disabled, and without
+    /// a registered decompressor.
+    CompressionMethod(u16),
+    /// WinZip AES encryption, which requires the `crypto` feature.
+    AesEncryption,
+    /// PKWARE strong encryption.
+    StrongEncryption,
+    /// A version of the zip specification needed to extract, higher than the supported one.
+    /// It is given as major * 10 + minor.
+    Version(u16),
+}
+
+impl fmt::Display for UnsupportedFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnsupportedFeature::CompressionMethod(method) => {
+                let name = match method {
+                    9 => "DEFLATE64",
+                    12 => "BZIP2",
+                    14 => "LZMA",
+                    93 => "Zstandard",
+                    95 => "XZ",
+                    98 => "PPMd",
+                    _ => return write!(f, "compression method {method}"),
+                };
+                write!(f, "{name} compression")
+            }
+            UnsupportedFeature::AesEncryption => write!(f, "AES encryption"),
+            UnsupportedFeature::StrongEncryption => write!(f, "PKWARE strong encryption"),
+            UnsupportedFeature::Version(version) => {
+                write!(f, "zip version {}.{}", version / 10, version % 10)
+            }
+        }
+    }
+}
+
+/// Enum that describes the errors that can occur while reading a zip archive.
+#[derive(Debug)]
+pub enum UnzipperError {
+    /// An I/O error occurred while accessing the zip file.
+    Io(std::io::Error),
+    /// The zip file has not been opened.
+    NotOpen,
+    /// The requested entry does not exist in the zip archive.
+    NotFound(String),
+    /// The entry uses a compression method that is not supported.
+    UnsupportedMethod(u16),
+    /// The entry needs capabilities that are not available. All the missing ones are given.
+    Unsupported {
+        /// The path of the entry in the zip archive.
+        file_path: String,
+        /// The capabilities needed by the entry and not available.
+        features: Vec<UnsupportedFeature>,
+    },
+    /// An expected record signature was not found. The record name is given.
+    BadSignature(&'static str),
+    /// The structure of the zip archive is invalid.
+    InvalidArchive(String),
+    /// The entry is encrypted and no password was given.
+    PasswordRequired(String),
+    /// The password given does not decrypt the entry.
+    InvalidPassword(String),
+    /// The authentication code of an entry encrypted with WinZip AES does not match its data.
+    AuthenticationFailed(String),
+    /// The compressed data of an entry could not be decompressed.
+    Decompression(String),
+    /// A symbolic link entry was rejected while extracting, by the symlink policy or because its target
+    /// is outside the destination directory.
+    SymlinkRejected(String),
+    /// The operation was cancelled through the cancellation token of the options.
+    Cancelled,
+    /// A decompression limit of the options was exceeded, as done by decompression bombs.
+    LimitExceeded {
+        /// The path of the entry in the zip archive.
+        file_path: String,
+        /// The limit exceeded: "entry size", "total size" or "compression ratio".
+        limit: &'static str,
+    },
+    /// A pattern selecting entries is invalid.
+    InvalidPattern(String),
+    /// A checksum manifest is invalid.
+    InvalidManifest(String),
+    /// A saved index of the entries is invalid, or does not match the zip file.
+    InvalidIndex(String),
+    /// The Open Container Format of an EPUB is not respected.
+    InvalidEpub(String),
+    /// Several entries share the same path, rejected by the duplicate policy of the options.
+    DuplicateEntry(String),
+    /// The buffer given to receive an entry is smaller than its decompressed content.
+    BufferTooSmall {
+        /// The path of the entry in the zip archive.
+        file_path: String,
+        /// The size of the buffer given.
+        size: usize,
+    },
+    /// The CRC-32 of a decompressed entry does not match the one stored in the zip archive.
+    CrcMismatch {
+        /// The path of the entry in the zip archive.
+        file_path: String,
+        /// The CRC-32 stored in the zip archive.
+        expected: u32,
+        /// The CRC-32 computed from the decompressed data.
+        actual: u32,
+    },
+}
+
+impl fmt::Display for UnzipperError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnzipperError::Io(error) => write!(f, "I/O error: {error}"),
+            UnzipperError::NotOpen => write!(f, "Zip file not open"),
+            UnzipperError::NotFound(file_path) => write!(f, "File not found: {file_path}"),
+            UnzipperError::UnsupportedMethod(method) => {
+                write!(f, "Unsupported compression method: {method}")
+            }
+            UnzipperError::Unsupported {
+                file_path,
+                features,
+            } => {
+                write!(f, "Unsupported features for {file_path}: ")?;
+                for (index, feature) in features.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{feature}")?;
+                }
+                Ok(())
+            }
+            UnzipperError::BadSignature(record) => write!(f, "Invalid {record} signature"),
+            UnzipperError::InvalidArchive(msg) => write!(f, "Invalid zip archive: {msg}"),
+            UnzipperError::PasswordRequired(file_path) => {
+                write!(f, "Password required to decrypt: {file_path}")
+            }
+            UnzipperError::InvalidPassword(file_path) => {
+                write!(f, "Invalid password for: {file_path}")
+            }
+            UnzipperError::AuthenticationFailed(file_path) => {
+                write!(f, "Authentication failed for: {file_path}")
+            }
+            UnzipperError::Decompression(msg) => write!(f, "Decompression failed: {msg}"),
+            UnzipperError::SymlinkRejected(file_path) => {
+                write!(f, "Symbolic link rejected: {file_path}")
+            }
+            UnzipperError::Cancelled => write!(f, "Operation cancelled"),
+            UnzipperError::LimitExceeded { file_path, limit } => {
+                write!(f, "Decompression limit exceeded for {file_path}: {limit}")
+            }
+            UnzipperError::InvalidPattern(msg) => write!(f, "Invalid pattern: {msg}"),
+            UnzipperError::InvalidManifest(msg) => write!(f, "Invalid manifest: {msg}"),
+            UnzipperError::InvalidIndex(msg) => write!(f, "Invalid index: {msg}"),
+            UnzipperError::InvalidEpub(msg) => write!(f, "Invalid EPUB: {msg}"),
+            UnzipperError::DuplicateEntry(file_path) => write!(f, "Duplicate entry: {file_path}"),
+            UnzipperError::BufferTooSmall { file_path, size } => {
+                write!(f, "Buffer of {size} bytes too small for {file_path}")
+            }
+            UnzipperError::CrcMismatch {
+                file_path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "CRC-32 mismatch for {file_path}: expected {expected:08x}, found {actual:08x}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UnzipperError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UnzipperError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Converts an I/O error into an UnzipperError.
+///
+/// An UnzipperError that was wrapped into an I/O error (as done by the `std::io::Read` implementations
+/// of this crate) is recovered as is.
+impl From<std::io::Error> for UnzipperError {
+    fn from(error: std::io::Error) -> Self {
+        if error
+            .get_ref()
+            .is_some_and(|inner| inner.is::<UnzipperError>())
+        {
+            let inner = error.into_inner().expect("inner error checked above");
+            *inner
+                .downcast::<UnzipperError>()
+                .expect("inner error type checked above")
+        } else {
+            UnzipperError::Io(error)
+        }
+    }
+}
+
+/// Converts an UnzipperError into an I/O error, for use in `std::io::Read` implementations.
+///
+/// The UnzipperError is kept as the inner error, and can be recovered with `UnzipperError::from()`.
+impl From<UnzipperError> for std::io::Error {
+    fn from(error: UnzipperError) -> Self {
+        match error {
+            UnzipperError::Io(error) => error,
+            UnzipperError::NotFound(_) => std::io::Error::new(std::io::ErrorKind::NotFound, error),
+            UnzipperError::UnsupportedMethod(_) | UnzipperError::Unsupported { .. } => {
+                std::io::Error::new(std::io::ErrorKind::Unsupported, error)
+            }
+            // Not mapped to ErrorKind::Interrupted, which std::io functions retry
+            UnzipperError::Cancelled => std::io::Error::other(error),
+            _ => std::io::Error::new(std::io::ErrorKind::InvalidData, error),
+        }
+    }
+}
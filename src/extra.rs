@@ -0,0 +1,315 @@
+//! Extra Field Module.
+//!
+//! This module provides the parsing of the extra fields attached to the file entries of a zip archive.
+//! An extra field is a sequence of records, each made of a 2-byte header ID, a 2-byte data size and the data itself,
+//! as described in the section 4.5 of the PKWARE APPNOTE.
+
+use crate::crc32::Crc32;
+use crate::datetime::{file_time, unix_time, Timestamps};
+
+/// Compression method recorded for the entries encrypted with WinZip AES.
+pub(crate) const AES_METHOD: u16 = 99;
+
+/// Header ID of the WinZip AES extra field.
+pub(crate) const AES_EXTRA_FIELD_ID: u16 = 0x9901;
+
+/// Header ID of the Info-ZIP Unicode Path extra field.
+pub(crate) const UNICODE_PATH_EXTRA_FIELD_ID: u16 = 0x7075;
+
+/// Header ID of the extended timestamp extra field.
+pub(crate) const EXTENDED_TIMESTAMP_EXTRA_FIELD_ID: u16 = 0x5455;
+
+/// Header ID of the NTFS extra field.
+pub(crate) const NTFS_EXTRA_FIELD_ID: u16 = 0x000a;
+
+/// Returns an iterator over the records of an extra field.
+///
+/// # Arguments
+/// * `extra` - The extra field of an entry, as read from the zip file.
+///
+/// # Returns
+/// An iterator of (header ID, data) pairs. The iteration stops at the first record extending beyond the extra field.
+pub(crate) fn extra_fields(extra: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let header = extra.get(offset..offset + 4)?;
+        let id = u16::from_le_bytes([header[0], header[1]]);
+        let size = u16::from_le_bytes([header[2], header[3]]) as usize;
+        let data = extra.get(offset + 4..offset + 4 + size)?;
+        offset += 4 + size;
+        Some((id, data))
+    })
+}
+
+/// Returns whether an extra field is a well-formed sequence of records.
+///
+/// # Arguments
+/// * `extra` - The extra field of an entry, as read from the zip file.
+///
+/// # Returns
+/// True if the records exactly cover the extra field, false if the last record is cut short
+/// or if the extra field ends with padding.
+pub(crate) fn is_well_formed(extra: &[u8]) -> bool {
+    let mut offset = 0;
+    while offset < extra.len() {
+        match extra.get(offset + 2..offset + 4) {
+            Some(size) => offset += 4 + u16::from_le_bytes([size[0], size[1]]) as usize,
+            None => return false,
+        }
+    }
+    offset == extra.len()
+}
+
+/// Returns the file name found in an Info-ZIP Unicode Path extra field.
+///
+/// # Arguments
+/// * `data` - The data of the Unicode Path extra field.
+/// * `name` - The file name bytes stored in the header.
+///
+/// # Returns
+/// The UTF-8 file name, or None if the field is invalid or if it is stale: its CRC-32 does not match
+/// the file name stored in the header, which was then changed by a tool unaware of the field.
+pub(crate) fn unicode_path(data: &[u8], name: &[u8]) -> Option<String> {
+    // version              1 byte   (1)
+    // name CRC-32          4 bytes  (of the file name stored in the header)
+    // unicode name         variable (UTF-8)
+    if data.len() < 5 || data[0] != 1 {
+        return None;
+    }
+
+    let mut crc = Crc32::new();
+    crc.update(name);
+    if crc.finalize() != u32::from_le_bytes([data[1], data[2], data[3], data[4]]) {
+        return None;
+    }
+
+    std::str::from_utf8(&data[5..]).ok().map(str::to_string)
+}
+
+/// Returns the timestamps found in the extra fields of an entry.
+///
+/// # Arguments
+/// * `extra` - The extra field of the entry.
+///
+/// # Returns
+/// The timestamps found, each of them being None when not recorded. The NTFS extra field, with its
+/// 100-nanosecond resolution, is preferred over the extended timestamp extra field.
+pub(crate) fn timestamps(extra: &[u8]) -> Timestamps {
+    let mut timestamps = Timestamps::default();
+    for (id, data) in extra_fields(extra) {
+        if id == EXTENDED_TIMESTAMP_EXTRA_FIELD_ID {
+            extended_timestamp(data, &mut timestamps);
+        }
+    }
+    for (id, data) in extra_fields(extra) {
+        if id == NTFS_EXTRA_FIELD_ID {
+            ntfs_timestamp(data, &mut timestamps);
+        }
+    }
+    timestamps
+}
+
+/// Parses the data of an extended timestamp extra field.
+///
+/// The flags tell which times are recorded in the local header. The central directory usually holds
+/// the modification time only, while keeping the flags of the local header: the times are then read
+/// as long as the data is available.
+fn extended_timestamp(data: &[u8], timestamps: &mut Timestamps) {
+    // flags                1 byte   (bit 0: modification, bit 1: access, bit 2: creation time)
+    // times                4 bytes each, signed seconds since 1970-01-01 UTC
+    let flags = match data.first() {
+        Some(&flags) => flags,
+        None => return,
+    };
+
+    let mut times = data[1..]
+        .chunks_exact(4)
+        .map(|time| unix_time(i32::from_le_bytes([time[0], time[1], time[2], time[3]]) as i64));
+    if flags & 0x01 != 0 {
+        timestamps.modified = times.next();
+    }
+    if flags & 0x02 != 0 {
+        timestamps.accessed = times.next();
+    }
+    if flags & 0x04 != 0 {
+        timestamps.created = times.next();
+    }
+}
+
+/// Parses the data of an NTFS extra field.
+///
+/// Only the attribute 0x0001, holding the modification, access and creation times, is defined.
+/// A zero time is considered as not recorded.
+fn ntfs_timestamp(data: &[u8], timestamps: &mut Timestamps) {
+    // reserved             4 bytes
+    // attributes           variable, each made of:
+    //   tag                2 bytes  (0x0001 for the timestamps)
+    //   size               2 bytes  (24 for the timestamps)
+    //   times              8 bytes each, modification, access and creation times,
+    //                      in 100-nanosecond intervals since 1601-01-01 UTC
+    let attributes = data.get(4..).unwrap_or_default();
+    for (tag, value) in extra_fields(attributes) {
+        if tag == 0x0001 && value.len() >= 24 {
+            let time = |index: usize| {
+                let bytes = value[index * 8..index * 8 + 8].try_into().unwrap_or([0; 8]);
+                match u64::from_le_bytes(bytes) {
+                    0 => None,
+                    intervals => Some(file_time(intervals)),
+                }
+            };
+            timestamps.modified = time(0).or(timestamps.modified);
+            timestamps.accessed = time(1).or(timestamps.accessed);
+            timestamps.created = time(2).or(timestamps.created);
+        }
+    }
+}
+
+/// Struct that holds the WinZip AES encryption parameters of an entry, as found in its 0x9901 extra field.
+//
+// vendor version       2 bytes  (1 = AE-1, 2 = AE-2)
+// vendor ID            2 bytes  ("AE")
+// encryption strength  1 byte   (1 = 128-bit, 2 = 192-bit, 3 = 256-bit key)
+// compression method   2 bytes  (of the data once decrypted)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AesExtra {
+    pub(crate) version: u16, // 1 = AE-1, 2 = AE-2 (CRC-32 not stored)
+    pub(crate) strength: u8, // 1 = AES-128, 2 = AES-192, 3 = AES-256
+    pub(crate) method: u16,  // actual compress method of the entry
+}
+
+impl AesExtra {
+    /// Parses the data of a WinZip AES extra field.
+    ///
+    /// # Returns
+    /// The encryption parameters, or None if the data is not a valid WinZip AES extra field.
+    pub(crate) fn parse(data: &[u8]) -> Option<AesExtra> {
+        if data.len() != 7 || &data[2..4] != b"AE" {
+            return None;
+        }
+
+        let aes_extra = AesExtra {
+            version: u16::from_le_bytes([data[0], data[1]]),
+            strength: data[4],
+            method: u16::from_le_bytes([data[5], data[6]]),
+        };
+        aes_extra.is_valid().then_some(aes_extra)
+    }
+
+    /// Returns true if the version and the encryption strength are known ones.
+    pub(crate) fn is_valid(&self) -> bool {
+        (1..=2).contains(&self.version) && (1..=3).contains(&self.strength)
+    }
+
+    /// Returns the length in bytes of the AES key.
+    #[cfg(feature = "crypto")]
+    pub(crate) fn key_length(&self) -> usize {
+        8 + 8 * self.strength as usize
+    }
+
+    /// Returns the length in bytes of the salt preceding the encrypted data.
+    #[cfg(feature = "crypto")]
+    pub(crate) fn salt_length(&self) -> usize {
+        self.key_length() / 2
+    }
+}
+
+#[cfg(test)]
+mod extra_tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_extra_extended_timestamp() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let unit_test = UnitTest::new("unzipper_timestamps");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("extended_timestamp.zip");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        let entry = unzipper
+            .entries()
+            .find(|entry| entry.name() == "all.txt")
+            .unwrap();
+        assert_eq!(
+            entry.modified(),
+            Some(UNIX_EPOCH + Duration::from_secs(1700000001))
+        );
+        assert_eq!(
+            entry.accessed(),
+            Some(UNIX_EPOCH + Duration::from_secs(1700000002))
+        );
+        assert_eq!(
+            entry.created(),
+            Some(UNIX_EPOCH - Duration::from_secs(86400))
+        );
+
+        // Central directory holding the modification time only, with the flags of the local header
+        let entry = unzipper
+            .entries()
+            .find(|entry| entry.name() == "mtime.txt")
+            .unwrap();
+        assert_eq!(
+            entry.modified(),
+            Some(UNIX_EPOCH + Duration::from_secs(1700000003))
+        );
+        assert_eq!(entry.accessed(), None);
+        assert_eq!(entry.created(), None);
+    }
+
+    #[test]
+    fn test_extra_ntfs_timestamp() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        let unit_test = UnitTest::new("unzipper_timestamps");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ntfs_timestamp.zip");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        // The NTFS times are preferred over the extended timestamp one, keeping their sub-second part
+        let entry = unzipper.entries().next().unwrap();
+        assert_eq!(
+            entry.modified(),
+            Some(UNIX_EPOCH + Duration::new(1700000005, 123456700))
+        );
+        assert_eq!(
+            entry.accessed(),
+            Some(UNIX_EPOCH + Duration::from_secs(1700000006))
+        );
+        assert_eq!(
+            entry.created(),
+            Some(UNIX_EPOCH + Duration::new(1600000000, 500))
+        );
+
+        assert_eq!(
+            file_time(0x019db1ded53e8000),
+            UNIX_EPOCH,
+            "1970-01-01 as a FILETIME"
+        );
+    }
+
+    #[test]
+    fn test_extra_unicode_path() {
+        let mut data = vec![1];
+        let mut crc = Crc32::new();
+        crc.update(b"na_ve.txt");
+        data.extend_from_slice(&crc.finalize().to_le_bytes());
+        data.extend_from_slice("naïve.txt".as_bytes());
+        assert_eq!(
+            unicode_path(&data, b"na_ve.txt").as_deref(),
+            Some("naïve.txt")
+        );
+        assert_eq!(unicode_path(&data, b"renamed.txt"), None);
+
+        let unit_test = UnitTest::new("unzipper_file_names");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("unicode_path.zip");
+
+        // The stale Unicode Path of the second entry is ignored
+        let unzipper = Unzipper::new(&file).unwrap();
+        let mut names: Vec<String> = unzipper.entries().map(|e| e.name().to_string()).collect();
+        names.sort();
+        assert_eq!(names, ["naïve.txt", "stale.txt"]);
+        assert_eq!(unzipper.get_file("naïve.txt").unwrap(), b"unicode path\n");
+    }
+}
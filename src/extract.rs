@@ -0,0 +1,1199 @@
+//! Extraction Module.
+//!
+//! This module provides functionality to extract the content of a zip archive to a directory on disk.
+//! Every entry path is checked before anything is written, so that entries whose path would escape the
+//! destination directory (the so-called "zip slip" vulnerability) are rejected.
+
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{Cursor, Read, Seek, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::entry::{EntryInfo, EntryKind};
+use crate::error::UnzipperError;
+use crate::glob::Glob;
+use crate::options::{SymlinkPolicy, UnzipperOptions};
+use crate::read_ahead::{read_ahead, READ_AHEAD_MAX_SIZE};
+use crate::unzipper::Unzipper;
+
+/// Maximum number of symbolic links followed to materialize a link.
+const MAX_SYMLINK_HOPS: usize = 8;
+
+/// Signature of the first local file header of a zip archive.
+const ZIP_SIGNATURE: &[u8] = b"PK\x03\x04";
+
+/// Applies the permission bits of a Unix mode to an extracted file.
+///
+/// The setuid, setgid and sticky bits are dropped. Nothing is done on systems other than Unix.
+fn set_permissions(path: &Path, mode: u32) -> Result<(), UnzipperError> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o777))?;
+    }
+    #[cfg(not(unix))]
+    let _ = (path, mode);
+    Ok(())
+}
+
+/// Creates a symbolic link. Nothing is done on systems other than Unix, where links are materialized instead.
+fn create_symlink(target: &str, path: &Path) -> Result<(), UnzipperError> {
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(target, path)?;
+    #[cfg(not(unix))]
+    let _ = (target, path);
+    Ok(())
+}
+
+/// Creates the file receiving the content of an extracted entry, written through io_uring if the options
+/// require it.
+fn create_output(path: &Path, options: &UnzipperOptions) -> Result<Box<dyn Write>, UnzipperError> {
+    let file = File::create(path)?;
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    if options.uring_output {
+        return Ok(Box::new(crate::uring::UringFile::new(file)));
+    }
+    #[cfg(not(all(feature = "io-uring", target_os = "linux")))]
+    let _ = options;
+    Ok(Box::new(file))
+}
+
+/// Computes the directory where a nested zip archive is extracted.
+///
+/// # Arguments
+/// * `destination` - The path of the extracted nested zip archive.
+///
+/// # Returns
+/// The path without its extension, such as `bundle/inner` for `bundle/inner.zip`, or with `.d` appended
+/// if the path has no extension.
+fn nested_destination(destination: &Path) -> PathBuf {
+    if destination.extension().is_some() {
+        destination.with_extension("")
+    } else {
+        let mut path = destination.as_os_str().to_os_string();
+        path.push(".d");
+        PathBuf::from(path)
+    }
+}
+
+/// Computes the location on disk of an entry extracted to the destination directory.
+///
+/// # Arguments
+/// * `dest` - The destination directory of the extraction.
+/// * `file_path` - The cleaned path of the entry in the zip archive.
+///
+/// # Returns
+/// The path of the entry inside the destination directory, or None if the entry path is absolute,
+/// contains drive prefixes, backslashes or parent directory indicators, and would then escape the destination.
+pub(crate) fn safe_destination(dest: &Path, file_path: &str) -> Option<PathBuf> {
+    if file_path.is_empty() || file_path.contains('\\') {
+        return None;
+    }
+
+    let relative = Path::new(file_path);
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    let destination = dest.join(relative);
+    if destination.starts_with(dest) {
+        Some(destination)
+    } else {
+        None
+    }
+}
+
+/// Resolves the target of a symbolic link entry into a path of the zip archive.
+///
+/// # Arguments
+/// * `link_path` - The cleaned path of the link entry in the zip archive.
+/// * `target` - The target of the link, relative to the directory of the link.
+///
+/// # Returns
+/// The cleaned path of the target in the zip archive, or None if the target is absolute, goes up beyond
+/// the root of the archive, or goes up after going down. In the latter case, the target could pass through
+/// another link and escape the destination directory although it looks inside.
+pub(crate) fn resolve_link_target(link_path: &str, target: &str) -> Option<String> {
+    if target.is_empty() || target.starts_with('/') || target.contains('\\') {
+        return None;
+    }
+
+    let mut parts: Vec<&str> = link_path.split('/').collect();
+    parts.pop(); // the link itself
+    let mut going_down = false;
+    for part in target.split('/') {
+        match part {
+            "" | "." => continue,
+            ".." if going_down => return None,
+            ".." => {
+                parts.pop()?;
+            }
+            _ => {
+                going_down = true;
+                parts.push(part);
+            }
+        }
+    }
+    Some(parts.join("/"))
+}
+
+/// Computes the path of an entry relative to a directory of the zip archive.
+///
+/// # Arguments
+/// * `dir_path` - The cleaned path of the directory, empty for the root of the archive.
+/// * `file_path` - The cleaned path of the entry in the zip archive.
+///
+/// # Returns
+/// The path of the entry relative to the directory, or None if the entry is not under the directory.
+pub(crate) fn relative_path<'a>(dir_path: &str, file_path: &'a str) -> Option<&'a str> {
+    if dir_path.is_empty() {
+        return Some(file_path);
+    }
+    file_path
+        .strip_prefix(dir_path)?
+        .strip_prefix('/')
+        .filter(|relative| !relative.is_empty())
+}
+
+/// Struct that describes the result of the extraction of each entry, given by `Unzipper::extract_all_with_report()`.
+#[derive(Debug, Default)]
+pub struct ExtractionReport {
+    results: Vec<(String, Result<(), UnzipperError>)>, // sorted by path
+}
+
+impl ExtractionReport {
+    /// Returns the cleaned path of each entry, sorted, with the result of its extraction.
+    pub fn results(&self) -> &[(String, Result<(), UnzipperError>)] {
+        &self.results
+    }
+
+    /// Returns an iterator over the paths of the entries extracted, including the symbolic links skipped
+    /// by the symlink policy of the options.
+    pub fn extracted(&self) -> impl Iterator<Item = &str> {
+        self.results
+            .iter()
+            .filter(|(_, result)| result.is_ok())
+            .map(|(file_path, _)| file_path.as_str())
+    }
+
+    /// Returns an iterator over the paths of the entries that could not be extracted, with their error.
+    pub fn failures(&self) -> impl Iterator<Item = (&str, &UnzipperError)> {
+        self.results
+            .iter()
+            .filter_map(|(file_path, result)| Some((file_path.as_str(), result.as_ref().err()?)))
+    }
+
+    /// Returns true if every entry was extracted.
+    pub fn is_complete(&self) -> bool {
+        self.failures().next().is_none()
+    }
+}
+
+/// Hands the result of an entry to the report of the extraction, if any, or returns it.
+///
+/// # Arguments
+/// * `report` - The report of the extraction, receiving the results of the entries.
+/// * `file_path` - The cleaned path of the entry.
+/// * `result` - The result of the entry.
+///
+/// # Returns
+/// A Result indicating success if the result is reported, or the result itself otherwise. A cancellation is
+/// never reported, as it ends the whole extraction.
+fn report_result(
+    report: &mut Option<&mut ExtractionReport>,
+    file_path: &str,
+    result: Result<(), UnzipperError>,
+) -> Result<(), UnzipperError> {
+    match report {
+        Some(report) if !matches!(result, Err(UnzipperError::Cancelled)) => {
+            report.results.push((file_path.to_string(), result));
+            Ok(())
+        }
+        _ => result,
+    }
+}
+
+/// Entries of the zip archive to extract, checked before writing anything.
+struct Extraction {
+    file_paths: Vec<String>, // sorted, so that parents come before their children
+    destinations: Vec<PathBuf>, // of the entries, in the same order
+    directories: HashSet<String>, // parents of other entries
+    links: HashMap<String, String>, // link targets, or files to copy for materialized links
+    policy: SymlinkPolicy,
+}
+
+impl<R: Read + Seek> Unzipper<R> {
+    /// Extracts every entry of the zip archive into the destination directory.
+    ///
+    /// # Arguments
+    /// * `dest` - A reference to the path of the directory where the entries will be written. It is created if missing.
+    ///
+    /// # Returns
+    /// A Result indicating success or an error if an entry could not be extracted.
+    /// All entry paths are validated before writing anything: if one of them would escape the destination
+    /// directory, an error is returned and no file is written. Intermediate directories are created as needed.
+    /// Unless disabled in the options, the Unix permissions of the entries are applied to the extracted files.
+    /// Symbolic links are handled according to the symlink policy of the options. The extraction stops with
+    /// `UnzipperError::Cancelled` when the cancellation token of the options is set, leaving the entries
+    /// already written in place. With the `extract_nested` option, the zip archives stored as entries are
+    /// extracted as well, into a directory named after them without their extension.
+    pub fn extract_all(&self, dest: &Path) -> Result<(), UnzipperError> {
+        let file_paths = self.select_entries(|_| true);
+        let extraction = self.prepare_extraction(dest, file_paths, "", None)?;
+        self.run_extraction(&extraction, dest, None)?;
+        if self.options.nested_depth > 0 {
+            self.extract_nested_archives(&extraction, None)?;
+        }
+        Ok(())
+    }
+
+    /// Extracts every entry of the zip archive into the destination directory, going on with the next entries
+    /// when one cannot be extracted.
+    ///
+    /// The entries are checked and written as done by `extract_all()`, except that an entry whose path would
+    /// escape the destination directory, a rejected symbolic link or an entry whose data is corrupted only fails
+    /// itself, so that the caller can report or retry just the failures. The file of an entry failing while it is
+    /// written may be left partially written. With the `extract_nested` option, a nested zip archive that cannot
+    /// be extracted entirely is reported as the failure of its entry.
+    ///
+    /// # Arguments
+    /// * `dest` - A reference to the path of the directory where the entries will be written. It is created if missing.
+    ///
+    /// # Returns
+    /// A Result containing the ExtractionReport giving the result of each entry, or an error if the destination
+    /// directory could not be created or the extraction is cancelled.
+    pub fn extract_all_with_report(&self, dest: &Path) -> Result<ExtractionReport, UnzipperError> {
+        let mut report = ExtractionReport::default();
+        let file_paths = self.select_entries(|_| true);
+        let extraction = self.prepare_extraction(dest, file_paths, "", Some(&mut report))?;
+        self.run_extraction(&extraction, dest, Some(&mut report))?;
+        if self.options.nested_depth > 0 {
+            self.extract_nested_archives(&extraction, Some(&mut report))?;
+        }
+        report
+            .results
+            .sort_by(|(first, _), (second, _)| first.cmp(second));
+        Ok(report)
+    }
+
+    /// Extracts the entries whose path matches a glob pattern into the destination directory.
+    ///
+    /// The entries are written at their path in the zip archive, and are checked and written as done
+    /// by `extract_all()`. The parent directories of the entries are created as needed.
+    ///
+    /// # Arguments
+    /// * `pattern` - A string slice representing the glob pattern, such as `**/*.xhtml`. See `Glob` for its syntax.
+    /// * `dest` - A reference to the path of the directory where the entries will be written. It is created if missing.
+    ///
+    /// # Returns
+    /// A Result containing the number of entries matching the pattern, or an error if the pattern is invalid
+    /// or an entry could not be extracted.
+    pub fn extract_matching(&self, pattern: &str, dest: &Path) -> Result<usize, UnzipperError> {
+        let glob = Glob::new(pattern)?;
+        let file_paths = self.select_entries(|file_path| glob.is_match(file_path));
+        let extraction = self.prepare_extraction(dest, file_paths, "", None)?;
+        self.run_extraction(&extraction, dest, None)?;
+        Ok(extraction.file_paths.len())
+    }
+
+    /// Extracts the entries whose path matches a regular expression into the destination directory.
+    ///
+    /// The entries are selected as done by `Unzipper::find_regex()`, and extracted as done by `extract_matching()`.
+    ///
+    /// This method is available with the `regex` feature.
+    ///
+    /// # Arguments
+    /// * `regex` - The regular expression, searched in the cleaned path of each entry.
+    /// * `dest` - A reference to the path of the directory where the entries will be written. It is created if missing.
+    ///
+    /// # Returns
+    /// A Result containing the number of entries matching the regular expression, or an error if an entry
+    /// could not be extracted.
+    #[cfg(feature = "regex")]
+    pub fn extract_matching_regex(
+        &self,
+        regex: &regex::Regex,
+        dest: &Path,
+    ) -> Result<usize, UnzipperError> {
+        let file_paths = self.find_regex(regex);
+        let extraction = self.prepare_extraction(dest, file_paths, "", None)?;
+        self.run_extraction(&extraction, dest, None)?;
+        Ok(extraction.file_paths.len())
+    }
+
+    /// Extracts the entries under a directory of the zip archive into the destination directory.
+    ///
+    /// The entries are written at their path relative to the directory, so that extracting `OEBPS/images`
+    /// writes `OEBPS/images/cover.png` as `cover.png` in the destination directory. The entries are checked
+    /// and written as done by `extract_all()`. A symbolic link whose target is outside the directory is rejected.
+    ///
+    /// # Arguments
+    /// * `dir_path` - A string slice representing the path of the directory in the zip archive.
+    /// * `dest` - A reference to the path of the directory where the entries will be written. It is created if missing.
+    ///
+    /// # Returns
+    /// A Result indicating success, or an error if no entry is under the directory or an entry could not be extracted.
+    pub fn extract_dir(&self, dir_path: &str, dest: &Path) -> Result<(), UnzipperError> {
+        let dir_path = self.clean_file_path(dir_path);
+        let file_paths =
+            self.select_entries(|file_path| relative_path(&dir_path, file_path).is_some());
+        let extraction = self.prepare_extraction(dest, file_paths, &dir_path, None)?;
+        if extraction.file_paths.is_empty() {
+            return Err(UnzipperError::NotFound(dir_path));
+        }
+        self.run_extraction(&extraction, dest, None)
+    }
+
+    /// Unzips the files under a directory of the zip archive into bytes vectors.
+    ///
+    /// # Arguments
+    /// * `dir_path` - A string slice representing the path of the directory in the zip archive.
+    ///
+    /// # Returns
+    /// A Result containing the path relative to the directory and the content of each file, sorted by path,
+    /// or an error if no entry is under the directory or a file could not be unzipped. The directory entries
+    /// are left out, and the content of a symbolic link entry is the path of its target.
+    pub fn get_dir(&self, dir_path: &str) -> Result<Vec<(String, Vec<u8>)>, UnzipperError> {
+        let dir_path = self.clean_file_path(dir_path);
+        let mut file_paths =
+            self.select_entries(|file_path| relative_path(&dir_path, file_path).is_some());
+        if file_paths.is_empty() {
+            return Err(UnzipperError::NotFound(dir_path));
+        }
+        file_paths
+            .retain(|file_path| self.parsed_entries()[file_path].kind() != EntryKind::Directory);
+
+        let paths: Vec<&str> = file_paths.iter().map(String::as_str).collect();
+        self.get_files(&paths)
+            .into_iter()
+            .map(|(file_path, result)| {
+                let relative = relative_path(&dir_path, &file_path).unwrap_or_default();
+                Ok((relative.to_string(), result?))
+            })
+            .collect()
+    }
+
+    /// Extracts the entries checked by `prepare_extraction()` one by one.
+    ///
+    /// # Arguments
+    /// * `extraction` - The entries to extract.
+    /// * `dest` - A reference to the path of the destination directory.
+    /// * `report` - The report receiving the result of each entry, the first failure ending the extraction
+    ///   without it.
+    ///
+    /// # Returns
+    /// A Result indicating success or an error if the extraction failed.
+    fn run_extraction(
+        &self,
+        extraction: &Extraction,
+        dest: &Path,
+        mut report: Option<&mut ExtractionReport>,
+    ) -> Result<(), UnzipperError> {
+        fs::create_dir_all(dest)?;
+
+        // The data of a split zip archive is not in the file of its path only
+        if self.options.read_ahead
+            && !self.filepath.as_os_str().is_empty()
+            && self.disk_offsets.is_empty()
+        {
+            return self.run_extraction_read_ahead(extraction, report);
+        }
+
+        for (index, file_path) in extraction.file_paths.iter().enumerate() {
+            self.options.check_cancelled()?;
+            let result = self.extract_entry(extraction, index);
+            report_result(&mut report, file_path, result)?;
+        }
+
+        Ok(())
+    }
+
+    /// Extracts the entries checked by `prepare_extraction()` one by one, the data of the next entries
+    /// being read ahead by a background thread. See `run_extraction()`.
+    fn run_extraction_read_ahead(
+        &self,
+        extraction: &Extraction,
+        mut report: Option<&mut ExtractionReport>,
+    ) -> Result<(), UnzipperError> {
+        // Only the files whose data is extracted are read ahead, not the directories and the links
+        let entries: Vec<Option<(usize, usize)>> = extraction
+            .file_paths
+            .iter()
+            .map(|file_path| {
+                let file_entry = &self.parsed_entries()[file_path];
+                let extracted = file_entry.kind() == EntryKind::File
+                    && !extraction.directories.contains(file_path)
+                    && file_entry.compressed_size as usize <= READ_AHEAD_MAX_SIZE;
+                extracted.then_some((
+                    file_entry.start_pos as usize,
+                    file_entry.compressed_size as usize,
+                ))
+            })
+            .collect();
+        let file = File::open(self.filepath.canonicalize()?)?;
+
+        thread::scope(|scope| {
+            // The thread reads one entry ahead at most, while another one waits in the channel
+            let (sender, receiver) = mpsc::sync_channel(1);
+            scope.spawn(move || read_ahead(file, entries, sender));
+
+            for (index, file_path) in extraction.file_paths.iter().enumerate() {
+                self.options.check_cancelled()?;
+                *self.lock_prefetched() = receiver.recv().ok().flatten();
+                let result = self.extract_entry(extraction, index);
+                *self.lock_prefetched() = None;
+                report_result(&mut report, file_path, result)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Extracts the nested zip archives among the entries extracted, one level deeper.
+    ///
+    /// # Arguments
+    /// * `extraction` - The entries extracted.
+    /// * `report` - The report holding the result of each entry extracted, in the order of the extraction,
+    ///   where the failure of a nested zip archive replaces the result of its entry.
+    ///
+    /// # Returns
+    /// A Result indicating success or an error if a nested zip archive is invalid or could not be extracted.
+    fn extract_nested_archives(
+        &self,
+        extraction: &Extraction,
+        mut report: Option<&mut ExtractionReport>,
+    ) -> Result<(), UnzipperError> {
+        let mut options = self.options.clone();
+        options.nested_depth -= 1;
+
+        // The results of the entries extracted follow the ones of the entries rejected beforehand
+        let first_result = report.as_ref().map_or(0, |report| {
+            report.results.len() - extraction.file_paths.len()
+        });
+        for (index, (file_path, destination)) in extraction
+            .file_paths
+            .iter()
+            .zip(&extraction.destinations)
+            .enumerate()
+        {
+            // The encrypted entries are left aside, unless a decryption provider handles them
+            let file_entry = &self.parsed_entries()[file_path];
+            let info = EntryInfo::new(file_path, file_entry, &self.options);
+            let provided = self.options.decryption_providers.find(&info).is_some();
+            if file_entry.kind() != EntryKind::File || (file_entry.flags & 0x0001 != 0 && !provided)
+            {
+                continue;
+            }
+            let failed = report
+                .as_ref()
+                .is_some_and(|report| report.results[first_result + index].1.is_err());
+            if failed {
+                continue;
+            }
+            match (
+                self.extract_nested_archive(file_path, destination, &options),
+                &mut report,
+            ) {
+                (Err(error), Some(report)) if !matches!(error, UnzipperError::Cancelled) => {
+                    report.results[first_result + index].1 = Err(error);
+                }
+                (result, _) => result?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Extracts an entry of the zip archive if it is a nested zip archive.
+    ///
+    /// # Arguments
+    /// * `file_path` - The cleaned path of the entry.
+    /// * `destination` - The path of the extracted entry, next to which the nested zip archive is extracted.
+    /// * `options` - The options of the nested zip archive.
+    ///
+    /// # Returns
+    /// A Result indicating success, the entry being left alone if it is not a zip archive, or an error if
+    /// the nested zip archive is invalid or could not be extracted.
+    fn extract_nested_archive(
+        &self,
+        file_path: &str,
+        destination: &Path,
+        options: &UnzipperOptions,
+    ) -> Result<(), UnzipperError> {
+        if self.read_range(file_path, 0, ZIP_SIGNATURE.len())? != ZIP_SIGNATURE {
+            return Ok(());
+        }
+        let bytes = self.get_file(file_path)?;
+        let nested = Unzipper::from_reader_with_options(Cursor::new(bytes), options.clone())?;
+        nested.extract_all(&nested_destination(destination))
+    }
+
+    /// Checks the entries selected in the zip archive before extracting them into the destination directory.
+    ///
+    /// # Arguments
+    /// * `dest` - A reference to the path of the destination directory.
+    /// * `file_paths` - The sorted cleaned paths of the entries to extract, as given by `select_entries()`.
+    /// * `dir_path` - The cleaned path of the directory the entries are extracted from, empty for the root
+    ///   of the archive. The entries are written at their path relative to it.
+    /// * `report` - The report receiving the failures of the entries rejected, which are then left out
+    ///   of the extraction instead of failing it.
+    ///
+    /// # Returns
+    /// A Result containing the entries to extract, or an error if an entry path would escape the destination
+    /// directory or if a symbolic link is rejected.
+    fn prepare_extraction(
+        &self,
+        dest: &Path,
+        file_paths: Vec<String>,
+        dir_path: &str,
+        mut report: Option<&mut ExtractionReport>,
+    ) -> Result<Extraction, UnzipperError> {
+        // Validate every entry path before writing anything
+        let mut entries = Vec::with_capacity(file_paths.len());
+        for file_path in file_paths {
+            let relative = relative_path(dir_path, &file_path).unwrap_or_default();
+            match safe_destination(dest, relative) {
+                Some(destination) => entries.push((file_path, destination)),
+                None => {
+                    let error = UnzipperError::InvalidArchive(format!(
+                        "Entry path escapes the destination directory: {file_path}"
+                    ));
+                    report_result(&mut report, &file_path, Err(error))?;
+                }
+            }
+        }
+
+        // Entries that are parents of other entries are directories
+        let directories: HashSet<String> = entries
+            .iter()
+            .flat_map(|(file_path, _)| {
+                file_path
+                    .match_indices('/')
+                    .map(move |(index, _)| file_path[..index].to_string())
+            })
+            .collect();
+
+        // Symbolic links are checked before writing anything as well
+        let mut policy = self.options.symlinks;
+        if !cfg!(unix) && policy == SymlinkPolicy::Create {
+            policy = SymlinkPolicy::Materialize;
+        }
+        let mut links: HashMap<String, String> = HashMap::new();
+        let mut rejected = HashSet::new();
+        for (file_path, _) in &entries {
+            if self.parsed_entries()[file_path].kind() != EntryKind::Symlink
+                || policy == SymlinkPolicy::Skip
+            {
+                continue;
+            }
+            match self.check_link(file_path, dir_path, policy, &directories) {
+                Ok(link) => {
+                    links.insert(file_path.clone(), link);
+                }
+                Err(error) => {
+                    report_result(&mut report, file_path, Err(error))?;
+                    rejected.insert(file_path.clone());
+                }
+            }
+        }
+        entries.retain(|(file_path, _)| !rejected.contains(file_path));
+
+        let (file_paths, destinations) = entries.into_iter().unzip();
+        Ok(Extraction {
+            file_paths,
+            destinations,
+            directories,
+            links,
+            policy,
+        })
+    }
+
+    /// Checks a symbolic link entry before extracting it.
+    ///
+    /// # Arguments
+    /// * `file_path` - The cleaned path of the link entry.
+    /// * `dir_path` - The cleaned path of the directory the entries are extracted from, empty for the root
+    ///   of the archive.
+    /// * `policy` - The symlink policy applied, other than `SymlinkPolicy::Skip`.
+    /// * `directories` - The entries that are parents of other entries.
+    ///
+    /// # Returns
+    /// A Result containing the target of the link, or the file copied for a materialized link, or an error
+    /// if the link is rejected.
+    fn check_link(
+        &self,
+        file_path: &str,
+        dir_path: &str,
+        policy: SymlinkPolicy,
+        directories: &HashSet<String>,
+    ) -> Result<String, UnzipperError> {
+        if policy == SymlinkPolicy::Error || directories.contains(file_path) {
+            return Err(UnzipperError::SymlinkRejected(file_path.to_string()));
+        }
+        if policy != SymlinkPolicy::Create {
+            return self.materialized_target(file_path);
+        }
+
+        // The target must be extracted as well, so that the link stays inside the destination
+        let target = self.link_target(file_path)?;
+        let resolved = resolve_link_target(file_path, &target);
+        if resolved.is_none_or(|resolved| relative_path(dir_path, &resolved).is_none()) {
+            return Err(UnzipperError::SymlinkRejected(file_path.to_string()));
+        }
+        Ok(target)
+    }
+
+    /// Extracts one of the entries checked by `prepare_extraction()`.
+    ///
+    /// # Arguments
+    /// * `extraction` - The entries to extract.
+    /// * `index` - The index of the entry to extract.
+    ///
+    /// # Returns
+    /// A Result indicating success or an error if the entry could not be extracted.
+    fn extract_entry(&self, extraction: &Extraction, index: usize) -> Result<(), UnzipperError> {
+        let file_path = &extraction.file_paths[index];
+        let destination = &extraction.destinations[index];
+
+        let kind = self.parsed_entries()[file_path].kind();
+        if kind == EntryKind::Directory || extraction.directories.contains(file_path) {
+            fs::create_dir_all(destination)?;
+            return Ok(());
+        }
+        if kind == EntryKind::Symlink && !extraction.links.contains_key(file_path) {
+            return Ok(()); // skipped by the policy
+        }
+
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // A materialized link is written with the content of its target
+        let source = match extraction.links.get(file_path) {
+            Some(target) if extraction.policy == SymlinkPolicy::Create => {
+                if fs::symlink_metadata(destination).is_ok() {
+                    fs::remove_file(destination)?;
+                }
+                return create_symlink(target, destination);
+            }
+            Some(target) => target.as_str(),
+            None => file_path.as_str(),
+        };
+
+        let mut output = create_output(destination, &self.options)?;
+        let mut reader = self.entry_reader(source)?;
+        std::io::copy(&mut reader, &mut output)?;
+        drop(reader);
+
+        if self.options.apply_permissions {
+            if let Some(mode) = self
+                .parsed_entries()
+                .get(source)
+                .and_then(|e| e.unix_mode())
+            {
+                set_permissions(destination, mode)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the target of a symbolic link entry, stored as its data.
+    fn link_target(&self, link_path: &str) -> Result<String, UnzipperError> {
+        String::from_utf8(self.get_file(link_path)?).map_err(|_| {
+            UnzipperError::InvalidArchive(format!("Invalid symbolic link target: {link_path}"))
+        })
+    }
+
+    /// Follows a symbolic link entry through the zip archive, up to the regular file it points to.
+    ///
+    /// # Arguments
+    /// * `link_path` - The cleaned path of the link entry in the zip archive.
+    ///
+    /// # Returns
+    /// A Result containing the path of the target file in the zip archive, or an error if the link
+    /// does not lead to a file of the archive.
+    fn materialized_target(&self, link_path: &str) -> Result<String, UnzipperError> {
+        let mut path = link_path.to_string();
+        for _ in 0..MAX_SYMLINK_HOPS {
+            let target = self.link_target(&path)?;
+            path = resolve_link_target(&path, &target)
+                .ok_or_else(|| UnzipperError::SymlinkRejected(link_path.to_string()))?;
+            match self.parsed_entries().get(&path).map(|entry| entry.kind()) {
+                Some(EntryKind::Symlink) => continue,
+                Some(EntryKind::File) => return Ok(path),
+                _ => {
+                    return Err(UnzipperError::InvalidArchive(format!(
+                        "Symbolic link target is not a file of the archive: {link_path}"
+                    )));
+                }
+            }
+        }
+        Err(UnzipperError::InvalidArchive(format!(
+            "Too many levels of symbolic links: {link_path}"
+        )))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl Unzipper<File> {
+    /// Extracts every entry of the zip archive into the destination directory, decompressing several entries
+    /// at the same time on the rayon thread pool.
+    ///
+    /// Each thread reads the zip file through its own file handle. The entries are checked as done by
+    /// `extract_all()`, and the same options apply, except that the progress receiver is not notified.
+    /// With the `extract_nested` option, the nested zip archives are extracted one by one once the entries
+    /// are written. When the Unzipper was not opened from a path, the entries are extracted one by one.
+    ///
+    /// This method is available with the `rayon` feature.
+    ///
+    /// # Arguments
+    /// * `dest` - A reference to the path of the directory where the entries will be written. It is created if missing.
+    ///
+    /// # Returns
+    /// A Result indicating success or the error of one of the entries that could not be extracted.
+    pub fn extract_all_parallel(&self, dest: &Path) -> Result<(), UnzipperError> {
+        use rayon::prelude::*;
+
+        if self.filepath.as_os_str().is_empty() {
+            return self.extract_all(dest);
+        }
+
+        let file_paths = self.select_entries(|_| true);
+        let extraction = self.prepare_extraction(dest, file_paths, "", None)?;
+        fs::create_dir_all(dest)?;
+
+        // The progress receiver of the Unzipper cannot be shared between threads
+        let (filepath, file_entries, options) =
+            (&self.filepath, self.parsed_entries(), &self.options);
+        let total_decompressed = &self.total_decompressed;
+        (0..extraction.file_paths.len())
+            .into_par_iter()
+            .try_for_each_init(
+                || None,
+                |unzipper: &mut Option<Unzipper<File>>, index| {
+                    if unzipper.is_none() {
+                        *unzipper = Some(Unzipper::open_shared(
+                            filepath,
+                            file_entries,
+                            options,
+                            total_decompressed,
+                        )?);
+                    }
+                    let unzipper = unzipper.as_mut().expect("reopened above");
+                    unzipper.options.check_cancelled()?;
+                    unzipper.extract_entry(&extraction, index)
+                },
+            )?;
+
+        // The nested zip archives are extracted afterwards, one by one
+        if self.options.nested_depth > 0 {
+            self.extract_nested_archives(&extraction, None)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod extract_tests {
+    use super::*;
+
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Arc;
+
+    use crate::options::ParseMode;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_unzipper_extract_all() {
+        let unit_test = UnitTest::new("unzipper_extract_all");
+        let dest = std::env::temp_dir().join("unzipper_extract_all");
+        let _ = fs::remove_dir_all(&dest);
+
+        let file = PathBuf::from(unit_test.test_case_folder()).join("zip_slip.zip");
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert!(unzipper.extract_all(&dest).is_err());
+        assert!(!dest.join("good.txt").exists());
+
+        let file = PathBuf::from(UnitTest::new("unzipper_open_epub_file").test_case_folder())
+            .join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        unzipper.extract_all(&dest).unwrap();
+
+        assert!(dest.join("Fonts").is_dir());
+        assert_eq!(
+            fs::read_to_string(dest.join("mimetype")).unwrap(),
+            "application/epub+zip"
+        );
+        assert_eq!(
+            fs::read(dest.join("META-INF/container.xml")).unwrap(),
+            unzipper.get_file("META-INF/container.xml").unwrap()
+        );
+
+        fs::remove_dir_all(&dest).unwrap();
+    }
+
+    #[test]
+    fn test_unzipper_extract_all_with_report() {
+        let unit_test = UnitTest::new("unzipper_extract_all");
+        let dest = std::env::temp_dir().join("unzipper_extract_all_with_report");
+        let _ = fs::remove_dir_all(&dest);
+
+        // The entry escaping the destination directory fails alone
+        let file = PathBuf::from(unit_test.test_case_folder()).join("zip_slip.zip");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let report = unzipper.extract_all_with_report(&dest).unwrap();
+        assert!(!report.is_complete());
+        assert_eq!(report.extracted().collect::<Vec<_>>(), ["good.txt"]);
+        let failures: Vec<&str> = report.failures().map(|(path, _)| path).collect();
+        assert_eq!(failures, ["/tmp/unzipper_zip_slip_evil.txt"]);
+        assert!(dest.join("good.txt").exists());
+        fs::remove_dir_all(&dest).unwrap();
+
+        // The entries whose data is corrupted fail alone
+        let unit_test = UnitTest::new("unzipper_verify");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("broken.zip");
+        let options = UnzipperOptions::new().parse_mode(ParseMode::Lenient);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        let report = unzipper.extract_all_with_report(&dest).unwrap();
+        assert_eq!(report.results().len(), 5);
+        assert_eq!(
+            report.extracted().collect::<Vec<_>>(),
+            ["bad_size.txt", "good.txt"]
+        );
+        let failures: Vec<&str> = report.failures().map(|(path, _)| path).collect();
+        assert_eq!(failures, ["bad_crc.txt", "bad_offset.txt", "beyond.txt"]);
+        assert!(matches!(
+            report.failures().next(),
+            Some((_, UnzipperError::CrcMismatch { .. }))
+        ));
+        assert_eq!(fs::read(dest.join("good.txt")).unwrap().len(), 260);
+        assert!(unzipper.extract_all(&dest).is_err());
+
+        // A cancellation still ends the whole extraction
+        let token = Arc::new(AtomicBool::new(true));
+        let options = UnzipperOptions::new().cancellation(token);
+        let unzipper =
+            Unzipper::with_options(&file, options.parse_mode(ParseMode::Lenient)).unwrap();
+        assert!(matches!(
+            unzipper.extract_all_with_report(&dest),
+            Err(UnzipperError::Cancelled)
+        ));
+
+        fs::remove_dir_all(&dest).unwrap();
+    }
+
+    #[test]
+    fn test_unzipper_extract_dir() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let dest = std::env::temp_dir().join("unzipper_extract_dir");
+        let _ = fs::remove_dir_all(&dest);
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        unzipper.extract_dir("Fonts/", &dest).unwrap();
+        assert_eq!(fs::read_dir(&dest).unwrap().count(), 6);
+        assert_eq!(
+            fs::read(dest.join("Swinging.ttf")).unwrap(),
+            unzipper.get_file("Fonts/Swinging.ttf").unwrap()
+        );
+        assert!(!dest.join("mimetype").exists());
+        fs::remove_dir_all(&dest).unwrap();
+
+        assert!(matches!(
+            unzipper.extract_dir("Font", &dest),
+            Err(UnzipperError::NotFound(_))
+        ));
+        assert!(!dest.exists());
+
+        // To memory, with the paths relative to the directory
+        let files = unzipper.get_dir("Images").unwrap();
+        let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["By-sa.png", "qrcode.png", "spacer.gif"]);
+        assert_eq!(files[2].1, unzipper.get_file("Images/spacer.gif").unwrap());
+        assert!(unzipper.get_dir("missing").is_err());
+
+        // A link to a file inside the directory is created
+        let unit_test = UnitTest::new("unzipper_symlinks");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("symlinks.zip");
+        let unzipper = Unzipper::new(&file).unwrap();
+        unzipper.extract_dir("docs", &dest).unwrap();
+        assert_eq!(fs::read(dest.join("readme.txt")).unwrap(), b"read me\n");
+        #[cfg(unix)]
+        assert_eq!(
+            fs::read_link(dest.join("link.txt")).unwrap(),
+            Path::new("readme.txt")
+        );
+        fs::remove_dir_all(&dest).unwrap();
+        assert_eq!(relative_path("docs", "docs/link.txt"), Some("link.txt"));
+        assert_eq!(relative_path("docs", "docsx/file"), None);
+        assert_eq!(relative_path("", "docs/link.txt"), Some("docs/link.txt"));
+    }
+
+    #[test]
+    fn test_unzipper_extract_matching() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let dest = std::env::temp_dir().join("unzipper_extract_matching");
+        let _ = fs::remove_dir_all(&dest);
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert_eq!(unzipper.extract_matching("**/*.ttf", &dest).unwrap(), 2);
+        assert!(dest.join("Fonts/Swinging.ttf").is_file());
+        assert!(dest.join("Fonts/ELZEVIER_C.ttf").is_file());
+        assert!(!dest.join("Fonts/LinBiolinum_R.otf").exists());
+        assert!(!dest.join("mimetype").exists());
+        fs::remove_dir_all(&dest).unwrap();
+
+        assert!(matches!(
+            unzipper.extract_matching("[a-", &dest),
+            Err(UnzipperError::InvalidPattern(_))
+        ));
+        assert!(!dest.exists());
+
+        #[cfg(feature = "regex")]
+        {
+            let regex = regex::Regex::new(r"^Images/.*\.(png|gif)$").unwrap();
+            assert_eq!(unzipper.extract_matching_regex(&regex, &dest).unwrap(), 3);
+            assert!(dest.join("Images/spacer.gif").is_file());
+            fs::remove_dir_all(&dest).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_unzipper_extract_nested() {
+        use crate::options::UnzipperOptions;
+
+        let unit_test = UnitTest::new("unzipper_nested");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("nested.zip");
+        let dest = std::env::temp_dir().join("unzipper_extract_nested");
+        let _ = fs::remove_dir_all(&dest);
+
+        // Nested zip archives are plain files by default
+        let unzipper = Unzipper::new(&file).unwrap();
+        unzipper.extract_all(&dest).unwrap();
+        assert!(dest.join("bundle/inner.zip").is_file());
+        assert!(!dest.join("bundle/inner").exists());
+        fs::remove_dir_all(&dest).unwrap();
+
+        let options = UnzipperOptions::new().extract_nested(1);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        unzipper.extract_all(&dest).unwrap();
+        assert!(dest.join("bundle/inner.zip").is_file());
+        assert_eq!(
+            fs::read(dest.join("bundle/inner/doc.txt")).unwrap(),
+            b"inner document\n"
+        );
+        assert!(dest.join("bundle/inner/deeper.zip").is_file());
+        assert!(!dest.join("bundle/inner/deeper").exists());
+        fs::remove_dir_all(&dest).unwrap();
+
+        let options = UnzipperOptions::new().extract_nested(2);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        unzipper.extract_all(&dest).unwrap();
+        assert_eq!(
+            fs::read(dest.join("bundle/inner/deeper/deep.txt")).unwrap(),
+            b"deep content\n"
+        );
+        fs::remove_dir_all(&dest).unwrap();
+
+        assert_eq!(
+            nested_destination(Path::new("out/archive")),
+            Path::new("out/archive.d")
+        );
+    }
+
+    #[test]
+    fn test_unzipper_extract_directories() {
+        let unit_test = UnitTest::new("unzipper_directories");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("directories.zip");
+        let dest = std::env::temp_dir().join("unzipper_extract_directories");
+        let _ = fs::remove_dir_all(&dest);
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert!(unzipper.metadata("empty").unwrap().is_dir());
+        assert!(unzipper.metadata("dosdir").unwrap().is_dir());
+        assert!(!unzipper.metadata("file.txt").unwrap().is_dir());
+        assert_eq!(unzipper.entries().filter(|entry| entry.is_dir()).count(), 2);
+
+        unzipper.extract_all(&dest).unwrap();
+        assert!(dest.join("empty").is_dir());
+        assert!(dest.join("dosdir").is_dir());
+        assert!(dest.join("file.txt").is_file());
+        fs::remove_dir_all(&dest).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_unzipper_extract_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        use crate::options::UnzipperOptions;
+
+        let unit_test = UnitTest::new("unzipper_permissions");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("permissions.zip");
+        let dest = std::env::temp_dir().join("unzipper_extract_permissions");
+        let _ = fs::remove_dir_all(&dest);
+        let mode =
+            |path: &str| fs::metadata(dest.join(path)).unwrap().permissions().mode() & 0o7777;
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        let entry = unzipper
+            .entries()
+            .find(|entry| entry.name() == "bin/run.sh")
+            .unwrap();
+        assert_eq!(entry.unix_mode(), Some(0o104755));
+        assert!(unzipper
+            .entries()
+            .any(|entry| entry.name() == "dos.txt" && entry.unix_mode().is_none()));
+
+        // The setuid bit is dropped
+        unzipper.extract_all(&dest).unwrap();
+        assert_eq!(mode("bin/run.sh"), 0o755);
+        assert_eq!(mode("private.txt"), 0o600);
+        fs::remove_dir_all(&dest).unwrap();
+
+        let options = UnzipperOptions::new().apply_permissions(false);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        unzipper.extract_all(&dest).unwrap();
+        assert_eq!(mode("bin/run.sh") & 0o111, 0);
+        fs::remove_dir_all(&dest).unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_unzipper_extract_symlinks() {
+        use crate::entry::EntryKind;
+        use crate::options::{SymlinkPolicy, UnzipperOptions};
+
+        let unit_test = UnitTest::new("unzipper_symlinks");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("symlinks.zip");
+        let dest = std::env::temp_dir().join("unzipper_extract_symlinks");
+        let _ = fs::remove_dir_all(&dest);
+        let extract = |policy: SymlinkPolicy| {
+            let options = UnzipperOptions::new().symlinks(policy);
+            Unzipper::with_options(&file, options)
+                .unwrap()
+                .extract_all(&dest)
+        };
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        let kind = |name: &str| {
+            unzipper
+                .entries()
+                .find(|entry| entry.name() == name)
+                .unwrap()
+                .kind()
+        };
+        assert_eq!(kind("bin/tool"), EntryKind::File);
+        assert_eq!(kind("tool"), EntryKind::Symlink);
+        assert_eq!(kind("empty"), EntryKind::Directory);
+
+        extract(SymlinkPolicy::Create).unwrap();
+        assert_eq!(
+            fs::read_link(dest.join("tool")).unwrap(),
+            Path::new("bin/tool")
+        );
+        assert_eq!(
+            fs::read_link(dest.join("docs/link.txt")).unwrap(),
+            Path::new("readme.txt")
+        );
+        assert_eq!(
+            fs::read(dest.join("latest")).unwrap(),
+            b"#!/bin/sh\necho tool\n"
+        );
+        assert!(dest.join("empty").is_dir());
+        fs::remove_dir_all(&dest).unwrap();
+
+        // Chains of links are followed
+        extract(SymlinkPolicy::Materialize).unwrap();
+        assert!(!fs::symlink_metadata(dest.join("latest"))
+            .unwrap()
+            .is_symlink());
+        assert_eq!(
+            fs::read(dest.join("latest")).unwrap(),
+            b"#!/bin/sh\necho tool\n"
+        );
+        assert_eq!(fs::read(dest.join("docs/link.txt")).unwrap(), b"read me\n");
+        fs::remove_dir_all(&dest).unwrap();
+
+        extract(SymlinkPolicy::Skip).unwrap();
+        assert!(dest.join("bin/tool").exists());
+        assert!(fs::symlink_metadata(dest.join("tool")).is_err());
+        fs::remove_dir_all(&dest).unwrap();
+
+        assert!(matches!(
+            extract(SymlinkPolicy::Error),
+            Err(UnzipperError::SymlinkRejected(_))
+        ));
+        assert!(!dest.exists());
+
+        // A link escaping the destination is rejected before writing anything
+        let file = PathBuf::from(unit_test.test_case_folder()).join("escape.zip");
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert!(matches!(
+            unzipper.extract_all(&dest),
+            Err(UnzipperError::SymlinkRejected(_))
+        ));
+        assert!(!dest.exists());
+
+        assert_eq!(
+            resolve_link_target("a/b/link", "../c").as_deref(),
+            Some("a/c")
+        );
+        assert_eq!(resolve_link_target("a/link", "../../c"), None);
+        assert_eq!(resolve_link_target("a/link", "b/../../c"), None);
+        assert_eq!(resolve_link_target("link", "/etc/passwd"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_unzipper_extract_all_parallel() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let dest = std::env::temp_dir().join("unzipper_extract_all_parallel");
+        let _ = fs::remove_dir_all(&dest);
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        unzipper.extract_all_parallel(&dest).unwrap();
+
+        let names: Vec<String> = unzipper.entries().map(|e| e.name().to_string()).collect();
+        for name in names {
+            if unzipper.metadata(&name).unwrap().is_dir() {
+                assert!(dest.join(&name).is_dir());
+            } else {
+                assert_eq!(
+                    fs::read(dest.join(&name)).unwrap(),
+                    unzipper.get_file(&name).unwrap(),
+                    "{name}"
+                );
+            }
+        }
+        fs::remove_dir_all(&dest).unwrap();
+
+        let file = PathBuf::from(UnitTest::new("unzipper_extract_all").test_case_folder())
+            .join("zip_slip.zip");
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert!(unzipper.extract_all_parallel(&dest).is_err());
+        assert!(!dest.exists());
+
+        // The nested zip archives are extracted as by extract_all()
+        let file =
+            PathBuf::from(UnitTest::new("unzipper_nested").test_case_folder()).join("nested.zip");
+        let options = crate::options::UnzipperOptions::new().extract_nested(2);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        unzipper.extract_all_parallel(&dest).unwrap();
+        assert_eq!(
+            fs::read(dest.join("bundle/inner/doc.txt")).unwrap(),
+            b"inner document\n"
+        );
+        assert_eq!(
+            fs::read(dest.join("bundle/inner/deeper/deep.txt")).unwrap(),
+            b"deep content\n"
+        );
+        fs::remove_dir_all(&dest).unwrap();
+    }
+}
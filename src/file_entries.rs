@@ -0,0 +1,361 @@
+//! File Entries Module.
+//!
+//! This module provides the index of the file entries of a zip archive. The entries are kept in the order
+//! of the central directory, which matters to formats such as EPUB (whose `mimetype` entry comes first)
+//! and to forensic tools, while being looked up by path through a hash map. For the zip archives of millions
+//! of entries, the compact index keys the hash map by a hash of the paths, so that they are not copied into it,
+//! each path being then kept once, with its entry.
+
+use std::collections::hash_map::{Entry, RandomState};
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::ops::Index;
+
+use crate::error::UnzipperError;
+use crate::options::{DuplicatePolicy, UnzipperOptions};
+use crate::unzipper::FileEntry;
+use crate::warning::Warning;
+
+/// Adds a number to a path before the extension of its last segment, such as `notes (2).txt` for `notes.txt`.
+fn numbered_path(file_path: &str, number: usize) -> String {
+    let name_start = file_path.rfind('/').map_or(0, |index| index + 1);
+    match file_path[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => {
+            let (stem, extension) = file_path.split_at(name_start + dot);
+            format!("{stem} ({number}){extension}")
+        }
+        _ => format!("{file_path} ({number})"),
+    }
+}
+
+/// Lookup of the positions of the entries.
+#[derive(Debug, Clone)]
+enum Lookup {
+    /// Positions by cleaned path.
+    Paths(HashMap<String, usize>),
+    /// Positions by hash of the cleaned path, with the positions of the entries whose hash was already taken.
+    Hashes {
+        hasher: RandomState,
+        positions: HashMap<u64, u32>,
+        collisions: Vec<u32>,
+    },
+}
+
+impl Default for Lookup {
+    fn default() -> Lookup {
+        Lookup::Paths(HashMap::new())
+    }
+}
+
+/// Index of the file entries, by cleaned path, in the order of the central directory.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct FileEntries {
+    entries: Vec<(String, FileEntry)>,
+    lookup: Lookup, // of the positions in entries
+}
+
+impl FileEntries {
+    /// Creates a new empty index.
+    pub(crate) fn new() -> FileEntries {
+        FileEntries::default()
+    }
+
+    /// Creates a new empty index, compact if set by the options. See `UnzipperOptions::compact_index()`.
+    pub(crate) fn with_options(options: &UnzipperOptions) -> FileEntries {
+        if !options.compact_index {
+            return FileEntries::new();
+        }
+        FileEntries {
+            entries: Vec::new(),
+            lookup: Lookup::Hashes {
+                hasher: RandomState::new(),
+                positions: HashMap::new(),
+                collisions: Vec::new(),
+            },
+        }
+    }
+
+    /// Adds an entry after the others. An entry with the same path is replaced, keeping its place.
+    pub(crate) fn insert(&mut self, file_path: String, file_entry: FileEntry) {
+        if let Some(position) = self.position(&file_path) {
+            self.entries[position].1 = file_entry;
+            return;
+        }
+        let position = self.entries.len();
+        match self.lookup {
+            Lookup::Paths(ref mut positions) => {
+                positions.insert(file_path.clone(), position);
+            }
+            Lookup::Hashes {
+                ref hasher,
+                ref mut positions,
+                ref mut collisions,
+            } => match positions.entry(hasher.hash_one(&file_path)) {
+                Entry::Vacant(entry) => {
+                    entry.insert(position as u32);
+                }
+                Entry::Occupied(_) => collisions.push(position as u32),
+            },
+        }
+        self.entries.push((file_path, file_entry));
+    }
+
+    /// Returns the position of the entry of a cleaned path, if any.
+    fn position(&self, file_path: &str) -> Option<usize> {
+        match self.lookup {
+            Lookup::Paths(ref positions) => positions.get(file_path).copied(),
+            Lookup::Hashes {
+                ref hasher,
+                ref positions,
+                ref collisions,
+            } => {
+                let position = *positions.get(&hasher.hash_one(file_path))? as usize;
+                if self.entries[position].0 == file_path {
+                    return Some(position);
+                }
+                collisions
+                    .iter()
+                    .map(|&position| position as usize)
+                    .find(|&position| self.entries[position].0 == file_path)
+            }
+        }
+    }
+
+    /// Adds an entry after the others, handling an entry with the same path according to a duplicate policy.
+    ///
+    /// # Arguments
+    /// * `file_path` - The cleaned path of the entry.
+    /// * `file_entry` - The entry.
+    /// * `policy` - The duplicate policy of the options.
+    /// * `warnings` - Receives the warning reporting a duplicate.
+    ///
+    /// # Returns
+    /// A Result indicating success, or an error if the entry is a duplicate rejected by the policy.
+    pub(crate) fn insert_with_policy(
+        &mut self,
+        file_path: String,
+        file_entry: FileEntry,
+        policy: DuplicatePolicy,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<(), UnzipperError> {
+        if !self.contains_key(&file_path) {
+            self.insert(file_path, file_entry);
+            return Ok(());
+        }
+
+        let message = match policy {
+            DuplicatePolicy::Error => return Err(UnzipperError::DuplicateEntry(file_path)),
+            DuplicatePolicy::LastWins => {
+                self.insert(file_path.clone(), file_entry);
+                "Duplicate entry name, replacing the previous entry".to_string()
+            }
+            DuplicatePolicy::FirstWins => "Duplicate entry name, ignored".to_string(),
+            DuplicatePolicy::KeepAll => {
+                let renamed = (2..)
+                    .map(|number| numbered_path(&file_path, number))
+                    .find(|renamed| !self.contains_key(renamed))
+                    .unwrap_or_default();
+                let message = format!("Duplicate entry name, renamed {renamed}");
+                self.insert(renamed, file_entry);
+                message
+            }
+        };
+        warnings.push(Warning::new(Some(&file_path), &message));
+        Ok(())
+    }
+
+    /// Returns the entry of a cleaned path, if any.
+    pub(crate) fn get(&self, file_path: &str) -> Option<&FileEntry> {
+        self.get_key_value(file_path)
+            .map(|(_, file_entry)| file_entry)
+    }
+
+    /// Returns the cleaned path and the entry of a cleaned path, if any.
+    pub(crate) fn get_key_value(&self, file_path: &str) -> Option<(&String, &FileEntry)> {
+        self.position(file_path).map(|position| {
+            let (name, file_entry) = &self.entries[position];
+            (name, file_entry)
+        })
+    }
+
+    /// Returns true if an entry has the given cleaned path.
+    pub(crate) fn contains_key(&self, file_path: &str) -> bool {
+        self.position(file_path).is_some()
+    }
+
+    /// Returns an iterator over the cleaned paths and the entries, in the order of the central directory.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&String, &FileEntry)> {
+        self.entries
+            .iter()
+            .map(|(name, file_entry)| (name, file_entry))
+    }
+
+    /// Returns an iterator over the cleaned paths, in the order of the central directory.
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &String> {
+        self.entries.iter().map(|(name, _)| name)
+    }
+
+    /// Returns true if there is no entry.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Index<&str> for FileEntries {
+    type Output = FileEntry;
+
+    /// Returns the entry of a cleaned path. Panics if there is none.
+    fn index(&self, file_path: &str) -> &FileEntry {
+        self.get(file_path).expect("entry of the archive")
+    }
+}
+
+impl<'a> IntoIterator for &'a FileEntries {
+    type Item = (&'a String, &'a FileEntry);
+    type IntoIter = std::iter::Map<
+        std::slice::Iter<'a, (String, FileEntry)>,
+        fn(&'a (String, FileEntry)) -> (&'a String, &'a FileEntry),
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries
+            .iter()
+            .map(|(name, file_entry)| (name, file_entry))
+    }
+}
+
+#[cfg(test)]
+mod file_entries_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_file_entries_order() {
+        let mut file_entries = FileEntries::new();
+        for (name, size) in [("b", 1), ("a", 2), ("c", 3), ("a", 4)] {
+            let file_entry = FileEntry {
+                size,
+                ..FileEntry::default()
+            };
+            file_entries.insert(name.to_string(), file_entry);
+        }
+        assert_eq!(file_entries.keys().count(), 3);
+        assert_eq!(file_entries.keys().collect::<Vec<_>>(), ["b", "a", "c"]);
+        assert_eq!(file_entries["a"].size, 4);
+        assert!(file_entries.get("d").is_none());
+
+        // The entries of an EPUB come in the order of the archive, mimetype first
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let names: Vec<String> = unzipper
+            .entries()
+            .take(3)
+            .map(|entry| entry.name().to_string())
+            .collect();
+        assert_eq!(names, ["mimetype", "META-INF", "META-INF/container.xml"]);
+    }
+
+    #[test]
+    fn test_file_entries_compact() {
+        use crate::options::UnzipperOptions;
+
+        let options = UnzipperOptions::new().compact_index(true);
+        let mut file_entries = FileEntries::with_options(&options);
+        for (name, size) in [("b", 1), ("a", 2), ("a", 3)] {
+            let file_entry = FileEntry {
+                size,
+                ..FileEntry::default()
+            };
+            file_entries.insert(name.to_string(), file_entry);
+        }
+        assert_eq!(file_entries.keys().collect::<Vec<_>>(), ["b", "a"]);
+        assert_eq!(file_entries["a"].size, 3);
+        assert!(!file_entries.contains_key("c"));
+
+        // A path whose hash is taken by the one of another path
+        if let Lookup::Hashes {
+            ref hasher,
+            ref mut positions,
+            ..
+        } = file_entries.lookup
+        {
+            positions.insert(hasher.hash_one("c"), 0);
+        }
+        assert!(!file_entries.contains_key("c"));
+        file_entries.insert("c".to_string(), FileEntry::default());
+        file_entries.insert("c".to_string(), FileEntry::default());
+        assert_eq!(file_entries.keys().collect::<Vec<_>>(), ["b", "a", "c"]);
+        assert_eq!(file_entries.get_key_value("c").unwrap().0, "c");
+        assert_eq!(file_entries["b"].size, 1);
+
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let expected = Unzipper::new(&file).unwrap();
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        assert_eq!(format!("{unzipper:?}"), format!("{expected:?}"));
+        assert_eq!(
+            unzipper.get_file("mimetype").unwrap(),
+            b"application/epub+zip"
+        );
+    }
+
+    #[test]
+    fn test_file_entries_duplicates() {
+        use crate::options::UnzipperOptions;
+
+        let unit_test = UnitTest::new("unzipper_duplicates");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("duplicates.zip");
+        let open = |policy: DuplicatePolicy| {
+            Unzipper::with_options(&file, UnzipperOptions::new().duplicates(policy))
+        };
+        let names = |unzipper: &Unzipper| -> Vec<String> {
+            unzipper
+                .entries()
+                .map(|entry| entry.name().to_string())
+                .collect()
+        };
+
+        let unzipper = open(DuplicatePolicy::LastWins).unwrap();
+        assert_eq!(names(&unzipper), ["notes.txt", "readme", "notes (2).txt"]);
+        assert_eq!(unzipper.get_file("notes.txt").unwrap(), b"second\n");
+        assert_eq!(unzipper.warnings().len(), 2);
+        assert_eq!(unzipper.warnings()[0].file_path(), Some("notes.txt"));
+
+        let unzipper = open(DuplicatePolicy::FirstWins).unwrap();
+        assert_eq!(names(&unzipper), ["notes.txt", "readme", "notes (2).txt"]);
+        assert_eq!(unzipper.get_file("notes.txt").unwrap(), b"first\n");
+        assert_eq!(unzipper.get_file("readme").unwrap(), b"readme\n");
+        assert_eq!(unzipper.warnings().len(), 2);
+
+        // An entry renamed first takes the path of a later entry, which is then renamed in turn
+        let unzipper = open(DuplicatePolicy::KeepAll).unwrap();
+        assert_eq!(
+            names(&unzipper),
+            [
+                "notes.txt",
+                "readme",
+                "notes (2).txt",
+                "notes (2) (2).txt",
+                "readme (2)"
+            ]
+        );
+        assert_eq!(unzipper.get_file("notes (2).txt").unwrap(), b"second\n");
+        assert_eq!(unzipper.get_file("notes (2) (2).txt").unwrap(), b"taken\n");
+        assert_eq!(unzipper.get_file("readme (2)").unwrap(), b"other readme\n");
+        assert_eq!(
+            unzipper.warnings()[0].message(),
+            "Duplicate entry name, renamed notes (2).txt"
+        );
+
+        assert!(matches!(
+            open(DuplicatePolicy::Error),
+            Err(UnzipperError::DuplicateEntry(file_path)) if file_path == "notes.txt"
+        ));
+        assert_eq!(numbered_path("dir.d/.profile", 2), "dir.d/.profile (2)");
+    }
+}
@@ -0,0 +1,319 @@
+//! Futures Unzipper Module.
+//!
+//! This module provides access to zip archives read from asynchronous sources implementing the `AsyncRead`
+//! and `AsyncSeek` traits of the futures crate, so that the crate can be used with any executor
+//! (async-std, smol, or custom asynchronous transports).
+//!
+//! The byte ranges needed by an operation (the end of the archive when opening it, the local header and data
+//! of an entry when reading it) are first read from the source asynchronously. They are then processed in memory
+//! by the regular Unzipper, which provides the decompression, decryption and verification of the entries.
+//!
+//! This module is available with the `futures` feature.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::{Arc, RwLock};
+
+use futures::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+use crate::entry::EntryInfo;
+use crate::error::UnzipperError;
+use crate::options::UnzipperOptions;
+use crate::unzipper::Unzipper;
+use crate::warning::Warning;
+
+/// Size of the end of central directory record, without the comment.
+const DIR_END_SIZE: usize = 22;
+
+/// Size of the local file header, without the file name and extra field.
+const FILE_HEADER_SIZE: usize = 30;
+
+/// Size of the largest data descriptor, following the entry data.
+const DATA_DESCRIPTOR_SIZE: usize = 24;
+
+/// In-memory copy of a range of the zip file, seen as the whole zip file.
+///
+/// Reading outside of the range fails, which never happens when the range holds everything an operation needs.
+#[derive(Debug, Default)]
+pub(crate) struct Window {
+    offset: usize,   // of the range in the zip file
+    data: Vec<u8>,   // content of the range
+    length: usize,   // of the zip file
+    position: usize, // current position in the zip file
+}
+
+impl Read for Window {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let start = self.position.checked_sub(self.offset).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Position before the range read from the source",
+            )
+        })?;
+        let available = self.data.get(start..).unwrap_or_default();
+        let length = available.len().min(buf.len());
+        buf[..length].copy_from_slice(&available[..length]);
+        self.position += length;
+        Ok(length)
+    }
+}
+
+impl Seek for Window {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(delta) => (self.length as u64).checked_add_signed(delta),
+            SeekFrom::Current(delta) => (self.position as u64).checked_add_signed(delta),
+        };
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid seek position")
+        })? as usize;
+        Ok(self.position as u64)
+    }
+}
+
+/// Struct that provides access to a zip archive read from an asynchronous source.
+///
+/// The entries are listed without accessing the source. Reading an entry reads its compressed data
+/// asynchronously, then decompresses it.
+pub struct FuturesUnzipper<R: AsyncRead + AsyncSeek + Unpin> {
+    source: R,
+    length: usize,              // of the zip file
+    unzipper: Unzipper<Window>, // holding the file entries, reading from the last window
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> FuturesUnzipper<R> {
+    /// Opens the zip archive read from an asynchronous source.
+    ///
+    /// # Arguments
+    /// * `source` - The source of the zip archive.
+    ///
+    /// # Returns
+    /// A Result containing the FuturesUnzipper, or an error if the zip archive is invalid.
+    pub async fn from_async_reader(source: R) -> Result<FuturesUnzipper<R>, UnzipperError> {
+        FuturesUnzipper::from_async_reader_with_options(source, UnzipperOptions::default()).await
+    }
+
+    /// Opens the zip archive read from an asynchronous source, using the given options.
+    ///
+    /// # Arguments
+    /// * `source` - The source of the zip archive.
+    /// * `options` - The options controlling how the zip archive is read.
+    ///
+    /// # Returns
+    /// A Result containing the FuturesUnzipper, or an error if the zip archive is invalid.
+    pub async fn from_async_reader_with_options(
+        mut source: R,
+        options: UnzipperOptions,
+    ) -> Result<FuturesUnzipper<R>, UnzipperError> {
+        let length = source.seek(SeekFrom::End(0)).await? as usize;
+
+        // The end of central directory record is within the last 64 KiB of the zip file, followed by the comment
+        let tail_offset = length.saturating_sub(DIR_END_SIZE + u16::MAX as usize);
+        let mut futures_unzipper = FuturesUnzipper {
+            source,
+            length,
+            unzipper: Unzipper::empty(options),
+        };
+        futures_unzipper
+            .load_window(tail_offset, length - tail_offset)
+            .await?;
+        let mut warnings = Vec::new();
+        let dir_end = futures_unzipper.unzipper.find_dir_end(&mut warnings)?;
+        futures_unzipper.unzipper.read_comment(&dir_end)?;
+
+        let mut entries = vec![0; dir_end.dir_size];
+        futures_unzipper
+            .read_source(&mut entries, dir_end.dir_offset)
+            .await?;
+        futures_unzipper.unzipper.file_entries = Arc::new(
+            futures_unzipper
+                .unzipper
+                .parse_central_directory(&entries, &dir_end, &mut warnings)?,
+        );
+        futures_unzipper.unzipper.record_warnings(warnings);
+        futures_unzipper.unzipper.reader = Some(RwLock::new(Window::default()));
+
+        Ok(futures_unzipper)
+    }
+
+    /// Returns an iterator over the file entries available in the zip archive. See `Unzipper::entries()`.
+    pub fn entries(&self) -> impl Iterator<Item = EntryInfo> + '_ {
+        self.unzipper.entries()
+    }
+
+    /// Returns the metadata of a file entry of the zip archive. See `Unzipper::metadata()`.
+    pub fn metadata(&self, file_path: &str) -> Option<EntryInfo> {
+        self.unzipper.metadata(file_path)
+    }
+
+    /// Returns the comment of the zip archive. See `Unzipper::comment()`.
+    pub fn comment(&self) -> &[u8] {
+        self.unzipper.comment()
+    }
+
+    /// Returns the inconsistencies tolerated while reading the zip archive. See `Unzipper::warnings()`.
+    pub fn warnings(&self) -> Vec<Warning> {
+        self.unzipper.warnings()
+    }
+
+    /// Unzips a file from the archive into a bytes vector.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to unzip.
+    ///
+    /// # Returns
+    /// A Result containing the content of the file, or an error if the file is not found or decompression fails.
+    pub async fn get_file(&mut self, file_path: &str) -> Result<Vec<u8>, UnzipperError> {
+        self.load_entry(file_path).await?;
+        let result = self.unzipper.get_file(file_path);
+        self.unzipper.reader = Some(RwLock::new(Window::default()));
+        result
+    }
+
+    /// Unzips an encrypted file from the archive into a bytes vector.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to unzip.
+    /// * `password` - The password used to decrypt the entry. It is ignored if the entry is not encrypted.
+    ///
+    /// # Returns
+    /// A Result containing the content of the file, or an error if the file is not found, the password is wrong
+    /// or decompression fails.
+    pub async fn get_file_with_password(
+        &mut self,
+        file_path: &str,
+        password: &[u8],
+    ) -> Result<Vec<u8>, UnzipperError> {
+        self.load_entry(file_path).await?;
+        let result = self.unzipper.get_file_with_password(file_path, password);
+        self.unzipper.reader = Some(RwLock::new(Window::default()));
+        result
+    }
+
+    /// Reads the local header, the data and the data descriptor of an entry from the source.
+    async fn load_entry(&mut self, file_path: &str) -> Result<(), UnzipperError> {
+        let cleaned_file_path = self.unzipper.clean_file_path(file_path);
+        let (start_pos, compressed_size) =
+            match self.unzipper.parsed_entries().get(&cleaned_file_path) {
+                Some(file_entry) => (
+                    file_entry.start_pos as usize,
+                    file_entry.compressed_size as usize,
+                ),
+                None => return Err(UnzipperError::NotFound(cleaned_file_path)),
+            };
+
+        // file name length               2 bytes  26
+        // extra field length             2 bytes  28
+        let mut header = [0u8; FILE_HEADER_SIZE];
+        self.read_source(&mut header, start_pos).await?;
+        let variable_size = u16::from_le_bytes([header[26], header[27]]) as usize
+            + u16::from_le_bytes([header[28], header[29]]) as usize;
+
+        let size = FILE_HEADER_SIZE + variable_size + compressed_size + DATA_DESCRIPTOR_SIZE;
+        let size = size.min(self.length.saturating_sub(start_pos));
+        self.load_window(start_pos, size).await
+    }
+
+    /// Reads a range of the source into the window of the Unzipper.
+    async fn load_window(&mut self, offset: usize, size: usize) -> Result<(), UnzipperError> {
+        let mut data = vec![0; size];
+        self.read_source(&mut data, offset).await?;
+        self.unzipper.reader = Some(RwLock::new(Window {
+            offset,
+            data,
+            length: self.length,
+            position: 0,
+        }));
+        Ok(())
+    }
+
+    /// Reads data from the source at the specified position into the provided buffer.
+    async fn read_source(
+        &mut self,
+        buffer: &mut [u8],
+        position: usize,
+    ) -> Result<(), UnzipperError> {
+        self.source.seek(SeekFrom::Start(position as u64)).await?;
+        self.source.read_exact(buffer).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod futures_unzipper_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use futures::executor::block_on;
+    use futures::io::Cursor;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_futures_unzipper_get_file() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let bytes = std::fs::read(&file).unwrap();
+        let unzipper = Unzipper::from_bytes(&bytes).unwrap();
+        let expected = unzipper.get_file("content.opf").unwrap();
+
+        block_on(async {
+            let mut futures_unzipper = FuturesUnzipper::from_async_reader(Cursor::new(bytes))
+                .await
+                .unwrap();
+            assert_eq!(futures_unzipper.entries().count(), 86);
+            assert_eq!(
+                futures_unzipper.get_file("mimetype").await.unwrap(),
+                b"application/epub+zip"
+            );
+            assert_eq!(
+                futures_unzipper.get_file("content.opf").await.unwrap(),
+                expected
+            );
+            assert!(matches!(
+                futures_unzipper.get_file("missing.txt").await,
+                Err(UnzipperError::NotFound(_))
+            ));
+        });
+
+        // With an archive comment
+        let unit_test = UnitTest::new("unzipper_comment");
+        let bytes =
+            std::fs::read(PathBuf::from(unit_test.test_case_folder()).join("comment.zip")).unwrap();
+        block_on(async {
+            let mut futures_unzipper = FuturesUnzipper::from_async_reader(Cursor::new(bytes))
+                .await
+                .unwrap();
+            assert!(futures_unzipper.comment().starts_with(b"build="));
+            assert_eq!(
+                futures_unzipper.get_file("book.txt").await.unwrap(),
+                b"content\n"
+            );
+        });
+
+        let unit_test = UnitTest::new("unzipper_zipcrypto");
+        let bytes =
+            std::fs::read(PathBuf::from(unit_test.test_case_folder()).join("zipcrypto.zip"))
+                .unwrap();
+        let unzipper = Unzipper::from_bytes(&bytes).unwrap();
+        let expected = unzipper
+            .get_file_with_password("secret.txt", b"secret")
+            .unwrap();
+        block_on(async {
+            let mut futures_unzipper = FuturesUnzipper::from_async_reader(Cursor::new(bytes))
+                .await
+                .unwrap();
+            assert!(matches!(
+                futures_unzipper.get_file("secret.txt").await,
+                Err(UnzipperError::PasswordRequired(_))
+            ));
+            assert_eq!(
+                futures_unzipper
+                    .get_file_with_password("secret.txt", b"secret")
+                    .await
+                    .unwrap(),
+                expected
+            );
+        });
+    }
+}
@@ -0,0 +1,312 @@
+//! Glob Module.
+//!
+//! This module provides the matching of entry paths against glob patterns, such as `**/*.xhtml`,
+//! to select entries of a zip archive for listing or extraction.
+
+use std::io::{Read, Seek};
+
+use crate::error::UnzipperError;
+use crate::unzipper::Unzipper;
+
+/// Element of a compiled glob pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// A character matched as is.
+    Literal(char),
+    /// `?`: any character except '/'.
+    AnyChar,
+    /// `*`: any sequence of characters except '/'.
+    AnyChars,
+    /// `**/`: any sequence of whole path segments, possibly none.
+    AnySegments,
+    /// A trailing `**`: anything, including '/'.
+    AnyPath,
+    /// `[...]`: a character of the ranges, or of none of them when negated with `[!...]`.
+    Class {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+}
+
+/// Struct that holds a compiled glob pattern, matched against the cleaned paths of the entries.
+///
+/// The syntax is the usual one of shells, where `**` spans directories:
+/// * `?` matches any character except '/'.
+/// * `*` matches any sequence of characters except '/'.
+/// * `**` as a whole path segment matches any sequence of segments, so that `**/*.xhtml` matches
+///   `a.xhtml` and `OEBPS/text/a.xhtml`, and `OEBPS/**` matches everything under `OEBPS`.
+/// * `[abc]`, `[a-z]` and `[!a-z]` match a character of, or not of, the given set.
+/// * `\` matches the following character as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glob {
+    tokens: Vec<Token>,
+}
+
+impl Glob {
+    /// Compiles a glob pattern.
+    ///
+    /// # Arguments
+    /// * `pattern` - A string slice representing the glob pattern.
+    ///
+    /// # Returns
+    /// A Result containing the Glob, or `UnzipperError::InvalidPattern` if a character class is not closed
+    /// or the pattern ends with an escape character.
+    pub fn new(pattern: &str) -> Result<Glob, UnzipperError> {
+        let chars: Vec<char> = pattern.chars().collect();
+        let mut tokens = Vec::new();
+        let mut index = 0;
+        while index < chars.len() {
+            let token = match chars[index] {
+                '?' => Token::AnyChar,
+                '*' if chars.get(index + 1) == Some(&'*')
+                    && (index == 0 || chars[index - 1] == '/') =>
+                {
+                    // A `**` forming a whole segment spans directories, otherwise it is a `*`
+                    match chars.get(index + 2) {
+                        None => {
+                            index += 1;
+                            Token::AnyPath
+                        }
+                        Some('/') => {
+                            index += 2;
+                            Token::AnySegments
+                        }
+                        Some(_) => {
+                            index += 1;
+                            Token::AnyChars
+                        }
+                    }
+                }
+                '*' => {
+                    while chars.get(index + 1) == Some(&'*') {
+                        index += 1;
+                    }
+                    Token::AnyChars
+                }
+                '[' => {
+                    let (token, end) = Self::parse_class(&chars, index, pattern)?;
+                    index = end;
+                    token
+                }
+                '\\' => {
+                    index += 1;
+                    match chars.get(index) {
+                        Some(&c) => Token::Literal(c),
+                        None => {
+                            return Err(UnzipperError::InvalidPattern(format!(
+                                "Trailing escape character: {pattern}"
+                            )));
+                        }
+                    }
+                }
+                c => Token::Literal(c),
+            };
+            tokens.push(token);
+            index += 1;
+        }
+        Ok(Glob { tokens })
+    }
+
+    /// Parses a character class starting at the given '['.
+    ///
+    /// # Returns
+    /// A Result containing the class and the index of its closing ']', or an error if it is not closed.
+    fn parse_class(
+        chars: &[char],
+        start: usize,
+        pattern: &str,
+    ) -> Result<(Token, usize), UnzipperError> {
+        let mut index = start + 1;
+        let negated = matches!(chars.get(index), Some('!') | Some('^'));
+        if negated {
+            index += 1;
+        }
+
+        // A ']' at the start of the class is one of its characters
+        let mut ranges = Vec::new();
+        let mut first = true;
+        while let Some(&c) = chars.get(index) {
+            if c == ']' && !first {
+                return Ok((Token::Class { ranges, negated }, index));
+            }
+            first = false;
+            if chars.get(index + 1) == Some(&'-') && chars.get(index + 2).is_some_and(|&e| e != ']')
+            {
+                ranges.push((c, chars[index + 2]));
+                index += 3;
+            } else {
+                ranges.push((c, c));
+                index += 1;
+            }
+        }
+        Err(UnzipperError::InvalidPattern(format!(
+            "Unclosed character class: {pattern}"
+        )))
+    }
+
+    /// Returns true if the path matches the pattern.
+    ///
+    /// # Arguments
+    /// * `path` - A string slice representing the cleaned path of an entry, without a leading '/'.
+    pub fn is_match(&self, path: &str) -> bool {
+        let chars: Vec<char> = path.chars().collect();
+        Self::match_tokens(&self.tokens, &chars)
+    }
+
+    /// Matches the tokens against the characters, backtracking over the wildcards.
+    fn match_tokens(tokens: &[Token], chars: &[char]) -> bool {
+        let Some((token, rest)) = tokens.split_first() else {
+            return chars.is_empty();
+        };
+        match token {
+            Token::Literal(c) => chars.first() == Some(c) && Self::match_tokens(rest, &chars[1..]),
+            Token::AnyChar => {
+                chars.first().is_some_and(|&c| c != '/') && Self::match_tokens(rest, &chars[1..])
+            }
+            Token::Class { ranges, negated } => {
+                chars.first().is_some_and(|&c| {
+                    c != '/' && ranges.iter().any(|&(low, high)| low <= c && c <= high) != *negated
+                }) && Self::match_tokens(rest, &chars[1..])
+            }
+            Token::AnyChars => {
+                let segment_end = chars.iter().position(|&c| c == '/').unwrap_or(chars.len());
+                (0..=segment_end).any(|length| Self::match_tokens(rest, &chars[length..]))
+            }
+            Token::AnySegments => {
+                // The rest matches from the start of the path or from the start of any later segment
+                Self::match_tokens(rest, chars)
+                    || (0..chars.len())
+                        .filter(|&index| chars[index] == '/')
+                        .any(|index| Self::match_tokens(rest, &chars[index + 1..]))
+            }
+            Token::AnyPath => true,
+        }
+    }
+}
+
+impl<R: Read + Seek> Unzipper<R> {
+    /// Finds the entries whose path matches a glob pattern.
+    ///
+    /// # Arguments
+    /// * `pattern` - A string slice representing the glob pattern, such as `**/*.xhtml`. See `Glob` for its syntax.
+    ///
+    /// # Returns
+    /// A Result containing the sorted cleaned paths of the matching entries, or an error if the pattern is invalid.
+    /// The paths of the directory entries are given without their trailing '/'.
+    pub fn find(&self, pattern: &str) -> Result<Vec<String>, UnzipperError> {
+        let glob = Glob::new(pattern)?;
+        Ok(self.select_entries(|file_path| glob.is_match(file_path)))
+    }
+
+    /// Finds the entries whose path matches a regular expression.
+    ///
+    /// This method is available with the `regex` feature.
+    ///
+    /// # Arguments
+    /// * `regex` - The regular expression, searched in the cleaned path of each entry. It must be anchored
+    ///   with `^` and `$` to match whole paths.
+    ///
+    /// # Returns
+    /// The sorted cleaned paths of the matching entries. The paths of the directory entries are given
+    /// without their trailing '/'.
+    #[cfg(feature = "regex")]
+    pub fn find_regex(&self, regex: &regex::Regex) -> Vec<String> {
+        self.select_entries(|file_path| regex.is_match(file_path))
+    }
+
+    /// Selects the entries whose cleaned path satisfies a predicate.
+    ///
+    /// # Returns
+    /// The sorted cleaned paths of the entries selected.
+    pub(crate) fn select_entries(&self, select: impl Fn(&str) -> bool) -> Vec<String> {
+        let mut file_paths: Vec<String> = self
+            .parsed_entries()
+            .keys()
+            .filter(|file_path| select(file_path))
+            .cloned()
+            .collect();
+        file_paths.sort();
+        file_paths
+    }
+}
+
+#[cfg(test)]
+mod glob_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_glob_is_match() {
+        let glob = Glob::new("**/*.xhtml").unwrap();
+        assert!(glob.is_match("a.xhtml"));
+        assert!(glob.is_match("OEBPS/text/a.xhtml"));
+        assert!(!glob.is_match("a.xhtml.bak"));
+
+        let glob = Glob::new("*.xhtml").unwrap();
+        assert!(glob.is_match("a.xhtml"));
+        assert!(!glob.is_match("OEBPS/a.xhtml"));
+
+        let glob = Glob::new("OEBPS/**").unwrap();
+        assert!(glob.is_match("OEBPS/a/b.png"));
+        assert!(!glob.is_match("OEBPS"));
+        assert!(!glob.is_match("OEBPSX/a"));
+
+        let glob = Glob::new("a/**/b").unwrap();
+        assert!(glob.is_match("a/b"));
+        assert!(glob.is_match("a/x/y/b"));
+        assert!(!glob.is_match("a/xb"));
+
+        let glob = Glob::new("ch[0-9][!a]?.txt").unwrap();
+        assert!(glob.is_match("ch1bc.txt"));
+        assert!(!glob.is_match("ch1ac.txt"));
+        assert!(!glob.is_match("chxbc.txt"));
+        assert!(!glob.is_match("ch1b/.txt"));
+
+        assert!(Glob::new(r"\*").unwrap().is_match("*"));
+        assert!(!Glob::new(r"\*").unwrap().is_match("a"));
+        assert!(Glob::new("[]]").unwrap().is_match("]"));
+        assert!(matches!(
+            Glob::new("[a-z"),
+            Err(UnzipperError::InvalidPattern(_))
+        ));
+        assert!(Glob::new(r"a\").is_err());
+    }
+
+    #[test]
+    fn test_glob_find() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        assert_eq!(
+            unzipper.find("Fonts/*.ttf").unwrap(),
+            ["Fonts/ELZEVIER_C.ttf", "Fonts/Swinging.ttf"]
+        );
+        assert_eq!(unzipper.find("**/*.xhtml").unwrap().len(), 67);
+        assert_eq!(unzipper.find("**/*.png").unwrap().len(), 2);
+        assert!(unzipper.find("*.pdf").unwrap().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn test_glob_find_regex() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        let regex = regex::Regex::new(r"^epub_split_00[0-2]\.xhtml$").unwrap();
+        assert_eq!(
+            unzipper.find_regex(&regex),
+            [
+                "epub_split_000.xhtml",
+                "epub_split_001.xhtml",
+                "epub_split_002.xhtml"
+            ]
+        );
+        let regex = regex::Regex::new(r"(?i)^fonts/lin.*_r\.otf$").unwrap();
+        assert_eq!(unzipper.find_regex(&regex).len(), 2);
+    }
+}
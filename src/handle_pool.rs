@@ -0,0 +1,189 @@
+//! Handle Pool Module.
+//!
+//! This module provides a pool of file handles on the same zip file, for the servers reading many entries
+//! concurrently. Each read takes a handle from the pool, or reopens one when they are all in use, so that
+//! the simultaneous reads do not wait for a single handle. The handles share the file entries read once
+//! from the central directory, and are given back to the pool when the reads are done.
+
+use std::fs::File;
+use std::io::Write;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, PoisonError};
+
+use crate::error::UnzipperError;
+use crate::unzipper::Unzipper;
+
+/// Default number of idle handles kept in the pool.
+const DEFAULT_MAX_IDLE: usize = 16;
+
+/// Pool of Unzippers reading the same zip file, each through its own file handle.
+///
+/// It is `Send + Sync`, so that it is shared between the threads of a server in an `Arc`.
+pub struct HandlePool {
+    unzipper: Unzipper<File>,            // from which the handles are reopened
+    handles: Mutex<Vec<Unzipper<File>>>, // idle handles
+    max_idle: usize,
+}
+
+impl HandlePool {
+    /// Creates a new HandlePool keeping 16 idle handles at most.
+    ///
+    /// The handles are duplicated from the given Unzipper with `Unzipper::try_clone()`, without its progress
+    /// receiver and password provider.
+    ///
+    /// # Arguments
+    /// * `unzipper` - The Unzipper of the zip file, whose file entries are shared with the handles.
+    pub fn new(unzipper: Unzipper<File>) -> HandlePool {
+        HandlePool {
+            unzipper,
+            handles: Mutex::new(Vec::new()),
+            max_idle: DEFAULT_MAX_IDLE,
+        }
+    }
+
+    /// Sets the number of idle handles kept in the pool, the handles given back beyond it being closed.
+    pub fn max_idle(mut self, max_idle: usize) -> HandlePool {
+        self.max_idle = max_idle;
+        self.handles_mut().truncate(max_idle);
+        self
+    }
+
+    /// Returns the Unzipper of the zip file, to list its entries.
+    pub fn unzipper(&self) -> &Unzipper<File> {
+        &self.unzipper
+    }
+
+    /// Returns the number of idle handles in the pool.
+    pub fn idle(&self) -> usize {
+        self.handles_mut().len()
+    }
+
+    /// Takes a handle from the pool, reopening one if they are all in use.
+    ///
+    /// # Returns
+    /// A Result containing the handle, given back to the pool when dropped, or an error if the file
+    /// could not be reopened.
+    pub fn get(&self) -> Result<PooledUnzipper<'_>, UnzipperError> {
+        let unzipper = match self.handles_mut().pop() {
+            Some(unzipper) => unzipper,
+            None => self.unzipper.try_clone()?,
+        };
+        Ok(PooledUnzipper {
+            pool: self,
+            unzipper: Some(unzipper),
+        })
+    }
+
+    /// Unzips a file from the archive into memory through a handle of the pool. See `Unzipper::get_file()`.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to unzip.
+    ///
+    /// # Returns
+    /// A Result containing the unzipped file content as a vector of bytes, or an error if the file is not found
+    /// or decompression fails.
+    pub fn get_file(&self, file_path: &str) -> Result<Vec<u8>, UnzipperError> {
+        self.get()?.get_file(file_path)
+    }
+
+    /// Unzips a file from the archive into a writer through a handle of the pool. See `Unzipper::get_file_to()`.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to unzip.
+    /// * `writer` - The sink receiving the decompressed content.
+    ///
+    /// # Returns
+    /// A Result containing the number of bytes written, or an error if the file is not found, decompression fails
+    /// or the writer fails.
+    pub fn get_file_to(
+        &self,
+        file_path: &str,
+        writer: &mut impl Write,
+    ) -> Result<u64, UnzipperError> {
+        self.get()?.get_file_to(file_path, writer)
+    }
+
+    /// Returns the idle handles, even if a thread panicked while holding them.
+    fn handles_mut(&self) -> std::sync::MutexGuard<'_, Vec<Unzipper<File>>> {
+        self.handles.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Handle taken from a HandlePool, given back to the pool when dropped.
+pub struct PooledUnzipper<'a> {
+    pool: &'a HandlePool,
+    unzipper: Option<Unzipper<File>>, // taken back on drop
+}
+
+impl Deref for PooledUnzipper<'_> {
+    type Target = Unzipper<File>;
+
+    fn deref(&self) -> &Unzipper<File> {
+        self.unzipper.as_ref().expect("given back on drop only")
+    }
+}
+
+impl DerefMut for PooledUnzipper<'_> {
+    fn deref_mut(&mut self) -> &mut Unzipper<File> {
+        self.unzipper.as_mut().expect("given back on drop only")
+    }
+}
+
+impl Drop for PooledUnzipper<'_> {
+    fn drop(&mut self) {
+        if let Some(unzipper) = self.unzipper.take() {
+            let mut handles = self.pool.handles_mut();
+            if handles.len() < self.pool.max_idle {
+                handles.push(unzipper);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod handle_pool_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_handle_pool_reads() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let expected = Unzipper::new(&file).unwrap();
+        let content = expected.get_file("content.opf").unwrap();
+
+        let pool = Arc::new(HandlePool::new(Unzipper::new(&file).unwrap()));
+        assert_eq!(pool.unzipper().entries().count(), 86);
+        assert_eq!(pool.get_file("content.opf").unwrap(), content);
+        assert_eq!(pool.get_file("mimetype").unwrap(), b"application/epub+zip");
+        assert_eq!(pool.idle(), 1);
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                let (pool, content) = (pool.clone(), &content);
+                scope.spawn(move || {
+                    for _ in 0..8 {
+                        assert_eq!(&pool.get_file("content.opf").unwrap(), content);
+                    }
+                });
+            }
+        });
+        assert!((1..=8).contains(&pool.idle()));
+
+        // The handles in use are reopened, and closed beyond the idle ones kept
+        let pool = HandlePool::new(Unzipper::new(&file).unwrap()).max_idle(1);
+        let first = pool.get().unwrap();
+        let second = pool.get().unwrap();
+        let mut written = Vec::new();
+        assert_eq!(first.get_file_to("mimetype", &mut written).unwrap(), 20);
+        assert!(Arc::ptr_eq(&first.file_entries, &second.file_entries));
+        drop((first, second));
+        assert_eq!(pool.idle(), 1);
+        assert!(pool.get_file("missing").is_err());
+        assert_eq!(pool.idle(), 1);
+    }
+}
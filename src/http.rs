@@ -0,0 +1,371 @@
+//! HTTP Module.
+//!
+//! This module provides the reading of a remote zip archive through HTTP range requests, so that a few entries
+//! can be pulled out of a large archive without downloading it all: the end of the zip file, holding the end of
+//! central directory record, is fetched first, then the central directory, then only the data of the entries
+//! read. The data is fetched by blocks, the last one being kept, so that the small reads of the headers do not
+//! each cost a request.
+//!
+//! This module is available with the `http` feature.
+
+use std::io::{Error, ErrorKind, Read, Seek, SeekFrom};
+
+use crate::error::UnzipperError;
+use crate::options::UnzipperOptions;
+use crate::unzipper::Unzipper;
+
+/// Default size of the blocks fetched from the server.
+const BLOCK_SIZE: usize = 64 * 1024;
+
+/// Reader presenting a remote file as a seekable stream, fetched through HTTP range requests.
+pub struct HttpRangeReader {
+    agent: ureq::Agent,
+    url: String,
+    length: u64,      // of the remote file
+    position: u64,    // current position in the stream
+    block: Vec<u8>,   // last block fetched
+    block_start: u64, // position of the last block fetched in the stream
+    block_size: usize,
+    requests: u64,
+    bytes_fetched: u64,
+}
+
+impl HttpRangeReader {
+    /// Opens a remote file, fetching its last block to learn its length.
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the remote file. The server must support range requests.
+    ///
+    /// # Returns
+    /// A Result containing the HttpRangeReader, or an error if the request failed or the server
+    /// does not support range requests.
+    pub fn open(url: &str) -> Result<HttpRangeReader, UnzipperError> {
+        let mut reader = HttpRangeReader {
+            agent: ureq::Agent::new_with_defaults(),
+            url: url.to_string(),
+            length: 0,
+            position: 0,
+            block: Vec::new(),
+            block_start: 0,
+            block_size: BLOCK_SIZE,
+            requests: 0,
+            bytes_fetched: 0,
+        };
+        // The end of the zip file is the first part read, to locate the central directory
+        let (block_start, length) = reader.fetch(None, BLOCK_SIZE as u64)?;
+        reader.block_start = block_start;
+        reader.length = length;
+        Ok(reader)
+    }
+
+    /// Sets the size of the blocks fetched from the server, 64 KiB by default. Larger blocks make fewer
+    /// requests, at the cost of fetching data that may not be read.
+    pub fn with_block_size(mut self, block_size: usize) -> HttpRangeReader {
+        self.block_size = block_size.max(1);
+        self
+    }
+
+    /// Returns the length of the remote file.
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    /// Returns the number of range requests made so far.
+    pub fn requests(&self) -> u64 {
+        self.requests
+    }
+
+    /// Returns the number of bytes fetched from the server so far.
+    pub fn bytes_fetched(&self) -> u64 {
+        self.bytes_fetched
+    }
+
+    /// Fetches a range of the remote file into the block.
+    ///
+    /// # Arguments
+    /// * `start` - The position of the range, or None for the range ending the remote file.
+    /// * `length` - The length of the range.
+    ///
+    /// # Returns
+    /// A Result containing the position of the range fetched and the length of the remote file,
+    /// as given by the Content-Range header of the response, or an error if the range of the response
+    /// is not the one requested.
+    fn fetch(&mut self, start: Option<u64>, length: u64) -> Result<(u64, u64), Error> {
+        let range = match start {
+            Some(start) => format!("bytes={start}-{}", start + length - 1),
+            None => format!("bytes=-{length}"),
+        };
+        let mut response = self
+            .agent
+            .get(&self.url)
+            .header("Range", range)
+            .call()
+            .map_err(ureq::Error::into_io)?;
+        self.requests += 1;
+
+        // A server ignoring the range would send the whole file
+        if response.status().as_u16() != 206 {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "The server does not support range requests",
+            ));
+        }
+        let (first, last, file_length) = response
+            .headers()
+            .get("Content-Range")
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_content_range)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Invalid Content-Range header"))?;
+
+        // The range of the response, bounding the content read, must be within the one requested
+        let expected = last - first + 1;
+        let requested = match start {
+            Some(start) => first == start,
+            None => last + 1 == file_length,
+        };
+        if !requested || expected > length {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Range response is not the range requested",
+            ));
+        }
+        let mut block = Vec::new();
+        response
+            .body_mut()
+            .as_reader()
+            .take(expected)
+            .read_to_end(&mut block)?;
+        if block.len() as u64 != expected {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "Range response is shorter than its Content-Range",
+            ));
+        }
+        self.block = block;
+        self.bytes_fetched += expected;
+        Ok((first, file_length))
+    }
+}
+
+/// Parses the value of a Content-Range header, such as `bytes 0-99/1000`.
+///
+/// # Returns
+/// The first and last positions of the range, and the length of the remote file, or None if the value
+/// is invalid or the length is not given.
+fn parse_content_range(value: &str) -> Option<(u64, u64, u64)> {
+    let (range, length) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, end) = range.split_once('-')?;
+    let (start, end, length) = (
+        start.trim().parse().ok()?,
+        end.trim().parse().ok()?,
+        length.trim().parse().ok()?,
+    );
+    (start <= end && end < length).then_some((start, end, length))
+}
+
+impl Read for HttpRangeReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.position >= self.length || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let block_end = self.block_start + self.block.len() as u64;
+        if self.position < self.block_start || self.position >= block_end {
+            let length = (self.length - self.position).min(self.block_size.max(buf.len()) as u64);
+            self.block_start = self.fetch(Some(self.position), length)?.0;
+        }
+
+        let offset = (self.position - self.block_start) as usize;
+        let length = buf.len().min(self.block.len() - offset);
+        buf[..length].copy_from_slice(&self.block[offset..offset + length]);
+        self.position += length as u64;
+        Ok(length)
+    }
+}
+
+impl Seek for HttpRangeReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(delta) => self.length.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        };
+        self.position =
+            position.ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Invalid seek position"))?;
+        Ok(self.position)
+    }
+}
+
+/// Unzipper reading a remote zip archive through HTTP range requests.
+pub type HttpUnzipper = Unzipper<HttpRangeReader>;
+
+impl Unzipper<HttpRangeReader> {
+    /// Opens a remote zip archive, fetching its central directory through HTTP range requests.
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the zip archive. The server must support range requests.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper, or an error if a request failed or the zip archive is invalid.
+    pub fn open_url(url: &str) -> Result<HttpUnzipper, UnzipperError> {
+        Unzipper::open_url_with_options(url, UnzipperOptions::default())
+    }
+
+    /// Opens a remote zip archive, using the given options. See `Unzipper::open_url()`.
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the zip archive. The server must support range requests.
+    /// * `options` - The options controlling how the zip archive is read.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper, or an error if a request failed or the zip archive is invalid.
+    pub fn open_url_with_options(
+        url: &str,
+        options: UnzipperOptions,
+    ) -> Result<HttpUnzipper, UnzipperError> {
+        Unzipper::from_reader_with_options(HttpRangeReader::open(url)?, options)
+    }
+}
+
+#[cfg(test)]
+mod http_tests {
+    use super::*;
+
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::path::PathBuf;
+    use std::thread;
+
+    use test_support::unit_test::UnitTest;
+
+    /// Serves the given content on a local port, honoring the range requests when `ranges` is true.
+    ///
+    /// # Returns
+    /// The URL of the content.
+    fn serve(content: Vec<u8>, ranges: bool) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/archive.zip", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut range = None;
+                for line in BufReader::new(&mut stream).lines() {
+                    let line = line.unwrap().to_lowercase();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(value) = line.strip_prefix("range: bytes=") {
+                        range = Some(value.to_string());
+                    }
+                }
+
+                let length = content.len();
+                let (start, end) = match range.as_deref().and_then(|r| r.split_once('-')) {
+                    Some(("", suffix)) => (length.saturating_sub(suffix.parse().unwrap()), length),
+                    Some((start, end)) => {
+                        (start.parse().unwrap(), end.parse::<usize>().unwrap() + 1)
+                    }
+                    None => (0, length),
+                };
+                let header = if ranges {
+                    format!(
+                        "HTTP/1.1 206 Partial Content\r\nContent-Range: bytes {start}-{}/{length}\r\n",
+                        end - 1
+                    )
+                } else {
+                    "HTTP/1.1 200 OK\r\n".to_string()
+                };
+                let (start, end) = if ranges { (start, end) } else { (0, length) };
+                // The client may close the connection without reading a response it rejects
+                let _ = write!(
+                    stream,
+                    "{header}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    end - start
+                );
+                let _ = stream.write_all(&content[start..end]);
+            }
+        });
+        url
+    }
+
+    /// Answers every request on a local port with the given Content-Range header and no content.
+    ///
+    /// # Returns
+    /// The URL of the content.
+    fn serve_content_range(content_range: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/archive.zip", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                for line in BufReader::new(&mut stream).lines() {
+                    if line.unwrap().is_empty() {
+                        break;
+                    }
+                }
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 206 Partial Content\r\nContent-Range: {content_range}\r\n\
+                     Content-Length: 0\r\nConnection: close\r\n\r\n"
+                );
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_http_open_url() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let content = std::fs::read(&file).unwrap();
+        let local = Unzipper::new(&file).unwrap();
+
+        let remote = Unzipper::open_url(&serve(content.clone(), true)).unwrap();
+        assert_eq!(remote.entries().count(), local.entries().count());
+        assert_eq!(
+            remote.get_file("content.opf").unwrap(),
+            local.get_file("content.opf").unwrap()
+        );
+
+        // Only the blocks holding the central directory and the entry were fetched
+        let mut reader = HttpRangeReader::open(&serve(content.clone(), true))
+            .unwrap()
+            .with_block_size(4096);
+        assert_eq!(reader.length(), content.len() as u64);
+        reader.seek(SeekFrom::Start(100)).unwrap();
+        let mut bytes = [0u8; 10];
+        reader.read_exact(&mut bytes).unwrap();
+        assert_eq!(bytes, content[100..110]);
+        assert_eq!(reader.requests(), 2);
+        assert_eq!(reader.bytes_fetched(), (BLOCK_SIZE + 4096) as u64);
+        let remote = Unzipper::from_reader(reader).unwrap();
+        assert_eq!(
+            remote.get_file("mimetype").unwrap(),
+            b"application/epub+zip"
+        );
+
+        assert!(matches!(
+            Unzipper::open_url(&serve(content, false)),
+            Err(UnzipperError::Io(error)) if error.kind() == ErrorKind::Unsupported
+        ));
+
+        // A range other than the one requested is rejected before its content is read
+        for content_range in ["bytes 0-999999999999/1000000000000", "bytes 0-99/1000"] {
+            assert!(
+                matches!(
+                    HttpRangeReader::open(&serve_content_range(content_range)),
+                    Err(UnzipperError::Io(error)) if error.kind() == ErrorKind::InvalidData
+                ),
+                "{content_range}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_http_content_range() {
+        assert_eq!(parse_content_range("bytes 0-99/1000"), Some((0, 99, 1000)));
+        assert_eq!(parse_content_range("bytes 0-99/*"), None);
+        assert_eq!(parse_content_range("bytes 99-0/1000"), None);
+        assert_eq!(parse_content_range("items 0-99/1000"), None);
+    }
+}
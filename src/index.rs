@@ -0,0 +1,515 @@
+//! Index Module.
+//!
+//! This module provides the saving of the entries read from the central directory into a compact binary index,
+//! and the opening of the zip file from such an index, so that the central directory of a large zip archive is
+//! not parsed again at every start of a process. The index records the size and the modification time of the
+//! zip file, and a CRC-32 of its end of central directory record, against which it is validated when loaded.
+
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::crc32::Crc32;
+use crate::datetime::{unix_seconds, Timestamps};
+use crate::error::UnzipperError;
+use crate::extra::AesExtra;
+use crate::file_entries::FileEntries;
+use crate::options::UnzipperOptions;
+use crate::unzipper::{FileEntry, FileStamp, Unzipper, FILE_CENTRAL_SIZE};
+use crate::warning::Warning;
+
+/// Signature at the start of an index.
+const INDEX_MAGIC: &[u8; 4] = b"UZIX";
+
+/// Version of the index format, an index of another version being rejected.
+const INDEX_VERSION: u8 = 1;
+
+/// Size of the tail of the zip file hashed, holding the end of central directory record and the longest comment.
+const TAIL_SIZE: usize = FILE_CENTRAL_SIZE + u16::MAX as usize;
+
+impl Unzipper<File> {
+    /// Saves the entries read from the central directory into a compact binary index, to be loaded later with
+    /// `Unzipper::with_index()` instead of parsing the central directory again.
+    ///
+    /// The index also holds the archive comment and the warnings recorded, and identifies the zip file by its
+    /// size, its modification time and a CRC-32 of its end of central directory record.
+    ///
+    /// # Returns
+    /// A Result containing the index, or an error if the Unzipper was not opened from a path or if the end
+    /// of the zip file could not be read.
+    pub fn export_index(&self) -> Result<Vec<u8>, UnzipperError> {
+        let stamp = match self.stamp {
+            Some(ref stamp) => stamp,
+            None => return Err(UnzipperError::NotOpen),
+        };
+        let mut index = Vec::new();
+        index.extend_from_slice(INDEX_MAGIC);
+        index.push(INDEX_VERSION);
+        put_u64(&mut index, stamp.length);
+        put_time(&mut index, stamp.modified);
+        put_u32(&mut index, self.tail_crc32()?);
+
+        put_u64(&mut index, self.base_offset as u64);
+        match self.apk_signing_block {
+            Some((position, size)) => {
+                index.push(1);
+                put_u64(&mut index, position as u64);
+                put_u64(&mut index, size as u64);
+            }
+            None => index.push(0),
+        }
+        put_bytes(&mut index, &self.comment);
+
+        put_u32(&mut index, self.parsed_entries().iter().count() as u32);
+        for (file_path, file_entry) in self.parsed_entries().iter() {
+            put_bytes(&mut index, file_path.as_bytes());
+            put_entry(&mut index, file_entry);
+        }
+
+        let warnings = self.warnings();
+        put_u32(&mut index, warnings.len() as u32);
+        for warning in &warnings {
+            match warning.file_path() {
+                Some(file_path) => {
+                    index.push(1);
+                    put_bytes(&mut index, file_path.as_bytes());
+                }
+                None => index.push(0),
+            }
+            put_bytes(&mut index, warning.message().as_bytes());
+        }
+
+        let mut crc32 = Crc32::new();
+        crc32.update(&index);
+        put_u32(&mut index, crc32.finalize());
+        Ok(index)
+    }
+
+    /// Creates a new Unzipper instance for the specified file path, using the entries of an index saved by
+    /// `Unzipper::export_index()` instead of parsing the central directory.
+    ///
+    /// The index is to be loaded with the options of the Unzipper that saved it, as the entries it holds were
+    /// read with them. When the index does not match the zip file, it is to be saved again:
+    ///
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # use unzipper::{Unzipper, UnzipperError, UnzipperOptions};
+    /// # fn open(filepath: &Path, index: &[u8]) -> Result<Unzipper, UnzipperError> {
+    /// let options = UnzipperOptions::default();
+    /// let unzipper = match Unzipper::with_index(filepath, index, options.clone()) {
+    ///     Err(UnzipperError::InvalidIndex(_)) => Unzipper::with_options(filepath, options)?,
+    ///     result => result?,
+    /// };
+    /// # Ok(unzipper)
+    /// # }
+    /// ```
+    ///
+    /// # Arguments
+    /// * `filepath` - A reference to the path of the zip file to be opened.
+    /// * `index` - The index saved for the zip file.
+    /// * `options` - The options controlling how the zip file is read.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper instance if successful, an `InvalidIndex` error if the index is corrupted
+    /// or the zip file was modified since the index was saved, or an error if the file could not be opened.
+    pub fn with_index(
+        filepath: &Path,
+        index: &[u8],
+        options: UnzipperOptions,
+    ) -> Result<Unzipper<File>, UnzipperError> {
+        let (content, crc32) = index
+            .split_last_chunk::<4>()
+            .ok_or_else(|| invalid("index is truncated"))?;
+        let mut hasher = Crc32::new();
+        hasher.update(content);
+        if hasher.finalize() != u32::from_le_bytes(*crc32) {
+            return Err(invalid("index is corrupted"));
+        }
+
+        let mut reader = IndexReader(content);
+        if reader.take(INDEX_MAGIC.len())? != INDEX_MAGIC {
+            return Err(invalid("not an index"));
+        }
+        if reader.u8()? != INDEX_VERSION {
+            return Err(invalid("unsupported index version"));
+        }
+        let mut unzipper = Unzipper::empty(options);
+        unzipper.attach_file(filepath)?;
+        let length = reader.u64()?;
+        let modified = reader.time()?;
+        match unzipper.stamp {
+            Some(FileStamp {
+                length: actual_length,
+                modified: actual_modified,
+                ..
+            }) if actual_length == length && actual_modified == modified => {}
+            _ => return Err(invalid("zip file was modified since the index was saved")),
+        }
+        if reader.u32()? != unzipper.tail_crc32()? {
+            return Err(invalid("zip file was modified since the index was saved"));
+        }
+
+        // The positions are checked to lie in the zip file, before they are used to compute others
+        let position = |value: u64| match usize::try_from(value) {
+            Ok(value) if value as u64 <= length => Ok(value),
+            _ => Err(invalid("invalid position")),
+        };
+        unzipper.base_offset = position(reader.u64()?)?;
+        unzipper.apk_signing_block = match reader.u8()? {
+            0 => None,
+            _ => Some((position(reader.u64()?)?, position(reader.u64()?)?)),
+        };
+        unzipper.comment = reader.bytes()?.to_vec();
+
+        let mut file_entries = FileEntries::with_options(&unzipper.options);
+        for _ in 0..reader.u32()? {
+            let file_path = reader.string()?;
+            file_entries.insert(file_path, reader.entry(length)?);
+        }
+        unzipper.file_entries = Arc::new(file_entries);
+
+        let mut warnings = Vec::new();
+        for _ in 0..reader.u32()? {
+            let file_path = match reader.u8()? {
+                0 => None,
+                _ => Some(reader.string()?),
+            };
+            let message = reader.string()?;
+            warnings.push(Warning::new(file_path.as_deref(), &message));
+        }
+        if !reader.0.is_empty() {
+            return Err(invalid("unexpected data at the end of the index"));
+        }
+        unzipper.record_warnings(warnings);
+
+        unzipper.close_if_lazy();
+        Ok(unzipper)
+    }
+
+    /// Returns the CRC-32 of the tail of the zip file, holding its end of central directory record.
+    fn tail_crc32(&self) -> Result<u32, UnzipperError> {
+        let length = self.zip_length()?;
+        let tail_offset = length.saturating_sub(TAIL_SIZE);
+        let mut tail = vec![0; length - tail_offset];
+        self.get_data(&mut tail, tail_offset, "end of central directory")?;
+        let mut crc32 = Crc32::new();
+        crc32.update(&tail);
+        Ok(crc32.finalize())
+    }
+}
+
+/// Returns an `InvalidIndex` error with the given message.
+fn invalid(msg: &str) -> UnzipperError {
+    UnzipperError::InvalidIndex(msg.to_string())
+}
+
+fn put_u16(index: &mut Vec<u8>, value: u16) {
+    index.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(index: &mut Vec<u8>, value: u32) {
+    index.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(index: &mut Vec<u8>, value: u64) {
+    index.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Adds bytes preceded by their length.
+fn put_bytes(index: &mut Vec<u8>, bytes: &[u8]) {
+    put_u32(index, bytes.len() as u32);
+    index.extend_from_slice(bytes);
+}
+
+/// Adds an optional time, as the seconds since the Unix epoch and the nanoseconds added to them.
+fn put_time(index: &mut Vec<u8>, time: Option<SystemTime>) {
+    match time {
+        Some(time) => {
+            let (seconds, nanoseconds) = unix_seconds(time);
+            index.push(1);
+            index.extend_from_slice(&seconds.to_le_bytes());
+            put_u32(index, nanoseconds);
+        }
+        None => index.push(0),
+    }
+}
+
+/// Adds the fields of a file entry.
+fn put_entry(index: &mut Vec<u8>, file_entry: &FileEntry) {
+    put_u32(index, file_entry.start_pos);
+    put_u32(index, file_entry.compressed_size);
+    put_u32(index, file_entry.size);
+    put_u16(index, file_entry.method);
+    put_u32(index, file_entry.crc32);
+    put_u16(index, file_entry.flags);
+    put_u16(index, file_entry.last_mod_time);
+    put_u16(index, file_entry.last_mod_date);
+    put_time(index, file_entry.timestamps.modified);
+    put_time(index, file_entry.timestamps.accessed);
+    put_time(index, file_entry.timestamps.created);
+    match file_entry.aes {
+        Some(ref aes) => {
+            index.push(1);
+            put_u16(index, aes.version);
+            index.push(aes.strength);
+            put_u16(index, aes.method);
+        }
+        None => index.push(0),
+    }
+    put_u16(index, file_entry.made_by);
+    put_u16(index, file_entry.extract_version);
+    put_u32(index, file_entry.external_attr);
+    index.push(file_entry.dir_name as u8);
+}
+
+/// Reader of the values of an index, failing with an `InvalidIndex` error at its end.
+struct IndexReader<'a>(&'a [u8]);
+
+impl<'a> IndexReader<'a> {
+    /// Returns the next bytes of the index.
+    fn take(&mut self, length: usize) -> Result<&'a [u8], UnzipperError> {
+        if length > self.0.len() {
+            return Err(invalid("index is truncated"));
+        }
+        let (bytes, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    /// Returns the next bytes of the index, as an array.
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], UnzipperError> {
+        let bytes = self.take(N)?;
+        Ok(bytes.try_into().unwrap_or([0; N]))
+    }
+
+    fn u8(&mut self) -> Result<u8, UnzipperError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, UnzipperError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, UnzipperError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, UnzipperError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    /// Returns the next bytes preceded by their length.
+    fn bytes(&mut self) -> Result<&'a [u8], UnzipperError> {
+        let length = self.u32()? as usize;
+        self.take(length)
+    }
+
+    /// Returns the next UTF-8 string preceded by its length.
+    fn string(&mut self) -> Result<String, UnzipperError> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| invalid("invalid UTF-8 string"))
+    }
+
+    /// Returns the next optional time.
+    fn time(&mut self) -> Result<Option<SystemTime>, UnzipperError> {
+        match self.u8()? {
+            0 => Ok(None),
+            _ => {
+                let seconds = i64::from_le_bytes(self.array()?);
+                let nanoseconds = self.u32()?;
+                if nanoseconds >= 1_000_000_000 {
+                    return Err(invalid("invalid time"));
+                }
+                let time = if seconds >= 0 {
+                    UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64))
+                } else {
+                    UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.unsigned_abs()))
+                };
+                time.and_then(|time| time.checked_add(Duration::from_nanos(nanoseconds as u64)))
+                    .map(Some)
+                    .ok_or_else(|| invalid("invalid time"))
+            }
+        }
+    }
+
+    /// Returns the next file entry, checked to lie in the zip file of the given length.
+    fn entry(&mut self, length: u64) -> Result<FileEntry, UnzipperError> {
+        let file_entry = FileEntry {
+            start_pos: self.u32()?,
+            compressed_size: self.u32()?,
+            size: self.u32()?,
+            method: self.u16()?,
+            crc32: self.u32()?,
+            flags: self.u16()?,
+            last_mod_time: self.u16()?,
+            last_mod_date: self.u16()?,
+            timestamps: Timestamps {
+                modified: self.time()?,
+                accessed: self.time()?,
+                created: self.time()?,
+            },
+            aes: match self.u8()? {
+                0 => None,
+                _ => Some(AesExtra {
+                    version: self.u16()?,
+                    strength: self.u8()?,
+                    method: self.u16()?,
+                }),
+            },
+            made_by: self.u16()?,
+            extract_version: self.u16()?,
+            external_attr: self.u32()?,
+            dir_name: self.u8()? != 0,
+        };
+        if file_entry.start_pos as u64 + file_entry.compressed_size as u64 > length {
+            return Err(invalid("invalid position"));
+        }
+        if file_entry.aes.is_some_and(|aes| !aes.is_valid()) {
+            return Err(invalid("invalid encryption parameters"));
+        }
+        Ok(file_entry)
+    }
+}
+
+#[cfg(test)]
+mod index_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_index_save_and_load() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let expected = Unzipper::new(&file).unwrap();
+        let index = expected.export_index().unwrap();
+
+        let unzipper = Unzipper::with_index(&file, &index, UnzipperOptions::default()).unwrap();
+        assert_eq!(format!("{unzipper:?}"), format!("{expected:?}"));
+        assert_eq!(unzipper.entries().count(), 86);
+        assert_eq!(
+            unzipper.get_file("content.opf").unwrap(),
+            expected.get_file("content.opf").unwrap()
+        );
+        assert_eq!(
+            unzipper.get_file("mimetype").unwrap(),
+            b"application/epub+zip"
+        );
+        assert_eq!(unzipper.warnings(), expected.warnings());
+        assert_eq!(unzipper.export_index().unwrap(), index);
+
+        let options = UnzipperOptions::new().lazy_open(true);
+        let unzipper = Unzipper::with_index(&file, &index, options).unwrap();
+        assert!(unzipper.reader.is_none());
+        assert_eq!(
+            unzipper.get_file("mimetype").unwrap(),
+            b"application/epub+zip"
+        );
+
+        // Corrupted or truncated indexes
+        let mut corrupted = index.clone();
+        corrupted[40] ^= 1;
+        for index in [&corrupted[..], &index[..index.len() - 1], &[]] {
+            assert!(matches!(
+                Unzipper::with_index(&file, index, UnzipperOptions::default()),
+                Err(UnzipperError::InvalidIndex(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_index_invalid_values() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let index = Unzipper::new(&file).unwrap().export_index().unwrap();
+        let with_crc32 = |mut content: Vec<u8>| {
+            let mut hasher = Crc32::new();
+            hasher.update(&content);
+            content.extend_from_slice(&hasher.finalize().to_le_bytes());
+            content
+        };
+        let invalid_index = |index: &[u8], msg: &str| {
+            matches!(
+                Unzipper::with_index(&file, index, UnzipperOptions::default()),
+                Err(UnzipperError::InvalidIndex(message)) if message == msg
+            )
+        };
+
+        // Times with too many nanoseconds, which would overflow the seconds
+        for (seconds, nanoseconds) in [(i64::MAX, u32::MAX), (0, 1_000_000_000)] {
+            let mut content = index[..index.len() - 4].to_vec();
+            content[14..22].copy_from_slice(&seconds.to_le_bytes());
+            content[22..26].copy_from_slice(&nanoseconds.to_le_bytes());
+            assert!(invalid_index(&with_crc32(content), "invalid time"));
+        }
+
+        // Archive offset beyond the end of the zip file
+        let mut content = index[..index.len() - 4].to_vec();
+        content[30..38].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(invalid_index(&with_crc32(content), "invalid position"));
+
+        // Entry data beyond the end of the zip file
+        let name = index.windows(8).position(|w| w == b"mimetype").unwrap();
+        let mut content = index[..index.len() - 4].to_vec();
+        content[name + 12..name + 16].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(invalid_index(&with_crc32(content), "invalid position"));
+
+        // Unknown WinZip AES encryption strength
+        let unit_test = UnitTest::new("unzipper_winzip_aes");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("winzip_aes.zip");
+        let index = Unzipper::new(&file).unwrap().export_index().unwrap();
+        let name = index.windows(10).position(|w| w == b"aes256.txt").unwrap();
+        let mut position = name + 10 + 24;
+        for _ in 0..3 {
+            position += if index[position] == 0 { 1 } else { 13 };
+        }
+        assert_eq!(index[position..position + 4], [1, 2, 0, 3]);
+        let mut content = index[..index.len() - 4].to_vec();
+        content[position + 3] = 200;
+        let invalid_index = |index: &[u8], msg: &str| {
+            matches!(
+                Unzipper::with_index(&file, index, UnzipperOptions::default()),
+                Err(UnzipperError::InvalidIndex(message)) if message == msg
+            )
+        };
+        assert!(invalid_index(
+            &with_crc32(content),
+            "invalid encryption parameters"
+        ));
+    }
+
+    #[test]
+    fn test_index_modified_file() {
+        let unit_test = UnitTest::new("unzipper_comment");
+        let source = PathBuf::from(unit_test.test_case_folder()).join("comment.zip");
+        let file = std::env::temp_dir().join("unzipper_index_modified_file.zip");
+        let mut bytes = std::fs::read(&source).unwrap();
+        std::fs::write(&file, &bytes).unwrap();
+
+        let index = Unzipper::new(&file).unwrap().export_index().unwrap();
+        let unzipper = Unzipper::with_index(&file, &index, UnzipperOptions::default()).unwrap();
+        assert_eq!(
+            unzipper.comment(),
+            Unzipper::new(&source).unwrap().comment()
+        );
+
+        // Same size, different comment, and the same modification time
+        let modified = std::fs::metadata(&file).unwrap().modified().unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        std::fs::write(&file, &bytes).unwrap();
+        File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert!(matches!(
+            Unzipper::with_index(&file, &index, UnzipperOptions::default()),
+            Err(UnzipperError::InvalidIndex(_))
+        ));
+
+        std::fs::remove_file(&file).unwrap();
+    }
+}
@@ -5,6 +5,87 @@
 //!
 //! The unzipper is open-source and can be freely used and modified under the terms of the MIT license.
 
+#[cfg(feature = "tokio")]
+pub mod async_unzipper;
+pub mod cache;
+pub mod compare;
+mod cp437;
+mod crc32;
+mod datetime;
+pub mod decompressor;
+pub mod decryption;
+pub mod entry;
+pub mod entry_reader;
+mod epub;
+pub mod error;
+mod extra;
+pub mod extract;
+mod file_entries;
+#[cfg(feature = "futures")]
+pub mod futures_unzipper;
+pub mod glob;
+pub mod handle_pool;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod index;
+mod listing;
+#[cfg(feature = "manifest")]
+pub mod manifest;
+mod mime;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+mod natural_sort;
+mod nested;
+pub mod options;
+pub mod password;
+pub mod progress;
+mod read_ahead;
+pub mod read_at;
+mod recovery;
+mod secret;
+pub mod seekable_reader;
+pub mod split;
+pub mod stream;
+pub mod tree;
 pub mod unzipper;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub mod uring;
+pub mod verify;
+pub mod warning;
+#[cfg(feature = "crypto")]
+mod winzip_aes;
+mod zipcrypto;
 
+#[cfg(feature = "tokio")]
+pub use async_unzipper::AsyncUnzipper;
+pub use cache::CachedReader;
+pub use compare::DirDiff;
+pub use decompressor::{DecompressResult, Decompressor};
+pub use decryption::{DecryptionProvider, Decryptor};
+pub use entry::{EntryInfo, EntryKind, EntryOrder, RawEntry};
+pub use entry_reader::EntryReader;
+pub use error::{UnsupportedFeature, UnzipperError};
+pub use extract::ExtractionReport;
+#[cfg(feature = "futures")]
+pub use futures_unzipper::FuturesUnzipper;
+pub use glob::Glob;
+pub use handle_pool::{HandlePool, PooledUnzipper};
+#[cfg(feature = "http")]
+pub use http::{HttpRangeReader, HttpUnzipper};
+#[cfg(feature = "manifest")]
+pub use manifest::{EntryHasher, HashingWriter, Manifest, ManifestReport};
+#[cfg(feature = "mmap")]
+pub use mmap::MmapUnzipper;
+pub use options::{DuplicatePolicy, ParseMode, SymlinkPolicy, UnzipperOptions};
+pub use password::PasswordProvider;
+pub use progress::Progress;
+pub use read_at::{ReadAt, ReadAtReader};
+pub use seekable_reader::SeekableEntryReader;
+pub use split::SplitReader;
+pub use stream::{StreamEntry, StreamUnzipper};
+pub use tree::TreeEntry;
 pub use unzipper::Unzipper;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub use uring::{UringFile, UringUnzipper};
+pub use verify::{IntegrityReport, Issue, Severity};
+pub use warning::Warning;
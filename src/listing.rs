@@ -0,0 +1,244 @@
+//! Listing Module.
+//!
+//! This module provides the listings of the content of a zip archive, formatted for users
+//! in the style of `unzip -v`, or in JSON for tools.
+
+use std::fmt::Write;
+use std::io::{Read, Seek};
+
+use crate::entry::EntryInfo;
+#[cfg(feature = "json")]
+use crate::entry::EntryKind;
+#[cfg(feature = "json")]
+use crate::error::UnzipperError;
+use crate::unzipper::Unzipper;
+
+/// Returns the name of a compression method, as shown in the listings.
+pub(crate) fn method_name(method: u16) -> String {
+    match method {
+        0 => "Stored".to_string(),
+        8 => "Defl".to_string(),
+        9 => "Defl64".to_string(),
+        12 => "BZip2".to_string(),
+        14 => "LZMA".to_string(),
+        93 => "Zstd".to_string(),
+        95 => "XZ".to_string(),
+        _ => format!("Unk:{method:03}"),
+    }
+}
+
+/// Computes the space saved by the compression of an entry, in percent.
+fn ratio(compressed_size: u64, size: u64) -> i64 {
+    if size == 0 {
+        0
+    } else {
+        100 - (compressed_size as f64 * 100.0 / size as f64).round() as i64
+    }
+}
+
+/// Listing of a zip archive, as serialized in JSON.
+#[cfg(feature = "json")]
+#[derive(serde::Serialize)]
+struct JsonListing<'a> {
+    comment: std::borrow::Cow<'a, str>,
+    entries: Vec<JsonEntry>,
+}
+
+/// Entry of a zip archive, as serialized in JSON.
+#[cfg(feature = "json")]
+#[derive(serde::Serialize)]
+struct JsonEntry {
+    name: String,
+    kind: &'static str,
+    size: u32,
+    compressed_size: u32,
+    method: u16,
+    method_name: String,
+    crc32: String,              // in hexadecimal, as shown by zip tools
+    modified: Option<String>,   // MS-DOS date and time, without time zone
+    modified_unix: Option<u64>, // from the extra fields when present, in seconds since the Unix epoch
+    encrypted: bool,
+    offset: u32,
+}
+
+#[cfg(feature = "json")]
+impl JsonEntry {
+    fn new(entry: EntryInfo) -> JsonEntry {
+        JsonEntry {
+            kind: match entry.kind() {
+                EntryKind::File => "file",
+                EntryKind::Directory => "directory",
+                EntryKind::Symlink => "symlink",
+            },
+            size: entry.size(),
+            compressed_size: entry.compressed_size(),
+            method: entry.method(),
+            method_name: method_name(entry.method()),
+            crc32: format!("{:08x}", entry.crc32()),
+            modified: entry.dos_date_time().map(|dt| {
+                format!(
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                    dt.year, dt.month, dt.day, dt.hour, dt.minute, dt.second
+                )
+            }),
+            modified_unix: entry
+                .modified()
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs()),
+            encrypted: entry.is_encrypted(),
+            offset: entry.offset(),
+            name: entry.name().to_string(),
+        }
+    }
+}
+
+impl<R: Read + Seek> Unzipper<R> {
+    /// Serializes the listing of the zip archive in JSON, for CI tools and asset auditors.
+    ///
+    /// This method is available with the `json` feature.
+    ///
+    /// # Returns
+    /// A Result containing a JSON object holding the archive `comment` and the `entries`, in the order of the
+    /// central directory. Each entry gives its `name`, `kind` ("file", "directory" or "symlink"), `size`,
+    /// `compressed_size`, `method`, `method_name`, `crc32` (in hexadecimal), `modified` (the MS-DOS date and time,
+    /// as "YYYY-MM-DDTHH:MM:SS" without time zone), `modified_unix` (in seconds), `encrypted` and `offset`.
+    #[cfg(feature = "json")]
+    pub fn to_json_listing(&self) -> Result<String, UnzipperError> {
+        let listing = JsonListing {
+            comment: self.comment_lossy(),
+            entries: self.entries().map(JsonEntry::new).collect(),
+        };
+        serde_json::to_string_pretty(&listing).map_err(|error| UnzipperError::Io(error.into()))
+    }
+
+    /// Formats a listing of the file entries of the zip archive, comparable to the one of `unzip -v`.
+    ///
+    /// # Returns
+    /// A string holding a header line, a line per entry in the order of the central directory, and a line
+    /// of totals. Each entry line gives the decompressed size, the compression method, the compressed size,
+    /// the space saved, the modification date and time, the CRC-32 and the path of the entry.
+    /// The paths of the directories end with '/'.
+    pub fn listing(&self) -> String {
+        let mut listing = String::new();
+        let _ = writeln!(
+            listing,
+            " Length   Method    Size  Cmpr    Date    Time   CRC-32   Name"
+        );
+        let _ = writeln!(
+            listing,
+            "--------  ------  ------- ---- ---------- ----- --------  ----"
+        );
+
+        let (mut total_size, mut total_compressed, mut count) = (0u64, 0u64, 0usize);
+        for entry in self.entries() {
+            let _ = writeln!(listing, "{}", Self::listing_line(&entry));
+            total_size += entry.size() as u64;
+            total_compressed += entry.compressed_size() as u64;
+            count += 1;
+        }
+
+        let _ = writeln!(
+            listing,
+            "--------          -------  ---                            -------"
+        );
+        let _ = writeln!(
+            listing,
+            "{total_size:>8}         {total_compressed:>8} {:>3}%                            {count} file{}",
+            ratio(total_compressed, total_size),
+            if count == 1 { "" } else { "s" }
+        );
+        listing
+    }
+
+    /// Formats the line of an entry in the listing.
+    fn listing_line(entry: &EntryInfo) -> String {
+        let date_time = match entry.dos_date_time() {
+            Some(dt) => format!(
+                "{:04}-{:02}-{:02} {:02}:{:02}",
+                dt.year, dt.month, dt.day, dt.hour, dt.minute
+            ),
+            None => "---------- -----".to_string(),
+        };
+        let suffix = if entry.is_dir() { "/" } else { "" };
+        format!(
+            "{:>8}  {:<6} {:>8} {:>3}% {date_time} {:08x}  {}{suffix}",
+            entry.size(),
+            method_name(entry.method()),
+            entry.compressed_size(),
+            ratio(entry.compressed_size() as u64, entry.size() as u64),
+            entry.crc32(),
+            entry.name()
+        )
+    }
+}
+
+#[cfg(test)]
+mod listing_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_listing() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        let listing = unzipper.listing();
+        let lines: Vec<&str> = listing.lines().collect();
+        assert_eq!(lines.len(), 86 + 4);
+        assert_eq!(
+            lines[0],
+            " Length   Method    Size  Cmpr    Date    Time   CRC-32   Name"
+        );
+        assert_eq!(
+            lines[2],
+            "      20  Stored       20   0% 2020-11-26 16:32 2cab616f  mimetype"
+        );
+        assert_eq!(
+            lines[3],
+            "       0  Defl          2   0% 2020-11-26 16:32 00000000  META-INF/"
+        );
+        assert_eq!(
+            lines[4],
+            "     244  Defl        154  37% 2020-11-26 16:32 74069f90  META-INF/container.xml"
+        );
+        assert_eq!(
+            lines[89],
+            " 1467257           717741  51%                            86 files"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_listing_to_json() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&unzipper.to_json_listing().unwrap()).unwrap();
+        assert_eq!(json["comment"], "");
+        let entries = json["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 86);
+        assert_eq!(
+            entries[0],
+            serde_json::json!({
+                "name": "mimetype",
+                "kind": "file",
+                "size": 20,
+                "compressed_size": 20,
+                "method": 0,
+                "method_name": "Stored",
+                "crc32": "2cab616f",
+                "modified": "2020-11-26T16:32:40",
+                "modified_unix": 1606408360,
+                "encrypted": false,
+                "offset": 0
+            })
+        );
+        assert_eq!(entries[1]["kind"], "directory");
+    }
+}
@@ -0,0 +1,468 @@
+//! Manifest Module.
+//!
+//! This module provides the manifest of a zip archive, mapping the path of each file entry to the digest
+//! of its content, for the attestation of zip-based packages. SHA-256 is used by default; other algorithms
+//! are plugged in through the `EntryHasher` trait, implemented by every hasher of the `digest` crate
+//! (such as `blake3` or `md-5`). It is built with the `manifest` feature.
+
+use core::fmt;
+use std::io::{Read, Seek, Write};
+
+use digest::Digest;
+use sha2::Sha256;
+
+use crate::entry::EntryKind;
+use crate::error::UnzipperError;
+use crate::unzipper::Unzipper;
+
+/// Struct that holds the digests of the file entries of a zip archive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Manifest {
+    digests: Vec<(String, String)>, // cleaned path and lowercase hexadecimal digest, in archive order
+}
+
+impl Manifest {
+    /// Parses a manifest in the format of the `sha256sum` tool, also used by `md5sum` or `b2sum`.
+    ///
+    /// # Arguments
+    /// * `text` - The manifest, one `<digest>  <path>` line per entry. The binary mode marker `*` before the path
+    ///   is accepted, and the empty lines are ignored.
+    ///
+    /// # Returns
+    /// A Result containing the Manifest, or an `InvalidManifest` error naming the first line that is not
+    /// a hexadecimal digest followed by a path.
+    pub fn parse(text: &str) -> Result<Manifest, UnzipperError> {
+        let mut manifest = Manifest::default();
+        for (number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let (digest, name) = line
+                .split_once(' ')
+                .map(|(digest, name)| (digest, name.strip_prefix([' ', '*']).unwrap_or(name)))
+                .filter(|(digest, name)| {
+                    !digest.is_empty()
+                        && digest.len() % 2 == 0
+                        && digest.bytes().all(|byte| byte.is_ascii_hexdigit())
+                        && !name.is_empty()
+                })
+                .ok_or_else(|| UnzipperError::InvalidManifest(format!("line {}", number + 1)))?;
+            manifest.insert(name, digest);
+        }
+        Ok(manifest)
+    }
+
+    /// Adds the digest of an entry to the manifest, replacing the previous digest of the same path.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the entry.
+    /// * `digest` - The digest as a hexadecimal string, in either case.
+    pub fn insert(&mut self, file_path: &str, digest: &str) {
+        let digest = digest.to_ascii_lowercase();
+        match self.digests.iter_mut().find(|(name, _)| name == file_path) {
+            Some(entry) => entry.1 = digest,
+            None => self.digests.push((file_path.to_string(), digest)),
+        }
+    }
+
+    /// Returns the digest of an entry.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the cleaned path of the entry.
+    ///
+    /// # Returns
+    /// The digest as a lowercase hexadecimal string, or None if the manifest has no such entry.
+    pub fn get(&self, file_path: &str) -> Option<&str> {
+        self.digests
+            .iter()
+            .find(|(name, _)| name == file_path)
+            .map(|(_, digest)| digest.as_str())
+    }
+
+    /// Returns an iterator over the paths and digests of the entries, in the order of the central directory.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.digests
+            .iter()
+            .map(|(name, digest)| (name.as_str(), digest.as_str()))
+    }
+
+    /// Returns the number of entries in the manifest.
+    pub fn len(&self) -> usize {
+        self.digests.len()
+    }
+
+    /// Returns true if the manifest has no entry.
+    pub fn is_empty(&self) -> bool {
+        self.digests.is_empty()
+    }
+
+    /// Writes the manifest in the format of the `sha256sum` tool, one `<digest>  <path>` line per entry,
+    /// so that it can be checked with `sha256sum -c` once the archive is extracted.
+    ///
+    /// # Arguments
+    /// * `writer` - The writer receiving the manifest.
+    ///
+    /// # Returns
+    /// A Result indicating success, or an error if the writer fails.
+    pub fn write_to(&self, writer: &mut impl Write) -> Result<(), UnzipperError> {
+        write!(writer, "{self}")?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Manifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, digest) in &self.digests {
+            writeln!(f, "{digest}  {name}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<S: AsRef<str>, D: AsRef<str>> FromIterator<(S, D)> for Manifest {
+    /// Builds a manifest from the paths and digests of the entries.
+    fn from_iter<I: IntoIterator<Item = (S, D)>>(iter: I) -> Manifest {
+        let mut manifest = Manifest::default();
+        for (file_path, digest) in iter {
+            manifest.insert(file_path.as_ref(), digest.as_ref());
+        }
+        manifest
+    }
+}
+
+/// Struct that describes the result of the verification of a zip archive against a manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManifestReport {
+    mismatched: Vec<String>,
+    missing: Vec<String>,
+    extra: Vec<String>,
+}
+
+impl ManifestReport {
+    /// Returns the paths of the entries whose content does not match their digest, or cannot be read.
+    pub fn mismatched(&self) -> &[String] {
+        &self.mismatched
+    }
+
+    /// Returns the paths of the manifest without a file entry in the zip archive.
+    pub fn missing(&self) -> &[String] {
+        &self.missing
+    }
+
+    /// Returns the paths of the file entries of the zip archive that are not in the manifest.
+    pub fn extra(&self) -> &[String] {
+        &self.extra
+    }
+
+    /// Returns true if the zip archive matches the manifest exactly.
+    pub fn is_ok(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+/// Trait of the hash functions computing the digest of the entries.
+///
+/// It is implemented by every hasher of the `digest` crate, such as `sha2::Sha256`, `blake3::Hasher`
+/// (with its `traits-preview` feature) or `md5::Md5`.
+pub trait EntryHasher {
+    /// Feeds the next bytes of the content to the hasher.
+    fn update(&mut self, data: &[u8]);
+
+    /// Consumes the hasher, returning the digest of the content fed.
+    fn finish(self) -> Vec<u8>;
+}
+
+impl<D: Digest> EntryHasher for D {
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(self, data);
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.finalize().to_vec()
+    }
+}
+
+/// Writer that hashes the bytes written to another writer, to compute the digest of an entry while it is
+/// being extracted.
+pub struct HashingWriter<W: Write, H: EntryHasher> {
+    writer: W,
+    hasher: H,
+}
+
+impl<W: Write, H: EntryHasher> HashingWriter<W, H> {
+    /// Creates a new HashingWriter.
+    ///
+    /// # Arguments
+    /// * `writer` - The writer receiving the bytes, such as `std::io::sink()` when only the digest is needed.
+    /// * `hasher` - The hasher computing the digest.
+    pub fn new(writer: W, hasher: H) -> HashingWriter<W, H> {
+        HashingWriter { writer, hasher }
+    }
+
+    /// Consumes the HashingWriter.
+    ///
+    /// # Returns
+    /// The inner writer and the digest of the bytes written to it.
+    pub fn finish(self) -> (W, Vec<u8>) {
+        (self.writer, self.hasher.finish())
+    }
+}
+
+impl<W: Write, H: EntryHasher> Write for HashingWriter<W, H> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // Only the bytes accepted by the inner writer are hashed
+        let length = self.writer.write(buf)?;
+        self.hasher.update(&buf[..length]);
+        Ok(length)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Formats bytes as a lowercase hexadecimal string.
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+impl<R: Read + Seek> Unzipper<R> {
+    /// Computes the SHA-256 digest of the content of a file entry.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file.
+    ///
+    /// # Returns
+    /// A Result containing the digest as a lowercase hexadecimal string, or an error if the file is not found
+    /// or cannot be decompressed. The content is streamed through the hasher, never being held fully in memory.
+    pub fn sha256(&self, file_path: &str) -> Result<String, UnzipperError> {
+        Ok(to_hex(&self.digest_with(file_path, Sha256::new())?))
+    }
+
+    /// Computes the digest of the content of a file entry with a given hash function.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file.
+    /// * `hasher` - The hasher computing the digest.
+    ///
+    /// # Returns
+    /// A Result containing the digest, or an error if the file is not found or cannot be decompressed.
+    /// The content is streamed through the hasher, never being held fully in memory.
+    pub fn digest_with<H: EntryHasher>(
+        &self,
+        file_path: &str,
+        hasher: H,
+    ) -> Result<Vec<u8>, UnzipperError> {
+        let mut writer = HashingWriter::new(std::io::sink(), hasher);
+        self.get_file_to(file_path, &mut writer)?;
+        Ok(writer.finish().1)
+    }
+
+    /// Computes the SHA-256 manifest of the zip archive.
+    ///
+    /// # Returns
+    /// A Result containing the Manifest with the digest of every file entry, in the order of the central
+    /// directory, or an error if an entry cannot be read. Directories are left out; the digest of a symbolic
+    /// link is the one of its target path.
+    pub fn sha256_manifest(&self) -> Result<Manifest, UnzipperError> {
+        self.manifest_with(Sha256::new)
+    }
+
+    /// Computes the manifest of the zip archive with a given hash function.
+    ///
+    /// # Arguments
+    /// * `new_hasher` - Creates the hasher of each entry, such as `blake3::Hasher::new`.
+    ///
+    /// # Returns
+    /// A Result containing the Manifest with the digest of every file entry, in the order of the central
+    /// directory, or an error if an entry cannot be read. Directories are left out.
+    pub fn manifest_with<H: EntryHasher>(
+        &self,
+        new_hasher: impl Fn() -> H,
+    ) -> Result<Manifest, UnzipperError> {
+        let names: Vec<String> = self
+            .entries()
+            .filter(|entry| entry.kind() != EntryKind::Directory)
+            .map(|entry| entry.name().to_string())
+            .collect();
+
+        let mut manifest = Manifest::default();
+        for name in names {
+            let digest = to_hex(&self.digest_with(&name, new_hasher())?);
+            manifest.digests.push((name, digest));
+        }
+        Ok(manifest)
+    }
+
+    /// Verifies the content of the zip archive against a SHA-256 manifest.
+    ///
+    /// # Arguments
+    /// * `manifest` - The expected SHA-256 digests of the file entries, such as one parsed by `Manifest::parse()`.
+    ///
+    /// # Returns
+    /// A Result containing the ManifestReport listing the entries whose digest does not match, the paths
+    /// of the manifest missing from the zip archive and the file entries not in the manifest, each in the order
+    /// of the manifest or of the central directory. An entry that cannot be read is reported as mismatched.
+    /// An error is returned only if the operation is cancelled by the progress observer.
+    pub fn verify_manifest(&self, manifest: &Manifest) -> Result<ManifestReport, UnzipperError> {
+        self.verify_manifest_with(manifest, Sha256::new)
+    }
+
+    /// Verifies the content of the zip archive against a manifest computed with a given hash function.
+    ///
+    /// # Arguments
+    /// * `manifest` - The expected digests of the file entries.
+    /// * `new_hasher` - Creates the hasher of each entry, using the hash function of the manifest.
+    ///
+    /// # Returns
+    /// A Result containing the ManifestReport, as given by `verify_manifest()`.
+    pub fn verify_manifest_with<H: EntryHasher>(
+        &self,
+        manifest: &Manifest,
+        new_hasher: impl Fn() -> H,
+    ) -> Result<ManifestReport, UnzipperError> {
+        let mut report = ManifestReport::default();
+        let mut expected: Vec<String> = Vec::new();
+        for (file_path, digest) in manifest.iter() {
+            let cleaned_file_path = self.clean_file_path(file_path);
+            let is_file = self
+                .metadata(&cleaned_file_path)
+                .is_some_and(|entry| entry.kind() != EntryKind::Directory);
+            if !is_file {
+                report.missing.push(file_path.to_string());
+                continue;
+            }
+            match self.digest_with(&cleaned_file_path, new_hasher()) {
+                Ok(actual) if to_hex(&actual) == digest => {}
+                Err(UnzipperError::Cancelled) => return Err(UnzipperError::Cancelled),
+                _ => report.mismatched.push(file_path.to_string()),
+            }
+            expected.push(cleaned_file_path);
+        }
+
+        report.extra = self
+            .entries()
+            .filter(|entry| entry.kind() != EntryKind::Directory)
+            .map(|entry| entry.name().to_string())
+            .filter(|name| !expected.contains(name))
+            .collect();
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod manifest_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use crate::crc32::Crc32;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_manifest_sha256() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        // The digest of "application/epub+zip", as given by sha256sum
+        let digest = unzipper.sha256("mimetype").unwrap();
+        assert_eq!(
+            digest,
+            "e468e350d1143eb648f60c7b0bd6031101ec0544a361ca74ecef256ac901f48b"
+        );
+
+        let manifest = unzipper.sha256_manifest().unwrap();
+        let files = unzipper
+            .entries()
+            .filter(|entry| entry.kind() != EntryKind::Directory)
+            .count();
+        assert_eq!(manifest.len(), files);
+        assert_eq!(manifest.iter().next(), Some(("mimetype", digest.as_str())));
+        assert_eq!(manifest.get("mimetype"), Some(digest.as_str()));
+        assert!(manifest.get("META-INF").is_none());
+
+        let mut out = Vec::new();
+        manifest.write_to(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.lines().count(), files);
+        assert!(text.starts_with(&format!("{digest}  mimetype\n")));
+
+        assert!(unzipper.sha256("missing.txt").is_err());
+        assert_eq!(Manifest::parse(&text).unwrap(), manifest);
+    }
+
+    #[test]
+    fn test_manifest_verify() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let manifest = unzipper.sha256_manifest().unwrap();
+        assert!(unzipper.verify_manifest(&manifest).unwrap().is_ok());
+
+        // Digests are compared without regard to case, and the binary mode marker is accepted
+        let text = "E468E350D1143EB648F60C7B0BD6031101EC0544A361CA74ECEF256AC901F48B *mimetype\n\n";
+        let expected = Manifest::parse(text).unwrap();
+        assert_eq!(expected.get("mimetype"), manifest.get("mimetype"));
+
+        let mut expected: Manifest = manifest
+            .iter()
+            .filter(|(name, _)| *name != "META-INF/container.xml")
+            .collect();
+        expected.insert("mimetype", &"0".repeat(64));
+        expected.insert("missing.txt", &"0".repeat(64));
+        let report = unzipper.verify_manifest(&expected).unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(report.mismatched(), ["mimetype"]);
+        assert_eq!(report.missing(), ["missing.txt"]);
+        assert_eq!(report.extra(), ["META-INF/container.xml"]);
+
+        assert!(matches!(
+            Manifest::parse("e468e35  mimetype"),
+            Err(UnzipperError::InvalidManifest(_))
+        ));
+        assert!(Manifest::parse(&format!("{}  ", "0".repeat(64))).is_err());
+    }
+
+    /// Hasher computing the CRC-32 of the content, in big-endian order.
+    struct Crc32Hasher(Crc32);
+
+    impl EntryHasher for Crc32Hasher {
+        fn update(&mut self, data: &[u8]) {
+            self.0.update(data);
+        }
+
+        fn finish(self) -> Vec<u8> {
+            self.0.finalize().to_be_bytes().to_vec()
+        }
+    }
+
+    #[test]
+    fn test_manifest_entry_hasher() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        // The CRC-32 computed by a custom hasher is the one of the central directory
+        let new_hasher = || Crc32Hasher(Crc32::new());
+        let manifest = unzipper.manifest_with(new_hasher).unwrap();
+        for (name, digest) in manifest.iter() {
+            let crc32 = unzipper.metadata(name).unwrap().crc32();
+            assert_eq!(digest, format!("{crc32:08x}"));
+        }
+        assert!(unzipper
+            .verify_manifest_with(&manifest, new_hasher)
+            .unwrap()
+            .is_ok());
+        assert!(!unzipper.verify_manifest(&manifest).unwrap().is_ok());
+
+        // The content is hashed while being written to another writer
+        let mut writer = HashingWriter::new(Vec::new(), Sha256::new());
+        unzipper.get_file_to("mimetype", &mut writer).unwrap();
+        let (content, digest) = writer.finish();
+        assert_eq!(content, b"application/epub+zip");
+        assert_eq!(to_hex(&digest), unzipper.sha256("mimetype").unwrap());
+    }
+}
@@ -0,0 +1,217 @@
+//! MIME Module.
+//!
+//! This module provides the guess of the content type of the entries, useful when serving the content
+//! of a zip archive over HTTP or when validating uploads. The binary formats are recognized by their
+//! signature, and the text formats, which mostly have none, by the extension of their path.
+
+use std::io::{Read, Seek};
+
+use crate::error::UnzipperError;
+use crate::unzipper::Unzipper;
+
+/// Number of bytes read at the beginning of an entry to guess its content type.
+const SNIFF_LENGTH: usize = 512;
+
+/// Signatures of the binary formats: offset of the signature, signature and content type.
+const SIGNATURES: [(usize, &[u8], &str); 23] = [
+    (0, b"\x89PNG\r\n\x1a\n", "image/png"),
+    (0, b"\xff\xd8\xff", "image/jpeg"),
+    (0, b"GIF87a", "image/gif"),
+    (0, b"GIF89a", "image/gif"),
+    (0, b"II*\x00", "image/tiff"),
+    (0, b"MM\x00*", "image/tiff"),
+    (0, b"\x00\x00\x01\x00", "image/x-icon"),
+    (0, b"%PDF-", "application/pdf"),
+    (0, b"PK\x03\x04", "application/zip"),
+    (0, b"\x1f\x8b", "application/gzip"),
+    (0, b"\xfd7zXZ\x00", "application/x-xz"),
+    (0, b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (0, b"\xca\xfe\xba\xbe", "application/java-vm"),
+    (0, b"\x00asm", "application/wasm"),
+    (0, b"ID3", "audio/mpeg"),
+    (0, b"OggS", "audio/ogg"),
+    (0, b"fLaC", "audio/flac"),
+    (4, b"ftyp", "video/mp4"),
+    (0, b"\x1aE\xdf\xa3", "video/webm"),
+    (0, b"\x00\x01\x00\x00", "font/ttf"),
+    (0, b"OTTO", "font/otf"),
+    (0, b"wOFF", "font/woff"),
+    (0, b"wOF2", "font/woff2"),
+];
+
+/// Content types of the formats stored in a RIFF container, by form type.
+const RIFF_FORMS: [(&[u8], &str); 3] = [
+    (b"WEBP", "image/webp"),
+    (b"WAVE", "audio/wav"),
+    (b"AVI ", "video/x-msvideo"),
+];
+
+/// Content types of the text formats, by extension.
+const TEXT_EXTENSIONS: [(&str, &str); 17] = [
+    ("htm", "text/html"),
+    ("html", "text/html"),
+    ("xhtml", "application/xhtml+xml"),
+    ("xht", "application/xhtml+xml"),
+    ("css", "text/css"),
+    ("js", "text/javascript"),
+    ("mjs", "text/javascript"),
+    ("json", "application/json"),
+    ("xml", "application/xml"),
+    ("svg", "image/svg+xml"),
+    ("opf", "application/oebps-package+xml"),
+    ("ncx", "application/x-dtbncx+xml"),
+    ("csv", "text/csv"),
+    ("md", "text/markdown"),
+    ("txt", "text/plain"),
+    ("rtf", "application/rtf"),
+    ("yaml", "application/yaml"),
+];
+
+/// Guesses the content type of the beginning of a content.
+///
+/// # Arguments
+/// * `bytes` - The first bytes of the content.
+/// * `file_path` - The path of the content, whose extension is used for the text formats.
+///
+/// # Returns
+/// The content type of the binary format recognized by its signature. Otherwise, for a text, the content type
+/// given by the extension or by an XML or HTML prologue, or `text/plain`. Otherwise, `application/octet-stream`.
+fn sniff(bytes: &[u8], file_path: &str) -> &'static str {
+    if bytes.starts_with(b"RIFF") && bytes.len() >= 12 {
+        if let Some((_, content_type)) = RIFF_FORMS.iter().find(|(form, _)| bytes[8..12] == **form)
+        {
+            return content_type;
+        }
+    }
+    if let Some((_, _, content_type)) = SIGNATURES
+        .iter()
+        .find(|(offset, signature, _)| bytes[(*offset).min(bytes.len())..].starts_with(signature))
+    {
+        return content_type;
+    }
+
+    let text = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+    if !is_text(text) {
+        return "application/octet-stream";
+    }
+
+    let extension = file_path
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase())
+        .unwrap_or_default();
+    if let Some((_, content_type)) = TEXT_EXTENSIONS
+        .iter()
+        .find(|(known, _)| *known == extension)
+    {
+        return content_type;
+    }
+
+    let start = String::from_utf8_lossy(text)
+        .trim_start()
+        .to_ascii_lowercase();
+    if start.starts_with("<!doctype html") || start.starts_with("<html") {
+        "text/html"
+    } else if start.starts_with("<?xml") || start.starts_with("<svg") {
+        if start.contains("<svg") {
+            "image/svg+xml"
+        } else if start.contains("<html") {
+            "application/xhtml+xml"
+        } else {
+            "application/xml"
+        }
+    } else {
+        "text/plain"
+    }
+}
+
+/// Checks if the beginning of a content is a UTF-8 text without control characters other than white space.
+fn is_text(bytes: &[u8]) -> bool {
+    let valid = match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        // A character may be cut at the end of the bytes read
+        Err(error) if error.error_len().is_none() => {
+            std::str::from_utf8(&bytes[..error.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return false,
+    };
+    valid
+        .chars()
+        .all(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r' | '\x0c'))
+}
+
+impl<R: Read + Seek> Unzipper<R> {
+    /// Guesses the content type of a file entry.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file.
+    ///
+    /// # Returns
+    /// A Result containing the best-guess MIME type, or an error if the file is not found or cannot be read.
+    /// Only the first 512 bytes of the content are decompressed. The binary formats are recognized by their
+    /// signature, and the text formats by the extension of the path or by their prologue. A text of unknown
+    /// format is `text/plain`, and binary content of unknown format is `application/octet-stream`.
+    pub fn content_type(&self, file_path: &str) -> Result<&'static str, UnzipperError> {
+        let bytes = self.read_range(file_path, 0, SNIFF_LENGTH)?;
+        Ok(sniff(&bytes, file_path))
+    }
+}
+
+#[cfg(test)]
+mod mime_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_mime_sniff() {
+        assert_eq!(sniff(b"GIF89a\x01\x00", "image"), "image/gif");
+        assert_eq!(
+            sniff(b"RIFF\x00\x00\x00\x00WEBPVP8 ", "a.png"),
+            "image/webp"
+        );
+        assert_eq!(sniff(b"\x00\x00\x00\x18ftypmp42", "movie"), "video/mp4");
+        assert_eq!(sniff(b"body { color: red; }", "style.CSS"), "text/css");
+        assert_eq!(sniff(b"\xef\xbb\xbf<!DOCTYPE html>", "index"), "text/html");
+        assert_eq!(
+            sniff(b"<?xml version=\"1.0\"?>\n<svg>", "logo"),
+            "image/svg+xml"
+        );
+        assert_eq!(
+            sniff(b"<?xml version=\"1.0\"?>\n<root/>", "data"),
+            "application/xml"
+        );
+        assert_eq!(
+            sniff("caf\u{e9}".as_bytes()[..4].as_ref(), "readme"),
+            "text/plain"
+        );
+        assert_eq!(
+            sniff(b"\x00\x10\x02binary", "data.txt"),
+            "application/octet-stream"
+        );
+        assert_eq!(sniff(b"", "empty"), "text/plain");
+    }
+
+    #[test]
+    fn test_mime_content_type() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        for (file_path, content_type) in [
+            ("mimetype", "text/plain"),
+            ("META-INF/container.xml", "application/xml"),
+            ("content.opf", "application/oebps-package+xml"),
+            ("toc.ncx", "application/x-dtbncx+xml"),
+            ("epub_split_000.xhtml", "application/xhtml+xml"),
+            ("Images/qrcode.png", "image/png"),
+            ("Images/spacer.gif", "image/gif"),
+            ("Fonts/Swinging.ttf", "font/ttf"),
+            ("Fonts/LinLibertine_R.otf", "font/otf"),
+        ] {
+            assert_eq!(unzipper.content_type(file_path).unwrap(), content_type);
+        }
+        assert!(unzipper.content_type("missing.txt").is_err());
+    }
+}
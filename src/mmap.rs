@@ -0,0 +1,169 @@
+//! Memory-Mapped File Module.
+//!
+//! This module provides an Unzipper backend reading the zip archive through a memory-mapped file.
+//! The central directory is parsed in place and stored entries are returned as slices of the mapped file,
+//! so that no seek and read system calls are needed to access them.
+//!
+//! This module is available with the `mmap` feature.
+
+use std::fs::File;
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
+
+use memmap2::Mmap;
+
+use crate::crc32::Crc32;
+use crate::error::UnzipperError;
+use crate::options::UnzipperOptions;
+use crate::unzipper::Unzipper;
+
+/// Unzipper reading the zip archive through a memory-mapped file.
+pub type MmapUnzipper = Unzipper<Cursor<Mmap>>;
+
+impl Unzipper<Cursor<Mmap>> {
+    /// Creates a new Unzipper instance mapping the zip file at the specified path into memory.
+    ///
+    /// # Arguments
+    /// * `filepath` - A reference to the path of the zip file to be opened.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper instance if successful, or an error if the file could not be mapped.
+    ///
+    /// # Safety
+    /// The zip file must not be modified or truncated, by this or another process, while the Unzipper is alive:
+    /// the mapped content would change underneath it, which is undefined behavior.
+    pub unsafe fn open_mmap(filepath: &Path) -> Result<MmapUnzipper, UnzipperError> {
+        Unzipper::open_mmap_with_options(filepath, UnzipperOptions::default())
+    }
+
+    /// Creates a new Unzipper instance mapping the zip file at the specified path into memory, using the given options.
+    ///
+    /// # Arguments
+    /// * `filepath` - A reference to the path of the zip file to be opened.
+    /// * `options` - The options controlling how the zip file is read.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper instance if successful, or an error if the file could not be mapped.
+    ///
+    /// # Safety
+    /// The zip file must not be modified or truncated, by this or another process, while the Unzipper is alive:
+    /// the mapped content would change underneath it, which is undefined behavior.
+    pub unsafe fn open_mmap_with_options(
+        filepath: &Path,
+        options: UnzipperOptions,
+    ) -> Result<MmapUnzipper, UnzipperError> {
+        let file = File::open(filepath.canonicalize()?)?;
+        let map = Mmap::map(&file)?;
+
+        let mut unzipper = Unzipper::empty(options);
+        unzipper.filepath = filepath.to_path_buf();
+        unzipper.reader = Some(RwLock::new(Cursor::new(map)));
+
+        // The central directory is parsed directly from the mapped file
+        let mut warnings = Vec::new();
+        let dir_end = unzipper.find_dir_end(&mut warnings)?;
+        let file_entries = match unzipper.reader {
+            Some(ref reader) => {
+                let cursor = reader.read().unwrap_or_else(PoisonError::into_inner);
+                let entries = mapped_slice(cursor.get_ref(), dir_end.dir_offset, dir_end.dir_size)?;
+                unzipper.parse_central_directory(entries, &dir_end, &mut warnings)?
+            }
+            None => return Err(UnzipperError::NotOpen),
+        };
+        unzipper.file_entries = Arc::new(file_entries);
+        unzipper.record_warnings(warnings);
+        unzipper.apk_signing_block = unzipper.find_apk_signing_block(dir_end.dir_offset)?;
+        unzipper.read_comment(&dir_end)?;
+
+        Ok(unzipper)
+    }
+
+    /// Returns the content of a stored (not compressed) file entry as a slice of the mapped zip file.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to access.
+    ///
+    /// # Returns
+    /// A Result containing the content of the entry, without any copy, or an error if the file is not found,
+    /// is compressed or is encrypted. Unless disabled in the options, the CRC-32 of the content is verified.
+    pub fn get_stored_file(&mut self, file_path: &str) -> Result<&[u8], UnzipperError> {
+        let (data_offset, file_entry) = self.locate_file_data(file_path)?;
+
+        if file_entry.flags & 0x0001 != 0 {
+            return Err(UnzipperError::PasswordRequired(
+                self.clean_file_path(file_path),
+            ));
+        }
+        if file_entry.method != 0 {
+            return Err(UnzipperError::UnsupportedMethod(file_entry.method));
+        }
+
+        let verify_crc = self.options.verify_crc;
+        let file_path = self.clean_file_path(file_path);
+        let map = match self.reader_mut() {
+            Some(cursor) => cursor.get_ref(),
+            None => return Err(UnzipperError::NotOpen),
+        };
+        let data = mapped_slice(map, data_offset, file_entry.size as usize)?;
+
+        if verify_crc {
+            let mut crc = Crc32::new();
+            crc.update(data);
+            if crc.finalize() != file_entry.crc32 {
+                return Err(UnzipperError::CrcMismatch {
+                    file_path,
+                    expected: file_entry.crc32,
+                    actual: crc.finalize(),
+                });
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+/// Returns a slice of the mapped zip file.
+///
+/// # Arguments
+/// * `map` - The mapped zip file.
+/// * `position` - The position of the first byte of the slice in the zip file.
+/// * `length` - The length of the slice.
+///
+/// # Returns
+/// A Result containing the slice, or an error if it extends beyond the end of the zip file.
+fn mapped_slice(map: &[u8], position: usize, length: usize) -> Result<&[u8], UnzipperError> {
+    position
+        .checked_add(length)
+        .and_then(|end| map.get(position..end))
+        .ok_or_else(|| {
+            UnzipperError::InvalidArchive("Data extends beyond the end of the file".to_string())
+        })
+}
+
+#[cfg(test)]
+mod mmap_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_unzipper_open_mmap() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+
+        let mut unzipper = unsafe { Unzipper::open_mmap(&file) }.unwrap();
+        assert_eq!(unzipper.entries().count(), 86);
+        assert_eq!(
+            unzipper.get_stored_file("mimetype").unwrap(),
+            b"application/epub+zip"
+        );
+        assert!(matches!(
+            unzipper.get_stored_file("content.opf"),
+            Err(UnzipperError::UnsupportedMethod(8))
+        ));
+        assert_eq!(unzipper.get_file("content.opf").unwrap().len(), 12472);
+    }
+}
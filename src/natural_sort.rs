@@ -0,0 +1,148 @@
+//! Natural Sort Module.
+//!
+//! This module provides the natural (numeric-aware) ordering of entry paths, in which the runs of digits
+//! are compared by their value, so that `page2.png` comes before `page10.png`.
+
+use std::cmp::Ordering;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Compares two paths in the natural order.
+///
+/// # Arguments
+/// * `a` - The first path.
+/// * `b` - The second path.
+///
+/// # Returns
+/// The ordering of the paths. The runs of digits are compared by value, and the other characters
+/// without regard to case. Paths equal in these terms are ordered by their characters, so that the order is total.
+pub(crate) fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    loop {
+        let ordering = match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x = take_number(&mut a_chars);
+                let y = take_number(&mut b_chars);
+                // Without leading zeros, a longer number is greater
+                x.len().cmp(&y.len()).then_with(|| x.cmp(&y))
+            }
+            (Some(&x), Some(&y)) => {
+                a_chars.next();
+                b_chars.next();
+                x.to_lowercase().cmp(y.to_lowercase())
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Takes a run of digits, returning it without its leading zeros.
+fn take_number(chars: &mut Peekable<Chars<'_>>) -> String {
+    let mut number = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        if !(number.is_empty() && c == '0') {
+            number.push(c);
+        }
+        chars.next();
+    }
+    number
+}
+
+#[cfg(test)]
+mod natural_sort_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use crate::entry::EntryOrder;
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_natural_sort_cmp() {
+        let mut names = vec![
+            "page10.png",
+            "page2.png",
+            "Page1.png",
+            "page02.png",
+            "cover.png",
+            "page2b.png",
+            "ch2/page1.png",
+            "ch10/page1.png",
+        ];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            names,
+            [
+                "ch2/page1.png",
+                "ch10/page1.png",
+                "cover.png",
+                "Page1.png",
+                "page02.png",
+                "page2.png",
+                "page2b.png",
+                "page10.png"
+            ]
+        );
+        assert_eq!(
+            natural_cmp("a99999999999999999999", "a100000000000000000000"),
+            Ordering::Less
+        );
+        assert_eq!(natural_cmp("abc", "abc"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_natural_sort_entries_in_order() {
+        let unit_test = UnitTest::new("unzipper_natural_sort");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("comic.cbz");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let names = |order: EntryOrder| -> Vec<String> {
+            unzipper
+                .entries_in_order(order)
+                .map(|entry| entry.name().to_string())
+                .collect()
+        };
+        assert_eq!(
+            names(EntryOrder::Natural),
+            [
+                "cover.png",
+                "page1.png",
+                "page2.png",
+                "page3.png",
+                "page10.png",
+                "page11.png"
+            ]
+        );
+        assert_eq!(
+            names(EntryOrder::Name),
+            [
+                "cover.png",
+                "page1.png",
+                "page10.png",
+                "page11.png",
+                "page2.png",
+                "page3.png"
+            ]
+        );
+        assert_eq!(
+            names(EntryOrder::Archive),
+            [
+                "page10.png",
+                "page2.png",
+                "page1.png",
+                "page11.png",
+                "page3.png",
+                "cover.png"
+            ]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+//! Nested Module.
+//!
+//! This module provides the access to the zip archives stored as entries of another zip archive,
+//! such as the documents of a delivery bundle. The nested archive is decompressed, then read by a new
+//! Unzipper using the options of the enclosing one, so that its decompression limits apply as well.
+
+use std::fs::File;
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+
+use crate::error::UnzipperError;
+use crate::unzipper::Unzipper;
+
+impl<R: Read + Seek> Unzipper<R> {
+    /// Opens a zip archive stored as an entry of the zip archive, decompressing it in memory.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the entry holding the nested zip archive.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper reading the nested zip archive, or an error if the entry is not found,
+    /// cannot be decompressed or is not a valid zip archive.
+    pub fn open_nested(&self, file_path: &str) -> Result<Unzipper<Cursor<Vec<u8>>>, UnzipperError> {
+        let bytes = self.get_file(file_path)?;
+        Unzipper::from_reader_with_options(Cursor::new(bytes), self.options.clone())
+    }
+
+    /// Opens a zip archive stored as an entry of the zip archive, decompressing it to a file.
+    ///
+    /// This keeps large nested zip archives out of memory.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the entry holding the nested zip archive.
+    /// * `temp_path` - The path of the file receiving the nested zip archive. It is created or truncated,
+    ///   and is left to the caller to remove once the returned Unzipper is dropped.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper reading the nested zip archive, or an error if the entry is not found,
+    /// cannot be decompressed, the file cannot be written or the entry is not a valid zip archive.
+    pub fn open_nested_to_file(
+        &self,
+        file_path: &str,
+        temp_path: &Path,
+    ) -> Result<Unzipper<File>, UnzipperError> {
+        let mut file = File::create(temp_path)?;
+        self.get_file_to(file_path, &mut file)?;
+        drop(file);
+        Unzipper::with_options(temp_path, self.options.clone())
+    }
+}
+
+#[cfg(test)]
+mod nested_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_nested_open() {
+        let unit_test = UnitTest::new("unzipper_nested");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("nested.zip");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        let inner = unzipper.open_nested("bundle/inner.zip").unwrap();
+        assert_eq!(inner.get_file("doc.txt").unwrap(), b"inner document\n");
+        let deeper = inner.open_nested("deeper.zip").unwrap();
+        assert_eq!(deeper.get_file("deep.txt").unwrap(), b"deep content\n");
+
+        let temp_path = std::env::temp_dir().join("unzipper_nested_inner.zip");
+        let inner = unzipper
+            .open_nested_to_file("bundle/inner.zip", &temp_path)
+            .unwrap();
+        assert_eq!(inner.get_file("doc.txt").unwrap(), b"inner document\n");
+        drop(inner);
+        std::fs::remove_file(&temp_path).unwrap();
+
+        assert!(matches!(
+            unzipper.open_nested("missing.zip"),
+            Err(UnzipperError::NotFound(_))
+        ));
+        assert!(unzipper.open_nested("readme.txt").is_err());
+    }
+}
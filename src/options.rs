@@ -0,0 +1,397 @@
+//! Options Module.
+//!
+//! This module provides the options controlling how an Unzipper reads a zip archive.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::decompressor::{Decompressor, DecompressorRegistry};
+use crate::decryption::{DecryptionProvider, DecryptionProviders};
+use crate::error::UnzipperError;
+use crate::warning::{Warning, WarningHandler};
+
+/// Default size of the buffers used to read the zip file.
+pub(crate) const DEFAULT_BUFFER_SIZE: usize = 16 * 1024;
+
+/// Enum that describes how the symbolic link entries are handled when extracting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SymlinkPolicy {
+    /// Creates the symbolic links. On systems other than Unix, the links are materialized instead.
+    #[default]
+    Create,
+    /// Writes a copy of the link target, which must be an entry of the zip archive.
+    Materialize,
+    /// Does not extract the symbolic links.
+    Skip,
+    /// Fails the extraction if the zip archive contains a symbolic link.
+    Error,
+}
+
+/// Enum that describes how the entries sharing the same path are handled.
+///
+/// Whatever the policy, each duplicate is recorded as a warning, available through `Unzipper::warnings()`.
+/// Extractors disagreeing on which entry wins is a known way to smuggle content past a check.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DuplicatePolicy {
+    /// Keeps the last entry, at the place of the first one in the order of the central directory.
+    #[default]
+    LastWins,
+    /// Keeps the first entry, ignoring the next ones.
+    FirstWins,
+    /// Rejects the zip archive with `UnzipperError::DuplicateEntry`.
+    Error,
+    /// Keeps all the entries, the next ones being renamed with a number before their extension,
+    /// such as `notes (2).txt`. A renamed entry keeps its new path, a later entry with that path being
+    /// renamed in turn.
+    KeepAll,
+}
+
+/// Enum that describes how strictly the structure of a zip archive is checked.
+///
+/// The modes are ordered from the strictest to the most tolerant. The inconsistencies tolerated are recorded
+/// as warnings, available through `Unzipper::warnings()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ParseMode {
+    /// Rejects any inconsistency of the zip archive.
+    Strict,
+    /// Tolerates the harmless quirks produced by common zip tools, such as odd extra fields, a wrong archive
+    /// comment length or a wrong decompressed size.
+    #[default]
+    Standard,
+    /// Also tolerates damaged archives, such as a truncated central directory, data between the central
+    /// directory records or a data descriptor that does not match the central directory, reading as much
+    /// as possible.
+    Lenient,
+}
+
+/// Struct that holds the options of an Unzipper.
+///
+/// The options are built by chaining the setter methods on the default options:
+///
+/// ```
+/// use unzipper::UnzipperOptions;
+///
+/// let options = UnzipperOptions::new().verify_crc(false);
+/// ```
+#[derive(Debug, Clone)]
+pub struct UnzipperOptions {
+    pub(crate) verify_crc: bool,
+    pub(crate) apply_permissions: bool,
+    pub(crate) symlinks: SymlinkPolicy,
+    pub(crate) cancellation: Option<Arc<AtomicBool>>,
+    pub(crate) max_entry_size: Option<u64>,
+    pub(crate) max_total_size: Option<u64>,
+    pub(crate) max_compression_ratio: Option<u32>,
+    pub(crate) parse_mode: ParseMode,
+    pub(crate) warning_handler: Option<WarningHandler>,
+    pub(crate) recovery: bool,
+    pub(crate) nested_depth: u32,
+    pub(crate) duplicates: DuplicatePolicy,
+    pub(crate) decompressors: DecompressorRegistry,
+    pub(crate) decryption_providers: DecryptionProviders,
+    pub(crate) read_ahead: bool,
+    pub(crate) lazy_open: bool,
+    pub(crate) compact_index: bool,
+    pub(crate) lazy_directory: bool,
+    pub(crate) check_local_headers: bool,
+    pub(crate) buffer_size: usize,
+    #[cfg(feature = "unicode")]
+    pub(crate) normalize_unicode: bool,
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    pub(crate) uring_output: bool,
+}
+
+impl Default for UnzipperOptions {
+    fn default() -> Self {
+        UnzipperOptions {
+            verify_crc: true,
+            apply_permissions: true,
+            symlinks: SymlinkPolicy::default(),
+            cancellation: None,
+            max_entry_size: None,
+            max_total_size: None,
+            max_compression_ratio: None,
+            parse_mode: ParseMode::default(),
+            warning_handler: None,
+            recovery: false,
+            nested_depth: 0,
+            duplicates: DuplicatePolicy::default(),
+            decompressors: DecompressorRegistry::default(),
+            decryption_providers: DecryptionProviders::default(),
+            read_ahead: false,
+            lazy_open: false,
+            compact_index: false,
+            lazy_directory: false,
+            check_local_headers: false,
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            #[cfg(feature = "unicode")]
+            normalize_unicode: false,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            uring_output: false,
+        }
+    }
+}
+
+impl UnzipperOptions {
+    /// Creates a new UnzipperOptions with the default values.
+    pub fn new() -> UnzipperOptions {
+        UnzipperOptions::default()
+    }
+
+    /// Sets whether the CRC-32 of the decompressed entries is verified against the one stored in the zip archive.
+    ///
+    /// Verification is enabled by default. Disabling it speeds up extraction, but corrupted data will go unnoticed.
+    pub fn verify_crc(mut self, verify_crc: bool) -> UnzipperOptions {
+        self.verify_crc = verify_crc;
+        self
+    }
+
+    /// Sets whether the Unix permissions of the entries are applied to the extracted files.
+    ///
+    /// Enabled by default, so that executables stay executable. Only the permission bits (0o777) are applied:
+    /// the setuid, setgid and sticky bits are never set. It has no effect on systems other than Unix,
+    /// nor for entries not made on a Unix system.
+    pub fn apply_permissions(mut self, apply_permissions: bool) -> UnzipperOptions {
+        self.apply_permissions = apply_permissions;
+        self
+    }
+
+    /// Sets how the symbolic link entries are handled when extracting.
+    ///
+    /// Links are created by default. Whatever the policy, a link whose target is outside the destination
+    /// directory is rejected.
+    pub fn symlinks(mut self, policy: SymlinkPolicy) -> UnzipperOptions {
+        self.symlinks = policy;
+        self
+    }
+
+    /// Sets the cancellation token of the Unzipper.
+    ///
+    /// Once the token is set to true, from any thread, the reading of entries and the extractions stop
+    /// between two chunks of data and return `UnzipperError::Cancelled`. The token must be reset to false
+    /// before reading entries again.
+    pub fn cancellation(mut self, token: Arc<AtomicBool>) -> UnzipperOptions {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Sets the maximum number of bytes decompressed from a single entry.
+    ///
+    /// There is no limit by default. The limit is verified as the data is decompressed, whatever the size
+    /// recorded in the zip archive, and `UnzipperError::LimitExceeded` is returned when it is exceeded.
+    pub fn max_entry_size(mut self, max_entry_size: u64) -> UnzipperOptions {
+        self.max_entry_size = Some(max_entry_size);
+        self
+    }
+
+    /// Sets the maximum number of bytes decompressed in total by the Unzipper, over all the entries read.
+    ///
+    /// There is no limit by default. When it is exceeded, `UnzipperError::LimitExceeded` is returned.
+    pub fn max_total_size(mut self, max_total_size: u64) -> UnzipperOptions {
+        self.max_total_size = Some(max_total_size);
+        self
+    }
+
+    /// Sets the maximum ratio between the decompressed size and the compressed size of an entry.
+    ///
+    /// There is no limit by default. Ordinary files rarely exceed a ratio of 100, while decompression bombs
+    /// reach ratios above 1000. When it is exceeded, `UnzipperError::LimitExceeded` is returned.
+    pub fn max_compression_ratio(mut self, max_compression_ratio: u32) -> UnzipperOptions {
+        self.max_compression_ratio = Some(max_compression_ratio);
+        self
+    }
+
+    /// Sets how strictly the structure of the zip archive is checked.
+    ///
+    /// The standard mode is used by default. See `ParseMode` for the inconsistencies tolerated by each mode.
+    pub fn parse_mode(mut self, parse_mode: ParseMode) -> UnzipperOptions {
+        self.parse_mode = parse_mode;
+        self
+    }
+
+    /// Sets the function notified of each inconsistency tolerated by the parsing mode, as it is recorded.
+    ///
+    /// There is no function by default. The warnings are still recorded, available through `Unzipper::warnings()`
+    /// and `StreamUnzipper::warnings()`, the function letting the application log them as they are found, such as
+    /// during a long extraction, including the ones found while the zip archive is opened. It is called from
+    /// the thread reading the zip archive, and replaces any function previously set.
+    pub fn on_warning(
+        mut self,
+        handler: impl Fn(&Warning) + Send + Sync + 'static,
+    ) -> UnzipperOptions {
+        self.warning_handler = Some(WarningHandler::new(handler));
+        self
+    }
+
+    /// Sets whether the file entries are rebuilt from the local file headers when the central directory
+    /// is missing or damaged.
+    ///
+    /// Disabled by default. The recovery scans the whole zip file for local file headers, keeping the entries
+    /// whose data can be located. It applies when opening a zip file, bytes or a reader, not to the other sources.
+    /// A warning reports that the entries were recovered, as their Unix attributes and comments are lost.
+    pub fn recovery(mut self, recovery: bool) -> UnzipperOptions {
+        self.recovery = recovery;
+        self
+    }
+
+    /// Sets how the entries sharing the same path are handled.
+    ///
+    /// The last entry wins by default. See `DuplicatePolicy` for the other policies.
+    pub fn duplicates(mut self, policy: DuplicatePolicy) -> UnzipperOptions {
+        self.duplicates = policy;
+        self
+    }
+
+    /// Registers the decompressor of a compression method that this crate does not support, such as
+    /// a proprietary method, or that replaces a built-in one.
+    ///
+    /// # Arguments
+    /// * `method` - The compression method, as recorded in the zip archive.
+    /// * `factory` - The function creating a decompressor, called for each entry read.
+    pub fn decompressor<D, F>(mut self, method: u16, factory: F) -> UnzipperOptions
+    where
+        D: Decompressor + 'static,
+        F: Fn() -> D + Send + Sync + 'static,
+    {
+        self.decompressors.register(method, factory);
+        self
+    }
+
+    /// Registers the provider of an encryption scheme that this crate does not support, such as a DRM wrapper.
+    ///
+    /// # Arguments
+    /// * `provider` - The provider, consulted for each entry read after the ones previously registered,
+    ///   and before the built-in schemes.
+    pub fn decryption_provider(
+        mut self,
+        provider: impl DecryptionProvider + 'static,
+    ) -> UnzipperOptions {
+        self.decryption_providers.register(provider);
+        self
+    }
+
+    /// Sets the maximum depth of the zip archives, stored as entries, that `Unzipper::extract_all()` extracts
+    /// recursively.
+    ///
+    /// Disabled (0) by default: the nested zip archives are extracted as plain files. With a depth of 1, the zip
+    /// archives stored in the archive are extracted as well, next to them, and with a depth of 2, the ones stored
+    /// in these, and so on. The nested zip archives are recognized by their signature, whatever their extension.
+    /// Limiting the depth stops the nested decompression bombs, the other limits of the options applying
+    /// to each nested archive.
+    pub fn extract_nested(mut self, max_depth: u32) -> UnzipperOptions {
+        self.nested_depth = max_depth;
+        self
+    }
+
+    /// Sets whether the data of the next entries is read ahead by a background thread during the extractions.
+    ///
+    /// Disabled by default. While an entry is decompressed and written by `Unzipper::extract_all()` and the other
+    /// extraction methods, the data of the next ones is read through another file handle, overlapping the reading
+    /// of the zip file with the decompression. It only applies to the Unzippers opened from a path, and to the
+    /// entries whose compressed size is at most 16 MiB, the others being read as usual.
+    pub fn read_ahead(mut self, read_ahead: bool) -> UnzipperOptions {
+        self.read_ahead = read_ahead;
+        self
+    }
+
+    /// Sets whether the zip file is kept closed between its reads, false by default.
+    ///
+    /// The Unzippers opened from a path then hold no file handle once the central directory is read,
+    /// the zip file being reopened for each read of it, so that thousands of zip archives can be managed
+    /// without running out of file handles. Larger buffers, set with `UnzipperOptions::buffer_size()`,
+    /// make fewer reads and so fewer reopenings.
+    pub fn lazy_open(mut self, lazy_open: bool) -> UnzipperOptions {
+        self.lazy_open = lazy_open;
+        self
+    }
+
+    /// Sets whether the entries are looked up by a hash of their path instead of a copy of it, false by default.
+    ///
+    /// The index of the entries then keeps a single copy of each path, with the entry, instead of a second one
+    /// as the key of its lookup: each entry saves the length of its path and about 16 bytes, its path and entry
+    /// being still kept in full. The lookups compare the path of the entry found with the one looked up, the rare
+    /// entries whose hash is taken by another path being searched in order.
+    pub fn compact_index(mut self, compact_index: bool) -> UnzipperOptions {
+        self.compact_index = compact_index;
+        self
+    }
+
+    /// Sets whether the local file header of each entry read is compared with its central directory record,
+    /// false by default.
+    ///
+    /// The compression method, the CRC-32 and the sizes of the two records differ in the zip archives tampered
+    /// with, so that extractors reading one or the other disagree, and in the ones of faulty writers. A difference
+    /// rejects the entry with `UnzipperError::InvalidArchive`, unless tolerated by the lenient parsing mode, which
+    /// records it as a warning. The values left to 0 in the local file header, for a data descriptor, are not
+    /// compared. `Unzipper::verify()` reports the differences whatever this option.
+    pub fn check_local_headers(mut self, check_local_headers: bool) -> UnzipperOptions {
+        self.check_local_headers = check_local_headers;
+        self
+    }
+
+    /// Sets whether the central directory is parsed on demand, false by default.
+    ///
+    /// Opening the zip file then only locates the central directory, whose records are parsed when the entries
+    /// are looked up, up to the entry looked up, or listed, all at once. This suits the callers reading a few
+    /// known entries of a huge zip archive. The invalid records are reported by the lookups reaching them, and
+    /// when the entries are listed, as a warning, the entries before them being kept.
+    ///
+    /// A lookup stops at the first record with the path looked up, which only gives the entry kept with the
+    /// `DuplicatePolicy::FirstWins` policy: the other duplicate policies, as the recovery mode, need the whole
+    /// central directory and disable this option.
+    pub fn lazy_directory(mut self, lazy_directory: bool) -> UnzipperOptions {
+        self.lazy_directory = lazy_directory;
+        self
+    }
+
+    /// Sets the size of the buffers used to read the zip file.
+    ///
+    /// 16 KiB by default. The entry readers read the compressed data by chunks of this size, and the smaller reads,
+    /// such as the ones of the local file headers, are served from a buffer of this size filled by a single read
+    /// of the zip file. Larger buffers make fewer reads, which helps with slow sources, at the cost of memory.
+    pub fn buffer_size(mut self, buffer_size: usize) -> UnzipperOptions {
+        self.buffer_size = buffer_size.max(1);
+        self
+    }
+
+    /// Sets whether the entry paths, and the paths used to look them up, are normalized to the Unicode NFC form.
+    ///
+    /// Disabled by default. Archives created on macOS store the paths in the decomposed NFD form, so that a path
+    /// typed or produced on another system, usually in the NFC form, does not find the entry although it looks
+    /// the same. With the normalization, both forms find the entry, and the paths of the entries are given
+    /// in the NFC form.
+    ///
+    /// This option is available with the `unicode` feature.
+    #[cfg(feature = "unicode")]
+    pub fn normalize_unicode(mut self, normalize_unicode: bool) -> UnzipperOptions {
+        self.normalize_unicode = normalize_unicode;
+        self
+    }
+
+    /// Sets whether the files extracted are written through io_uring, false by default.
+    ///
+    /// This option is available with the `io-uring` feature, on Linux only.
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    pub fn uring_output(mut self, uring_output: bool) -> UnzipperOptions {
+        self.uring_output = uring_output;
+        self
+    }
+
+    /// Notifies the warning handler, if any, of a warning recorded.
+    pub(crate) fn notify_warning(&self, warning: &Warning) {
+        if let Some(ref handler) = self.warning_handler {
+            handler.notify(warning);
+        }
+    }
+
+    /// Returns an error if the cancellation token has been set.
+    pub(crate) fn check_cancelled(&self) -> Result<(), UnzipperError> {
+        match self.cancellation {
+            Some(ref token) if token.load(Ordering::Relaxed) => Err(UnzipperError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Password Module.
+//!
+//! This module provides the hook through which an Unzipper asks for the passwords of the encrypted entries
+//! read without a password given, such as by `get_file()` or `extract_all()`. The provider may prompt the user
+//! or consult a keyring, and is asked again, a limited number of times, while the password returned is wrong.
+
+use crate::entry::EntryInfo;
+
+/// Trait implemented by the providers of the passwords of the encrypted entries.
+///
+/// It is installed with `Unzipper::set_password_provider()`. It is implemented by the closures taking
+/// the entry and the number of the attempt, and returning the password to try.
+pub trait PasswordProvider {
+    /// Returns the password of an encrypted entry.
+    ///
+    /// # Arguments
+    /// * `entry` - The entry to decrypt.
+    /// * `attempt` - The number of the attempt for the entry, from 1, incremented each time the password
+    ///   returned is wrong.
+    ///
+    /// # Returns
+    /// The password to try, or None to give up, the reading failing with `UnzipperError::PasswordRequired`.
+    fn password(&mut self, entry: &EntryInfo, attempt: u32) -> Option<Vec<u8>>;
+}
+
+impl<F> PasswordProvider for F
+where
+    F: FnMut(&EntryInfo, u32) -> Option<Vec<u8>>,
+{
+    fn password(&mut self, entry: &EntryInfo, attempt: u32) -> Option<Vec<u8>> {
+        self(entry, attempt)
+    }
+}
+
+#[cfg(test)]
+mod password_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    use crate::error::UnzipperError;
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_password_provider() {
+        let unit_test = UnitTest::new("unzipper_zipcrypto");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("zipcrypto.zip");
+        let secret = b"Secret message stored in a ZipCrypto encrypted entry.\n";
+        let mut unzipper = Unzipper::new(&file).unwrap();
+
+        // The right password on the second attempt
+        let asked = Arc::new(Mutex::new(Vec::new()));
+        let recorder = asked.clone();
+        unzipper.set_password_provider(
+            move |entry: &EntryInfo, attempt| {
+                recorder
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {attempt}", entry.name()));
+                Some(if attempt == 1 {
+                    b"wrong".to_vec()
+                } else {
+                    b"secret".to_vec()
+                })
+            },
+            3,
+        );
+        assert_eq!(unzipper.get_file("secret.txt").unwrap(), secret);
+        assert_eq!(*asked.lock().unwrap(), ["secret.txt 1", "secret.txt 2"]);
+
+        // A password given explicitly is used as is
+        assert!(matches!(
+            unzipper.get_file_with_password("secret.txt", b"wrong"),
+            Err(UnzipperError::InvalidPassword(_))
+        ));
+        assert_eq!(asked.lock().unwrap().len(), 2);
+
+        // The attempts are exhausted
+        unzipper.set_password_provider(|_: &EntryInfo, _| Some(b"wrong".to_vec()), 2);
+        assert!(matches!(
+            unzipper.get_file("secret.txt"),
+            Err(UnzipperError::InvalidPassword(_))
+        ));
+
+        // The provider gives up
+        unzipper.set_password_provider(|_: &EntryInfo, _| None, 2);
+        assert!(matches!(
+            unzipper.get_file("secret.txt"),
+            Err(UnzipperError::PasswordRequired(_))
+        ));
+
+        unzipper.clear_password_provider();
+        assert!(matches!(
+            unzipper.get_file("secret.txt"),
+            Err(UnzipperError::PasswordRequired(_))
+        ));
+    }
+}
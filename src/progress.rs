@@ -0,0 +1,116 @@
+//! Progress Module.
+//!
+//! This module provides the hook through which an Unzipper reports the progress of the entries being read,
+//! whether through `get_file()`, an EntryReader or a bulk extraction such as `extract_all()`.
+
+use crate::entry::EntryInfo;
+
+/// Trait implemented by the receivers of the progress of an Unzipper.
+///
+/// It is installed with `Unzipper::set_progress()`. Every method has an empty default implementation,
+/// so that only the events of interest need to be handled.
+pub trait Progress {
+    /// Called when the reading of an entry starts, once the entry has been opened.
+    ///
+    /// # Arguments
+    /// * `entry` - The entry being read.
+    fn entry_started(&mut self, entry: &EntryInfo) {
+        let _ = entry;
+    }
+
+    /// Called each time a chunk of the entry has been decompressed.
+    ///
+    /// # Arguments
+    /// * `entry` - The entry being read. Its `size()` gives the expected total.
+    /// * `bytes_decompressed` - The number of bytes of the entry decompressed so far.
+    fn bytes_decompressed(&mut self, entry: &EntryInfo, bytes_decompressed: u64) {
+        let _ = (entry, bytes_decompressed);
+    }
+
+    /// Called when the end of the entry has been reached and its data has been verified.
+    ///
+    /// # Arguments
+    /// * `entry` - The entry that was read.
+    fn entry_finished(&mut self, entry: &EntryInfo) {
+        let _ = entry;
+    }
+}
+
+#[cfg(test)]
+mod progress_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+    use std::sync::{Arc, Mutex};
+
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    struct Recorder {
+        events: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Progress for Recorder {
+        fn entry_started(&mut self, entry: &EntryInfo) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("started {}", entry.name()));
+        }
+
+        fn bytes_decompressed(&mut self, entry: &EntryInfo, bytes_decompressed: u64) {
+            self.events.lock().unwrap().push(format!(
+                "{} {}/{}",
+                entry.name(),
+                bytes_decompressed,
+                entry.size()
+            ));
+        }
+
+        fn entry_finished(&mut self, entry: &EntryInfo) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("finished {}", entry.name()));
+        }
+    }
+
+    #[test]
+    fn test_progress_get_file_and_extract_all() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+
+        let mut unzipper = Unzipper::new(&file).unwrap();
+        unzipper.set_progress(Recorder {
+            events: events.clone(),
+        });
+        unzipper.get_file("content.opf").unwrap();
+
+        let events = std::mem::take(&mut *events.lock().unwrap());
+        assert_eq!(events.first().unwrap(), "started content.opf");
+        assert_eq!(events[events.len() - 2], "content.opf 12472/12472");
+        assert_eq!(events.last().unwrap(), "finished content.opf");
+        assert!(
+            events.len() > 3,
+            "the entry is decompressed in several chunks"
+        );
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let unit_test = UnitTest::new("unzipper_directories");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("directories.zip");
+        let dest = std::env::temp_dir().join("unzipper_progress_extract_all");
+        let _ = std::fs::remove_dir_all(&dest);
+
+        let mut unzipper = Unzipper::new(&file).unwrap();
+        unzipper.set_progress(Recorder {
+            events: events.clone(),
+        });
+        unzipper.extract_all(&dest).unwrap();
+        assert_eq!(
+            *events.lock().unwrap(),
+            ["started file.txt", "file.txt 5/5", "finished file.txt"]
+        );
+        std::fs::remove_dir_all(&dest).unwrap();
+    }
+}
@@ -0,0 +1,111 @@
+//! Read-Ahead Module.
+//!
+//! This module provides the prefetching of the entry data during the bulk extractions. A background thread
+//! reads the data of the next entries through its own file handle while the current entry is decompressed
+//! and written, so that the reading of the zip file overlaps the decompression. The data read ahead is then
+//! served from memory to the Unzipper.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::sync::mpsc::SyncSender;
+
+use crate::unzipper::FileHeader;
+
+/// Largest compressed size of the entries read ahead, the larger ones being read as usual.
+pub(crate) const READ_AHEAD_MAX_SIZE: usize = 16 * 1024 * 1024;
+
+/// Size of a data descriptor with a signature in the zip64 format, the largest one following the entry data.
+const DATA_DESCRIPTOR_MAX_SIZE: usize = 24;
+
+/// Struct that holds the part of the zip file read ahead for an entry, from its local file header
+/// to its data descriptor.
+pub(crate) struct Prefetched {
+    position: usize, // of the local file header in the zip file
+    data: Vec<u8>,
+}
+
+impl Prefetched {
+    /// Returns the bytes at the given position in the zip file, if they were all read ahead.
+    pub(crate) fn get(&self, position: usize, length: usize) -> Option<&[u8]> {
+        let start = position.checked_sub(self.position)?;
+        self.data.get(start..start.checked_add(length)?)
+    }
+}
+
+/// Reads ahead the entries to extract, in order, sending their data to the extraction.
+///
+/// # Arguments
+/// * `file` - A file handle of the zip file, for the background thread only.
+/// * `entries` - The position of the local file header and the compressed size of each entry to extract,
+///   or None for the entries that are not read ahead.
+/// * `sender` - Receives the data of each entry, or None when it is not read ahead or could not be read,
+///   the entry being then read as usual.
+pub(crate) fn read_ahead(
+    mut file: File,
+    entries: Vec<Option<(usize, usize)>>,
+    sender: SyncSender<Option<Prefetched>>,
+) {
+    for entry in entries {
+        let prefetched = entry.and_then(|(position, compressed_size)| {
+            read_entry(&mut file, position, compressed_size)
+        });
+        // The extraction stopped on an error
+        if sender.send(prefetched).is_err() {
+            break;
+        }
+    }
+}
+
+/// Reads the local file header, the data and the data descriptor of an entry.
+fn read_entry(file: &mut File, position: usize, compressed_size: usize) -> Option<Prefetched> {
+    let mut header = [0u8; FileHeader::SIZE];
+    file.seek(SeekFrom::Start(position as u64)).ok()?;
+    file.read_exact(&mut header).ok()?;
+    let file_header = FileHeader::parse(&header).ok()?;
+
+    let length = file_header.file_path_length as usize
+        + file_header.extra_field_length as usize
+        + compressed_size
+        + DATA_DESCRIPTOR_MAX_SIZE;
+    let mut data = Vec::with_capacity(FileHeader::SIZE + length);
+    data.extend_from_slice(&header);
+    file.take(length as u64).read_to_end(&mut data).ok()?;
+    Some(Prefetched { position, data })
+}
+
+#[cfg(test)]
+mod read_ahead_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use crate::options::UnzipperOptions;
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_read_ahead_extraction() {
+        let prefetched = Prefetched {
+            position: 100,
+            data: (0..50).collect(),
+        };
+        assert_eq!(prefetched.get(110, 3), Some([10, 11, 12].as_slice()));
+        assert_eq!(prefetched.get(140, 10).map(<[u8]>::len), Some(10));
+        assert_eq!(prefetched.get(140, 11), None);
+        assert_eq!(prefetched.get(99, 2), None);
+
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let dest = std::env::temp_dir().join("unzipper_read_ahead_extraction");
+        let _ = std::fs::remove_dir_all(&dest);
+
+        let options = UnzipperOptions::new().read_ahead(true);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        unzipper.extract_all(&dest).unwrap();
+        assert!(unzipper.compare_dir(&dest).unwrap().is_up_to_date());
+        assert!(unzipper.lock_prefetched().is_none());
+        assert_eq!(unzipper.extract_matching("**/*.css", &dest).unwrap(), 2);
+
+        std::fs::remove_dir_all(&dest).unwrap();
+    }
+}
@@ -0,0 +1,227 @@
+//! Read-At Module.
+//!
+//! This module provides the extension point through which applications read zip archives kept in their own
+//! storage, such as cloud objects, block devices or database blobs. The storage only has to read the bytes
+//! found at a given offset, and is presented to the Unzipper as a seekable stream by a ReadAtReader.
+
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read, Seek, SeekFrom};
+use std::sync::{Arc, RwLock};
+
+use crate::error::UnzipperError;
+use crate::options::UnzipperOptions;
+use crate::unzipper::Unzipper;
+
+/// Trait implemented by the storages read at an offset.
+///
+/// It is implemented by `File` on Unix and Windows, byte slices and vectors, and by the references
+/// and the `Arc`s of the storages implementing it.
+pub trait ReadAt {
+    /// Reads bytes at an offset of the storage.
+    ///
+    /// # Arguments
+    /// * `offset` - The position of the first byte to read.
+    /// * `buf` - The buffer receiving the bytes.
+    ///
+    /// # Returns
+    /// A Result containing the number of bytes read, which may be less than the size of the buffer,
+    /// 0 meaning that the offset is at or past the end of the storage.
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize>;
+}
+
+impl ReadAt for [u8] {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        let start = usize::try_from(offset)
+            .unwrap_or(usize::MAX)
+            .min(self.len());
+        let length = buf.len().min(self.len() - start);
+        buf[..length].copy_from_slice(&self[start..start + length]);
+        Ok(length)
+    }
+}
+
+impl ReadAt for Vec<u8> {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.as_slice().read_at(offset, buf)
+    }
+}
+
+#[cfg(any(unix, windows))]
+impl ReadAt for File {
+    #[cfg(unix)]
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        std::os::unix::fs::FileExt::read_at(self, buf, offset)
+    }
+
+    #[cfg(windows)]
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        std::os::windows::fs::FileExt::seek_read(self, buf, offset)
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for &T {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        (**self).read_at(offset, buf)
+    }
+}
+
+impl<T: ReadAt + ?Sized> ReadAt for Arc<T> {
+    fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        (**self).read_at(offset, buf)
+    }
+}
+
+/// Reader presenting a storage read at an offset as a seekable stream.
+pub struct ReadAtReader<T: ReadAt> {
+    source: T,
+    length: u64,   // of the storage
+    position: u64, // current position in the stream
+}
+
+impl<T: ReadAt> ReadAtReader<T> {
+    /// Creates a new ReadAtReader.
+    ///
+    /// # Arguments
+    /// * `source` - The storage holding the zip archive.
+    /// * `length` - The length of the zip archive in the storage.
+    pub fn new(source: T, length: u64) -> ReadAtReader<T> {
+        ReadAtReader {
+            source,
+            length,
+            position: 0,
+        }
+    }
+
+    /// Returns the storage read.
+    pub fn get_ref(&self) -> &T {
+        &self.source
+    }
+
+    /// Returns the storage read, consuming the reader.
+    pub fn into_inner(self) -> T {
+        self.source
+    }
+
+    /// Reads bytes of the storage at an offset, without going past the length of the zip archive.
+    fn read_at_offset(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+        if offset >= self.length {
+            return Ok(0);
+        }
+        let length = buf
+            .len()
+            .min(usize::try_from(self.length - offset).unwrap_or(usize::MAX));
+        self.source.read_at(offset, &mut buf[..length])
+    }
+}
+
+impl<T: ReadAt> Read for ReadAtReader<T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let length = self.read_at_offset(self.position, buf)?;
+        self.position += length as u64;
+        Ok(length)
+    }
+}
+
+impl<T: ReadAt> Seek for ReadAtReader<T> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(delta) => self.length.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        };
+        self.position =
+            position.ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Invalid seek position"))?;
+        Ok(self.position)
+    }
+}
+
+impl<T: ReadAt> Unzipper<ReadAtReader<T>> {
+    /// Creates a new Unzipper instance reading the zip archive from a storage read at an offset.
+    ///
+    /// # Arguments
+    /// * `source` - The storage holding the zip archive, implementing `ReadAt`.
+    /// * `length` - The length of the zip archive in the storage.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper instance if successful, or an error if the zip archive is invalid.
+    pub fn from_read_at(
+        source: T,
+        length: u64,
+    ) -> Result<Unzipper<ReadAtReader<T>>, UnzipperError> {
+        Unzipper::from_read_at_with_options(source, length, UnzipperOptions::default())
+    }
+
+    /// Creates a new Unzipper instance reading the zip archive from a storage read at an offset,
+    /// using the given options. See `Unzipper::from_read_at()`.
+    ///
+    /// # Arguments
+    /// * `source` - The storage holding the zip archive, implementing `ReadAt`.
+    /// * `length` - The length of the zip archive in the storage.
+    /// * `options` - The options controlling how the zip archive is read.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper instance if successful, or an error if the zip archive is invalid.
+    pub fn from_read_at_with_options(
+        source: T,
+        length: u64,
+        options: UnzipperOptions,
+    ) -> Result<Unzipper<ReadAtReader<T>>, UnzipperError> {
+        let mut unzipper = Unzipper::empty(options);
+        unzipper.reader = Some(RwLock::new(ReadAtReader::new(source, length)));
+        // The storage is read at the offsets directly, without the cursor of the reader
+        unzipper.positional = Some(|reader, buf, offset| reader.read_at_offset(offset, buf));
+        unzipper.read_central_directory()?;
+        Ok(unzipper)
+    }
+}
+
+#[cfg(test)]
+mod read_at_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use test_support::unit_test::UnitTest;
+
+    /// Storage made of fixed-size blocks, each read returning the bytes of a single block.
+    struct Blocks(Vec<Vec<u8>>);
+
+    impl ReadAt for Blocks {
+        fn read_at(&self, offset: u64, buf: &mut [u8]) -> std::io::Result<usize> {
+            let (index, start) = ((offset / 100) as usize, (offset % 100) as usize);
+            match self.0.get(index) {
+                Some(block) => {
+                    let length = buf.len().min(block.len() - start);
+                    buf[..length].copy_from_slice(&block[start..start + length]);
+                    Ok(length)
+                }
+                None => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_at_source() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let bytes = std::fs::read(&file).unwrap();
+        let expected = Unzipper::new(&file).unwrap();
+        let content = expected.get_file("content.opf").unwrap();
+
+        let blocks = Blocks(bytes.chunks(100).map(<[u8]>::to_vec).collect());
+        let unzipper = Unzipper::from_read_at(blocks, bytes.len() as u64).unwrap();
+        assert_eq!(unzipper.get_file("content.opf").unwrap(), content);
+
+        let shared = Arc::new(File::open(&file).unwrap());
+        let unzipper = Unzipper::from_read_at(shared.clone(), bytes.len() as u64).unwrap();
+        assert_eq!(unzipper.get_file("content.opf").unwrap(), content);
+        let unzipper = Unzipper::from_read_at(&*shared, bytes.len() as u64).unwrap();
+        assert_eq!(unzipper.entries().count(), expected.entries().count());
+
+        let unzipper = Unzipper::from_read_at(bytes.as_slice(), bytes.len() as u64).unwrap();
+        assert_eq!(unzipper.get_file("content.opf").unwrap(), content);
+
+        // A length cutting the central directory
+        assert!(Unzipper::from_read_at(bytes.as_slice(), bytes.len() as u64 - 30).is_err());
+    }
+}
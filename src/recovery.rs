@@ -0,0 +1,260 @@
+//! Recovery Module.
+//!
+//! This module provides the rebuilding of the file entries of a damaged zip archive, whose central directory
+//! is missing or truncated. The zip file is scanned forward for local file headers, and every entry whose data
+//! can be located is kept, so that the files of a partially downloaded or corrupted archive can still be read.
+
+use std::io::{Read, Seek};
+use std::sync::Arc;
+
+use crate::error::UnzipperError;
+use crate::extra::{extra_fields, timestamps, AesExtra, AES_EXTRA_FIELD_ID, AES_METHOD};
+use crate::unzipper::{
+    decode_file_name, FileEntry, FileHeader, Unzipper, DATA_DESCRIPTOR_SIGNATURE,
+    FILE_HEADER_SIGNATURE,
+};
+
+/// Size of the blocks read while scanning the zip file for a signature.
+const SCAN_BLOCK_SIZE: usize = 64 * 1024;
+
+/// Size of a data descriptor with a signature, in the format used by the recovered entries.
+const DATA_DESCRIPTOR_SIZE: usize = 16;
+
+impl<R: Read + Seek> Unzipper<R> {
+    /// Rebuilds the file entries from the local file headers found in the zip file.
+    ///
+    /// # Returns
+    /// A Result indicating success, or an error if no entry could be recovered.
+    pub(crate) fn recover_file_entries(&mut self) -> Result<(), UnzipperError> {
+        let length = self.zip_length()?;
+
+        let mut position = 0;
+        while let Some(offset) = self.find_signature(FILE_HEADER_SIGNATURE, position, length)? {
+            // The data of a recovered entry is skipped, so that the headers of nested archives are ignored
+            position = match self.recover_file_entry(offset, length)? {
+                Some(data_end) => data_end,
+                None => offset + 4,
+            };
+        }
+
+        if self.file_entries.is_empty() {
+            return Err(UnzipperError::InvalidArchive(
+                "No entry could be recovered from the local file headers".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the file entry of a local file header.
+    ///
+    /// # Arguments
+    /// * `offset` - The position of the local file header in the zip file.
+    /// * `length` - The length of the zip file.
+    ///
+    /// # Returns
+    /// A Result containing the position following the entry data, or None if the header is not a valid
+    /// local file header or if the end of its data could not be located.
+    fn recover_file_entry(
+        &mut self,
+        offset: usize,
+        length: usize,
+    ) -> Result<Option<usize>, UnzipperError> {
+        // The position of an entry past the first 4 GiB cannot be recorded
+        let Ok(start_pos) = u32::try_from(offset) else {
+            return Ok(None);
+        };
+        if offset + FileHeader::SIZE > length {
+            return Ok(None);
+        }
+        let mut buffer = [0u8; FileHeader::SIZE];
+        self.get_data(&mut buffer, offset, "file header")?;
+        let header = FileHeader::parse(&buffer)?;
+
+        let flags = header.flags;
+        let mut method = header.compression_method;
+        let name_length = header.file_path_length as usize;
+        let extra_length = header.extra_field_length as usize;
+
+        let data_start = offset + FileHeader::SIZE + name_length + extra_length;
+        if data_start > length {
+            return Ok(None);
+        }
+        let mut variable = vec![0u8; name_length + extra_length];
+        self.get_data(&mut variable, offset + FileHeader::SIZE, "file name")?;
+        let (name, extra) = variable.split_at(name_length);
+
+        let mut crc32 = header.crc32;
+        let mut compressed_size = header.compressed_size;
+        let mut size = header.uncompressed_size;
+
+        // With a data descriptor (bit 3), the sizes are usually only known once the data has been written
+        let data_end = if flags & 0x0008 != 0 && compressed_size == 0 {
+            match self.find_data_descriptor(data_start, length)? {
+                Some((descriptor, data_end)) => {
+                    crc32 = self.get_u32(&descriptor[4..8]);
+                    compressed_size = self.get_u32(&descriptor[8..12]);
+                    size = self.get_u32(&descriptor[12..16]);
+                    data_end + DATA_DESCRIPTOR_SIZE
+                }
+                None => return Ok(None),
+            }
+        } else {
+            data_start + compressed_size as usize
+        };
+        if data_end > length {
+            return Ok(None);
+        }
+
+        let f_name = decode_file_name(name, extra, flags);
+        let file_path = self.clean_file_path(&f_name);
+
+        let aes = if method == AES_METHOD {
+            extra_fields(extra)
+                .find(|(id, _)| *id == AES_EXTRA_FIELD_ID)
+                .and_then(|(_, data)| AesExtra::parse(data))
+        } else {
+            None
+        };
+        if let Some(ref aes) = aes {
+            method = aes.method;
+        }
+
+        // The host system and the attributes are only recorded in the central directory
+        let file_entry = FileEntry {
+            start_pos,
+            compressed_size,
+            size,
+            method,
+            crc32,
+            flags,
+            last_mod_time: header.last_mod_time,
+            last_mod_date: header.last_mod_date,
+            timestamps: timestamps(extra),
+            aes,
+            made_by: 0,
+            extract_version: header.extract_version,
+            external_attr: 0,
+            dir_name: f_name.ends_with('/'),
+        };
+        let mut warnings = Vec::new();
+        Arc::make_mut(&mut self.file_entries).insert_with_policy(
+            file_path,
+            file_entry,
+            self.options.duplicates,
+            &mut warnings,
+        )?;
+        self.record_warnings(warnings);
+
+        Ok(Some(data_end))
+    }
+
+    /// Locates the data descriptor following the data of an entry, from its signature.
+    ///
+    /// # Arguments
+    /// * `data_start` - The position of the entry data in the zip file.
+    /// * `length` - The length of the zip file.
+    ///
+    /// # Returns
+    /// A Result containing the data descriptor and its position, or None if no data descriptor records
+    /// a compressed size matching its distance from the start of the data.
+    fn find_data_descriptor(
+        &mut self,
+        data_start: usize,
+        length: usize,
+    ) -> Result<Option<([u8; DATA_DESCRIPTOR_SIZE], usize)>, UnzipperError> {
+        let mut position = data_start;
+        while let Some(offset) = self.find_signature(DATA_DESCRIPTOR_SIGNATURE, position, length)? {
+            if offset + DATA_DESCRIPTOR_SIZE <= length {
+                let mut descriptor = [0u8; DATA_DESCRIPTOR_SIZE];
+                self.get_data(&mut descriptor, offset, "data descriptor")?;
+                if self.get_u32(&descriptor[8..12]) as usize == offset - data_start {
+                    return Ok(Some((descriptor, offset)));
+                }
+            }
+            position = offset + 1;
+        }
+        Ok(None)
+    }
+
+    /// Scans the zip file forward for a signature.
+    ///
+    /// # Arguments
+    /// * `signature` - The signature searched.
+    /// * `from` - The position in the zip file where the search starts.
+    /// * `length` - The length of the zip file.
+    ///
+    /// # Returns
+    /// A Result containing the position of the first occurrence of the signature, or None if it was not found.
+    fn find_signature(
+        &mut self,
+        signature: u32,
+        from: usize,
+        length: usize,
+    ) -> Result<Option<usize>, UnzipperError> {
+        self.options.check_cancelled()?;
+
+        let pattern = signature.to_le_bytes();
+        let mut block = vec![0u8; SCAN_BLOCK_SIZE.min(length.saturating_sub(from))];
+        let mut position = from;
+        while position + pattern.len() <= length {
+            let size = block.len().min(length - position);
+            self.get_data(&mut block[..size], position, "zip file")?;
+            if let Some(index) = block[..size].windows(4).position(|w| w == pattern) {
+                return Ok(Some(position + index));
+            }
+            // The blocks overlap, so that a signature spanning two blocks is found
+            position += size - (pattern.len() - 1);
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod recovery_tests {
+    use std::path::PathBuf;
+
+    use crate::options::{ParseMode, UnzipperOptions};
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_recovery_damaged_archive() {
+        let unit_test = UnitTest::new("unzipper_recovery");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("damaged.zip");
+        assert!(Unzipper::new(&file).is_err());
+
+        let options = UnzipperOptions::new().recovery(true);
+        let unzipper = Unzipper::with_options(&file, options.clone()).unwrap();
+        assert_eq!(unzipper.entries().count(), 3);
+        assert!(unzipper.warnings()[0]
+            .message()
+            .starts_with("Entries recovered from the local file headers"));
+        assert_eq!(unzipper.get_file("first.txt").unwrap(), b"stored entry\n");
+        assert_eq!(
+            unzipper.get_file("dir/second.txt").unwrap(),
+            b"deflated entry\n".repeat(8)
+        );
+        // The sizes of this entry are only recorded in its data descriptor
+        assert_eq!(
+            unzipper.get_file("third.txt").unwrap(),
+            b"streamed entry\n".repeat(8)
+        );
+
+        // A truncated central directory is rebuilt, even in the lenient mode
+        let unit_test = UnitTest::new("unzipper_quirks");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("truncated.zip");
+        let options = options.parse_mode(ParseMode::Lenient);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        assert_eq!(unzipper.entries().count(), 2);
+        assert_eq!(unzipper.warnings().len(), 1);
+        assert_eq!(unzipper.get_file("one.txt").unwrap(), b"one\n");
+
+        // A valid archive is read from its central directory
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper =
+            Unzipper::with_options(&file, UnzipperOptions::new().recovery(true)).unwrap();
+        assert_eq!(unzipper.entries().count(), 86);
+        assert!(unzipper.warnings().is_empty());
+    }
+}
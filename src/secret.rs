@@ -0,0 +1,74 @@
+//! Secret Module.
+//!
+//! This module provides the wiping of the secrets handled while decrypting the entries: the passwords,
+//! the keys derived from them and the decrypted data buffered by the readers. With the `zeroize` feature,
+//! they are overwritten with zeros once no longer needed, in a way the compiler cannot optimize away.
+//! Without it, the wiping does nothing.
+
+/// Overwrites the given values with zeros, with the `zeroize` feature.
+#[cfg(feature = "zeroize")]
+pub(crate) fn wipe<T: zeroize::DefaultIsZeroes>(values: &mut [T]) {
+    zeroize::Zeroize::zeroize(values);
+}
+
+/// Overwrites the given values with zeros, with the `zeroize` feature.
+#[cfg(not(feature = "zeroize"))]
+pub(crate) fn wipe<T>(values: &mut [T]) {
+    let _ = values;
+}
+
+/// Struct that holds a password, wiped when dropped.
+#[derive(Clone)]
+pub(crate) struct Secret(Vec<u8>);
+
+impl Secret {
+    /// Returns the bytes of the password.
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Takes the bytes of a password, without copying them.
+impl From<Vec<u8>> for Secret {
+    fn from(bytes: Vec<u8>) -> Self {
+        Secret(bytes)
+    }
+}
+
+/// Copies the bytes of a password.
+impl From<&[u8]> for Secret {
+    fn from(bytes: &[u8]) -> Self {
+        Secret(bytes.to_vec())
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+#[cfg(test)]
+mod secret_tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "zeroize")]
+    fn test_secret_wipe() {
+        let mut keys = [0x12345678u32, 0x23456789, 0x34567890];
+        wipe(&mut keys);
+        assert_eq!(keys, [0; 3]);
+
+        let mut buffer = b"decrypted data".to_vec();
+        wipe(&mut buffer);
+        assert!(buffer.iter().all(|byte| *byte == 0));
+        assert_eq!(buffer.len(), 14);
+    }
+
+    #[test]
+    fn test_secret_password() {
+        let password = Secret::from(b"secret".as_slice());
+        assert_eq!(password.clone().as_bytes(), b"secret");
+        assert_eq!(Secret::from(b"key".to_vec()).as_bytes(), b"key");
+    }
+}
@@ -0,0 +1,152 @@
+//! Seekable Reader Module.
+//!
+//! This module provides a reader over the content of a single file entry of a zip archive that also implements
+//! `std::io::Seek`, so that formats requiring random access (fonts, databases, nested archives) can be read
+//! in place, without extracting them first.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::entry_reader::EntryReader;
+use crate::error::UnzipperError;
+use crate::unzipper::Unzipper;
+
+/// Struct that provides seekable access to the decompressed content of a file entry.
+///
+/// It is obtained through `Unzipper::seekable_entry_reader()` and implements `std::io::Read` and `std::io::Seek`.
+/// The content of a stored entry that is not encrypted is read directly at the requested position.
+/// The content of the other entries is decompressed forward up to the requested position, the decompression
+/// restarting from the start of the entry when seeking backward. Seeking backward in a large compressed entry
+/// is then costly, and reading it sequentially is preferable whenever possible.
+pub struct SeekableEntryReader<'a, R: Read + Seek = File> {
+    reader: EntryReader<'a, R>,
+    position: u64, // in the decompressed content
+    size: u64,     // of the decompressed content, as recorded in the central directory
+}
+
+impl<'a, R: Read + Seek> SeekableEntryReader<'a, R> {
+    /// Creates a new SeekableEntryReader over an EntryReader at the start of the entry content.
+    pub(crate) fn new(reader: EntryReader<'a, R>) -> SeekableEntryReader<'a, R> {
+        let size = reader.size() as u64;
+        SeekableEntryReader {
+            reader,
+            position: 0,
+            size,
+        }
+    }
+
+    /// Returns the size of the entry once decompressed, as recorded in the central directory.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl<R: Read + Seek> Read for SeekableEntryReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let length = self.reader.read(buf)?;
+        self.position += length as u64;
+        Ok(length)
+    }
+}
+
+impl<R: Read + Seek> Seek for SeekableEntryReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(delta) => self.size.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid seek position")
+        })?;
+        if target == self.position {
+            return Ok(target);
+        }
+
+        // Restarting from the start of the entry verifies the CRC-32 again when reading it fully
+        if target != 0 && self.reader.seek_stored(target) {
+            self.position = target;
+            return Ok(target);
+        }
+        if target < self.position {
+            self.reader.rewind()?;
+            self.position = 0;
+        }
+
+        // The content before the target is decompressed and discarded. Seeking beyond the end of the content
+        // is allowed, the following reads returning no data
+        std::io::copy(
+            &mut self.reader.by_ref().take(target - self.position),
+            &mut std::io::sink(),
+        )?;
+        self.position = target;
+        Ok(target)
+    }
+}
+
+impl<R: Read + Seek> Unzipper<R> {
+    /// Opens a seekable reader over the decompressed content of a file entry.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to read.
+    ///
+    /// # Returns
+    /// A Result containing a SeekableEntryReader implementing `std::io::Read` and `std::io::Seek`,
+    /// or an error if the file could not be opened.
+    pub fn seekable_entry_reader(
+        &self,
+        file_path: &str,
+    ) -> Result<SeekableEntryReader<'_, R>, UnzipperError> {
+        Ok(SeekableEntryReader::new(self.entry_reader(file_path)?))
+    }
+}
+
+#[cfg(test)]
+mod seekable_reader_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_seekable_reader_seek() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let expected = unzipper.get_file("content.opf").unwrap();
+
+        // Compressed entry, decompressed forward and restarted when seeking backward
+        let mut reader = unzipper.seekable_entry_reader("content.opf").unwrap();
+        assert_eq!(reader.size(), 12472);
+        let mut chunk = [0u8; 16];
+        reader.seek(SeekFrom::Start(10000)).unwrap();
+        reader.read_exact(&mut chunk).unwrap();
+        assert_eq!(chunk, expected[10000..10016]);
+        assert_eq!(reader.seek(SeekFrom::Current(-100)).unwrap(), 9916);
+        reader.read_exact(&mut chunk).unwrap();
+        assert_eq!(chunk, expected[9916..9932]);
+        assert_eq!(reader.seek(SeekFrom::End(-16)).unwrap(), 12456);
+        reader.read_exact(&mut chunk).unwrap();
+        assert_eq!(chunk, expected[12456..]);
+        assert_eq!(reader.read(&mut chunk).unwrap(), 0);
+
+        // The whole content is read again from the start, its CRC-32 being verified
+        reader.rewind().unwrap();
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).unwrap();
+        assert_eq!(content, expected);
+        drop(reader);
+
+        // Stored entry, read directly at the requested position
+        let mut reader = unzipper.seekable_entry_reader("mimetype").unwrap();
+        reader.seek(SeekFrom::Start(12)).unwrap();
+        let mut epub = [0u8; 4];
+        reader.read_exact(&mut epub).unwrap();
+        assert_eq!(&epub, b"epub");
+        reader.seek(SeekFrom::Start(0)).unwrap();
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "application/epub+zip");
+    }
+}
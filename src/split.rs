@@ -0,0 +1,181 @@
+//! Split Archive Module.
+//!
+//! This module provides access to split (spanned) zip archives, whose data is spread over several volumes:
+//! `archive.z01`, `archive.z02`, ..., the last volume being `archive.zip`, which holds the central directory.
+//! The volumes are stitched together into one logical stream, in which the disk numbers recorded in the
+//! central directory give the volume holding each entry.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::error::UnzipperError;
+use crate::options::UnzipperOptions;
+use crate::unzipper::Unzipper;
+
+/// Reader presenting the volumes of a split zip archive as a single stream.
+pub struct SplitReader {
+    volumes: Vec<File>,
+    offsets: Vec<u64>, // of the start of each volume in the stream, followed by the length of the stream
+    position: u64,     // current position in the stream
+}
+
+impl SplitReader {
+    /// Opens the volumes of a split zip archive.
+    ///
+    /// # Arguments
+    /// * `filepath` - A reference to the path of the last volume, with the `.zip` extension.
+    ///
+    /// # Returns
+    /// A Result containing the SplitReader, or an error if a volume could not be opened.
+    /// A zip file that is not split is opened as a single volume.
+    pub fn open(filepath: &Path) -> Result<SplitReader, UnzipperError> {
+        let mut paths: Vec<PathBuf> = (1..)
+            .map(|number| filepath.with_extension(format!("z{number:02}")))
+            .take_while(|path| path.is_file())
+            .collect();
+        paths.push(filepath.to_path_buf());
+
+        let mut volumes = Vec::with_capacity(paths.len());
+        let mut offsets = vec![0];
+        for path in paths {
+            let volume = File::open(path.canonicalize()?)?;
+            let length = volume.metadata()?.len();
+            offsets.push(offsets[offsets.len() - 1] + length);
+            volumes.push(volume);
+        }
+
+        Ok(SplitReader {
+            volumes,
+            offsets,
+            position: 0,
+        })
+    }
+
+    /// Returns the positions of the start of each volume in the stream, indexed by disk number.
+    pub fn volume_offsets(&self) -> &[u64] {
+        &self.offsets[..self.volumes.len()]
+    }
+
+    /// Returns the length of the stream, which is the total length of the volumes.
+    fn length(&self) -> u64 {
+        self.offsets[self.volumes.len()]
+    }
+}
+
+impl Read for SplitReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.position >= self.length() {
+            return Ok(0);
+        }
+
+        // Reads from the volume holding the position, up to its end at most
+        let index = self
+            .offsets
+            .partition_point(|&offset| offset <= self.position)
+            - 1;
+        let available = (self.offsets[index + 1] - self.position) as usize;
+        let length = available.min(buf.len());
+        let volume = &mut self.volumes[index];
+        volume.seek(SeekFrom::Start(self.position - self.offsets[index]))?;
+        let length = volume.read(&mut buf[..length])?;
+        self.position += length as u64;
+        Ok(length)
+    }
+}
+
+impl Seek for SplitReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(delta) => self.length().checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        };
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid seek position")
+        })?;
+        Ok(self.position)
+    }
+}
+
+impl Unzipper<SplitReader> {
+    /// Opens a split zip archive.
+    ///
+    /// # Arguments
+    /// * `filepath` - A reference to the path of the last volume, with the `.zip` extension.
+    ///   The other volumes are found next to it, with the `.z01`, `.z02`, ... extensions.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper, or an error if a volume is missing or the zip archive is invalid.
+    pub fn open_split(filepath: &Path) -> Result<Unzipper<SplitReader>, UnzipperError> {
+        Unzipper::open_split_with_options(filepath, UnzipperOptions::default())
+    }
+
+    /// Opens a split zip archive, using the given options. See `Unzipper::open_split()`.
+    ///
+    /// # Arguments
+    /// * `filepath` - A reference to the path of the last volume, with the `.zip` extension.
+    /// * `options` - The options controlling how the zip archive is read.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper, or an error if a volume is missing or the zip archive is invalid.
+    pub fn open_split_with_options(
+        filepath: &Path,
+        options: UnzipperOptions,
+    ) -> Result<Unzipper<SplitReader>, UnzipperError> {
+        let reader = SplitReader::open(filepath)?;
+        let mut unzipper = Unzipper::empty(options);
+        unzipper.filepath = filepath.to_path_buf();
+        unzipper.disk_offsets = reader
+            .volume_offsets()
+            .iter()
+            .map(|&offset| offset as usize)
+            .collect();
+        unzipper.reader = Some(RwLock::new(reader));
+        unzipper.read_central_directory()?;
+        Ok(unzipper)
+    }
+}
+
+#[cfg(test)]
+mod split_tests {
+    use super::*;
+
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_split_open_split() {
+        let unit_test = UnitTest::new("unzipper_split");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("spanned.zip");
+
+        // The last volume alone does not hold the whole zip archive
+        assert!(matches!(
+            Unzipper::new(&file),
+            Err(UnzipperError::InvalidArchive(_))
+        ));
+
+        let unzipper = Unzipper::open_split(&file).unwrap();
+        assert_eq!(unzipper.entries().count(), 3);
+        assert!(unzipper.warnings().is_empty());
+        assert_eq!(
+            unzipper.get_file("alpha.txt").unwrap(),
+            b"alpha line\n".repeat(300)
+        );
+        // Spans both volumes, its CRC-32 being verified
+        assert_eq!(unzipper.get_file("random.bin").unwrap().len(), 70000);
+        assert_eq!(unzipper.get_file("omega.txt").unwrap(), b"omega\n");
+
+        // A zip file that is not split is a single volume
+        let unit_test = UnitTest::new("unzipper_comment");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("comment.zip");
+        let unzipper = Unzipper::open_split(&file).unwrap();
+        assert_eq!(unzipper.get_file("book.txt").unwrap(), b"content\n");
+
+        // The highest volume number recorded is reported without overflow
+        assert!(matches!(
+            unzipper.disk_offset(u16::MAX),
+            Err(UnzipperError::InvalidArchive(msg)) if msg.starts_with("Volume 65536 ")
+        ));
+    }
+}
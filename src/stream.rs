@@ -0,0 +1,743 @@
+//! Stream Module.
+//!
+//! This module provides the reading of a zip archive from a forward-only source, such as the standard input
+//! or the body of an HTTP response, without seeking and without the central directory. The local file headers
+//! are walked as they arrive, and the entries are yielded one after the other. The end of the data of an entry
+//! written with a data descriptor is found from the end of its compressed stream, or, when stored, from the
+//! data descriptor itself.
+
+use std::io::Read;
+
+use crate::crc32::Crc32;
+use crate::decompressor::Decompressor;
+use crate::entry::EntryInfo;
+use crate::error::UnzipperError;
+use crate::extra::{extra_fields, timestamps, AesExtra, AES_EXTRA_FIELD_ID, AES_METHOD};
+use crate::options::{ParseMode, UnzipperOptions};
+use crate::unzipper::{
+    clean_path, decode_file_name, truncated_record, FileEntry, FileHeader,
+    DATA_DESCRIPTOR_SIGNATURE, DIR_END_SIGNATURE, DIR_FILE_HEADER_SIGNATURE, FILE_HEADER_SIGNATURE,
+    ZIP64_DIR_END_SIGNATURE,
+};
+use crate::warning::{tolerate, Warning};
+
+/// Size of a data descriptor with a signature.
+const DATA_DESCRIPTOR_SIZE: usize = 16;
+
+/// Struct that reads the entries of a zip archive from a forward-only source, in the order of the archive.
+///
+/// The entries are obtained one after the other with `next_entry()`, which skips whatever remains of the data
+/// of the previous entry. The source is never rewound, so that an entry cannot be read again once passed, and
+/// the information only recorded in the central directory, such as the file attributes, is not available.
+/// The decompression limits, the CRC-32 verification and the decompressors of the options apply as they do
+/// for an Unzipper. Encrypted entries cannot be decrypted from a stream: reading them returns
+/// `UnzipperError::PasswordRequired`, while they can still be skipped.
+pub struct StreamUnzipper<R: Read> {
+    source: R,
+    options: UnzipperOptions,
+    buffer: Vec<u8>,
+    buffer_pos: usize,
+    buffer_len: usize,
+    position: u64, // in the source, of the byte at buffer_pos
+    entry: Option<EntryState>,
+    finished: bool, // once the central directory or the end of the source has been reached
+    total_decompressed: u64,
+    warnings: Vec<Warning>,
+}
+
+/// How the end of the data of an entry is found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DataEnd {
+    /// From the compressed size of the local file header, with the number of bytes not yet read.
+    Remaining(u64),
+    /// From the end of the compressed stream, the local file header not giving the compressed size.
+    StreamEnd,
+    /// From the data descriptor recording a compressed size matching the data read so far.
+    Descriptor,
+}
+
+/// State of the entry being read.
+struct EntryState {
+    info: EntryInfo,
+    data_end: DataEnd,
+    decompressor: Option<Box<dyn Decompressor>>,
+    crc: Option<Crc32>,
+    compressed_read: u64,
+    bytes_decompressed: u64,
+    finished: bool,
+}
+
+impl EntryState {
+    /// Returns an error if the data of the entry cannot be decompressed.
+    fn check_readable(&self) -> Result<(), UnzipperError> {
+        let features = self.info.unsupported_features();
+        if !features.is_empty() {
+            Err(UnzipperError::Unsupported {
+                file_path: self.info.name().to_string(),
+                features: features.to_vec(),
+            })
+        } else if self.info.is_encrypted() {
+            Err(UnzipperError::PasswordRequired(
+                self.info.name().to_string(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Struct that provides the decompressed content of an entry read from a StreamUnzipper.
+///
+/// It implements `std::io::Read`. Unless disabled in the options, the CRC-32 of the data is verified once
+/// the end of the entry is reached, against the one of the data descriptor when the entry has one.
+pub struct StreamEntry<'a, R: Read> {
+    unzipper: &'a mut StreamUnzipper<R>,
+    info: EntryInfo,
+}
+
+impl<R: Read> StreamEntry<'_, R> {
+    /// Returns the information about the entry, as recorded in its local file header. The CRC-32 and
+    /// the sizes are zero for an entry written with a data descriptor, which only follows its data.
+    pub fn info(&self) -> &EntryInfo {
+        &self.info
+    }
+}
+
+impl<R: Read> Read for StreamEntry<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        Ok(self.unzipper.read_entry(buf)?)
+    }
+}
+
+impl<R: Read> StreamUnzipper<R> {
+    /// Creates a new StreamUnzipper reading the given source, with the default options.
+    ///
+    /// # Arguments
+    /// * `source` - The source of the zip archive, read from its start.
+    pub fn new(source: R) -> StreamUnzipper<R> {
+        StreamUnzipper::with_options(source, UnzipperOptions::default())
+    }
+
+    /// Creates a new StreamUnzipper reading the given source, with the given options.
+    ///
+    /// # Arguments
+    /// * `source` - The source of the zip archive, read from its start.
+    /// * `options` - The options of the reading.
+    pub fn with_options(source: R, options: UnzipperOptions) -> StreamUnzipper<R> {
+        StreamUnzipper {
+            source,
+            options,
+            buffer: Vec::new(),
+            buffer_pos: 0,
+            buffer_len: 0,
+            position: 0,
+            entry: None,
+            finished: false,
+            total_decompressed: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Returns the inconsistencies of the zip archive tolerated by the parsing mode of the options,
+    /// in the order they were found.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Moves to the next entry of the zip archive.
+    ///
+    /// The data of the previous entry not yet read is skipped, being decompressed when the end of the entry
+    /// can only be found from the end of its compressed stream.
+    ///
+    /// # Returns
+    /// A Result containing the next entry, None once the central directory has been reached, or an error if
+    /// the local file header is invalid or the source cannot be read. A source ending without a central
+    /// directory is only tolerated in the Lenient parsing mode.
+    pub fn next_entry(&mut self) -> Result<Option<StreamEntry<'_, R>>, UnzipperError> {
+        self.skip_entry()?;
+        if self.finished {
+            return Ok(None);
+        }
+        self.options.check_cancelled()?;
+
+        if !self.fill(4)? {
+            self.finished = true;
+            self.tolerate(
+                Warning::new(None, "Zip file ends without a central directory"),
+                ParseMode::Lenient,
+            )?;
+            return Ok(None);
+        }
+        match self.u32_at(0) {
+            FILE_HEADER_SIGNATURE => {}
+            DIR_FILE_HEADER_SIGNATURE | DIR_END_SIGNATURE | ZIP64_DIR_END_SIGNATURE => {
+                self.finished = true;
+                return Ok(None);
+            }
+            _ => return Err(UnzipperError::BadSignature("local file header")),
+        }
+
+        if !self.fill(FileHeader::SIZE)? {
+            return Err(truncated_record("local file header"));
+        }
+        let header = FileHeader::parse(&self.buffer[self.buffer_pos..self.buffer_len])?;
+        let name_length = header.file_path_length as usize;
+        let header_size = FileHeader::SIZE + name_length + header.extra_field_length as usize;
+        if !self.fill(header_size)? {
+            return Err(truncated_record("local file header"));
+        }
+
+        let variable =
+            &self.buffer[self.buffer_pos + FileHeader::SIZE..self.buffer_pos + header_size];
+        let (name, extra) = variable.split_at(name_length);
+        let f_name = decode_file_name(name, extra, header.flags);
+        let file_path = clean_path(&f_name, &self.options);
+
+        let mut method = header.compression_method;
+        let aes = if method == AES_METHOD {
+            extra_fields(extra)
+                .find(|(id, _)| *id == AES_EXTRA_FIELD_ID)
+                .and_then(|(_, data)| AesExtra::parse(data))
+        } else {
+            None
+        };
+        if let Some(ref aes) = aes {
+            method = aes.method;
+        }
+
+        // The host system and the attributes are only recorded in the central directory
+        let file_entry = FileEntry {
+            start_pos: u32::try_from(self.position).unwrap_or(u32::MAX),
+            compressed_size: header.compressed_size,
+            size: header.uncompressed_size,
+            method,
+            crc32: header.crc32,
+            flags: header.flags,
+            last_mod_time: header.last_mod_time,
+            last_mod_date: header.last_mod_date,
+            timestamps: timestamps(extra),
+            aes,
+            made_by: 0,
+            extract_version: header.extract_version,
+            external_attr: 0,
+            dir_name: f_name.ends_with('/'),
+        };
+        self.consume(header_size);
+
+        let info = EntryInfo::new(&file_path, &file_entry, &self.options);
+        let mut entry = EntryState {
+            info: info.clone(),
+            data_end: DataEnd::Remaining(file_entry.compressed_size as u64),
+            decompressor: None,
+            crc: self.options.verify_crc.then(Crc32::new),
+            compressed_read: 0,
+            bytes_decompressed: 0,
+            finished: false,
+        };
+        if entry.check_readable().is_ok() {
+            // The sizes of the local file header may only be given by a data descriptor
+            entry.decompressor = self.options.decompressors.new_decompressor(method, 0)?;
+        }
+
+        // With a data descriptor (bit 3), the sizes are usually only known once the data has been written
+        if file_entry.flags & 0x0008 != 0 && file_entry.compressed_size == 0 {
+            entry.data_end = if entry.decompressor.is_some() {
+                DataEnd::StreamEnd
+            } else {
+                DataEnd::Descriptor
+            };
+        }
+        self.entry = Some(entry);
+
+        Ok(Some(StreamEntry {
+            unzipper: self,
+            info,
+        }))
+    }
+
+    /// Skips the data of the current entry not yet read, if any.
+    fn skip_entry(&mut self) -> Result<(), UnzipperError> {
+        let Some(entry) = self.entry.as_ref() else {
+            return Ok(());
+        };
+        if !entry.finished {
+            if entry.data_end == DataEnd::StreamEnd {
+                // The end of the entry is only known once its data has been decompressed
+                let mut sink = vec![0u8; self.options.buffer_size];
+                while self.read_entry(&mut sink)? > 0 {}
+            } else {
+                loop {
+                    let length = self.available_data()?;
+                    if length == 0 {
+                        break;
+                    }
+                    self.consume_data(length);
+                }
+                self.finish_entry(false)?;
+            }
+        }
+        self.entry = None;
+        Ok(())
+    }
+
+    /// Decompresses the data of the current entry into the provided buffer.
+    ///
+    /// # Returns
+    /// A Result containing the number of bytes written, 0 once the end of the entry has been reached.
+    fn read_entry(&mut self, buf: &mut [u8]) -> Result<usize, UnzipperError> {
+        let Some(entry) = self.entry.as_ref() else {
+            return Ok(0);
+        };
+        if entry.finished || buf.is_empty() {
+            return Ok(0);
+        }
+        self.options.check_cancelled()?;
+        entry.check_readable()?;
+
+        let (length, finished) = if entry.decompressor.is_some() {
+            self.read_compressed(buf)?
+        } else {
+            let length = self.read_stored(buf)?;
+            (length, length == 0)
+        };
+
+        if let Some(entry) = self.entry.as_mut() {
+            if let Some(ref mut crc) = entry.crc {
+                crc.update(&buf[..length]);
+            }
+            entry.bytes_decompressed += length as u64;
+        }
+        self.total_decompressed += length as u64;
+        self.check_limits()?;
+        if finished {
+            self.finish_entry(true)?;
+        }
+        Ok(length)
+    }
+
+    /// Copies the data of the current entry, stored without compression, into the provided buffer.
+    ///
+    /// # Returns
+    /// A Result containing the number of bytes copied, 0 once the end of the data has been reached.
+    fn read_stored(&mut self, buf: &mut [u8]) -> Result<usize, UnzipperError> {
+        let length = self.available_data()?.min(buf.len());
+        buf[..length].copy_from_slice(&self.buffer[self.buffer_pos..self.buffer_pos + length]);
+        self.consume_data(length);
+        Ok(length)
+    }
+
+    /// Decompresses the data of the current entry into the provided buffer, reading the source as required.
+    ///
+    /// # Returns
+    /// A Result containing the number of bytes written, and true once the end of the compressed stream
+    /// has been reached.
+    fn read_compressed(&mut self, buf: &mut [u8]) -> Result<(usize, bool), UnzipperError> {
+        loop {
+            let available = self.available_data()?;
+            let Some(decompressor) = self
+                .entry
+                .as_mut()
+                .and_then(|entry| entry.decompressor.as_mut())
+            else {
+                return Err(UnzipperError::Decompression(
+                    "Decompression state not initialized".to_string(),
+                ));
+            };
+
+            let input = &self.buffer[self.buffer_pos..self.buffer_pos + available];
+            let result = decompressor.decompress(input, buf)?;
+            self.consume_data(result.bytes_consumed);
+
+            if result.finished || result.bytes_written > 0 {
+                return Ok((result.bytes_written, result.finished));
+            }
+            if available == 0 {
+                return Err(UnzipperError::Decompression(
+                    "Compressed data is truncated".to_string(),
+                ));
+            }
+        }
+    }
+
+    /// Returns the number of bytes of the data of the current entry available at the start of the buffer,
+    /// reading the source as required.
+    ///
+    /// # Returns
+    /// A Result containing the number of bytes available, 0 once the end of the data has been reached, or
+    /// the end of the source for an entry ending with its compressed stream.
+    fn available_data(&mut self) -> Result<usize, UnzipperError> {
+        let Some(entry) = self.entry.as_ref() else {
+            return Ok(0);
+        };
+        match entry.data_end {
+            DataEnd::Remaining(0) => Ok(0),
+            DataEnd::Remaining(remaining) => {
+                if !self.fill(1)? {
+                    return Err(truncated_record("entry data"));
+                }
+                Ok((self.buffer_len - self.buffer_pos).min(remaining as usize))
+            }
+            DataEnd::StreamEnd => {
+                self.fill(1)?;
+                Ok(self.buffer_len - self.buffer_pos)
+            }
+            DataEnd::Descriptor => {
+                let compressed_read = entry.compressed_read;
+                if !self.fill(DATA_DESCRIPTOR_SIZE)? {
+                    let file_path = self.entry.as_ref().map_or("", |entry| entry.info.name());
+                    return Err(UnzipperError::InvalidArchive(format!(
+                        "Data descriptor not found: {file_path}"
+                    )));
+                }
+
+                // The data ends at the first data descriptor recording the size of the data preceding it
+                let data = &self.buffer[self.buffer_pos..self.buffer_len];
+                let candidates = data.len() - DATA_DESCRIPTOR_SIZE + 1;
+                let end = (0..candidates).find(|&offset| {
+                    let descriptor = &data[offset..offset + DATA_DESCRIPTOR_SIZE];
+                    descriptor[0..4] == DATA_DESCRIPTOR_SIGNATURE.to_le_bytes()
+                        && u32::from_le_bytes([
+                            descriptor[8],
+                            descriptor[9],
+                            descriptor[10],
+                            descriptor[11],
+                        ]) as u64
+                            == compressed_read + offset as u64
+                });
+                Ok(end.unwrap_or(candidates))
+            }
+        }
+    }
+
+    /// Consumes bytes of the data of the current entry from the buffer.
+    fn consume_data(&mut self, length: usize) {
+        self.consume(length);
+        if let Some(entry) = self.entry.as_mut() {
+            entry.compressed_read += length as u64;
+            if let DataEnd::Remaining(ref mut remaining) = entry.data_end {
+                *remaining -= length as u64;
+            }
+        }
+    }
+
+    /// Completes the reading of the current entry, once the end of its data has been reached, by skipping
+    /// any data left after the end of its compressed stream and reading its data descriptor.
+    ///
+    /// # Arguments
+    /// * `verify` - True if the data was decompressed, to verify its CRC-32 and its size.
+    fn finish_entry(&mut self, verify: bool) -> Result<(), UnzipperError> {
+        let Some(entry) = self.entry.as_ref() else {
+            return Ok(());
+        };
+        if let DataEnd::Remaining(remaining) = entry.data_end {
+            let mut remaining = remaining as usize;
+            while remaining > 0 {
+                if !self.fill(1)? {
+                    return Err(truncated_record("entry data"));
+                }
+                let length = (self.buffer_len - self.buffer_pos).min(remaining);
+                self.consume_data(length);
+                remaining -= length;
+            }
+        }
+
+        let Some(entry) = self.entry.as_ref() else {
+            return Ok(());
+        };
+        let file_path = entry.info.name().to_string();
+        let (crc32, compressed_size, size) = if entry.info.has_data_descriptor() {
+            self.read_data_descriptor()?
+        } else {
+            (
+                entry.info.crc32(),
+                entry.info.compressed_size(),
+                entry.info.size(),
+            )
+        };
+
+        let Some(entry) = self.entry.as_mut() else {
+            return Ok(());
+        };
+        entry.finished = true;
+        if compressed_size as u64 != entry.compressed_read {
+            return Err(UnzipperError::InvalidArchive(format!(
+                "Compressed size does not match the data descriptor: {file_path}"
+            )));
+        }
+        if !verify {
+            return Ok(());
+        }
+        match entry.crc {
+            Some(ref crc) if crc.finalize() != crc32 => {
+                return Err(UnzipperError::CrcMismatch {
+                    file_path,
+                    expected: crc32,
+                    actual: crc.finalize(),
+                });
+            }
+            _ => {}
+        }
+        if entry.bytes_decompressed != size as u64 {
+            self.tolerate(
+                Warning::new(
+                    Some(&file_path),
+                    "Decompressed size does not match the local file header",
+                ),
+                ParseMode::Standard,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Reads the data descriptor following the data of the current entry, with or without its signature.
+    ///
+    /// # Returns
+    /// A Result containing the CRC-32, the compressed size and the size recorded in the data descriptor.
+    fn read_data_descriptor(&mut self) -> Result<(u32, u32, u32), UnzipperError> {
+        if !self.fill(12)? {
+            return Err(truncated_record("data descriptor"));
+        }
+        if self.u32_at(0) == DATA_DESCRIPTOR_SIGNATURE {
+            self.consume(4);
+            if !self.fill(12)? {
+                return Err(truncated_record("data descriptor"));
+            }
+        }
+        let fields = (self.u32_at(0), self.u32_at(4), self.u32_at(8));
+        self.consume(12);
+        Ok(fields)
+    }
+
+    /// Records an inconsistency of the zip archive as a warning, notifying the warning handler of the options,
+    /// or turns it into an error, according to the parsing mode of the options. See `tolerate()`.
+    fn tolerate(&mut self, warning: Warning, tolerated_by: ParseMode) -> Result<(), UnzipperError> {
+        let count = self.warnings.len();
+        tolerate(
+            self.options.parse_mode,
+            &mut self.warnings,
+            warning,
+            tolerated_by,
+        )?;
+        for warning in &self.warnings[count..] {
+            self.options.notify_warning(warning);
+        }
+        Ok(())
+    }
+
+    /// Verifies the decompression limits of the options, for the data decompressed so far.
+    fn check_limits(&self) -> Result<(), UnzipperError> {
+        let Some(entry) = self.entry.as_ref() else {
+            return Ok(());
+        };
+        let options = &self.options;
+        let exceeded = |limit: &'static str| {
+            Err(UnzipperError::LimitExceeded {
+                file_path: entry.info.name().to_string(),
+                limit,
+            })
+        };
+
+        if options
+            .max_entry_size
+            .is_some_and(|max| entry.bytes_decompressed > max)
+        {
+            return exceeded("entry size");
+        }
+        if options.max_compression_ratio.is_some_and(|ratio| {
+            entry.bytes_decompressed > ratio as u64 * entry.compressed_read.max(1)
+        }) {
+            return exceeded("compression ratio");
+        }
+        if options
+            .max_total_size
+            .is_some_and(|max| self.total_decompressed > max)
+        {
+            return exceeded("total size");
+        }
+        Ok(())
+    }
+
+    /// Reads the source until at least `min` bytes are available in the buffer.
+    ///
+    /// # Returns
+    /// A Result containing true if the bytes are available, or false if the source ended before.
+    fn fill(&mut self, min: usize) -> Result<bool, UnzipperError> {
+        if self.buffer_len - self.buffer_pos >= min {
+            return Ok(true);
+        }
+        // The bytes not yet consumed are moved to the start of the buffer
+        self.buffer.copy_within(self.buffer_pos..self.buffer_len, 0);
+        self.buffer_len -= self.buffer_pos;
+        self.buffer_pos = 0;
+        let size = min.max(self.options.buffer_size);
+        if self.buffer.len() < size {
+            self.buffer.resize(size, 0);
+        }
+
+        while self.buffer_len < min {
+            let length = match self.source.read(&mut self.buffer[self.buffer_len..]) {
+                Ok(length) => length,
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error.into()),
+            };
+            if length == 0 {
+                return Ok(false);
+            }
+            self.buffer_len += length;
+        }
+        Ok(true)
+    }
+
+    /// Consumes bytes from the buffer.
+    fn consume(&mut self, length: usize) {
+        self.buffer_pos += length;
+        self.position += length as u64;
+    }
+
+    /// Returns the u32 value at the given offset from the start of the bytes available in the buffer.
+    fn u32_at(&self, offset: usize) -> u32 {
+        let start = self.buffer_pos + offset;
+        let bytes: &[u8; 4] = self.buffer[start..start + 4].try_into().unwrap_or(&[0; 4]);
+        u32::from_le_bytes(*bytes)
+    }
+}
+
+#[cfg(test)]
+mod stream_tests {
+    use super::*;
+
+    use std::fs::File;
+    use std::path::PathBuf;
+
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    /// Forward-only source returning at most a few bytes per read, as a slow network connection does.
+    struct Trickle<R: Read>(R);
+
+    impl<R: Read> Read for Trickle<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let length = buf.len().min(7);
+            self.0.read(&mut buf[..length])
+        }
+    }
+
+    /// Reads all the entries of a stream, returning their names and contents.
+    fn read_all<R: Read>(
+        unzipper: &mut StreamUnzipper<R>,
+    ) -> Result<Vec<(String, Vec<u8>)>, UnzipperError> {
+        let mut entries = Vec::new();
+        while let Some(mut entry) = unzipper.next_entry()? {
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content)?;
+            entries.push((entry.info().name().to_string(), content));
+        }
+        Ok(entries)
+    }
+
+    #[test]
+    fn test_stream_entries() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        let mut stream = StreamUnzipper::new(Trickle(File::open(&file).unwrap()));
+        let entries = read_all(&mut stream).unwrap();
+        assert_eq!(entries.len(), unzipper.entries().count());
+        for (name, content) in entries {
+            assert_eq!(content, unzipper.get_file(&name).unwrap(), "{name}");
+        }
+        assert!(stream.next_entry().unwrap().is_none());
+
+        // The entries not read are skipped
+        let mut stream = StreamUnzipper::new(File::open(&file).unwrap());
+        let mut names = Vec::new();
+        while let Some(entry) = stream.next_entry().unwrap() {
+            names.push(entry.info().name().to_string());
+        }
+        assert_eq!(names.len(), unzipper.entries().count());
+        assert!(stream.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_stream_data_descriptor() {
+        let unit_test = UnitTest::new("unzipper_data_descriptor");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("streamed.zip");
+        let bytes = std::fs::read(&file).unwrap();
+        let stored = b"Stored entry written by a streaming writer.\n".to_vec();
+        let deflated = "Deflated entry written by a streaming writer.\n".repeat(100);
+
+        let mut stream = StreamUnzipper::new(Trickle(bytes.as_slice()));
+        let entries = read_all(&mut stream).unwrap();
+        assert_eq!(
+            entries,
+            [
+                ("stored.txt".to_string(), stored),
+                ("deflated.txt".to_string(), deflated.into_bytes())
+            ]
+        );
+
+        let mut stream = StreamUnzipper::new(bytes.as_slice());
+        let entry = stream.next_entry().unwrap().unwrap();
+        assert!(entry.info().has_data_descriptor());
+        assert_eq!(entry.info().size(), 0);
+        let entry = stream.next_entry().unwrap().unwrap();
+        assert_eq!(entry.info().name(), "deflated.txt");
+        assert!(stream.next_entry().unwrap().is_none());
+
+        // The CRC-32 of the data descriptor is verified
+        let mut damaged = bytes.clone();
+        damaged[30 + "stored.txt".len()] ^= 0x01;
+        let mut stream = StreamUnzipper::new(damaged.as_slice());
+        assert!(matches!(
+            read_all(&mut stream),
+            Err(UnzipperError::CrcMismatch {
+                expected: 0x16dd88cb,
+                ..
+            })
+        ));
+
+        // Without central directory
+        let central_pos = bytes.windows(4).position(|w| w == b"PK\x01\x02").unwrap();
+        let mut stream = StreamUnzipper::new(&bytes[..central_pos]);
+        assert!(matches!(
+            read_all(&mut stream),
+            Err(UnzipperError::InvalidArchive(_))
+        ));
+        let options = UnzipperOptions::new().parse_mode(ParseMode::Lenient);
+        let mut stream = StreamUnzipper::with_options(&bytes[..central_pos], options);
+        assert_eq!(read_all(&mut stream).unwrap().len(), 2);
+        assert_eq!(stream.warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_stream_unreadable_entries() {
+        let unit_test = UnitTest::new("unzipper_unsupported");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("custom_encryption.zip");
+
+        let mut stream = StreamUnzipper::new(File::open(&file).unwrap());
+        let mut entry = stream.next_entry().unwrap().unwrap();
+        assert_eq!(entry.info().name(), "secret.txt");
+        let error = UnzipperError::from(entry.read(&mut [0u8; 16]).unwrap_err());
+        assert!(matches!(error, UnzipperError::PasswordRequired(_)));
+
+        let mut entry = stream.next_entry().unwrap().unwrap();
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content).unwrap();
+        assert_eq!(content, b"Public content.\n");
+        assert!(stream.next_entry().unwrap().is_none());
+
+        let options = UnzipperOptions::new().max_entry_size(8);
+        let mut stream = StreamUnzipper::with_options(File::open(&file).unwrap(), options);
+        stream.next_entry().unwrap();
+        let mut entry = stream.next_entry().unwrap().unwrap();
+        let error = UnzipperError::from(entry.read_to_end(&mut Vec::new()).unwrap_err());
+        assert!(matches!(
+            error,
+            UnzipperError::LimitExceeded {
+                limit: "entry size",
+                ..
+            }
+        ));
+    }
+}
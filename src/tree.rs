@@ -0,0 +1,203 @@
+//! Tree Module.
+//!
+//! This module provides a file-browser view of a zip archive. The directory tree is computed from the
+//! entry paths, so that directories without an entry of their own, which many zip tools do not store,
+//! are listed as well.
+
+use std::collections::BTreeMap;
+use std::io::{Read, Seek};
+
+use crate::entry::{EntryInfo, EntryKind};
+use crate::error::UnzipperError;
+use crate::unzipper::Unzipper;
+
+/// Struct that describes a node of the directory tree of a zip archive: a file or a directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TreeEntry {
+    path: String,
+    is_dir: bool,
+    info: Option<EntryInfo>,
+}
+
+impl TreeEntry {
+    /// Returns the cleaned path of the node in the zip archive.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the name of the node, which is the last segment of its path.
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or_default()
+    }
+
+    /// Returns the path of the directory holding the node, empty for the nodes at the root of the archive.
+    pub fn parent(&self) -> &str {
+        self.path.rsplit_once('/').map_or("", |(parent, _)| parent)
+    }
+
+    /// Returns the depth of the node in the tree, 0 for the nodes at the root of the archive.
+    pub fn depth(&self) -> usize {
+        self.path.matches('/').count()
+    }
+
+    /// Returns true if the node is a directory, whether or not the zip archive has an entry for it.
+    pub fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+
+    /// Returns the description of the entry of the node, or None for a directory without an entry of its own.
+    pub fn info(&self) -> Option<&EntryInfo> {
+        self.info.as_ref()
+    }
+}
+
+impl<R: Read + Seek> Unzipper<R> {
+    /// Lists the files and directories directly under a directory of the zip archive.
+    ///
+    /// # Arguments
+    /// * `dir_path` - A string slice representing the path of the directory, such as `OEBPS/`,
+    ///   or an empty string for the root of the archive.
+    ///
+    /// # Returns
+    /// A Result containing the nodes directly under the directory, sorted by name, or an error if the path is
+    /// not a directory of the zip archive. The directories implied by the paths of deeper entries are included.
+    pub fn list_dir(&self, dir_path: &str) -> Result<Vec<TreeEntry>, UnzipperError> {
+        let dir_path = self.clean_file_path(dir_path);
+        let children: Vec<TreeEntry> = self
+            .walk()
+            .filter(|node| node.parent() == dir_path)
+            .collect();
+
+        let is_dir_entry = self
+            .metadata(&dir_path)
+            .is_some_and(|entry| entry.kind() == EntryKind::Directory);
+        if children.is_empty() && !is_dir_entry && !dir_path.is_empty() {
+            return Err(UnzipperError::NotFound(dir_path));
+        }
+        Ok(children)
+    }
+
+    /// Walks the directory tree of the zip archive.
+    ///
+    /// # Returns
+    /// An iterator over the nodes of the tree in depth-first order: each directory comes before its content,
+    /// and the nodes of a directory are sorted by name. The depth and parent of each node are available through
+    /// `TreeEntry::depth()` and `TreeEntry::parent()`. The directories implied by the entry paths are included.
+    pub fn walk(&self) -> impl Iterator<Item = TreeEntry> + '_ {
+        // Sorting the paths segment by segment gives the depth-first order
+        let mut nodes: BTreeMap<Vec<&str>, TreeEntry> = BTreeMap::new();
+        for (file_path, file_entry) in self.parsed_entries().iter() {
+            let segments: Vec<&str> = file_path.split('/').collect();
+            for depth in 1..segments.len() {
+                let parent_path = segments[..depth].join("/");
+                nodes
+                    .entry(segments[..depth].to_vec())
+                    .or_insert_with(|| TreeEntry {
+                        path: parent_path,
+                        is_dir: true,
+                        info: None,
+                    })
+                    .is_dir = true;
+            }
+            let node = nodes.entry(segments).or_insert_with(|| TreeEntry {
+                path: file_path.clone(),
+                is_dir: false,
+                info: None,
+            });
+            node.is_dir |= file_entry.kind() == EntryKind::Directory;
+            node.info = Some(EntryInfo::new(file_path, file_entry, &self.options));
+        }
+        nodes.into_values()
+    }
+}
+
+#[cfg(test)]
+mod tree_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_tree_list_dir() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        let children = unzipper.list_dir("Images/").unwrap();
+        let names: Vec<&str> = children.iter().map(TreeEntry::name).collect();
+        assert_eq!(names, ["By-sa.png", "qrcode.png", "spacer.gif"]);
+        assert_eq!(children[0].path(), "Images/By-sa.png");
+        assert_eq!(children[0].parent(), "Images");
+        assert_eq!(children[0].depth(), 1);
+        assert!(!children[0].is_dir());
+        assert_eq!(children[0].info().unwrap().size(), 8439);
+
+        let root = unzipper.list_dir("").unwrap();
+        let fonts = root.iter().find(|child| child.name() == "Fonts").unwrap();
+        assert!(fonts.is_dir());
+        assert_eq!(fonts.depth(), 0);
+        assert!(root.iter().any(|child| child.name() == "mimetype"));
+        assert!(!root.iter().any(|child| child.name().contains('/')));
+
+        assert!(matches!(
+            unzipper.list_dir("mimetype"),
+            Err(UnzipperError::NotFound(_))
+        ));
+        assert!(unzipper.list_dir("missing/").is_err());
+
+        // Directories without an entry of their own are listed from the paths of their files
+        let unit_test = UnitTest::new("unzipper_symlinks");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("symlinks.zip");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let root = unzipper.list_dir("").unwrap();
+        let bin = root.iter().find(|child| child.name() == "bin").unwrap();
+        assert!(bin.is_dir());
+        assert!(bin.info().is_none());
+        assert_eq!(unzipper.list_dir("bin").unwrap().len(), 1);
+        assert!(unzipper.list_dir("empty").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tree_walk() {
+        let unit_test = UnitTest::new("unzipper_symlinks");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("symlinks.zip");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        let nodes: Vec<(String, usize, bool)> = unzipper
+            .walk()
+            .map(|node| (node.path().to_string(), node.depth(), node.is_dir()))
+            .collect();
+        assert_eq!(
+            nodes,
+            [
+                ("bin".to_string(), 0, true),
+                ("bin/tool".to_string(), 1, false),
+                ("docs".to_string(), 0, true),
+                ("docs/link.txt".to_string(), 1, false),
+                ("docs/readme.txt".to_string(), 1, false),
+                ("empty".to_string(), 0, true),
+                ("latest".to_string(), 0, false),
+                ("tool".to_string(), 0, false),
+            ]
+        );
+
+        // A directory comes before its content, even when a sibling name sorts between them
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let nodes: Vec<TreeEntry> = unzipper.walk().collect();
+        assert_eq!(nodes.len(), unzipper.entries().count());
+        for (index, node) in nodes.iter().enumerate() {
+            if node.depth() > 0 {
+                let parent = nodes[..index]
+                    .iter()
+                    .rposition(|other| other.depth() < node.depth())
+                    .unwrap();
+                assert_eq!(nodes[parent].path(), node.parent());
+            }
+        }
+    }
+}
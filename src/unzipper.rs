@@ -10,14 +10,33 @@
 use log::debug;
 
 use core::fmt;
-use std::collections::HashMap;
+use std::borrow::Cow;
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
-use std::mem::size_of;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
-
-use miniz_oxide::inflate::stream::{inflate, InflateState};
-use miniz_oxide::{DataFormat, MZFlush};
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError, RwLock};
+use std::time::SystemTime;
+
+use crate::cp437;
+use crate::datetime::Timestamps;
+use crate::decompressor::{Decompressor, DecompressorRegistry};
+use crate::decryption::DecryptionProvider;
+use crate::entry::{EntryInfo, EntryKind, EntryOrder, RawEntry};
+use crate::entry_reader::EntryReader;
+use crate::error::{UnsupportedFeature, UnzipperError};
+use crate::extra::{
+    extra_fields, is_well_formed, timestamps, unicode_path, AesExtra, AES_EXTRA_FIELD_ID,
+    AES_METHOD, UNICODE_PATH_EXTRA_FIELD_ID,
+};
+use crate::file_entries::FileEntries;
+use crate::natural_sort::natural_cmp;
+use crate::options::{DuplicatePolicy, ParseMode, UnzipperOptions};
+use crate::password::PasswordProvider;
+use crate::progress::Progress;
+use crate::read_ahead::Prefetched;
+use crate::secret::Secret;
+use crate::warning::{tolerate, Warning};
 
 // File header:
 
@@ -42,7 +61,6 @@ use miniz_oxide::{DataFormat, MZFlush};
 // file name (variable size)
 // extra field (variable size)
 // file comment (variable size)
-#[repr(packed(1))]
 struct DirFileHeader {
     signature: u32,
     version: u16,
@@ -79,38 +97,500 @@ struct DirFileHeader {
 
 // file name (variable size)
 // extra field (variable size)
-#[repr(packed(1))]
 #[derive(Debug, Clone, Copy)]
-struct FileHeader {
-    signature: u32,
-    extract_version: u16,
-    flags: u16,
-    compression_method: u16,
-    last_mod_time: u16,
-    last_mod_date: u16,
-    crc32: u32,
-    compressed_size: u32,
-    uncompressed_size: u32,
-    file_path_length: u16,
-    extra_field_length: u16,
+pub(crate) struct FileHeader {
+    pub(crate) signature: u32,
+    pub(crate) extract_version: u16,
+    pub(crate) flags: u16,
+    pub(crate) compression_method: u16,
+    pub(crate) last_mod_time: u16,
+    pub(crate) last_mod_date: u16,
+    pub(crate) crc32: u32,
+    pub(crate) compressed_size: u32,
+    pub(crate) uncompressed_size: u32,
+    pub(crate) file_path_length: u16,
+    pub(crate) extra_field_length: u16,
+}
+
+/// Reader of the little-endian fields of a fixed-size record, in sequence.
+struct Fields<'a>(&'a [u8]);
+
+impl Fields<'_> {
+    /// Returns the next u16 field, or 0 past the end of the record.
+    fn u16(&mut self) -> u16 {
+        match self.0.split_first_chunk::<2>() {
+            Some((field, rest)) => {
+                self.0 = rest;
+                u16::from_le_bytes(*field)
+            }
+            None => 0,
+        }
+    }
+
+    /// Returns the next u32 field, or 0 past the end of the record.
+    fn u32(&mut self) -> u32 {
+        match self.0.split_first_chunk::<4>() {
+            Some((field, rest)) => {
+                self.0 = rest;
+                u32::from_le_bytes(*field)
+            }
+            None => 0,
+        }
+    }
+}
+
+impl DirFileHeader {
+    /// Size of the fixed part of the record.
+    const SIZE: usize = 46;
+
+    /// Parses the fixed part of a central directory file header.
+    ///
+    /// # Arguments
+    /// * `bytes` - A byte slice starting with the record.
+    ///
+    /// # Returns
+    /// A Result containing the header, or an error if the byte slice is shorter than the fixed part.
+    fn parse(bytes: &[u8]) -> Result<Self, UnzipperError> {
+        let mut fields = Fields(
+            bytes
+                .get(..Self::SIZE)
+                .ok_or_else(|| truncated_record("central directory file header"))?,
+        );
+        Ok(DirFileHeader {
+            signature: fields.u32(),
+            version: fields.u16(),
+            extract_version: fields.u16(),
+            flags: fields.u16(),
+            compresion_method: fields.u16(),
+            last_mod_time: fields.u16(),
+            last_mod_date: fields.u16(),
+            crc32: fields.u32(),
+            compressed_size: fields.u32(),
+            uncompressed_size: fields.u32(),
+            file_path_length: fields.u16(),
+            extra_field_length: fields.u16(),
+            comment_field_length: fields.u16(),
+            disk_number_start: fields.u16(),
+            internal_file_attr: fields.u16(),
+            external_file_attr: fields.u32(),
+            header_offset: fields.u32(),
+        })
+    }
+}
+
+impl FileHeader {
+    /// Size of the fixed part of the record.
+    pub(crate) const SIZE: usize = 30;
+
+    /// Parses the fixed part of a local file header.
+    ///
+    /// # Arguments
+    /// * `bytes` - A byte slice starting with the record.
+    ///
+    /// # Returns
+    /// A Result containing the header, or an error if the byte slice is shorter than the fixed part.
+    pub(crate) fn parse(bytes: &[u8]) -> Result<Self, UnzipperError> {
+        let mut fields = Fields(
+            bytes
+                .get(..Self::SIZE)
+                .ok_or_else(|| truncated_record("local file header"))?,
+        );
+        Ok(FileHeader {
+            signature: fields.u32(),
+            extract_version: fields.u16(),
+            flags: fields.u16(),
+            compression_method: fields.u16(),
+            last_mod_time: fields.u16(),
+            last_mod_date: fields.u16(),
+            crc32: fields.u32(),
+            compressed_size: fields.u32(),
+            uncompressed_size: fields.u32(),
+            file_path_length: fields.u16(),
+            extra_field_length: fields.u16(),
+        })
+    }
+}
+
+/// Returns the error of a record cut short.
+pub(crate) fn truncated_record(record: &str) -> UnzipperError {
+    UnzipperError::InvalidArchive(format!("Truncated {record}"))
+}
+
+/// Decodes the file name of an entry.
+///
+/// # Arguments
+/// * `name` - The file name bytes, as read from the central directory.
+/// * `extra` - The extra field of the entry, as read from the central directory.
+/// * `flags` - The general purpose bit flag of the entry.
+///
+/// # Returns
+/// The file name of the Info-ZIP Unicode Path extra field when present and up to date. Otherwise, the file
+/// name decoded as UTF-8 when the bit 11 (language encoding flag) is set, or as CP437 otherwise.
+/// Invalid UTF-8 sequences are replaced by U+FFFD, so that the name is always a valid string.
+pub(crate) fn decode_file_name(name: &[u8], extra: &[u8], flags: u16) -> String {
+    if let Some(unicode_name) = extra_fields(extra)
+        .filter(|(id, _)| *id == UNICODE_PATH_EXTRA_FIELD_ID)
+        .find_map(|(_, data)| unicode_path(data, name))
+    {
+        unicode_name
+    } else if flags & 0x0800 != 0 {
+        String::from_utf8_lossy(name).into_owned()
+    } else {
+        cp437::decode(name)
+    }
+}
+
+/// Cleans a file path, as done by `Unzipper::clean_file_path()`.
+///
+/// # Arguments
+/// * `path` - A string slice representing the file path to clean.
+/// * `options` - The options of the Unzipper, telling whether the path is normalized to the Unicode NFC form.
+pub(crate) fn clean_path(path: &str, options: &UnzipperOptions) -> String {
+    let mut parts = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => continue, // skip empty or current dir
+            ".." => {
+                parts.pop();
+            } // go up one directory
+            _ => parts.push(part),
+        }
+    }
+    let cleaned = parts.join("/");
+    #[cfg(feature = "unicode")]
+    let cleaned = if options.normalize_unicode {
+        use unicode_normalization::UnicodeNormalization;
+        cleaned.nfc().collect()
+    } else {
+        cleaned
+    };
+    #[cfg(not(feature = "unicode"))]
+    let _ = options;
+    if path.starts_with('/') {
+        format!("/{}", cleaned)
+    } else {
+        cleaned
+    }
 }
 
-const DIR_FILE_HEADER_SIGNATURE: u32 = 0x02014b50;
-const FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
-const DIR_END_SIGNATURE: u32 = 0x06054b50;
+pub(crate) const DIR_FILE_HEADER_SIGNATURE: u32 = 0x02014b50;
+pub(crate) const FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
+pub(crate) const DIR_END_SIGNATURE: u32 = 0x06054b50;
+pub(crate) const ZIP64_DIR_END_SIGNATURE: u32 = 0x06064b50;
+const ZIP64_DIR_END_LOCATOR_SIGNATURE: u32 = 0x07064b50;
+pub(crate) const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x08074b50;
 
-const BUFFER_SIZE: usize = 1024 * 16;
-const FILE_CENTRAL_SIZE: usize = 22;
+pub(crate) const FILE_CENTRAL_SIZE: usize = 22;
+
+/// Highest version of the zip specification needed to extract that is supported (6.3).
+const SUPPORTED_VERSION: u16 = 63;
+const ZIP64_DIR_END_LOCATOR_SIZE: usize = 20;
+
+const APK_SIGNING_BLOCK_MAGIC: &[u8; 16] = b"APK Sig Block 42";
+const APK_SIGNING_BLOCK_FOOTER_SIZE: usize = 24;
 
 #[derive(Debug, Default, Clone)]
-struct FileEntry {
-    start_pos: u32,       // in zip file
-    compressed_size: u32, // in zip file
-    size: u32,            // once decompressed
-    method: u16,          // compress method (0 = not compressed, 8 = DEFLATE)
+pub(crate) struct FileEntry {
+    pub(crate) start_pos: u32,         // in zip file
+    pub(crate) compressed_size: u32,   // in zip file
+    pub(crate) size: u32,              // once decompressed
+    pub(crate) method: u16,            // compress method (0 = not compressed, 8 = DEFLATE)
+    pub(crate) crc32: u32,             // of the decompressed data
+    pub(crate) flags: u16,             // general purpose bit flag
+    pub(crate) last_mod_time: u16,     // in MS-DOS format
+    pub(crate) last_mod_date: u16,     // in MS-DOS format
+    pub(crate) timestamps: Timestamps, // from the extra fields
+    pub(crate) aes: Option<AesExtra>,  // WinZip AES encryption parameters
+    pub(crate) made_by: u16,           // version made by (upper byte = host system)
+    pub(crate) extract_version: u16,   // version needed to extract (lower byte)
+    pub(crate) external_attr: u32,     // host system dependent file attributes
+    pub(crate) dir_name: bool,         // file name ending with '/'
+}
+
+impl FileEntry {
+    /// Returns the capabilities needed to read the entry that are not available, in the order
+    /// of the version needed, the encryption and the compression method.
+    ///
+    /// # Arguments
+    /// * `decompressors` - The decompressors registered, which support their compression method.
+    /// * `decrypted` - True if a registered decryption provider handles the entry, whatever its encryption.
+    pub(crate) fn unsupported_features(
+        &self,
+        decompressors: &DecompressorRegistry,
+        decrypted: bool,
+    ) -> Vec<UnsupportedFeature> {
+        let mut features = Vec::new();
+        let version = self.extract_version & 0x00ff;
+        if version > SUPPORTED_VERSION {
+            features.push(UnsupportedFeature::Version(version));
+        }
+        // The encryption scheme of an entry handled by a decryption provider is the one of the provider
+        if !decrypted && self.flags & 0x0041 == 0x0041 {
+            features.push(UnsupportedFeature::StrongEncryption);
+        } else if !decrypted && self.aes.is_some() && !cfg!(feature = "crypto") {
+            features.push(UnsupportedFeature::AesEncryption);
+        }
+        if !decompressors.is_supported(self.method) {
+            features.push(UnsupportedFeature::CompressionMethod(self.method));
+        }
+        features
+    }
+
+    /// Returns the differences between the local file header of the entry and its central directory record,
+    /// a sign of tampering or of a faulty writer.
+    ///
+    /// The CRC-32 and the sizes left to 0 in the local file header, for a data descriptor, and the sizes
+    /// stored in the zip64 extra fields are not compared.
+    ///
+    /// # Arguments
+    /// * `file_header` - The local file header of the entry.
+    ///
+    /// # Returns
+    /// The description of each difference, empty if the local file header matches.
+    pub(crate) fn header_mismatches(&self, file_header: &FileHeader) -> Vec<String> {
+        let mut mismatches = Vec::new();
+        // The central directory gives the actual method of AES entries, stored in their extra field
+        let method = if self.aes.is_some() {
+            AES_METHOD
+        } else {
+            self.method
+        };
+        if file_header.compression_method != method {
+            mismatches.push(format!(
+                "Local file header gives the compression method {}, the central directory {method}",
+                file_header.compression_method
+            ));
+        }
+
+        let descriptor = file_header.flags & 0x0008 != 0;
+        let skipped = |local: u32| descriptor && local == 0;
+        if file_header.crc32 != self.crc32 && !skipped(file_header.crc32) {
+            mismatches.push(format!(
+                "Local file header gives the CRC-32 {:08x}, the central directory {:08x}",
+                file_header.crc32, self.crc32
+            ));
+        }
+        let sizes = [
+            (
+                "compressed size",
+                file_header.compressed_size,
+                self.compressed_size,
+            ),
+            ("size", file_header.uncompressed_size, self.size),
+        ];
+        for (name, local, central) in sizes {
+            if local != central && !skipped(local) && local != u32::MAX && central != u32::MAX {
+                mismatches.push(format!(
+                    "Local file header gives the {name} {local}, the central directory {central}"
+                ));
+            }
+        }
+        mismatches
+    }
+
+    /// Returns the Unix mode of the entry (file type and permission bits), if it was made on a Unix system.
+    pub(crate) fn unix_mode(&self) -> Option<u32> {
+        // Host systems 3 (Unix) and 19 (OS X) store the mode in the upper 16 bits of the external attributes
+        let mode = self.external_attr >> 16;
+        match self.made_by >> 8 {
+            3 | 19 if mode != 0 => Some(mode),
+            _ => None,
+        }
+    }
+
+    /// Returns the kind of the entry, from its Unix file type, a trailing '/' in its name
+    /// or its MS-DOS directory attribute.
+    pub(crate) fn kind(&self) -> EntryKind {
+        match self.unix_mode().map(|mode| mode & 0o170000) {
+            Some(0o120000) => EntryKind::Symlink,
+            Some(0o040000) => EntryKind::Directory,
+            _ if self.dir_name || self.external_attr & 0x10 != 0 => EntryKind::Directory,
+            _ => EntryKind::File,
+        }
+    }
+}
+
+// Data descriptor, following the entry data when the bit 3 of the general purpose bit flag is set.
+
+// data descriptor signature       4 bytes  (0x08074b50, optional)
+// crc-32                          4 bytes
+// compressed size                 4 bytes  (8 bytes for zip64)
+// uncompressed size               4 bytes  (8 bytes for zip64)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DataDescriptor {
+    pub(crate) crc32: u32,
+    pub(crate) compressed_size: u64,
+    pub(crate) size: u64,
+}
+
+/// Location of the end of central directory record and of the central directory in the zip file.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DirEnd {
+    pub(crate) offset: usize,         // of the end of central directory record
+    pub(crate) dir_offset: usize,     // of the central directory
+    pub(crate) dir_size: usize,       // of the central directory
+    pub(crate) num_entries: u16,      // in the central directory
+    pub(crate) comment_length: usize, // of the zip archive comment
+}
+
+/// Function reading bytes of the reader at a position, without using or moving its cursor.
+///
+/// It returns the number of bytes read, which may be less than the size of the buffer, 0 meaning that
+/// the position is at or past the end of the reader.
+pub(crate) type PositionalRead<R> = fn(&R, &mut [u8], u64) -> std::io::Result<usize>;
+
+/// Function reopening the zip file at its path, for the Unzippers not keeping it open.
+type Reopen<R> = fn(&Path) -> std::io::Result<R>;
+
+/// Struct that holds the bytes of the zip file last read to serve the small reads, such as the headers.
+#[derive(Default)]
+struct ReadBuffer {
+    data: Vec<u8>,
+    start: usize, // position of the data in the zip file
+}
+
+impl ReadBuffer {
+    /// Returns the bytes at the given position in the zip file, if they are all in the buffer.
+    fn get(&self, position: usize, length: usize) -> Option<&[u8]> {
+        let start = position.checked_sub(self.start)?;
+        self.data.get(start..start.checked_add(length)?)
+    }
+}
+
+/// Size of the chunks of the central directory read at a time, unless a record is longer.
+const DIR_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Struct that holds the part of the central directory being parsed, read by chunks so that a large central
+/// directory is not held in memory at once.
+pub(crate) struct DirRecords<'a> {
+    data: Cow<'a, [u8]>,       // bytes of the central directory from start
+    start: usize,              // offset of the data in the central directory
+    size: usize,               // of the central directory
+    dir_offset: Option<usize>, // in the zip file, None when the whole central directory is in data
+}
+
+impl<'a> DirRecords<'a> {
+    /// Creates the DirRecords of a central directory already in memory.
+    pub(crate) fn from_bytes(bytes: &'a [u8]) -> DirRecords<'a> {
+        DirRecords {
+            data: Cow::Borrowed(bytes),
+            start: 0,
+            size: bytes.len(),
+            dir_offset: None,
+        }
+    }
+
+    /// Creates the DirRecords of a central directory read by chunks from the zip file.
+    fn from_zip(dir_end: &DirEnd) -> DirRecords<'static> {
+        DirRecords {
+            data: Cow::Owned(Vec::new()),
+            start: 0,
+            size: dir_end.dir_size,
+            dir_offset: Some(dir_end.dir_offset),
+        }
+    }
+
+    /// Returns the bytes at an offset in the central directory, reading the next chunk if they are not
+    /// all in memory. The bytes are cut at the end of the central directory.
+    ///
+    /// # Arguments
+    /// * `unzipper` - The Unzipper reading the zip file.
+    /// * `offset` - The offset of the bytes in the central directory.
+    /// * `length` - The number of bytes.
+    fn get<R: Read + Seek>(
+        &mut self,
+        unzipper: &Unzipper<R>,
+        offset: usize,
+        length: usize,
+    ) -> Result<&[u8], UnzipperError> {
+        let end = offset.saturating_add(length).min(self.size);
+        if offset >= end {
+            return Ok(&[]);
+        }
+        if let Some(dir_offset) = self.dir_offset {
+            if offset < self.start || end > self.start + self.data.len() {
+                let chunk_end = end.max(offset.saturating_add(DIR_CHUNK_SIZE).min(self.size));
+                let data = self.data.to_mut();
+                data.resize(chunk_end - offset, 0);
+                unzipper.get_data(data, dir_offset + offset, "central directory")?;
+                self.start = offset;
+            }
+        }
+        Ok(self
+            .data
+            .get(offset - self.start..end - self.start)
+            .unwrap_or_default())
+    }
+
+    /// Searches the central directory for the signature of a record, from an offset in it.
+    ///
+    /// # Returns
+    /// A Result containing the offset of the signature in the central directory, or None if it was not found.
+    fn find_record<R: Read + Seek>(
+        &mut self,
+        unzipper: &Unzipper<R>,
+        mut offset: usize,
+    ) -> Result<Option<usize>, UnzipperError> {
+        let signature = DIR_FILE_HEADER_SIGNATURE.to_le_bytes();
+        loop {
+            let chunk = self.get(unzipper, offset, DIR_CHUNK_SIZE)?;
+            if chunk.len() < signature.len() {
+                return Ok(None);
+            }
+            if let Some(position) = chunk.windows(signature.len()).position(|w| w == signature) {
+                return Ok(Some(offset + position));
+            }
+            // The next chunk overlaps this one, for a signature across them
+            offset += chunk.len() + 1 - signature.len();
+        }
+    }
+}
+
+/// Struct that holds the state of the parsing of the central directory records.
+pub(crate) struct DirParse<'a> {
+    records: DirRecords<'a>,
+    dir_end: DirEnd,
+    offset: usize,             // of the next record in the central directory
+    remaining: u16,            // number of records left to parse
+    file_entries: FileEntries, // parsed so far
+}
+
+/// Struct that holds the central directory parsed on demand, see `UnzipperOptions::lazy_directory()`.
+struct LazyDir {
+    parse: Mutex<Option<DirParse<'static>>>, // taken once all the records are to be parsed
+    file_entries: OnceLock<Arc<FileEntries>>, // once all the records are parsed
 }
 
-type FileEntries = HashMap<String, FileEntry>;
+impl LazyDir {
+    /// Locks the state of the parsing, even if a thread panicked while holding it.
+    fn lock_parse(&self) -> MutexGuard<'_, Option<DirParse<'static>>> {
+        self.parse.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Struct that identifies a version of the zip file, so that its replacement or modification is detected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FileStamp {
+    pub(crate) length: u64,
+    pub(crate) modified: Option<SystemTime>,
+    #[cfg(unix)]
+    inode: (u64, u64), // device and inode numbers, changed when the file is replaced
+}
+
+impl FileStamp {
+    /// Creates the FileStamp of the file with the given metadata.
+    pub(crate) fn new(metadata: &std::fs::Metadata) -> FileStamp {
+        FileStamp {
+            length: metadata.len(),
+            modified: metadata.modified().ok(),
+            #[cfg(unix)]
+            inode: {
+                use std::os::unix::fs::MetadataExt;
+                (metadata.dev(), metadata.ino())
+            },
+        }
+    }
+}
 
 /// Struct that provides functionality to unzip files from a zip archive.
 ///
@@ -118,20 +598,41 @@ type FileEntries = HashMap<String, FileEntry>;
 /// It supports both compressed and uncompressed files, and handles errors related to zip operations.
 /// It can be used to read files from zip archives, such as EPUB files, and extract their contents.
 /// It is designed to be efficient and easy to use, providing methods to open zip files, read file entries, and extract files into memory.
-pub struct Unzipper {
-    filepath: PathBuf, // The path to the zip file
-    file: Option<File>,
-    file_entries: FileEntries,
-    current_file_entry: Option<FileEntry>,
-    current_file_header: Option<FileHeader>,
+///
+/// The archive is read from a `std::fs::File` by default, but any source implementing `Read + Seek` can be used
+/// through `Unzipper::from_reader()`.
+///
+/// The Unzipper is `Send + Sync` when its source is, as a `File` is, so that it can be shared between threads
+/// in an `Arc`. The entries are read through a shared reference, the files being read with positional reads,
+/// while the other sources are locked for the time of each read.
+pub struct Unzipper<R: Read + Seek = File> {
+    pub(crate) filepath: PathBuf, // The path to the zip file, empty when reading from another source
+    pub(crate) reader: Option<RwLock<R>>, // shared by the positional reads, locked by the others
+    pub(crate) file_entries: Arc<FileEntries>, // shared with the clones of the Unzipper
+    pub(crate) comment: Vec<u8>,  // of the zip archive
+    pub(crate) options: UnzipperOptions,
+    progress: Option<Mutex<Box<dyn Progress + Send>>>, // locked, so that the Unzipper is Sync
+    password_provider: Option<Mutex<Box<dyn PasswordProvider + Send>>>,
+    max_password_attempts: u32, // asked to the password provider for each entry
+    pub(crate) total_decompressed: Arc<AtomicU64>, // by all the entry readers, for the limits of the options
+    warnings: Mutex<Vec<Warning>>, // inconsistencies tolerated by the parsing mode of the options
+    pub(crate) base_offset: usize, // of the zip archive in the file, after any prepended data
+    pub(crate) apk_signing_block: Option<(usize, usize)>, // position and size, for signed Android packages
+    pub(crate) disk_offsets: Vec<usize>, // of the volumes of a split zip archive, empty otherwise
+    prefetched: Mutex<Option<Prefetched>>, // entry data read ahead during an extraction
+    read_buffer: Mutex<ReadBuffer>,      // bytes of the zip file serving the small reads
+    pub(crate) positional: Option<PositionalRead<R>>, // for the readers supporting positional reads
+    reopen: Option<Reopen<R>>,           // when the zip file is not kept open between its reads
+    lazy_dir: Option<LazyDir>, // central directory parsed on demand, instead of the file entries
+    pub(crate) stamp: Option<FileStamp>, // of the zip file opened from a path, to detect its replacement
 }
 
 /// Implements the Debug trait for Unzipper to provide a formatted output of its state.
-impl fmt::Debug for Unzipper {
+impl<R: Read + Seek> fmt::Debug for Unzipper<R> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // As HashMap is not ordered, we need to sort the entries for comparison in testings
-        let mut v: Vec<_> = self.file_entries.iter().collect();
-        v.sort_by(|x, y| x.0.cmp(&y.0));
+        // The entries are sorted by name, so that the output does not depend on the archive layout
+        let mut v: Vec<_> = self.parsed_entries().iter().collect();
+        v.sort_by(|x, y| x.0.cmp(y.0));
 
         f.debug_struct("Specificity")
             .field(
@@ -139,13 +640,11 @@ impl fmt::Debug for Unzipper {
                 &self.filepath.file_name().unwrap_or("not found".as_ref()),
             )
             .field("file_entries", &v)
-            .field("current_file_entry", &self.current_file_entry)
-            .field("current_file_header", &self.current_file_header)
             .finish()
     }
 }
 
-impl Unzipper {
+impl Unzipper<File> {
     /// Creates a new Unzipper instance for the specified file path.
     ///
     /// # Arguments
@@ -153,18 +652,237 @@ impl Unzipper {
     ///
     /// # Returns
     /// A Result containing the Unzipper instance if successful, or an error if the file could not be opened.
-    pub fn new(filepath: &Path) -> Result<Unzipper, std::io::Error> {
-        let mut unzipper = Unzipper {
-            filepath: filepath.to_path_buf(),
-            file: None,
-            file_entries: FileEntries::new(),
-            current_file_entry: None,
-            current_file_header: None,
-        };
+    pub fn new(filepath: &Path) -> Result<Unzipper<File>, UnzipperError> {
+        Unzipper::with_options(filepath, UnzipperOptions::default())
+    }
+
+    /// Creates a new Unzipper instance for the specified file path, using the given options.
+    ///
+    /// # Arguments
+    /// * `filepath` - A reference to the path of the zip file to be opened.
+    /// * `options` - The options controlling how the zip file is read.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper instance if successful, or an error if the file could not be opened.
+    pub fn with_options(
+        filepath: &Path,
+        options: UnzipperOptions,
+    ) -> Result<Unzipper<File>, UnzipperError> {
+        let mut unzipper = Unzipper::empty(options);
         unzipper.open(filepath)?;
         Ok(unzipper)
     }
 
+    /// Opens a zip file at the specified path and reads its central directory.
+    ///
+    /// # Arguments
+    /// * `path` - A reference to the path of the zip file to be opened.
+    ///
+    /// # Returns
+    /// A Result indicating success or an error if the file could not be opened or if the zip file is invalid.
+    pub fn open(&mut self, path: &Path) -> Result<(), UnzipperError> {
+        self.attach_file(path)?;
+        self.read_central_directory()?;
+        self.close_if_lazy();
+        Ok(())
+    }
+
+    /// Opens the zip file at the specified path as the reader of the Unzipper, recording its stamp.
+    pub(crate) fn attach_file(&mut self, path: &Path) -> Result<(), UnzipperError> {
+        self.filepath = path.to_path_buf();
+        let file = File::open(path.canonicalize()?)?;
+        self.stamp = Some(FileStamp::new(&file.metadata()?));
+        self.reader = Some(RwLock::new(file));
+        self.positional = Unzipper::file_positional();
+        Ok(())
+    }
+
+    /// Closes the file handle once the zip file is read, when it is not kept open between its reads.
+    pub(crate) fn close_if_lazy(&mut self) {
+        // The zip file is then reopened for each of its reads
+        if self.options.lazy_open {
+            self.reader = None;
+            self.reopen = Some(|path| File::open(path));
+        }
+    }
+
+    /// Reads the zip file again if it was replaced or modified since it was opened, such as by a deployment
+    /// while a long-lived service keeps it open.
+    ///
+    /// The replacement is detected from the size and the modification time of the zip file, and from its inode
+    /// on Unix. The zip file is then reopened at its path and its central directory read again, so that the
+    /// next reads see the new content. The Unzippers cloned before keep reading the previous content.
+    ///
+    /// # Returns
+    /// A Result containing true if the zip file was read again, false if it is unchanged, or an error if
+    /// the Unzipper was not opened from a path or if the new zip file is invalid.
+    pub fn refresh(&mut self) -> Result<bool, UnzipperError> {
+        if self.filepath.as_os_str().is_empty() {
+            return Err(UnzipperError::NotOpen);
+        }
+        let stamp = FileStamp::new(&std::fs::metadata(&self.filepath)?);
+        if self.stamp.as_ref() == Some(&stamp) {
+            return Ok(false);
+        }
+        let filepath = self.filepath.clone();
+        self.open(&filepath)?;
+        Ok(true)
+    }
+
+    /// Returns the positional reads of the files, where the system provides them (`pread` on Unix,
+    /// `ReadFile` at an offset on Windows), so that the cursor of the file is not used.
+    pub(crate) fn file_positional() -> Option<PositionalRead<File>> {
+        #[cfg(any(unix, windows))]
+        return Some(|file, buf, offset| crate::read_at::ReadAt::read_at(file, offset, buf));
+        #[cfg(not(any(unix, windows)))]
+        None
+    }
+
+    /// Creates a new Unzipper reading an already parsed zip file through its own file handle.
+    ///
+    /// # Arguments
+    /// * `filepath` - A reference to the path of the zip file.
+    /// * `file_entries` - The file entries already read from the central directory of the zip file.
+    /// * `options` - The options controlling how the zip file is read.
+    /// * `total_decompressed` - The counter of the bytes decompressed, shared with the Unzippers of the zip file.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper, without progress receiver, or an error if the zip file could not be opened.
+    pub(crate) fn open_shared(
+        filepath: &Path,
+        file_entries: &Arc<FileEntries>,
+        options: &UnzipperOptions,
+        total_decompressed: &Arc<AtomicU64>,
+    ) -> Result<Unzipper<File>, UnzipperError> {
+        let mut unzipper = Unzipper::empty(options.clone());
+        unzipper.total_decompressed = total_decompressed.clone();
+        unzipper.filepath = filepath.to_path_buf();
+        unzipper.reader = Some(RwLock::new(File::open(filepath.canonicalize()?)?));
+        unzipper.positional = Unzipper::file_positional();
+        unzipper.file_entries = file_entries.clone();
+        Ok(unzipper)
+    }
+
+    /// Creates a new Unzipper reading the same zip file through a duplicate of its file handle, sharing
+    /// the file entries already read, so that each thread or task reads the zip file with its own handle
+    /// without parsing the central directory again.
+    ///
+    /// The duplicate handle shares the cursor of the original one at the system level, which does not matter
+    /// on Unix and Windows, where the files are read with positional reads. An Unzipper not keeping its zip file
+    /// open, see `UnzipperOptions::lazy_open()`, is cloned without file handle. The options, the warnings and the
+    /// counter of the bytes decompressed are carried over, while the progress receiver and the password
+    /// provider are not.
+    ///
+    /// # Returns
+    /// A Result containing the new Unzipper, or an error if the file handle could not be duplicated.
+    pub fn try_clone(&self) -> Result<Unzipper<File>, UnzipperError> {
+        let reader = match self.reader {
+            Some(ref file) => {
+                let file = file.read().unwrap_or_else(PoisonError::into_inner);
+                Some(RwLock::new(file.try_clone()?))
+            }
+            None if self.reopen.is_some() => None,
+            None => return Err(UnzipperError::NotOpen),
+        };
+        let mut unzipper = Unzipper::empty(self.options.clone());
+        unzipper.filepath = self.filepath.clone();
+        unzipper.reader = reader;
+        unzipper.positional = self.positional;
+        unzipper.reopen = self.reopen;
+        unzipper.stamp = self.stamp.clone();
+        unzipper.file_entries = self.parsed_entries().clone();
+        unzipper.comment = self.comment.clone();
+        unzipper.max_password_attempts = self.max_password_attempts;
+        unzipper.total_decompressed = self.total_decompressed.clone();
+        unzipper.warnings = Mutex::new(self.warnings());
+        unzipper.base_offset = self.base_offset;
+        unzipper.apk_signing_block = self.apk_signing_block;
+        unzipper.disk_offsets = self.disk_offsets.clone();
+        Ok(unzipper)
+    }
+}
+
+impl<'a> Unzipper<Cursor<&'a [u8]>> {
+    /// Creates a new Unzipper instance reading the zip archive from an in-memory byte slice.
+    ///
+    /// # Arguments
+    /// * `bytes` - A byte slice containing the whole zip archive. It is borrowed, not copied.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper instance if successful, or an error if the zip archive is invalid.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Unzipper<Cursor<&'a [u8]>>, UnzipperError> {
+        Unzipper::from_reader(Cursor::new(bytes))
+    }
+}
+
+impl Unzipper<Cursor<Vec<u8>>> {
+    /// Creates a new Unzipper instance reading the zip archive from an in-memory bytes vector.
+    ///
+    /// # Arguments
+    /// * `bytes` - A bytes vector containing the whole zip archive. The Unzipper takes ownership of it.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper instance if successful, or an error if the zip archive is invalid.
+    pub fn from_vec(bytes: Vec<u8>) -> Result<Unzipper<Cursor<Vec<u8>>>, UnzipperError> {
+        Unzipper::from_reader(Cursor::new(bytes))
+    }
+}
+
+impl<R: Read + Seek> Unzipper<R> {
+    /// Creates a new Unzipper instance reading the zip archive from the given source.
+    ///
+    /// # Arguments
+    /// * `reader` - The source of the zip archive, implementing `Read + Seek`.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper instance if successful, or an error if the zip archive is invalid.
+    pub fn from_reader(reader: R) -> Result<Unzipper<R>, UnzipperError> {
+        Unzipper::from_reader_with_options(reader, UnzipperOptions::default())
+    }
+
+    /// Creates a new Unzipper instance reading the zip archive from the given source, using the given options.
+    ///
+    /// # Arguments
+    /// * `reader` - The source of the zip archive, implementing `Read + Seek`.
+    /// * `options` - The options controlling how the zip archive is read.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper instance if successful, or an error if the zip archive is invalid.
+    pub fn from_reader_with_options(
+        reader: R,
+        options: UnzipperOptions,
+    ) -> Result<Unzipper<R>, UnzipperError> {
+        let mut unzipper = Unzipper::empty(options);
+        unzipper.reader = Some(RwLock::new(reader));
+        unzipper.read_central_directory()?;
+        Ok(unzipper)
+    }
+
+    /// Creates a new Unzipper instance with no zip archive attached yet.
+    pub(crate) fn empty(options: UnzipperOptions) -> Unzipper<R> {
+        Unzipper {
+            filepath: PathBuf::new(),
+            reader: None,
+            file_entries: Arc::default(),
+            comment: Vec::new(),
+            options,
+            progress: None,
+            password_provider: None,
+            max_password_attempts: 0,
+            total_decompressed: Arc::new(AtomicU64::new(0)),
+            warnings: Mutex::default(),
+            base_offset: 0,
+            apk_signing_block: None,
+            disk_offsets: Vec::new(),
+            prefetched: Mutex::default(),
+            read_buffer: Mutex::default(),
+            positional: None,
+            reopen: None,
+            lazy_dir: None,
+            stamp: None,
+        }
+    }
+
     /// Returns the u32 value from the given byte slice.
     ///
     /// # Arguments
@@ -173,7 +891,7 @@ impl Unzipper {
     /// # Returns
     /// The u32 value extracted from the byte slice, or a default value of 0 if the slice is not exactly 4 bytes long.
     #[inline]
-    fn get_u32(&self, bytes: &[u8]) -> u32 {
+    pub(crate) fn get_u32(&self, bytes: &[u8]) -> u32 {
         let bytes: &[u8; 4] = bytes.try_into().unwrap_or(&[0; 4]);
         u32::from_le_bytes(*bytes)
     }
@@ -186,9 +904,9 @@ impl Unzipper {
     /// # Returns
     /// The u16 value extracted from the byte slice, or a default value of 0 if the slice is not exactly 2 bytes long.
     #[inline]
-    fn get_u16(&self, bytes: &[u8]) -> u16 {
+    pub(crate) fn get_u16(&self, bytes: &[u8]) -> u16 {
         let bb: &[u8; 2] = bytes.try_into().unwrap_or(&[0; 2]);
-        return u16::from_le_bytes(*bb);
+        u16::from_le_bytes(*bb)
     }
 
     /// Cleans the file path by removing unnecessary parts like empty segments, current directory indicators (.), and parent directory indicators (..).
@@ -197,24 +915,10 @@ impl Unzipper {
     /// * `path` - A string slice representing the file path to be cleaned.
     ///
     /// # Returns
-    /// A cleaned string representing the file path, with unnecessary segments removed.
+    /// A cleaned string representing the file path, with unnecessary segments removed. It is normalized
+    /// to the Unicode NFC form when enabled in the options.
     pub fn clean_file_path(&self, path: &str) -> String {
-        let mut parts = Vec::new();
-        for part in path.split('/') {
-            match part {
-                "" | "." => continue, // skip empty or current dir
-                ".." => {
-                    parts.pop();
-                } // go up one directory
-                _ => parts.push(part),
-            }
-        }
-        let cleaned = parts.join("/");
-        if path.starts_with('/') {
-            format!("/{}", cleaned)
-        } else {
-            cleaned
-        }
+        clean_path(path, &self.options)
     }
 
     /// Reads data from the zip file at the specified position into the provided buffer.
@@ -226,205 +930,694 @@ impl Unzipper {
     ///
     /// # Returns
     /// A Result indicating success or an error if the read operation fails.
-    fn get_data(
-        &mut self,
+    pub(crate) fn get_data(
+        &self,
         buffer: &mut [u8],
         position: usize,
         msg: &str,
-    ) -> Result<(), std::io::Error> {
-        if let Some(ref mut file) = self.file {
-            if file.seek(SeekFrom::Start(position as u64))? != position as u64 {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    format!("Unable to seek to {msg}"),
-                ));
+    ) -> Result<(), UnzipperError> {
+        if self.options.read_ahead {
+            let prefetched = self.lock_prefetched();
+            if let Some(data) = prefetched
+                .as_ref()
+                .and_then(|prefetched| prefetched.get(position, buffer.len()))
+            {
+                buffer.copy_from_slice(data);
+                return Ok(());
             }
-            let length = buffer.len();
-            file.read_exact(&mut buffer[..length])
-        } else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "File not open",
-            ));
         }
+        let length = buffer.len();
+        if length < self.options.buffer_size {
+            let mut read_buffer = self
+                .read_buffer
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if read_buffer.get(position, length).is_none() {
+                self.fill_read_buffer(&mut read_buffer, position, length, msg)?;
+            }
+            return match read_buffer.get(position, length) {
+                Some(data) => {
+                    buffer.copy_from_slice(data);
+                    Ok(())
+                }
+                None => Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+            };
+        }
+        if self.read_at(buffer, position, length, msg)? < length {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(())
     }
 
-    /// Opens a zip file at the specified path and reads its central directory.
+    /// Reads bytes of the zip file at the given position, with a positional read when the reader supports it,
+    /// or by seeking its cursor otherwise.
     ///
     /// # Arguments
-    /// * `path` - A reference to the path of the zip file to be opened.
+    /// * `buffer` - The buffer receiving the bytes.
+    /// * `position` - The position of the first byte to read in the zip file.
+    /// * `min` - The number of bytes to read at least, unless the end of the zip file is reached first.
+    /// * `msg` - The description of the part read, for the error messages.
     ///
     /// # Returns
-    /// A Result indicating success or an error if the file could not be opened or if the zip file is invalid.
-    pub fn open(&mut self, path: &Path) -> Result<(), std::io::Error> {
-        self.file = Some(File::open(path.canonicalize()?)?);
-
-        if let Some(ref mut file) = self.file {
-            // Seek to beginning of central directory
-            //
-            // We seek the file back until we reach the "End Of Central Directory"
-            // signature "PK\5\6". (ecd_offset)
-            //
-            // end of central dir signature    4 bytes  (0x06054b50)
-            // number of this disk             2 bytes   4
-            // number of the disk with the
-            // start of the central directory  2 bytes   6
-            // total number of entries in the
-            // central directory on this disk  2 bytes   8
-            // total number of entries in
-            // the central directory           2 bytes  10
-            // size of the central directory   4 bytes  12
-            // offset of start of central
-            // directory with respect to
-            // the starting disk number        4 bytes  16
-            // .ZIP file comment length        2 bytes  20
-            // --- SIZE UNTIL HERE: UNZIP_EOCD_SIZE ---
-            // .ZIP file comment       (variable size)
-
-            let length = file.seek(SeekFrom::End(0))? as usize;
-
-            // Get the length of the file in bytes and check if it is large enough
-            // to be a valid zip file
-            if length < FILE_CENTRAL_SIZE {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "File is too small to be a zip file",
-                ));
+    /// A Result containing the number of bytes read, up to the size of the buffer.
+    fn read_at(
+        &self,
+        buffer: &mut [u8],
+        position: usize,
+        min: usize,
+        msg: &str,
+    ) -> Result<usize, UnzipperError> {
+        // The positional reads share the reader, while the others lock it to move its cursor
+        self.with_reader(|reader| match self.positional {
+            Some(read_at) => {
+                let reader = reader.read().unwrap_or_else(PoisonError::into_inner);
+                Self::read_min(buffer, min, |buf, filled| {
+                    read_at(&reader, buf, (position + filled) as u64)
+                })
             }
-            let mut ecd_offset = length - FILE_CENTRAL_SIZE;
-
-            let mut buff = [0; FILE_CENTRAL_SIZE + 5];
-            self.get_data(
-                &mut buff[..FILE_CENTRAL_SIZE],
-                ecd_offset,
-                "end of central directory",
-            )?;
-
-            // Check if the end of central directory signature is present
-
-            if self.get_u32(&buff[..4]) != DIR_END_SIGNATURE {
-                // There must be a comment in the last entry. Search for the beginning of that entry
-                // going backwards in the file.
-                // We will search backwards in 64kB blocks until we find the signature
-                // "PK\5\6" or we reach the beginning of the file.
-
-                let end_offset = if ecd_offset > 65536 {
-                    ecd_offset - 65536
-                } else {
-                    0
-                };
-
-                ecd_offset = if ecd_offset >= FILE_CENTRAL_SIZE {
-                    ecd_offset - FILE_CENTRAL_SIZE
-                } else {
-                    0
-                };
-
-                let mut found = false;
-                while !found && (ecd_offset > end_offset) {
-                    self.get_data(&mut buff, ecd_offset, "end of central directory")?;
-
-                    // Check if the end of central directory signature is present
-                    if self.get_u32(&buff[0..4]) == DIR_END_SIGNATURE {
-                        found = true;
-                        break;
-                    }
-
-                    if let Some(p) = buff.windows(4).position(|w| w == b"PK\x05\x06") {
-                        ecd_offset += p;
-                        self.get_data(
-                            &mut buff[..FILE_CENTRAL_SIZE],
-                            ecd_offset,
-                            "end of central directory",
-                        )?;
-
-                        found = true;
-                        break;
-                    }
-                    ecd_offset -= FILE_CENTRAL_SIZE;
-                }
-
-                if !found {
-                    ecd_offset = 0;
+            None => {
+                let mut reader = reader.write().unwrap_or_else(PoisonError::into_inner);
+                if reader.seek(SeekFrom::Start(position as u64))? != position as u64 {
+                    return Err(UnzipperError::InvalidArchive(format!(
+                        "Unable to seek to {msg}"
+                    )));
                 }
+                Self::read_min(buffer, min, |buf, _| reader.read(buf))
             }
+        })
+    }
 
-            if (ecd_offset == 0) || (self.get_u32(&buff[0..4]) != DIR_END_SIGNATURE) {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Unable to find end of central directory",
-                ));
-            }
-
-            let start_offset = self.get_u32(&buff[16..20]) as usize;
-            let mut num_entries = self.get_u16(&buff[10..12]);
-            let entries_total_size = ecd_offset - start_offset;
-            let mut entries = vec![0; entries_total_size];
-
-            self.get_data(&mut entries, start_offset, "central directory")?;
+    /// Returns the length of the zip file, moving the cursor of its reader to its end.
+    pub(crate) fn zip_length(&self) -> Result<usize, UnzipperError> {
+        self.with_reader(|reader| {
+            let mut reader = reader.write().unwrap_or_else(PoisonError::into_inner);
+            Ok(reader.seek(SeekFrom::End(0))? as usize)
+        })
+    }
 
-            // Check if the central directory signature is present
-            if self.get_u32(&entries[0..4]) != DIR_FILE_HEADER_SIGNATURE {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Unable to find central directory",
-                ));
+    /// Runs an operation with the reader of the zip file, reopening the zip file when it is not kept open.
+    ///
+    /// # Arguments
+    /// * `operation` - The operation, given the reader.
+    ///
+    /// # Returns
+    /// A Result containing the result of the operation, or an error if the zip file is not open and could
+    /// not be reopened.
+    fn with_reader<T>(
+        &self,
+        operation: impl FnOnce(&RwLock<R>) -> Result<T, UnzipperError>,
+    ) -> Result<T, UnzipperError> {
+        match (&self.reader, self.reopen) {
+            (Some(reader), _) => operation(reader),
+            (None, Some(reopen)) => operation(&RwLock::new(reopen(&self.filepath)?)),
+            (None, None) => Err(UnzipperError::NotOpen),
+        }
+    }
+
+    /// Fills a buffer with successive reads, until `min` bytes are read or the end of the zip file is reached.
+    ///
+    /// # Arguments
+    /// * `buffer` - The buffer receiving the bytes.
+    /// * `min` - The number of bytes to read at least.
+    /// * `read` - The function reading into the rest of the buffer, given the number of bytes already read.
+    ///
+    /// # Returns
+    /// A Result containing the number of bytes read, up to the size of the buffer.
+    fn read_min(
+        buffer: &mut [u8],
+        min: usize,
+        mut read: impl FnMut(&mut [u8], usize) -> std::io::Result<usize>,
+    ) -> Result<usize, UnzipperError> {
+        let mut filled = 0;
+        while filled < min {
+            match read(&mut buffer[filled..], filled) {
+                Ok(0) => break,
+                Ok(count) => filled += count,
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error.into()),
             }
+        }
+        Ok(filled)
+    }
 
-            let mut file_entry_offset: usize = 0;
+    /// Fills the read buffer with the bytes of the zip file from the given position, reading at least
+    /// `length` bytes unless the end of the zip file is reached first.
+    fn fill_read_buffer(
+        &self,
+        read_buffer: &mut ReadBuffer,
+        position: usize,
+        length: usize,
+        msg: &str,
+    ) -> Result<(), UnzipperError> {
+        read_buffer.data.resize(self.options.buffer_size, 0);
+        let result = self.read_at(&mut read_buffer.data, position, length, msg);
+        read_buffer.data.truncate(*result.as_ref().unwrap_or(&0));
+        read_buffer.start = position;
+        result.map(|_| ())
+    }
 
-            while num_entries > 0 {
-                let dir_file_header =
-                    unsafe { &*(entries.as_ptr().add(file_entry_offset) as *const DirFileHeader) };
+    /// Reads the central directory of the zip archive, building the list of file entries.
+    ///
+    /// With the recovery mode of the options, the file entries are rebuilt from the local file headers
+    /// when the central directory cannot be read.
+    ///
+    /// # Returns
+    /// A Result indicating success or an error if the zip archive is invalid.
+    pub(crate) fn read_central_directory(&mut self) -> Result<(), UnzipperError> {
+        self.read_buffer = Mutex::default();
+        self.file_entries = Arc::default();
+        self.lazy_dir = None;
+        self.warnings_mut().clear();
+
+        match self.read_file_entries() {
+            Err(error) if self.options.recovery => {
+                self.file_entries = Arc::new(FileEntries::with_options(&self.options));
+                self.comment.clear();
+                self.warnings_mut().clear();
+                self.record_warnings(vec![Warning::new(
+                    None,
+                    &format!("Entries recovered from the local file headers ({error})"),
+                )]);
+                self.recover_file_entries()
+            }
+            result => result,
+        }
+    }
 
-                if dir_file_header.signature != DIR_FILE_HEADER_SIGNATURE {
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        "Invalid central directory signature",
-                    ));
-                }
+    /// Reads the file entries from the central directory, and the zip archive comment.
+    ///
+    /// # Returns
+    /// A Result indicating success or an error if the central directory could not be read.
+    fn read_file_entries(&mut self) -> Result<(), UnzipperError> {
+        let mut warnings = Vec::new();
+        let dir_end = self.find_dir_end(&mut warnings)?;
+        self.record_warnings(warnings);
+        let records = DirRecords::from_zip(&dir_end);
+        if self.options.lazy_directory
+            && !self.options.recovery
+            && self.options.duplicates == DuplicatePolicy::FirstWins
+        {
+            // The records are parsed when the entries are looked up
+            let parse = self.start_dir_parse(records, &dir_end)?;
+            self.lazy_dir = Some(LazyDir {
+                parse: Mutex::new(Some(parse)),
+                file_entries: OnceLock::new(),
+            });
+        } else {
+            let mut warnings = Vec::new();
+            let file_entries = self.parse_dir_records(records, &dir_end, &mut warnings)?;
+            self.file_entries = Arc::new(file_entries);
+            self.record_warnings(warnings);
+        }
+        self.apk_signing_block = self.find_apk_signing_block(dir_end.dir_offset)?;
+        self.read_comment(&dir_end)
+    }
 
-                let f_name = unsafe {
-                    let start = file_entry_offset + std::mem::size_of::<DirFileHeader>();
-                    let end = start + dir_file_header.file_path_length as usize;
-                    std::str::from_utf8_unchecked(&entries[start..end])
-                };
-                let file_path = self.clean_file_path(f_name);
+    /// Locates the APK Signing Block, inserted by Android between the last entry data and the central directory.
+    ///
+    /// # Arguments
+    /// * `dir_offset` - The position of the central directory in the zip file.
+    ///
+    /// # Returns
+    /// A Result containing the position and size of the block, or None if the zip archive has no such block.
+    pub(crate) fn find_apk_signing_block(
+        &mut self,
+        dir_offset: usize,
+    ) -> Result<Option<(usize, usize)>, UnzipperError> {
+        // size of block                   8 bytes  (excluding this field)
+        // ID-value pairs          (variable size)
+        // size of block                   8 bytes   0 (from the footer)
+        // magic "APK Sig Block 42"       16 bytes   8
+        let footer_offset = match dir_offset.checked_sub(APK_SIGNING_BLOCK_FOOTER_SIZE) {
+            Some(footer_offset) if footer_offset >= self.base_offset => footer_offset,
+            _ => return Ok(None),
+        };
+        let mut footer = [0u8; APK_SIGNING_BLOCK_FOOTER_SIZE];
+        self.get_data(&mut footer, footer_offset, "APK signing block")?;
+        if &footer[8..] != APK_SIGNING_BLOCK_MAGIC {
+            return Ok(None);
+        }
 
-                let file_entry = FileEntry {
-                    start_pos: dir_file_header.header_offset,
-                    compressed_size: dir_file_header.compressed_size,
-                    size: dir_file_header.uncompressed_size,
-                    method: dir_file_header.compresion_method,
-                };
+        let block_size = u64::from_le_bytes(footer[..8].try_into().unwrap_or([0; 8])) as usize;
+        match dir_offset.checked_sub(block_size.saturating_add(8)) {
+            Some(block_offset) if block_offset >= self.base_offset => {
+                Ok(Some((block_offset, block_size + 8)))
+            }
+            _ => Err(UnzipperError::InvalidArchive(
+                "APK signing block extends beyond the start of the file".to_string(),
+            )),
+        }
+    }
+
+    /// Reads the zip archive comment, following the end of central directory record.
+    ///
+    /// # Arguments
+    /// * `dir_end` - The location of the end of central directory record.
+    ///
+    /// # Returns
+    /// A Result indicating success or an error if the comment could not be read.
+    pub(crate) fn read_comment(&mut self, dir_end: &DirEnd) -> Result<(), UnzipperError> {
+        let mut comment = vec![0; dir_end.comment_length];
+        self.get_data(
+            &mut comment,
+            dir_end.offset + FILE_CENTRAL_SIZE,
+            "zip archive comment",
+        )?;
+        self.comment = comment;
+        Ok(())
+    }
 
-                self.file_entries.insert(file_path, file_entry);
+    /// Locates the end of central directory record of the zip archive.
+    ///
+    /// # Arguments
+    /// * `warnings` - Receives the inconsistencies tolerated by the parsing mode of the options.
+    ///
+    /// # Returns
+    /// A Result containing the location of the end of central directory record and of the central directory,
+    /// or an error if the record could not be found.
+    pub(crate) fn find_dir_end(
+        &mut self,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<DirEnd, UnzipperError> {
+        // end of central dir signature    4 bytes  (0x06054b50)
+        // number of this disk             2 bytes   4
+        // number of the disk with the
+        // start of the central directory  2 bytes   6
+        // total number of entries in the
+        // central directory on this disk  2 bytes   8
+        // total number of entries in
+        // the central directory           2 bytes  10
+        // size of the central directory   4 bytes  12
+        // offset of start of central
+        // directory with respect to
+        // the starting disk number        4 bytes  16
+        // .ZIP file comment length        2 bytes  20
+        // --- SIZE UNTIL HERE: UNZIP_EOCD_SIZE ---
+        // .ZIP file comment       (variable size)
+
+        let length = self.zip_length()?;
+
+        // Get the length of the file in bytes and check if it is large enough
+        // to be a valid zip file
+        if length < FILE_CENTRAL_SIZE {
+            return Err(UnzipperError::InvalidArchive(
+                "File is too small to be a zip file".to_string(),
+            ));
+        }
 
-                file_entry_offset += std::mem::size_of::<DirFileHeader>()
-                    + dir_file_header.file_path_length as usize
-                    + dir_file_header.extra_field_length as usize
-                    + dir_file_header.comment_field_length as usize;
+        // The record is followed by the comment, at most 65535 bytes long, so that it is within
+        // the tail of the file
+        let tail_offset = length.saturating_sub(FILE_CENTRAL_SIZE + u16::MAX as usize);
+        let mut tail = vec![0; length - tail_offset];
+        self.get_data(&mut tail, tail_offset, "end of central directory")?;
+        let ecd_position = match Self::locate_dir_end(&tail) {
+            Some(ecd_position) => ecd_position,
+            None => return Err(UnzipperError::BadSignature("end of central directory")),
+        };
+        let ecd_offset = tail_offset + ecd_position;
+        let buff = &tail[ecd_position..ecd_position + FILE_CENTRAL_SIZE];
+
+        // The comment length is bounded by the end of the file, as some writers truncate the comment
+        // The offset of the central directory is relative to the volume holding its start
+        let disk_number = self.get_u16(&buff[4..6]) as usize;
+        if self.disk_offsets.is_empty() && disk_number != 0 {
+            return Err(UnzipperError::InvalidArchive(format!(
+                "Zip archive is split into {} volumes, to be opened with open_split()",
+                disk_number + 1
+            )));
+        }
+        let start_offset =
+            self.disk_offset(self.get_u16(&buff[6..8]))? + self.get_u32(&buff[16..20]) as usize;
+        let comment_length = self.get_u16(&buff[20..22]) as usize;
+        let available_length = length - ecd_offset - FILE_CENTRAL_SIZE;
+        if comment_length > available_length {
+            tolerate(
+                self.options.parse_mode,
+                warnings,
+                Warning::new(None, "Archive comment extends beyond the end of the file"),
+                ParseMode::Standard,
+            )?;
+        }
 
-                num_entries -= 1;
+        // The central directory ends where the record starts. When it starts after its recorded offset,
+        // data was prepended to the zip archive (self-extracting archives, installer stubs), and every
+        // recorded offset is relative to the start of the zip archive instead of the start of the file
+        let dir_end_offset = match Self::locate_zip64_dir_end(&tail[..ecd_position]) {
+            Some(zip64_position) => tail_offset + zip64_position,
+            None => ecd_offset,
+        };
+        let recorded_size = self.get_u32(&buff[12..16]) as usize;
+        let (dir_offset, dir_size) = match dir_end_offset.checked_sub(recorded_size) {
+            Some(dir_offset) if dir_offset >= start_offset => {
+                self.base_offset = dir_offset - start_offset;
+                (dir_offset, recorded_size)
             }
-            Ok(())
-        } else {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Zip file not open",
-            ));
+            _ => {
+                let dir_size = dir_end_offset.checked_sub(start_offset).ok_or_else(|| {
+                    UnzipperError::InvalidArchive(
+                        "Central directory offset is beyond the end of central directory record"
+                            .to_string(),
+                    )
+                })?;
+                tolerate(
+                    self.options.parse_mode,
+                    warnings,
+                    Warning::new(None, "Central directory size does not match its location"),
+                    ParseMode::Standard,
+                )?;
+                self.base_offset = 0;
+                (start_offset, dir_size)
+            }
+        };
+        Ok(DirEnd {
+            offset: ecd_offset,
+            dir_offset,
+            dir_size,
+            num_entries: self.get_u16(&buff[10..12]),
+            comment_length: comment_length.min(available_length),
+        })
+    }
+
+    /// Returns the position of the start of a volume of a split zip archive.
+    ///
+    /// # Arguments
+    /// * `disk_number` - The number of the volume, as recorded in the zip archive.
+    ///
+    /// # Returns
+    /// A Result containing the position of the volume in the stitched volumes, always 0 for a zip archive
+    /// that is not split, or an error if there is no such volume.
+    pub(crate) fn disk_offset(&self, disk_number: u16) -> Result<usize, UnzipperError> {
+        if self.disk_offsets.is_empty() {
+            return Ok(0);
+        }
+        self.disk_offsets
+            .get(disk_number as usize)
+            .copied()
+            .ok_or_else(|| {
+                UnzipperError::InvalidArchive(format!(
+                    "Volume {} of the split zip archive is missing",
+                    u32::from(disk_number) + 1
+                ))
+            })
+    }
+
+    /// Locates the zip64 end of central directory record, between the central directory and
+    /// the end of central directory record.
+    ///
+    /// # Arguments
+    /// * `tail` - The last bytes of the zip file, up to the end of central directory record.
+    ///
+    /// # Returns
+    /// The position of the zip64 record in the tail, or None if the zip archive has no zip64 locator.
+    fn locate_zip64_dir_end(tail: &[u8]) -> Option<usize> {
+        // The zip64 end of central directory locator immediately precedes the end of central directory record
+        let locator_position = tail.len().checked_sub(ZIP64_DIR_END_LOCATOR_SIZE)?;
+        if tail[locator_position..locator_position + 4]
+            != ZIP64_DIR_END_LOCATOR_SIGNATURE.to_le_bytes()
+        {
+            return None;
+        }
+        // It is preceded by the zip64 record, of variable size. Its recorded offset is not used, as it does
+        // not account for data prepended to the zip archive
+        let signature = ZIP64_DIR_END_SIGNATURE.to_le_bytes();
+        tail[..locator_position]
+            .windows(4)
+            .rposition(|window| window == signature)
+    }
+
+    /// Locates the end of central directory record in the tail of the zip file.
+    ///
+    /// The signature may also appear in the comment. The record nearest to the end of the file whose comment
+    /// exactly reaches the end of the file is preferred, then the record nearest to the end of the file,
+    /// which has a truncated comment or is followed by other data.
+    ///
+    /// # Arguments
+    /// * `tail` - The last bytes of the zip file, at least as long as the record.
+    ///
+    /// # Returns
+    /// The position of the record in the tail, or None if its signature was not found.
+    fn locate_dir_end(tail: &[u8]) -> Option<usize> {
+        let signature = DIR_END_SIGNATURE.to_le_bytes();
+        let mut nearest = None;
+        for position in (0..=tail.len() - FILE_CENTRAL_SIZE).rev() {
+            if tail[position..position + 4] != signature {
+                continue;
+            }
+            let comment_length =
+                u16::from_le_bytes([tail[position + 20], tail[position + 21]]) as usize;
+            if position + FILE_CENTRAL_SIZE + comment_length == tail.len() {
+                return Some(position);
+            }
+            nearest.get_or_insert(position);
+        }
+        nearest
+    }
+
+    /// Parses the central directory records, building the list of file entries.
+    ///
+    /// # Arguments
+    /// * `entries` - A byte slice containing the whole central directory.
+    /// * `dir_end` - The end of central directory record, giving the number of records and the position
+    ///   of the central directory.
+    /// * `warnings` - Receives the inconsistencies tolerated by the parsing mode of the options.
+    ///
+    /// # Returns
+    /// A Result containing the file entries, or an error if the central directory is invalid. The local file
+    /// header and the data of each entry must lie before the central directory: otherwise the error names
+    /// the entry, unless tolerated by the lenient mode.
+    pub(crate) fn parse_central_directory(
+        &self,
+        entries: &[u8],
+        dir_end: &DirEnd,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<FileEntries, UnzipperError> {
+        self.parse_dir_records(DirRecords::from_bytes(entries), dir_end, warnings)
+    }
+
+    /// Parses the central directory records, read by chunks, building the list of file entries.
+    /// See `Unzipper::parse_central_directory()`.
+    fn parse_dir_records(
+        &self,
+        records: DirRecords<'_>,
+        dir_end: &DirEnd,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<FileEntries, UnzipperError> {
+        let mut parse = self.start_dir_parse(records, dir_end)?;
+        self.parse_dir_rest(&mut parse, warnings)?;
+        Ok(parse.file_entries)
+    }
+
+    /// Starts the parsing of the central directory records, checking the signature of the first one.
+    fn start_dir_parse<'a>(
+        &self,
+        mut records: DirRecords<'a>,
+        dir_end: &DirEnd,
+    ) -> Result<DirParse<'a>, UnzipperError> {
+        // Check if the central directory signature is present, unless the zip archive is empty
+        if dir_end.num_entries > 0
+            && self.get_u32(records.get(self, 0, 4)?) != DIR_FILE_HEADER_SIGNATURE
+        {
+            return Err(UnzipperError::BadSignature("central directory"));
+        }
+        Ok(DirParse {
+            records,
+            dir_end: *dir_end,
+            offset: 0,
+            remaining: dir_end.num_entries,
+            file_entries: FileEntries::with_options(&self.options),
+        })
+    }
+
+    /// Parses the central directory records left, and checks that nothing follows them.
+    fn parse_dir_rest(
+        &self,
+        parse: &mut DirParse<'_>,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<(), UnzipperError> {
+        while self.parse_dir_record(parse, warnings)? {}
+        if parse.offset < parse.records.size {
+            tolerate(
+                self.options.parse_mode,
+                warnings,
+                Warning::new(None, "Central directory is followed by unexpected data"),
+                ParseMode::Standard,
+            )?;
         }
+        Ok(())
     }
 
-    /// Returns the size of the currently opened file entry.
+    /// Parses the next central directory record, adding its entry to the file entries.
     ///
     /// # Returns
-    /// A Result containing the size of the file entry in bytes if successful, or an error message if no file is open.
-    fn get_file_size(&self) -> Result<u32, String> {
-        match self.current_file_entry {
-            Some(ref entry) => Ok(entry.size),
-            None => Err("File not open".to_string()),
+    /// A Result containing false once all the records are parsed, or an error if the record is invalid.
+    fn parse_dir_record(
+        &self,
+        parse: &mut DirParse<'_>,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<bool, UnzipperError> {
+        if parse.remaining == 0 {
+            return Ok(false);
+        }
+        let dir_end = parse.dir_end;
+        let num_entries = dir_end.num_entries;
+        let mode = self.options.parse_mode;
+        // With the recovery mode, a truncated central directory is rebuilt rather than read partially
+        let truncation_mode = if self.options.recovery {
+            ParseMode::Strict
+        } else {
+            mode
+        };
+
+        // A record that is cut short or missing ends the central directory early
+        let truncated = |parse: &mut DirParse<'_>, warnings: &mut Vec<Warning>| {
+            let message = format!(
+                "Central directory is truncated: {} of {num_entries} entries found",
+                num_entries - parse.remaining
+            );
+            tolerate(
+                truncation_mode,
+                warnings,
+                Warning::new(None, &message),
+                ParseMode::Lenient,
+            )?;
+            parse.remaining = 0;
+            Ok(false)
+        };
+
+        let header = parse.records.get(self, parse.offset, DirFileHeader::SIZE)?;
+        let Ok(dir_file_header) = DirFileHeader::parse(header) else {
+            return truncated(parse, warnings);
+        };
+        let name_start = parse.offset + DirFileHeader::SIZE;
+
+        if dir_file_header.signature != DIR_FILE_HEADER_SIGNATURE {
+            if truncation_mode < ParseMode::Lenient {
+                return Err(UnzipperError::BadSignature("central directory file header"));
+            }
+            // Data padding or corrupting the gap between two records is skipped, up to the next record
+            let Some(next_offset) = parse.records.find_record(self, parse.offset + 1)? else {
+                return truncated(parse, warnings);
+            };
+            let message = format!(
+                "Skipped {} bytes of unexpected data in the central directory",
+                next_offset - parse.offset
+            );
+            tolerate(
+                truncation_mode,
+                warnings,
+                Warning::new(None, &message),
+                ParseMode::Lenient,
+            )?;
+            parse.offset = next_offset;
+            return Ok(true);
+        }
+
+        // The name and the extra field, cut at the end of the central directory
+        let name_length = dir_file_header.file_path_length as usize;
+        let extra_length = dir_file_header.extra_field_length as usize;
+        let record = parse
+            .records
+            .get(self, name_start, name_length + extra_length)?;
+        if record.len() < name_length {
+            return truncated(parse, warnings);
+        }
+        let (name, extra) = record.split_at(name_length);
+        let extra = if extra.len() == extra_length {
+            extra
+        } else {
+            &[]
+        };
+
+        let f_name = decode_file_name(name, extra, dir_file_header.flags);
+        let file_path = self.clean_file_path(&f_name);
+
+        if extra.len() != dir_file_header.extra_field_length as usize || !is_well_formed(extra) {
+            tolerate(
+                mode,
+                warnings,
+                Warning::new(Some(&file_path), "Malformed extra field"),
+                ParseMode::Standard,
+            )?;
+        }
+
+        // For WinZip AES encrypted entries, the actual compression method is in the extra field
+        let mut method = dir_file_header.compresion_method;
+        let aes = if method == AES_METHOD {
+            extra_fields(extra)
+                .find(|(id, _)| *id == AES_EXTRA_FIELD_ID)
+                .and_then(|(_, data)| AesExtra::parse(data))
+        } else {
+            None
+        };
+        if let Some(ref aes) = aes {
+            method = aes.method;
         }
+
+        // The position in the file of an entry past the first 4 GiB cannot be recorded
+        let start_pos = self.disk_offset(dir_file_header.disk_number_start)?
+            + dir_file_header.header_offset as usize
+            + self.base_offset;
+        let start_pos = u32::try_from(start_pos).map_err(|_| {
+            UnzipperError::InvalidArchive(format!(
+                "Local file header is beyond 4 GiB in the file: {file_path}"
+            ))
+        })?;
+
+        let file_entry = FileEntry {
+            start_pos,
+            compressed_size: dir_file_header.compressed_size,
+            size: dir_file_header.uncompressed_size,
+            method,
+            crc32: dir_file_header.crc32,
+            flags: dir_file_header.flags,
+            last_mod_time: dir_file_header.last_mod_time,
+            last_mod_date: dir_file_header.last_mod_date,
+            timestamps: timestamps(extra),
+            aes,
+            made_by: dir_file_header.version,
+            extract_version: dir_file_header.extract_version,
+            external_attr: dir_file_header.external_file_attr,
+            dir_name: f_name.ends_with('/'),
+        };
+
+        // Checked now rather than failing to seek or reading garbage once the entry is read
+        let header_end = file_entry.start_pos as usize + FileHeader::SIZE;
+        let bounds_error = if header_end > dir_end.dir_offset {
+            Some(format!(
+                "Local file header at offset {} is beyond the central directory at offset {}",
+                file_entry.start_pos, dir_end.dir_offset
+            ))
+        } else if header_end + file_entry.compressed_size as usize > dir_end.dir_offset {
+            Some(format!(
+                "Entry data of {} bytes extends beyond the central directory at offset {}",
+                file_entry.compressed_size, dir_end.dir_offset
+            ))
+        } else {
+            None
+        };
+        if let Some(message) = bounds_error {
+            tolerate(
+                mode,
+                warnings,
+                Warning::new(Some(&file_path), &message),
+                ParseMode::Lenient,
+            )?;
+        }
+
+        parse.file_entries.insert_with_policy(
+            file_path,
+            file_entry,
+            self.options.duplicates,
+            warnings,
+        )?;
+
+        parse.offset += DirFileHeader::SIZE
+            + dir_file_header.file_path_length as usize
+            + dir_file_header.extra_field_length as usize
+            + dir_file_header.comment_field_length as usize;
+
+        parse.remaining -= 1;
+        Ok(true)
     }
 
     /// Checks if a file exists in the zip archive.
@@ -436,7 +1629,66 @@ impl Unzipper {
     /// A boolean indicating whether the file exists in the archive.
     fn file_exists(&self, file_path: &str) -> bool {
         let cleaned_file_path = self.clean_file_path(file_path);
-        self.file_entries.get(&cleaned_file_path).is_some()
+        matches!(self.find_entry(&cleaned_file_path), Ok(Some(_)))
+    }
+
+    /// Returns the file entries, parsing the rest of the central directory first when it is parsed on demand.
+    ///
+    /// An invalid record found then is recorded as a warning, the entries before it being kept.
+    pub(crate) fn parsed_entries(&self) -> &Arc<FileEntries> {
+        let Some(ref lazy_dir) = self.lazy_dir else {
+            return &self.file_entries;
+        };
+        lazy_dir.file_entries.get_or_init(|| {
+            let Some(mut parse) = lazy_dir.lock_parse().take() else {
+                return Arc::default();
+            };
+            let mut warnings = Vec::new();
+            if let Err(error) = self.parse_dir_rest(&mut parse, &mut warnings) {
+                let message = format!("Central directory could not be read entirely ({error})");
+                warnings.push(Warning::new(None, &message));
+            }
+            self.record_warnings(warnings);
+            Arc::new(parse.file_entries)
+        })
+    }
+
+    /// Looks up the entry of a cleaned path, parsing the central directory up to it when it is parsed on demand.
+    ///
+    /// # Arguments
+    /// * `cleaned_file_path` - The cleaned path of the entry.
+    ///
+    /// # Returns
+    /// A Result containing the cleaned path and the entry, or None if there is no such entry, or an error if
+    /// a record parsed before the entry is found is invalid.
+    pub(crate) fn find_entry(
+        &self,
+        cleaned_file_path: &str,
+    ) -> Result<Option<(String, FileEntry)>, UnzipperError> {
+        if let Some(ref lazy_dir) = self.lazy_dir {
+            let mut state = lazy_dir.lock_parse();
+            if let Some(ref mut parse) = *state {
+                let mut warnings = Vec::new();
+                let result = loop {
+                    if let Some((name, file_entry)) =
+                        parse.file_entries.get_key_value(cleaned_file_path)
+                    {
+                        break Ok(Some((name.clone(), file_entry.clone())));
+                    }
+                    match self.parse_dir_record(parse, &mut warnings) {
+                        Ok(true) => {}
+                        Ok(false) => break Ok(None),
+                        Err(error) => break Err(error),
+                    }
+                };
+                self.record_warnings(warnings);
+                return result;
+            }
+        }
+        Ok(self
+            .parsed_entries()
+            .get_key_value(cleaned_file_path)
+            .map(|(name, file_entry)| (name.clone(), file_entry.clone())))
     }
 
     /// Opens a file entry in the zip archive.
@@ -445,64 +1697,268 @@ impl Unzipper {
     /// * `file_path` - A string slice representing the path of the file to open.
     ///
     /// # Returns
-    /// A Result indicating success or an error if the file could not be opened or if the file is not found.
-    /// This method reads the file header and checks the signature and compression method.
-    fn open_file(&mut self, file_path: &str) -> Result<(), std::io::Error> {
+    /// A Result containing the file entry and its local file header, or an error if the file could not be opened
+    /// or if the file is not found. This method reads the file header and checks the signature and compression method.
+    fn open_file(&self, file_path: &str) -> Result<(FileEntry, FileHeader), UnzipperError> {
         let cleaned_file_path = self.clean_file_path(file_path);
+        let Some((_, file_entry)) = self.find_entry(&cleaned_file_path)? else {
+            return Err(UnzipperError::NotFound(cleaned_file_path));
+        };
+
+        let mut buffer = [0; FileHeader::SIZE];
+        self.get_data(&mut buffer, file_entry.start_pos as usize, "file header")?;
+        let file_header = FileHeader::parse(&buffer)?;
+        if file_header.signature != FILE_HEADER_SIGNATURE {
+            return Err(UnzipperError::BadSignature("local file header"));
+        }
+        if self.options.check_local_headers {
+            for message in file_entry.header_mismatches(&file_header) {
+                self.tolerate_warning(
+                    Warning::new(Some(&cleaned_file_path), &message),
+                    ParseMode::Lenient,
+                )?;
+            }
+        }
 
-        if let Some(file_entry) = self.file_entries.get(&cleaned_file_path) {
-            self.current_file_entry = Some(file_entry.clone());
+        // The method from the central directory is used, as it gives the actual method of AES entries
+        let features = EntryInfo::new(&cleaned_file_path, &file_entry, &self.options)
+            .unsupported_features()
+            .to_vec();
+        if !features.is_empty() {
+            return Err(UnzipperError::Unsupported {
+                file_path: cleaned_file_path,
+                features,
+            });
+        }
+        Ok((file_entry, file_header))
+    }
 
-            // Extract the start position before calling get_data
-            let start_pos = file_entry.start_pos as usize;
+    /// Returns the comment of the zip archive, stored after its end of central directory record.
+    ///
+    /// # Returns
+    /// The raw bytes of the comment, empty if the zip archive has none. Its encoding is not specified by the format.
+    pub fn comment(&self) -> &[u8] {
+        &self.comment
+    }
 
-            // Use a temporary buffer to avoid borrowing self multiple times
-            let mut temp_buffer = vec![0; size_of::<FileHeader>()];
-            self.get_data(&mut temp_buffer, start_pos, "file header")?;
-            self.current_file_header =
-                unsafe { Some(*(temp_buffer.as_ptr() as *const FileHeader)) };
+    /// Returns the comment of the zip archive as a string.
+    ///
+    /// # Returns
+    /// The comment decoded as UTF-8, where invalid sequences are replaced with U+FFFD.
+    pub fn comment_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.comment)
+    }
 
-            if let Some(file_header) = &self.current_file_header {
-                let signature = file_header.signature; // Copy to local variable
-                let compression_method = file_header.compression_method; // Copy to local variable
+    /// Returns the offset of the zip archive in the file.
+    ///
+    /// # Returns
+    /// The size of the data prepended to the zip archive, such as the stub of a self-extracting archive,
+    /// or 0 if the zip archive starts at the beginning of the file.
+    pub fn archive_offset(&self) -> u64 {
+        self.base_offset as u64
+    }
 
-                if signature != FILE_HEADER_SIGNATURE {
-                    self.close_file();
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Invalid file header signature: {}", signature),
-                    ));
-                }
-                if compression_method != 0 && compression_method != 8 {
-                    self.close_file();
-                    return Err(std::io::Error::new(
-                        std::io::ErrorKind::InvalidData,
-                        format!("Unsupported compression method: {}", compression_method),
-                    ));
-                }
-            }
+    /// Returns the location of the APK Signing Block of a signed Android package.
+    ///
+    /// The block lies between the last entry data and the central directory. It is not part of any entry,
+    /// and is ignored when reading the entries.
+    ///
+    /// # Returns
+    /// The position and size of the block in the file, or None if the zip archive has no such block.
+    pub fn apk_signing_block(&self) -> Option<(u64, u64)> {
+        self.apk_signing_block
+            .map(|(offset, size)| (offset as u64, size as u64))
+    }
 
-            Ok(())
-        } else {
-            self.close_file();
-            Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                format!("File not found {cleaned_file_path}"),
-            ))
+    /// Returns the inconsistencies of the zip archive tolerated by the parsing mode of the options.
+    ///
+    /// # Returns
+    /// A copy of the warnings recorded while opening the zip archive, followed by the ones recorded while reading
+    /// its entries.
+    pub fn warnings(&self) -> Vec<Warning> {
+        self.lock_warnings().clone()
+    }
+
+    /// Returns the warnings recorded, to add to them.
+    pub(crate) fn warnings_mut(&mut self) -> &mut Vec<Warning> {
+        self.warnings
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Locks the warnings recorded.
+    pub(crate) fn lock_warnings(&self) -> MutexGuard<'_, Vec<Warning>> {
+        self.warnings.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Records warnings, notifying the warning handler of the options of each one.
+    ///
+    /// # Arguments
+    /// * `warnings` - The inconsistencies tolerated by the parsing mode of the options.
+    pub(crate) fn record_warnings(&self, mut warnings: Vec<Warning>) {
+        // The handler is called without the lock, so that it may read the warnings recorded
+        for warning in &warnings {
+            self.options.notify_warning(warning);
         }
+        self.lock_warnings().append(&mut warnings);
     }
 
-    /// Closes the currently opened file entry by resetting the current file entry and header.
-    /// This method is called after the file has been read or if an error occurs.
-    /// It ensures that the Unzipper is ready to open a new file entry.
-    /// It does not close the underlying file, allowing for multiple file reads without reopening the zip file.
-    /// It is important to call this method after finishing with a file to avoid leaving the Unzipper in an inconsistent state.
-    /// It is also used internally to reset the state of the Unzipper when opening a new file.
+    /// Records an inconsistency of the zip archive as a warning, or turns it into an error, according to
+    /// the parsing mode of the options. See `tolerate()`.
+    ///
+    /// # Arguments
+    /// * `warning` - The inconsistency found.
+    /// * `tolerated_by` - The strictest parsing mode tolerating the inconsistency.
+    ///
     /// # Returns
-    /// None
-    fn close_file(&mut self) {
-        self.current_file_entry = None;
-        self.current_file_header = None;
+    /// A Result indicating that the inconsistency is tolerated, or an InvalidArchive error otherwise.
+    pub(crate) fn tolerate_warning(
+        &self,
+        warning: Warning,
+        tolerated_by: ParseMode,
+    ) -> Result<(), UnzipperError> {
+        let mut warnings = Vec::new();
+        tolerate(
+            self.options.parse_mode,
+            &mut warnings,
+            warning,
+            tolerated_by,
+        )?;
+        self.record_warnings(warnings);
+        Ok(())
+    }
+
+    /// Locks the entry data read ahead during an extraction, if any.
+    pub(crate) fn lock_prefetched(&self) -> MutexGuard<'_, Option<Prefetched>> {
+        self.prefetched
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Returns the reader of the zip archive, if attached, to move its cursor.
+    pub(crate) fn reader_mut(&mut self) -> Option<&mut R> {
+        self.reader
+            .as_mut()
+            .map(|reader| reader.get_mut().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Installs the receiver of the progress of the entries being read.
+    ///
+    /// # Arguments
+    /// * `progress` - The receiver, notified when an entry starts being read, as its data is decompressed,
+    ///   and when it has been fully read. It replaces any receiver previously installed.
+    pub fn set_progress(&mut self, progress: impl Progress + Send + 'static) {
+        self.progress = Some(Mutex::new(Box::new(progress)));
+    }
+
+    /// Locks the receiver of the progress, if any.
+    pub(crate) fn lock_progress(&self) -> Option<MutexGuard<'_, Box<dyn Progress + Send>>> {
+        self.progress
+            .as_ref()
+            .map(|progress| progress.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Registers the decompressor of a compression method, for the entries read from now on.
+    ///
+    /// It is also used by the zip archives opened with `open_nested()`. See `UnzipperOptions::decompressor()`
+    /// to register it before opening the zip archive.
+    ///
+    /// # Arguments
+    /// * `method` - The compression method, as recorded in the zip archive.
+    /// * `factory` - The function creating a decompressor, called for each entry read. It replaces any
+    ///   decompressor of the method, including a built-in one.
+    pub fn register_decompressor<D, F>(&mut self, method: u16, factory: F)
+    where
+        D: Decompressor + 'static,
+        F: Fn() -> D + Send + Sync + 'static,
+    {
+        self.options.decompressors.register(method, factory);
+    }
+
+    /// Installs the provider of the passwords of the encrypted entries read without a password given.
+    ///
+    /// # Arguments
+    /// * `provider` - The provider, such as a closure prompting the user, asked for the password of each
+    ///   encrypted entry. It replaces any provider previously installed.
+    /// * `max_attempts` - The number of times, at least one, the provider is asked for the password of an entry
+    ///   while the password returned is wrong. The reading then fails with `UnzipperError::InvalidPassword`.
+    pub fn set_password_provider(
+        &mut self,
+        provider: impl PasswordProvider + Send + 'static,
+        max_attempts: u32,
+    ) {
+        self.password_provider = Some(Mutex::new(Box::new(provider)));
+        self.max_password_attempts = max_attempts.max(1);
+    }
+
+    /// Removes the provider of the passwords, if any.
+    pub fn clear_password_provider(&mut self) {
+        self.password_provider = None;
+    }
+
+    /// Registers a decryption provider, for the entries read from now on.
+    ///
+    /// See `UnzipperOptions::decryption_provider()` to register it before opening the zip archive.
+    ///
+    /// # Arguments
+    /// * `provider` - The provider, consulted for each entry read after the ones previously registered.
+    pub fn register_decryption_provider(&mut self, provider: impl DecryptionProvider + 'static) {
+        self.options.decryption_providers.register(provider);
+    }
+
+    /// Removes the receiver of the progress, if any.
+    pub fn clear_progress(&mut self) {
+        self.progress = None;
+    }
+
+    /// Returns the options used by this Unzipper.
+    pub fn options(&self) -> &UnzipperOptions {
+        &self.options
+    }
+
+    /// Returns an iterator over the file entries available in the zip archive.
+    ///
+    /// # Returns
+    /// An iterator of EntryInfo, describing the name, sizes, compression method and position of each entry.
+    /// The entries come in the order of the central directory, which is usually the order of their data.
+    pub fn entries(&self) -> impl Iterator<Item = EntryInfo> + '_ {
+        self.parsed_entries()
+            .iter()
+            .map(|(name, file_entry)| EntryInfo::new(name, file_entry, &self.options))
+    }
+
+    /// Returns an iterator over the file entries available in the zip archive, in the given order.
+    ///
+    /// # Arguments
+    /// * `order` - The order of the entries: the one of the central directory, or the natural or character order
+    ///   of their paths.
+    ///
+    /// # Returns
+    /// An iterator of EntryInfo, as given by `entries()`.
+    pub fn entries_in_order(&self, order: EntryOrder) -> impl Iterator<Item = EntryInfo> + '_ {
+        let mut entries: Vec<EntryInfo> = self.entries().collect();
+        match order {
+            EntryOrder::Archive => {}
+            EntryOrder::Name => entries.sort_by(|a, b| a.name().cmp(b.name())),
+            EntryOrder::Natural => entries.sort_by(|a, b| natural_cmp(a.name(), b.name())),
+        }
+        entries.into_iter()
+    }
+
+    /// Returns the metadata of a file entry of the zip archive.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file entry.
+    ///
+    /// # Returns
+    /// The EntryInfo of the entry, giving its CRC-32, sizes, compression method, flags, timestamps and attributes,
+    /// or None if the entry does not exist.
+    pub fn metadata(&self, file_path: &str) -> Option<EntryInfo> {
+        let cleaned_file_path = self.clean_file_path(file_path);
+        match self.find_entry(&cleaned_file_path) {
+            Ok(Some((name, file_entry))) => Some(EntryInfo::new(&name, &file_entry, &self.options)),
+            _ => None,
+        }
     }
 
     /// Displays the file entries available in the zip archive.
@@ -512,106 +1968,417 @@ impl Unzipper {
     /// - Compressed size
     /// - Uncompressed size
     /// - Compression method
-    /// - File name
+    /// - File name, followed by a '/' for directories
     ///
     /// It is useful for debugging and understanding the contents of the zip archive.
     /// It prints a header before the list and a footer after the list to indicate the end of the entries.
+    /// See `listing()` for a listing formatted for users.
     /// # Returns
     /// None
     pub fn show_file_entries(&self) {
         debug!("---- Files available: ----");
-        for (name, entry) in &self.file_entries {
+        for (name, entry) in self.parsed_entries().iter() {
+            let suffix = if entry.kind() == EntryKind::Directory {
+                "/"
+            } else {
+                ""
+            };
             debug!(
-                "pos: {:<7} zip size: {:<7} out size: {:<7} method: {:<1} name: <{}>",
-                entry.start_pos, entry.compressed_size, entry.size, entry.method, name
+                "pos: {:<7} zip size: {:<7} out size: {:<7} method: {:<1} name: <{}{}>",
+                entry.start_pos, entry.compressed_size, entry.size, entry.method, name, suffix
             );
         }
         debug!("[End of List]");
     }
 
-    /// Unzips a file from the archive into a bytes vector.
+    /// Opens a streaming reader over the decompressed content of a file entry.
     ///
-    /// Returns an error if the file is not found or decompression fails.
-    /// Uses an iterator with 8192 byte buffer for reading compressed data.
-    pub fn get_file(&mut self, file_path: &str) -> Result<Vec<u8>, std::io::Error> {
-        // Open the file entry in the zip
-        self.open_file(file_path)?;
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to read.
+    ///
+    /// # Returns
+    /// A Result containing an EntryReader implementing `std::io::Read`, or an error if the file could not be opened.
+    /// The data is decompressed on the fly as the reader is consumed, so large entries never need to be held fully in memory.
+    pub fn entry_reader(&self, file_path: &str) -> Result<EntryReader<'_, R>, UnzipperError> {
+        self.open_entry_reader(file_path, None)
+    }
 
-        let file_entry = match &self.current_file_entry {
-            Some(entry) => entry,
-            None => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::NotFound,
-                    "File entry not found",
-                ));
-            }
-        };
-        let file_header = match &self.current_file_header {
-            Some(header) => header,
-            None => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::NotFound,
-                    "File header not found",
-                ));
+    /// Opens a streaming reader over the decompressed content of an encrypted file entry.
+    ///
+    /// Entries encrypted with ZipCrypto are always supported. Entries encrypted with WinZip AES require the
+    /// `crypto` feature.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to read.
+    /// * `password` - The password used to decrypt the entry. It is ignored if the entry is not encrypted.
+    ///
+    /// # Returns
+    /// A Result containing an EntryReader implementing `std::io::Read`, or an error if the file could not be opened
+    /// or if the password is wrong.
+    pub fn entry_reader_with_password(
+        &self,
+        file_path: &str,
+        password: &[u8],
+    ) -> Result<EntryReader<'_, R>, UnzipperError> {
+        self.open_entry_reader(file_path, Some(password))
+    }
+
+    /// Opens a streaming reader over the decompressed content of a file entry, decrypting it if needed.
+    fn open_entry_reader(
+        &self,
+        file_path: &str,
+        password: Option<&[u8]>,
+    ) -> Result<EntryReader<'_, R>, UnzipperError> {
+        let (data_offset, file_entry) = self.locate_file_data(file_path)?;
+        let file_path = self.clean_file_path(file_path);
+
+        let password = match password {
+            None if self.password_provider.is_some() => {
+                self.ask_password(&file_path, data_offset, &file_entry)?
             }
+            password => password.map(Secret::from),
         };
+        EntryReader::new(
+            self,
+            &file_path,
+            data_offset,
+            &file_entry,
+            password.as_ref().map(Secret::as_bytes),
+        )
+    }
+
+    /// Asks the password provider for the password of an entry, until the password is right.
+    ///
+    /// # Arguments
+    /// * `file_path` - The cleaned path of the entry in the zip archive.
+    /// * `data_offset` - The position of the entry data in the zip file.
+    /// * `file_entry` - The file entry as read from the central directory.
+    ///
+    /// # Returns
+    /// A Result containing the right password, None if the entry does not need one, or an error if the provider
+    /// gives up, if the attempts are exhausted or if the encryption header cannot be read.
+    fn ask_password(
+        &self,
+        file_path: &str,
+        data_offset: usize,
+        file_entry: &FileEntry,
+    ) -> Result<Option<Secret>, UnzipperError> {
+        match EntryReader::check_password(self, file_path, data_offset, file_entry, None) {
+            Err(UnzipperError::PasswordRequired(_)) => {}
+            result => return result.map(|_| None),
+        }
+
+        let info = EntryInfo::new(file_path, file_entry, &self.options);
+        for attempt in 1..=self.max_password_attempts {
+            let password: Secret = self
+                .password_provider
+                .as_ref()
+                .and_then(|provider| {
+                    let mut provider = provider.lock().unwrap_or_else(PoisonError::into_inner);
+                    provider.password(&info, attempt)
+                })
+                .ok_or_else(|| UnzipperError::PasswordRequired(file_path.to_string()))?
+                .into();
+            match EntryReader::check_password(
+                self,
+                file_path,
+                data_offset,
+                file_entry,
+                Some(password.as_bytes()),
+            ) {
+                Err(UnzipperError::InvalidPassword(_)) => {}
+                result => return result.map(|_| Some(password)),
+            }
+        }
+        Err(UnzipperError::InvalidPassword(file_path.to_string()))
+    }
+
+    /// Opens a file entry and computes the position of its data in the zip file.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to locate.
+    ///
+    /// # Returns
+    /// A Result containing the position of the entry data and the file entry, or an error if the file could not be opened.
+    pub(crate) fn locate_file_data(
+        &self,
+        file_path: &str,
+    ) -> Result<(usize, FileEntry), UnzipperError> {
+        let (file_entry, file_header) = self.open_file(file_path)?;
 
         // Calculate the offset to the file data
         let data_offset = file_entry.start_pos as usize
-            + std::mem::size_of::<FileHeader>()
+            + FileHeader::SIZE
             + file_header.file_path_length as usize
             + file_header.extra_field_length as usize;
 
-        // Prepare output buffer
-        let mut output = vec![0u8; file_entry.size as usize];
-
-        match file_entry.method {
-            0 => {
-                // No compression, just copy
-                self.get_data(output.as_mut_slice(), data_offset, "file data")?;
-            }
-            8 => {
-                // Deflate compression using iterator with BUFFER_SIZE byte buffer
-                let mut compressed_size = file_entry.compressed_size as usize;
-                let mut buffer = vec![0u8; BUFFER_SIZE];
-
-                let mut inflate_state = InflateState::new(DataFormat::Raw);
-                let mut output_pos = 0;
-
-                while compressed_size > 0 {
-                    let chunk_size = std::cmp::min(BUFFER_SIZE, compressed_size);
-                    let pos = data_offset + output_pos;
-                    self.get_data(&mut buffer[..chunk_size], pos, "compressed data")?;
-
-                    let stream_result = inflate(
-                        &mut inflate_state,
-                        &buffer[..chunk_size],
-                        &mut output[output_pos..],
-                        if compressed_size <= BUFFER_SIZE {
-                            MZFlush::Finish
-                        } else {
-                            MZFlush::None
-                        },
-                    );
-                    if stream_result.status.is_err() {
-                        return Err(std::io::Error::new(
-                            std::io::ErrorKind::InvalidData,
-                            "Decompression failed",
-                        ));
-                    }
-                    output_pos += stream_result.bytes_written;
-                    compressed_size -= chunk_size;
+        Ok((data_offset, file_entry))
+    }
+
+    /// Reads the data descriptor following the data of a file entry.
+    ///
+    /// The signature of the data descriptor is optional, and its sizes are 4 or 8 bytes long (zip64).
+    /// The size format is selected by matching the sizes against the ones of the central directory.
+    ///
+    /// # Arguments
+    /// * `position` - The position of the data descriptor in the zip file, just after the entry data.
+    /// * `file_entry` - The file entry as read from the central directory.
+    ///
+    /// # Returns
+    /// A Result containing the data descriptor, or an error if it could not be read.
+    pub(crate) fn read_data_descriptor(
+        &self,
+        position: usize,
+        file_entry: &FileEntry,
+    ) -> Result<DataDescriptor, UnzipperError> {
+        // Large enough for the zip64 format with a signature. The central directory
+        // follows the entry data, so that much is always available in a valid archive.
+        let mut buffer = [0u8; 24];
+        self.get_data(&mut buffer, position, "data descriptor")?;
+
+        let fields = if self.get_u32(&buffer[0..4]) == DATA_DESCRIPTOR_SIGNATURE {
+            &buffer[4..]
+        } else {
+            &buffer[..20]
+        };
+
+        let crc32 = self.get_u32(&fields[0..4]);
+        let compressed_size = self.get_u32(&fields[4..8]);
+        let size = self.get_u32(&fields[8..12]);
+
+        if compressed_size == file_entry.compressed_size && size == file_entry.size {
+            Ok(DataDescriptor {
+                crc32,
+                compressed_size: compressed_size as u64,
+                size: size as u64,
+            })
+        } else {
+            Ok(DataDescriptor {
+                crc32,
+                compressed_size: u64::from_le_bytes(fields[4..12].try_into().unwrap_or([0; 8])),
+                size: u64::from_le_bytes(fields[12..20].try_into().unwrap_or([0; 8])),
+            })
+        }
+    }
+
+    /// Unzips a file from the archive into a bytes vector.
+    ///
+    /// Returns an error if the file is not found or decompression fails.
+    /// The content is obtained through an EntryReader, reading the compressed data in chunks of the buffer size
+    /// of the options.
+    pub fn get_file(&self, file_path: &str) -> Result<Vec<u8>, UnzipperError> {
+        let reader = self.entry_reader(file_path)?;
+        Self::read_entry(reader)
+    }
+
+    /// Unzips a file from the archive into a writer.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to unzip.
+    /// * `writer` - The sink receiving the decompressed content, such as a file, a hasher or a socket.
+    ///
+    /// # Returns
+    /// A Result containing the number of bytes written, or an error if the file is not found, decompression fails
+    /// or the writer fails. The compressed data is read in chunks of the buffer size of the options, and the content
+    /// is streamed, never being held fully in memory.
+    pub fn get_file_to(
+        &self,
+        file_path: &str,
+        writer: &mut impl Write,
+    ) -> Result<u64, UnzipperError> {
+        let mut reader = self.entry_reader(file_path)?;
+        Ok(std::io::copy(&mut reader, writer)?)
+    }
+
+    /// Reads the still-compressed data of a file entry, without decompressing it.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to read.
+    ///
+    /// # Returns
+    /// A Result containing the RawEntry holding the data with the method and CRC-32 of the entry,
+    /// or an error if the file is not found or its data extends beyond the end of the zip file.
+    /// The data is neither decrypted nor verified.
+    pub fn get_raw(&self, file_path: &str) -> Result<RawEntry, UnzipperError> {
+        let (data_offset, file_entry) = self.locate_file_data(file_path)?;
+        // The compressed size is checked before its buffer is allocated, as it is up to 4 GiB
+        if data_offset + file_entry.compressed_size as usize > self.zip_length()? {
+            return Err(UnzipperError::InvalidArchive(format!(
+                "Data extends beyond the end of the file: {file_path}"
+            )));
+        }
+        let mut data = vec![0u8; file_entry.compressed_size as usize];
+        self.get_data(&mut data, data_offset, "file data")?;
+
+        let info = EntryInfo::new(&self.clean_file_path(file_path), &file_entry, &self.options);
+        Ok(RawEntry::new(info, data))
+    }
+
+    /// Reads a range of bytes of the decompressed content of a file entry.
+    ///
+    /// The range of a stored (method 0) entry that is neither encrypted nor handled by a decryption provider
+    /// is read directly from the zip file, up to the end of its data, a size recorded in the central directory
+    /// that differs from it being recorded as a warning.
+    /// The content of the other entries is decompressed up to the end of the range, the bytes before it
+    /// being discarded. The CRC-32 is only verified when the range reaches the end of the entry content.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to read.
+    /// * `offset` - The position of the first byte of the range in the decompressed content.
+    /// * `length` - The length of the range.
+    ///
+    /// # Returns
+    /// A Result containing the bytes of the range, shorter than `length` if the range extends beyond the end
+    /// of the content, or an error if the file is not found or decompression fails.
+    pub fn read_range(
+        &self,
+        file_path: &str,
+        offset: u64,
+        length: usize,
+    ) -> Result<Vec<u8>, UnzipperError> {
+        let mut reader = self.entry_reader(file_path)?;
+        if !reader.seek_stored(offset) {
+            std::io::copy(&mut reader.by_ref().take(offset), &mut std::io::sink())?;
+        }
+        let mut range = Vec::with_capacity(length.min(reader.output_capacity()));
+        reader.take(length as u64).read_to_end(&mut range)?;
+        Ok(range)
+    }
+
+    /// Unzips a file from the archive, handing its content to a closure chunk by chunk.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to unzip.
+    /// * `process` - The closure called with each chunk of decompressed content, in order, to hash, parse
+    ///   or forward the content incrementally.
+    ///
+    /// # Returns
+    /// A Result containing the number of bytes processed, or an error if the file is not found or decompression fails.
+    /// The chunks are at most as long as the buffer size of the options, and the whole content is never held
+    /// in memory.
+    pub fn process_file(
+        &self,
+        file_path: &str,
+        mut process: impl FnMut(&[u8]),
+    ) -> Result<u64, UnzipperError> {
+        let mut reader = self.entry_reader(file_path)?;
+        let mut chunk = vec![0u8; self.options.buffer_size];
+        let mut total = 0;
+        loop {
+            match reader.read(&mut chunk)? {
+                0 => return Ok(total),
+                length => {
+                    process(&chunk[..length]);
+                    total += length as u64;
                 }
             }
-            _ => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    "Unsupported compression method",
-                ));
+        }
+    }
+
+    /// Unzips a file from the archive into a buffer provided by the caller, instead of allocating the output.
+    ///
+    /// The reading of the entry still allocates its input buffer and decompressor, as `Unzipper::entry_reader()`.
+    ///
+    /// # Arguments
+    /// * `file_path` - A string slice representing the path of the file to unzip.
+    /// * `buffer` - The buffer receiving the decompressed content, usually sized from `EntryInfo::size()`.
+    ///
+    /// # Returns
+    /// A Result containing the number of bytes written at the start of the buffer, or an error if the file
+    /// is not found, decompression fails or the content does not fit in the buffer (`UnzipperError::BufferTooSmall`).
+    pub fn get_file_into(
+        &self,
+        file_path: &str,
+        buffer: &mut [u8],
+    ) -> Result<usize, UnzipperError> {
+        let mut reader = self.entry_reader(file_path)?;
+        let mut length = 0;
+        while length < buffer.len() {
+            match reader.read(&mut buffer[length..])? {
+                0 => return Ok(length),
+                count => length += count,
             }
         }
 
-        self.close_file();
+        // The buffer is full: the content fits only if nothing remains, which also verifies the entry
+        if reader.read(&mut [0u8; 1])? != 0 {
+            drop(reader);
+            return Err(UnzipperError::BufferTooSmall {
+                file_path: self.clean_file_path(file_path),
+                size: buffer.len(),
+            });
+        }
+        Ok(length)
+    }
+
+    /// Unzips several files from the archive into bytes vectors, in a single pass over the zip file.
+    ///
+    /// The files are read in the order of their data in the zip file, sharing one buffer for the compressed data.
+    ///
+    /// # Arguments
+    /// * `file_paths` - The paths of the files to unzip.
+    ///
+    /// # Returns
+    /// A vector holding, in the order of `file_paths`, the cleaned path of each file with its content,
+    /// or the error that occurred while unzipping it. An error does not prevent the other files from being unzipped.
+    pub fn get_files(&self, file_paths: &[&str]) -> Vec<(String, Result<Vec<u8>, UnzipperError>)> {
+        let names: Vec<String> = file_paths
+            .iter()
+            .map(|path| self.clean_file_path(path))
+            .collect();
+
+        // The files not found are placed last, their error not requiring any read
+        let mut order: Vec<usize> = (0..names.len()).collect();
+        order.sort_by_key(|&index| match self.find_entry(&names[index]) {
+            Ok(Some((_, file_entry))) => file_entry.start_pos as u64,
+            _ => u64::MAX,
+        });
+
+        let mut buffer = Vec::new();
+        let mut results: Vec<(usize, Result<Vec<u8>, UnzipperError>)> = order
+            .into_iter()
+            .map(|index| {
+                let result = self.entry_reader(&names[index]).and_then(|mut reader| {
+                    reader.swap_buffer(&mut buffer);
+                    let mut output = Vec::with_capacity(reader.output_capacity());
+                    let result = reader.read_to_end(&mut output);
+                    reader.swap_buffer(&mut buffer);
+                    result?;
+                    Ok(output)
+                });
+                (index, result)
+            })
+            .collect();
+
+        // The results are given back in the order of the requested paths
+        results.sort_by_key(|&(index, _)| index);
+        names
+            .into_iter()
+            .zip(results)
+            .map(|(name, (_, result))| (name, result))
+            .collect()
+    }
+
+    /// Unzips an encrypted file from the archive into a bytes vector.
+    ///
+    /// Returns an error if the file is not found, the password is wrong or decompression fails.
+    /// Entries encrypted with WinZip AES require the `crypto` feature.
+    /// The password is ignored if the entry is not encrypted.
+    pub fn get_file_with_password(
+        &self,
+        file_path: &str,
+        password: &[u8],
+    ) -> Result<Vec<u8>, UnzipperError> {
+        let reader = self.entry_reader_with_password(file_path, password)?;
+        Self::read_entry(reader)
+    }
+
+    /// Reads the whole content of an entry into a bytes vector.
+    fn read_entry(mut reader: EntryReader<'_, R>) -> Result<Vec<u8>, UnzipperError> {
+        // Prepare output buffer
+        let mut output = Vec::with_capacity(reader.output_capacity());
+        reader.read_to_end(&mut output)?;
+
         Ok(output)
     }
 }
@@ -647,7 +2414,1018 @@ mod unzipper_tests {
                 );
 
                 let data = format!("{:#?}", unzipper);
-                assert!(unit_test.check_result_with_file(&data, &file_name));
+                assert!(unit_test.check_result_with_file(&data, file_name));
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn test_unzipper_normalize_unicode() {
+        let unit_test = UnitTest::new("unzipper_file_names");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("nfd.zip");
+        let nfc = "caf\u{e9}/r\u{e9}sum\u{e9}.txt";
+        let nfd = "cafe\u{301}/re\u{301}sume\u{301}.txt";
+
+        // The paths are stored in the NFD form
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert!(matches!(
+            unzipper.get_file(nfc),
+            Err(UnzipperError::NotFound(_))
+        ));
+        assert_eq!(unzipper.get_file(nfd).unwrap(), b"resume\n");
+
+        let options = UnzipperOptions::new().normalize_unicode(true);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        assert_eq!(unzipper.get_file(nfc).unwrap(), b"resume\n");
+        assert_eq!(unzipper.get_file(nfd).unwrap(), b"resume\n");
+        assert!(unzipper.entries().any(|entry| entry.name() == nfc));
+        assert_eq!(unzipper.get_file("plain.txt").unwrap(), b"plain\n");
+    }
+
+    #[test]
+    fn test_unzipper_get_files() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        let results = unzipper.get_files(&["content.opf", "missing.txt", "./mimetype"]);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].0, "content.opf");
+        assert_eq!(
+            results[0].1.as_ref().unwrap(),
+            &unzipper.get_file("content.opf").unwrap()
+        );
+        assert_eq!(results[1].0, "missing.txt");
+        assert!(matches!(results[1].1, Err(UnzipperError::NotFound(_))));
+        assert_eq!(results[2].0, "mimetype");
+        assert_eq!(results[2].1.as_ref().unwrap(), b"application/epub+zip");
+
+        // Every entry of the archive, read in one pass
+        let names: Vec<String> = unzipper
+            .entries()
+            .filter(|entry| !entry.is_dir())
+            .map(|entry| entry.name().to_string())
+            .collect();
+        let paths: Vec<&str> = names.iter().map(String::as_str).collect();
+        for (name, result) in unzipper.get_files(&paths) {
+            assert_eq!(result.unwrap(), unzipper.get_file(&name).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_unzipper_get_raw() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        // Deflated entry, whose raw data inflates to the content
+        let raw = unzipper.get_raw("content.opf").unwrap();
+        let expected = unzipper.get_file("content.opf").unwrap();
+        assert_eq!(raw.method(), 8);
+        assert_eq!(raw.info().name(), "content.opf");
+        assert_eq!(raw.data().len(), raw.info().compressed_size() as usize);
+        assert_eq!(raw.size() as usize, expected.len());
+        let mut decompressor = DecompressorRegistry::default()
+            .new_decompressor(8, 0)
+            .unwrap()
+            .unwrap();
+        let mut content = vec![0u8; expected.len()];
+        let result = decompressor.decompress(raw.data(), &mut content).unwrap();
+        assert!(result.finished);
+        assert_eq!(content, expected);
+        let mut crc = crate::crc32::Crc32::new();
+        crc.update(&expected);
+        assert_eq!(raw.crc32(), crc.finalize());
+
+        // Stored entry, whose raw data is the content
+        let raw = unzipper.get_raw("mimetype").unwrap();
+        assert_eq!(raw.method(), 0);
+        assert_eq!(raw.into_data(), b"application/epub+zip");
+
+        assert!(matches!(
+            unzipper.get_raw("missing.txt"),
+            Err(UnzipperError::NotFound(_))
+        ));
+
+        // Compressed size beyond the end of the file, only warned about in lenient mode
+        let mut bytes = std::fs::read(&file).unwrap();
+        let record = bytes
+            .windows(54)
+            .position(|w| w[..4] == *b"PK\x01\x02" && w[46..] == *b"mimetype")
+            .unwrap();
+        bytes[record + 20..record + 24].copy_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+        let options = UnzipperOptions::new().parse_mode(ParseMode::Lenient);
+        let unzipper = Unzipper::from_reader_with_options(Cursor::new(bytes), options).unwrap();
+        assert!(matches!(
+            unzipper.get_raw("mimetype"),
+            Err(UnzipperError::InvalidArchive(message)) if message.starts_with("Data extends beyond")
+        ));
+    }
+
+    #[test]
+    fn test_unzipper_entry_reader() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        // Stored entry
+        let mut data = String::new();
+        unzipper
+            .entry_reader("mimetype")
+            .unwrap()
+            .read_to_string(&mut data)
+            .unwrap();
+        assert_eq!(data, "application/epub+zip");
+
+        // Deflated entry spanning many compressed chunks, read with a small buffer
+        let mut reader = unzipper.entry_reader("Fonts/LinLibertine_R.otf").unwrap();
+        assert_eq!(reader.size(), 159912);
+        let mut streamed = Vec::new();
+        let mut chunk = [0u8; 1000];
+        loop {
+            let length = reader.read(&mut chunk).unwrap();
+            if length == 0 {
+                break;
+            }
+            streamed.extend_from_slice(&chunk[..length]);
+        }
+        drop(reader);
+
+        assert_eq!(streamed.len(), 159912);
+        assert_eq!(
+            streamed,
+            unzipper.get_file("Fonts/LinLibertine_R.otf").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_unzipper_entries() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        assert_eq!(unzipper.entries().count(), 86);
+
+        let entry = unzipper
+            .entries()
+            .find(|entry| entry.name() == "content.opf")
+            .unwrap();
+        assert_eq!(entry.size(), 12472);
+        assert_eq!(entry.compressed_size(), 2544);
+        assert_eq!(entry.method(), 8);
+    }
+
+    #[test]
+    fn test_unzipper_metadata() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        let metadata = unzipper.metadata("./mimetype").unwrap();
+        assert_eq!(metadata.name(), "mimetype");
+        assert_eq!(metadata.crc32(), 0x2cab616f);
+        assert_eq!(metadata.size(), 20);
+        assert_eq!(metadata.compressed_size(), 20);
+        assert_eq!(metadata.method(), 0);
+        assert_ne!(metadata.flags() & 0x0800, 0, "UTF-8 file name");
+        assert_eq!(metadata.version_made_by() >> 8, 3);
+        // The file type bits are not recorded by the tool that created the archive
+        assert_eq!(metadata.external_attributes() >> 16, 0o600);
+        assert_eq!(metadata.unix_mode(), Some(0o600));
+
+        assert_eq!(
+            unzipper.metadata("META-INF/container.xml").unwrap().crc32(),
+            0x74069f90
+        );
+        assert_eq!(unzipper.metadata("missing.txt"), None);
+    }
+
+    #[test]
+    fn test_unzipper_comment() {
+        let unit_test = UnitTest::new("unzipper_comment");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("comment.zip");
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert_eq!(
+            unzipper.comment(),
+            b"build=2026.10.16 commit=4f2a9c1 caf\xe9"
+        );
+        assert_eq!(
+            unzipper.comment_lossy(),
+            "build=2026.10.16 commit=4f2a9c1 caf\u{fffd}"
+        );
+        assert_eq!(unzipper.get_file("book.txt").unwrap(), b"content\n");
+
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        assert!(Unzipper::new(&file).unwrap().comment().is_empty());
+    }
+
+    #[test]
+    fn test_unzipper_dir_end_search() {
+        let unit_test = UnitTest::new("unzipper_directories");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("directories.zip");
+        let bytes = std::fs::read(&file).unwrap();
+
+        // Replaces the (empty) comment of the zip archive
+        let with_comment = |bytes: &[u8], comment: &[u8]| {
+            let mut bytes = bytes.to_vec();
+            let length = bytes.len();
+            bytes[length - 2..].copy_from_slice(&(comment.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(comment);
+            bytes
+        };
+
+        for comment_length in [0, 1, 21, 22, 23, 65534, 65535] {
+            let comment = vec![b'c'; comment_length];
+            let unzipper = Unzipper::from_vec(with_comment(&bytes, &comment)).unwrap();
+            assert_eq!(unzipper.comment().len(), comment_length);
+            assert_eq!(unzipper.get_file("file.txt").unwrap(), b"file\n");
+        }
+
+        // A comment holding a record whose comment length does not reach the end of the file
+        let mut comment = vec![b'c'; 65535 - FILE_CENTRAL_SIZE];
+        comment.extend_from_slice(&DIR_END_SIGNATURE.to_le_bytes());
+        comment.extend_from_slice(&[0; FILE_CENTRAL_SIZE - 6]);
+        comment.extend_from_slice(&[1, 0]);
+        let unzipper = Unzipper::from_vec(with_comment(&bytes, &comment)).unwrap();
+        assert_eq!(unzipper.comment(), comment);
+
+        // An empty zip archive, with the record at the start of the file
+        let empty = [&DIR_END_SIGNATURE.to_le_bytes()[..], &[0; 18]].concat();
+        let unzipper = Unzipper::from_vec(with_comment(&empty, &[b'c'; 65535])).unwrap();
+        assert_eq!(unzipper.entries().count(), 0);
+        assert_eq!(unzipper.comment().len(), 65535);
+
+        assert!(matches!(
+            Unzipper::from_vec(vec![0; 70000]),
+            Err(UnzipperError::BadSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_unzipper_prepended_data() {
+        let unit_test = UnitTest::new("unzipper_comment");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("comment.zip");
+        let bytes = std::fs::read(&file).unwrap();
+        assert_eq!(Unzipper::from_bytes(&bytes).unwrap().archive_offset(), 0);
+
+        // A self-extracting archive: an executable stub followed by the zip archive
+        let stub = b"#!/bin/sh\nexec unzip \"$0\"\n".repeat(40);
+        let sfx = [&stub[..], &bytes].concat();
+        let unzipper = Unzipper::from_vec(sfx).unwrap();
+        assert_eq!(unzipper.archive_offset(), stub.len() as u64);
+        assert!(unzipper.warnings().is_empty());
+        assert!(unzipper.comment().starts_with(b"build="));
+        assert_eq!(unzipper.get_file("book.txt").unwrap(), b"content\n");
+
+        // An entry pushed past the first 4 GiB of the file by the volumes before it
+        let mut bytes = bytes;
+        let signature = DIR_FILE_HEADER_SIGNATURE.to_le_bytes();
+        let central_pos = bytes.windows(4).position(|w| w == signature).unwrap();
+        bytes[central_pos + 34] = 1;
+        let mut unzipper = Unzipper::empty(UnzipperOptions::default());
+        unzipper.disk_offsets = vec![0, u32::MAX as usize + 1];
+        unzipper.reader = Some(RwLock::new(Cursor::new(bytes)));
+        assert!(matches!(
+            unzipper.read_central_directory(),
+            Err(UnzipperError::InvalidArchive(msg)) if msg.contains("beyond 4 GiB")
+        ));
+    }
+
+    #[test]
+    fn test_unzipper_header_parsing() {
+        let unit_test = UnitTest::new("unzipper_comment");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("comment.zip");
+        let bytes = std::fs::read(&file).unwrap();
+
+        let header = FileHeader::parse(&bytes).unwrap();
+        assert_eq!(header.signature, FILE_HEADER_SIGNATURE);
+        assert_eq!(header.file_path_length, 8);
+        assert!(matches!(
+            FileHeader::parse(&bytes[..FileHeader::SIZE - 1]),
+            Err(UnzipperError::InvalidArchive(msg)) if msg == "Truncated local file header"
+        ));
+        assert!(DirFileHeader::parse(&bytes[..DirFileHeader::SIZE - 1]).is_err());
+
+        // No truncation of the zip archive makes the parsing panic
+        let options = UnzipperOptions::new().parse_mode(ParseMode::Lenient);
+        for length in 0..bytes.len() {
+            let _ = Unzipper::from_bytes(&bytes[..length]);
+            let _ =
+                Unzipper::from_reader_with_options(Cursor::new(&bytes[..length]), options.clone());
+        }
+    }
+
+    #[test]
+    fn test_unzipper_dir_records() {
+        // A central directory of 200000 bytes, after 100 bytes of entries
+        let bytes: Vec<u8> = (0..200_100).map(|index| index as u8).collect();
+        let mut unzipper = Unzipper::empty(UnzipperOptions::default());
+        unzipper.reader = Some(RwLock::new(Cursor::new(bytes.clone())));
+        let dir_end = DirEnd {
+            offset: 200_100,
+            dir_offset: 100,
+            dir_size: 200_000,
+            num_entries: 0,
+            comment_length: 0,
+        };
+        let mut records = DirRecords::from_zip(&dir_end);
+        let mut get = |offset: usize, length: usize| {
+            let data = records.get(&unzipper, offset, length).unwrap().to_vec();
+            (data, (records.start, records.data.len()))
+        };
+
+        assert_eq!(get(0, 4), (bytes[100..104].to_vec(), (0, DIR_CHUNK_SIZE)));
+        assert_eq!(get(65530, 4).1, (0, DIR_CHUNK_SIZE));
+        // A record across the end of the chunk, then one in the chunk read for it
+        assert_eq!(get(65534, 10).0, &bytes[65634..65644]);
+        assert_eq!(get(65544, 100).1, (65534, DIR_CHUNK_SIZE));
+        // A record longer than a chunk, and the records cut at the end of the central directory
+        assert_eq!(get(70_000, 100_000).1, (70_000, 100_000));
+        assert_eq!(
+            get(199_990, 100),
+            (bytes[200_090..].to_vec(), (199_990, 10))
+        );
+        assert!(get(200_000, 4).0.is_empty());
+
+        // A record signature across two chunks
+        let mut bytes = bytes;
+        bytes[65634..65638].copy_from_slice(&DIR_FILE_HEADER_SIGNATURE.to_le_bytes());
+        unzipper.reader = Some(RwLock::new(Cursor::new(bytes)));
+        let mut records = DirRecords::from_zip(&dir_end);
+        assert_eq!(records.find_record(&unzipper, 1).unwrap(), Some(65534));
+        assert_eq!(records.find_record(&unzipper, 65535).unwrap(), None);
+
+        // The central directory read by chunks gives the entries parsed from memory
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let bytes = std::fs::read(&file).unwrap();
+        let mut unzipper = Unzipper::new(&file).unwrap();
+        let dir_end = unzipper.find_dir_end(&mut Vec::new()).unwrap();
+        let entries = &bytes[dir_end.dir_offset..dir_end.dir_offset + dir_end.dir_size];
+        let file_entries = unzipper
+            .parse_central_directory(entries, &dir_end, &mut Vec::new())
+            .unwrap();
+        assert_eq!(
+            format!("{:?}", file_entries.iter().collect::<Vec<_>>()),
+            format!("{:?}", unzipper.file_entries.iter().collect::<Vec<_>>())
+        );
+    }
+
+    #[test]
+    fn test_unzipper_check_local_headers() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+
+        // The local file header of mimetype, the first entry, gives another compression method
+        let mut bytes = std::fs::read(&file).unwrap();
+        bytes[8..10].copy_from_slice(&8u16.to_le_bytes());
+        let message = "Local file header gives the compression method 8, the central directory 0";
+
+        let unzipper = Unzipper::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            unzipper.get_file("mimetype").unwrap(),
+            b"application/epub+zip"
+        );
+
+        let options = UnzipperOptions::new().check_local_headers(true);
+        let unzipper = Unzipper::from_reader_with_options(Cursor::new(&bytes), options.clone());
+        let unzipper = unzipper.unwrap();
+        assert!(matches!(
+            unzipper.get_file("mimetype"),
+            Err(UnzipperError::InvalidArchive(msg)) if msg == format!("{message}: mimetype")
+        ));
+        assert!(unzipper.get_file("content.opf").is_ok());
+
+        let options = options.parse_mode(ParseMode::Lenient);
+        let unzipper = Unzipper::from_reader_with_options(Cursor::new(&bytes), options).unwrap();
+        assert_eq!(
+            unzipper.get_file("mimetype").unwrap(),
+            b"application/epub+zip"
+        );
+        assert_eq!(unzipper.warnings()[0].message(), message);
+    }
+
+    #[test]
+    fn test_unzipper_dir_garbage() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let expected = Unzipper::new(&file).unwrap();
+
+        // Garbage inserted before the fifth central directory record, counted in the central directory size
+        let mut bytes = std::fs::read(&file).unwrap();
+        let signature = DIR_FILE_HEADER_SIGNATURE.to_le_bytes();
+        let fifth = (0..bytes.len() - 4)
+            .filter(|&position| bytes[position..position + 4] == signature)
+            .nth(4)
+            .unwrap();
+        bytes.splice(fifth..fifth, *b"garbage");
+        let ecd_offset = bytes.len() - FILE_CENTRAL_SIZE;
+        let dir_size =
+            u32::from_le_bytes(bytes[ecd_offset + 12..ecd_offset + 16].try_into().unwrap());
+        bytes[ecd_offset + 12..ecd_offset + 16].copy_from_slice(&(dir_size + 7).to_le_bytes());
+
+        assert!(matches!(
+            Unzipper::from_bytes(&bytes),
+            Err(UnzipperError::BadSignature("central directory file header"))
+        ));
+        let options = UnzipperOptions::new().parse_mode(ParseMode::Lenient);
+        let unzipper = Unzipper::from_reader_with_options(Cursor::new(&bytes), options).unwrap();
+        assert_eq!(unzipper.entries().count(), 86);
+        assert_eq!(
+            unzipper.warnings()[0].message(),
+            "Skipped 7 bytes of unexpected data in the central directory"
+        );
+        assert_eq!(
+            unzipper.get_file("content.opf").unwrap(),
+            expected.get_file("content.opf").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_unzipper_unsupported_features() {
+        let unit_test = UnitTest::new("unzipper_unsupported");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("unsupported.zip");
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert_eq!(unzipper.get_file("plain.txt").unwrap(), b"plain content\n");
+        assert!(unzipper
+            .metadata("plain.txt")
+            .unwrap()
+            .unsupported_features()
+            .is_empty());
+
+        for (file_path, features) in [
+            ("lzma.txt", vec![UnsupportedFeature::CompressionMethod(14)]),
+            ("future.txt", vec![UnsupportedFeature::Version(90)]),
+            (
+                "strong.txt",
+                vec![
+                    UnsupportedFeature::StrongEncryption,
+                    UnsupportedFeature::CompressionMethod(14),
+                ],
+            ),
+        ] {
+            assert_eq!(
+                unzipper.metadata(file_path).unwrap().unsupported_features(),
+                features
+            );
+            match unzipper.get_file(file_path) {
+                Err(UnzipperError::Unsupported {
+                    file_path: path,
+                    features: missing,
+                }) => {
+                    assert_eq!(path, file_path);
+                    assert_eq!(missing, features);
+                }
+                result => panic!("unexpected result for {file_path}: {result:?}"),
+            }
+        }
+        assert_eq!(
+            unzipper.get_file("strong.txt").unwrap_err().to_string(),
+            "Unsupported features for strong.txt: PKWARE strong encryption, LZMA compression"
+        );
+        assert_eq!(
+            UnsupportedFeature::Version(90).to_string(),
+            "zip version 9.0"
+        );
+    }
+
+    #[test]
+    fn test_unzipper_entry_bounds() {
+        let unit_test = UnitTest::new("unzipper_verify");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("overlap.zip");
+        assert!(matches!(
+            Unzipper::new(&file),
+            Err(UnzipperError::InvalidArchive(msg))
+                if msg == "Entry data of 45 bytes extends beyond the central directory at offset 98: third.txt"
+        ));
+
+        let file = PathBuf::from(unit_test.test_case_folder()).join("broken.zip");
+        let options = UnzipperOptions::new().parse_mode(ParseMode::Lenient);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        assert_eq!(unzipper.warnings().len(), 1);
+        assert!(unzipper.warnings()[0]
+            .to_string()
+            .contains("Local file header at offset 100000 is beyond the central directory"));
+    }
+
+    #[test]
+    fn test_unzipper_apk_signing_block() {
+        let unit_test = UnitTest::new("unzipper_apk");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("signed.apk");
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert_eq!(unzipper.apk_signing_block(), Some((585, 108)));
+        assert!(unzipper.warnings().is_empty());
+        assert_eq!(unzipper.entries().count(), 3);
+        assert!(unzipper
+            .get_file("classes.dex")
+            .unwrap()
+            .starts_with(b"dex\n035\0"));
+        assert_eq!(unzipper.get_file("resources.arsc").unwrap().len(), 128);
+
+        // Also in strict mode, and behind prepended data
+        let bytes = [&b"stub"[..], &std::fs::read(&file).unwrap()].concat();
+        let options = UnzipperOptions::new().parse_mode(ParseMode::Strict);
+        let unzipper = Unzipper::from_reader_with_options(Cursor::new(bytes), options).unwrap();
+        assert_eq!(unzipper.apk_signing_block(), Some((589, 108)));
+        assert_eq!(
+            unzipper.get_file("AndroidManifest.xml").unwrap(),
+            b"<manifest package=\"org.example\"/>\n"
+        );
+
+        let unit_test = UnitTest::new("unzipper_comment");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("comment.zip");
+        assert_eq!(Unzipper::new(&file).unwrap().apk_signing_block(), None);
+    }
+
+    #[test]
+    fn test_unzipper_get_file_to() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let expected = unzipper.get_file("content.opf").unwrap();
+
+        let mut output = Vec::new();
+        assert_eq!(
+            unzipper.get_file_to("content.opf", &mut output).unwrap(),
+            12472
+        );
+        assert_eq!(output, expected);
+
+        // The errors of the writer are returned
+        let mut buffer = [0u8; 100];
+        let mut full = &mut buffer[..];
+        assert!(matches!(
+            unzipper.get_file_to("content.opf", &mut full),
+            Err(UnzipperError::Io(_))
+        ));
+        assert!(matches!(
+            unzipper.get_file_to("missing.txt", &mut output),
+            Err(UnzipperError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_unzipper_get_file_into() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let expected = unzipper.get_file("content.opf").unwrap();
+
+        let size = unzipper.metadata("content.opf").unwrap().size() as usize;
+        let mut buffer = vec![0u8; size];
+        assert_eq!(
+            unzipper.get_file_into("content.opf", &mut buffer).unwrap(),
+            size
+        );
+        assert_eq!(buffer, expected);
+
+        // A larger buffer is partially filled
+        let mut buffer = [0u8; 64];
+        assert_eq!(unzipper.get_file_into("mimetype", &mut buffer).unwrap(), 20);
+        assert_eq!(&buffer[..20], b"application/epub+zip");
+
+        let mut buffer = vec![0u8; size - 1];
+        assert!(matches!(
+            unzipper.get_file_into("content.opf", &mut buffer),
+            Err(UnzipperError::BufferTooSmall { size: 12471, .. })
+        ));
+    }
+
+    #[test]
+    fn test_unzipper_process_file() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let expected = unzipper.get_file("content.opf").unwrap();
+
+        let mut content = Vec::new();
+        let mut chunks = 0;
+        let total = unzipper
+            .process_file("content.opf", |chunk| {
+                content.extend_from_slice(chunk);
+                chunks += 1;
+            })
+            .unwrap();
+        assert_eq!(total, 12472);
+        assert_eq!(content, expected);
+        assert!(chunks >= 1);
+
+        // The chunks are bounded by the buffer size of the options
+        let options = UnzipperOptions::new().buffer_size(1000);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        let mut chunks = 0;
+        unzipper
+            .process_file("content.opf", |chunk| {
+                assert!(chunk.len() <= 1000);
+                chunks += 1;
+            })
+            .unwrap();
+        assert!(chunks >= 13);
+
+        assert!(matches!(
+            unzipper.process_file("missing.txt", |_| panic!("no chunk expected")),
+            Err(UnzipperError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_unzipper_read_range() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+
+        // Stored entry, read directly from the zip file
+        assert_eq!(unzipper.read_range("mimetype", 12, 4).unwrap(), b"epub");
+        assert_eq!(unzipper.read_range("mimetype", 17, 100).unwrap(), b"zip");
+        assert!(unzipper.read_range("mimetype", 100, 4).unwrap().is_empty());
+
+        // Deflated entry, decompressed up to the end of the range
+        let expected = unzipper.get_file("content.opf").unwrap();
+        assert_eq!(
+            unzipper.read_range("content.opf", 0, 5).unwrap(),
+            &expected[..5]
+        );
+        assert_eq!(
+            unzipper.read_range("content.opf", 10000, 100).unwrap(),
+            &expected[10000..10100]
+        );
+        assert_eq!(
+            unzipper.read_range("content.opf", 12400, 1000).unwrap(),
+            &expected[12400..]
+        );
+        assert!(matches!(
+            unzipper.read_range("missing.txt", 0, 1),
+            Err(UnzipperError::NotFound(_))
+        ));
+
+        // The range of a stored entry ends with its data, whatever the size recorded
+        let mut bytes = std::fs::read(&file).unwrap();
+        let signature = DIR_FILE_HEADER_SIGNATURE.to_le_bytes();
+        let central_pos = bytes.windows(4).position(|w| w == signature).unwrap();
+        bytes[central_pos + 24..central_pos + 28].copy_from_slice(&100u32.to_le_bytes());
+        let unzipper = Unzipper::from_vec(bytes).unwrap();
+        assert_eq!(unzipper.metadata("mimetype").unwrap().size(), 100);
+        assert_eq!(
+            unzipper.read_range("mimetype", 12, 100).unwrap(),
+            b"epub+zip"
+        );
+        assert_eq!(
+            unzipper.warnings()[0].message(),
+            "Decompressed size does not match the central directory"
+        );
+    }
+
+    #[test]
+    fn test_unzipper_from_reader() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let bytes = std::fs::read(&file).unwrap();
+
+        let cursor = Cursor::new(bytes.clone());
+        let unzipper = Unzipper::from_reader(cursor).unwrap();
+        assert_eq!(unzipper.entries().count(), 86);
+        assert_eq!(
+            unzipper.get_file("mimetype").unwrap(),
+            b"application/epub+zip"
+        );
+
+        let unzipper = Unzipper::from_bytes(&bytes).unwrap();
+        assert_eq!(
+            unzipper.get_file("mimetype").unwrap(),
+            b"application/epub+zip"
+        );
+
+        let unzipper = Unzipper::from_vec(bytes).unwrap();
+        assert_eq!(
+            unzipper.get_file("mimetype").unwrap(),
+            b"application/epub+zip"
+        );
+
+        assert!(Unzipper::from_bytes(b"not a zip archive at all").is_err());
+    }
+
+    #[test]
+    fn test_unzipper_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Unzipper<File>>();
+        assert_send_sync::<Unzipper<Cursor<Vec<u8>>>>();
+
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let mut unzipper = Unzipper::new(&file).unwrap();
+        unzipper.set_password_provider(|_: &EntryInfo, _| None, 1);
+        let unzipper = Arc::new(unzipper);
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let unzipper = unzipper.clone();
+                scope.spawn(move || {
+                    assert_eq!(unzipper.entries().count(), 86);
+                    assert_eq!(unzipper.metadata("mimetype").unwrap().size(), 20);
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_unzipper_shared_reads() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let content = unzipper.get_file("content.opf").unwrap();
+
+        // Several entries read at once, by a reader moving its cursor
+        let in_memory = Unzipper::from_vec(std::fs::read(&file).unwrap()).unwrap();
+        let mut first = in_memory.entry_reader("content.opf").unwrap();
+        let mut second = in_memory.entry_reader("content.opf").unwrap();
+        let (mut first_content, mut second_content) = (Vec::new(), Vec::new());
+        let mut buffer = [0u8; 100];
+        loop {
+            let length = first.read(&mut buffer).unwrap();
+            first_content.extend_from_slice(&buffer[..length]);
+            let length = second.read(&mut buffer).unwrap();
+            second_content.extend_from_slice(&buffer[..length]);
+            if length == 0 {
+                break;
+            }
+        }
+        assert_eq!(first_content, content);
+        assert_eq!(second_content, content);
+
+        std::thread::scope(|scope| {
+            for name in ["content.opf", "mimetype", "toc.ncx", "content.opf"] {
+                let (unzipper, in_memory) = (&unzipper, &in_memory);
+                scope.spawn(move || {
+                    for _ in 0..8 {
+                        assert_eq!(
+                            unzipper.get_file(name).unwrap(),
+                            in_memory.get_file(name).unwrap()
+                        );
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_unzipper_lazy_open() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let expected = Unzipper::new(&file).unwrap();
+        let dest = std::env::temp_dir().join("unzipper_lazy_open");
+        let _ = std::fs::remove_dir_all(&dest);
+
+        // No file handle is held between the reads
+        let options = UnzipperOptions::new().lazy_open(true);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        assert!(unzipper.reader.is_none());
+        assert_eq!(unzipper.entries().count(), 86);
+        assert_eq!(
+            unzipper.get_file("content.opf").unwrap(),
+            expected.get_file("content.opf").unwrap()
+        );
+        let clone = unzipper.try_clone().unwrap();
+        assert!(clone.reader.is_none());
+        assert_eq!(clone.get_file("mimetype").unwrap(), b"application/epub+zip");
+
+        let mut unzipper = unzipper;
+        assert!(unzipper.verify().unwrap().is_ok());
+        unzipper.extract_all(&dest).unwrap();
+        assert!(expected.compare_dir(&dest).unwrap().is_up_to_date());
+        assert!(unzipper.reader.is_none());
+
+        std::fs::remove_dir_all(&dest).unwrap();
+    }
+
+    #[test]
+    fn test_unzipper_lazy_directory() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let expected = Unzipper::new(&file).unwrap();
+        let parsed = |unzipper: &Unzipper| {
+            let lazy_dir = unzipper.lazy_dir.as_ref().unwrap();
+            let parse = lazy_dir.lock_parse();
+            parse
+                .as_ref()
+                .map(|parse| parse.file_entries.keys().count())
+        };
+
+        // The records are parsed up to the entries looked up
+        let options = UnzipperOptions::new()
+            .lazy_directory(true)
+            .duplicates(DuplicatePolicy::FirstWins);
+        let unzipper = Unzipper::with_options(&file, options.clone()).unwrap();
+        assert_eq!(parsed(&unzipper), Some(0));
+        assert_eq!(
+            unzipper.get_file("mimetype").unwrap(),
+            b"application/epub+zip"
+        );
+        assert_eq!(parsed(&unzipper), Some(1));
+        assert!(unzipper.metadata("META-INF/container.xml").is_some());
+        assert_eq!(parsed(&unzipper), Some(3));
+        assert_eq!(unzipper.entries().count(), 86);
+        assert_eq!(parsed(&unzipper), None);
+        assert_eq!(format!("{unzipper:?}"), format!("{expected:?}"));
+        assert!(matches!(
+            unzipper.get_file("missing"),
+            Err(UnzipperError::NotFound(_))
+        ));
+
+        // An invalid record is reported by the lookups reaching it
+        let mut bytes = std::fs::read(&file).unwrap();
+        let signature = DIR_FILE_HEADER_SIGNATURE.to_le_bytes();
+        let fifth = (0..bytes.len() - 4)
+            .filter(|&position| bytes[position..position + 4] == signature)
+            .nth(4)
+            .unwrap();
+        bytes[fifth] = 0;
+        let corrupted = std::env::temp_dir().join("unzipper_lazy_directory.epub");
+        std::fs::write(&corrupted, &bytes).unwrap();
+
+        let unzipper = Unzipper::with_options(&corrupted, options).unwrap();
+        assert_eq!(
+            unzipper.get_file("mimetype").unwrap(),
+            b"application/epub+zip"
+        );
+        assert!(matches!(
+            unzipper.get_file("content.opf"),
+            Err(UnzipperError::BadSignature("central directory file header"))
+        ));
+        assert_eq!(unzipper.entries().count(), 4);
+        assert!(unzipper.warnings()[0]
+            .message()
+            .starts_with("Central directory could not be read entirely"));
+        assert!(Unzipper::new(&corrupted).is_err());
+
+        std::fs::remove_file(&corrupted).unwrap();
+    }
+
+    #[test]
+    fn test_unzipper_lazy_directory_duplicates() {
+        let unit_test = UnitTest::new("unzipper_duplicates");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("duplicates.zip");
+        let open = |lazy_directory: bool, policy: DuplicatePolicy| {
+            let options = UnzipperOptions::new()
+                .lazy_directory(lazy_directory)
+                .duplicates(policy);
+            Unzipper::with_options(&file, options)
+        };
+
+        // A lookup gives the entry kept by the policy, whether or not the central directory is parsed on demand
+        for lazy_directory in [false, true] {
+            let unzipper = open(lazy_directory, DuplicatePolicy::FirstWins).unwrap();
+            assert_eq!(unzipper.lazy_dir.is_some(), lazy_directory);
+            assert_eq!(unzipper.get_file("notes.txt").unwrap(), b"first\n");
+
+            let unzipper = open(lazy_directory, DuplicatePolicy::LastWins).unwrap();
+            assert!(unzipper.lazy_dir.is_none());
+            assert_eq!(unzipper.get_file("notes.txt").unwrap(), b"second\n");
+
+            assert!(matches!(
+                open(lazy_directory, DuplicatePolicy::Error),
+                Err(UnzipperError::DuplicateEntry(file_path)) if file_path == "notes.txt"
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unzipper_refresh() {
+        let epub = PathBuf::from(UnitTest::new("unzipper_open_epub_file").test_case_folder())
+            .join("ebook.epub");
+        let comment =
+            PathBuf::from(UnitTest::new("unzipper_comment").test_case_folder()).join("comment.zip");
+        let path = std::env::temp_dir().join("unzipper_refresh.zip");
+        std::fs::copy(&epub, &path).unwrap();
+
+        let mut unzipper = Unzipper::new(&path).unwrap();
+        assert!(!unzipper.refresh().unwrap());
+        assert_eq!(unzipper.entries().count(), 86);
+
+        // Replaced by another file
+        let replacement = std::env::temp_dir().join("unzipper_refresh.zip.new");
+        std::fs::copy(&comment, &replacement).unwrap();
+        std::fs::rename(&replacement, &path).unwrap();
+        let expected = Unzipper::new(&comment).unwrap();
+        assert!(unzipper.refresh().unwrap());
+        assert_eq!(unzipper.entries().count(), expected.entries().count());
+        assert_eq!(unzipper.comment(), expected.comment());
+        assert!(!unzipper.refresh().unwrap());
+
+        // Rewritten in place
+        std::fs::write(&path, std::fs::read(&epub).unwrap()).unwrap();
+        assert!(unzipper.refresh().unwrap());
+        assert_eq!(
+            unzipper.get_file("mimetype").unwrap(),
+            b"application/epub+zip"
+        );
+
+        let mut unzipper = Unzipper::from_reader(File::open(&path).unwrap()).unwrap();
+        assert!(matches!(unzipper.refresh(), Err(UnzipperError::NotOpen)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_unzipper_try_clone() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let unzipper = Unzipper::new(&file).unwrap();
+        let content = unzipper.get_file("content.opf").unwrap();
+
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                let clone = unzipper.try_clone().unwrap();
+                assert!(Arc::ptr_eq(&clone.file_entries, &unzipper.file_entries));
+                let content = &content;
+                scope.spawn(move || {
+                    assert_eq!(clone.entries().count(), 86);
+                    for _ in 0..8 {
+                        assert_eq!(&clone.get_file("content.opf").unwrap(), content);
+                    }
+                });
+            }
+        });
+        assert_eq!(unzipper.get_file("content.opf").unwrap(), content);
+
+        let unzipper: Unzipper<File> = Unzipper::empty(UnzipperOptions::default());
+        assert!(matches!(unzipper.try_clone(), Err(UnzipperError::NotOpen)));
+    }
+
+    #[test]
+    #[cfg(any(unix, windows))]
+    fn test_unzipper_positional_reads() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let expected = Unzipper::from_vec(std::fs::read(&file).unwrap()).unwrap();
+        assert!(expected.positional.is_none());
+
+        // The cursor of the file is not used to read the entries
+        let mut unzipper = Unzipper::new(&file).unwrap();
+        assert!(unzipper.positional.is_some());
+        let cursor = unzipper.reader_mut().unwrap().stream_position().unwrap();
+        for name in ["mimetype", "content.opf"] {
+            assert_eq!(
+                unzipper.get_file(name).unwrap(),
+                expected.get_file(name).unwrap()
+            );
+        }
+        let reader = unzipper.reader_mut().unwrap();
+        assert_eq!(reader.stream_position().unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_unzipper_buffered_reads() {
+        /// Source counting the reads reaching it.
+        struct Counting(Cursor<Vec<u8>>, usize);
+
+        impl Read for Counting {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                self.1 += 1;
+                self.0.read(buf)
+            }
+        }
+
+        impl Seek for Counting {
+            fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+                self.0.seek(pos)
+            }
+        }
+
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let bytes = std::fs::read(&file).unwrap();
+        let expected = Unzipper::from_bytes(&bytes).unwrap();
+
+        // The local file header and the data of a small entry are read at once
+        let source = Counting(Cursor::new(bytes.clone()), 0);
+        let mut unzipper = Unzipper::from_reader(source).unwrap();
+        let count = unzipper.reader_mut().unwrap().1;
+        assert_eq!(
+            unzipper.get_file("mimetype").unwrap(),
+            b"application/epub+zip"
+        );
+        assert_eq!(unzipper.reader_mut().unwrap().1, count + 1);
+
+        // Without the buffering, each part is read on its own
+        let source = Counting(Cursor::new(bytes.clone()), 0);
+        let options = UnzipperOptions::new().buffer_size(1);
+        let mut unzipper = Unzipper::from_reader_with_options(source, options).unwrap();
+        let count = unzipper.reader_mut().unwrap().1;
+        unzipper.get_file("mimetype").unwrap();
+        assert_eq!(unzipper.reader_mut().unwrap().1, count + 2);
+
+        for buffer_size in [64, 1024 * 1024] {
+            let options = UnzipperOptions::new().buffer_size(buffer_size);
+            let unzipper =
+                Unzipper::from_reader_with_options(Cursor::new(&bytes), options).unwrap();
+            for entry in expected.entries().collect::<Vec<_>>() {
+                assert_eq!(
+                    unzipper.get_file(entry.name()).unwrap(),
+                    expected.get_file(entry.name()).unwrap()
+                );
             }
         }
     }
@@ -0,0 +1,277 @@
+//! io_uring Module.
+//!
+//! This module provides the reading of the zip file, and the writing of the files extracted, through io_uring
+//! on Linux. Each read or write is submitted at an explicit offset and waited for with a single system call,
+//! instead of the seek and read (or write) pair of a regular file, which reduces the system call overhead of
+//! servers unpacking many archives concurrently. The threads each submit through their own ring, created on
+//! their first use and shared by all the files they access.
+//!
+//! This module is available with the `io-uring` feature, on Linux only.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+use io_uring::{opcode, squeue, types, IoUring};
+
+use crate::error::UnzipperError;
+use crate::options::UnzipperOptions;
+use crate::unzipper::Unzipper;
+
+/// Number of entries of the rings, a single operation being in flight at a time.
+const RING_ENTRIES: u32 = 8;
+
+thread_local! {
+    /// Ring of the current thread, created on its first use.
+    static RING: RefCell<Option<IoUring>> = const { RefCell::new(None) };
+}
+
+/// User data of the operation submitted, telling its completion from the one of its cancellation.
+const OPERATION: u64 = 1;
+
+/// User data of the cancellation of the operation submitted.
+const CANCELLATION: u64 = 2;
+
+/// Submits an operation to the ring of the current thread and waits for its completion.
+///
+/// The completion is waited for even when the waiting fails, the operation being cancelled, as the kernel
+/// may access the buffer of an operation in flight. If the ring cannot be waited for any more, the process
+/// is aborted, as the buffer cannot be released safely.
+///
+/// # Safety
+/// The buffer of the operation must stay valid until this function returns.
+///
+/// # Returns
+/// A Result containing the result of the operation, such as the number of bytes read or written.
+unsafe fn submit(entry: squeue::Entry) -> std::io::Result<usize> {
+    RING.with(|slot| {
+        let mut slot = slot.borrow_mut();
+        let ring = match slot.as_mut() {
+            Some(ring) => ring,
+            None => slot.insert(IoUring::new(RING_ENTRIES)?),
+        };
+
+        if ring.submission().push(&entry.user_data(OPERATION)).is_err() {
+            return Err(Error::other("io_uring submission queue is full"));
+        }
+        let mut failure = None;
+        loop {
+            match ring.submit_and_wait(1) {
+                Ok(_) => {}
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) if failure.is_none() => {
+                    let mut submission = ring.submission();
+                    submission.sync();
+                    if !submission.is_empty() {
+                        // The kernel did not take the operation, which is dropped with the ring
+                        drop(submission);
+                        *slot = None;
+                        return Err(error);
+                    }
+                    // The operation is in flight: it is cancelled, and its completion still waited for
+                    let cancellation = opcode::AsyncCancel::new(OPERATION)
+                        .build()
+                        .user_data(CANCELLATION);
+                    let _ = submission.push(&cancellation);
+                    failure = Some(error);
+                }
+                Err(error)
+                    if matches!(
+                        error.kind(),
+                        ErrorKind::WouldBlock | ErrorKind::ResourceBusy
+                    ) => {}
+                Err(_) => std::process::abort(),
+            }
+
+            // The completions of the cancellations, including the ones of previous operations, are skipped
+            let result = ring
+                .completion()
+                .filter(|completion| completion.user_data() == OPERATION)
+                .last()
+                .map(|completion| completion.result());
+            match result {
+                None => {}
+                Some(result) if result < 0 => {
+                    return Err(failure.unwrap_or_else(|| Error::from_raw_os_error(-result)));
+                }
+                Some(result) => return Ok(result as usize),
+            }
+        }
+    })
+}
+
+/// File read and written through io_uring, at the position of its own cursor.
+///
+/// It implements `Read`, `Seek` and `Write`, so that it is given to `Unzipper::from_reader()` or used
+/// as the output of an extraction.
+#[derive(Debug)]
+pub struct UringFile {
+    file: File,
+    position: u64, // current position in the file
+}
+
+impl UringFile {
+    /// Creates a new UringFile accessing the given file, from its start.
+    pub fn new(file: File) -> UringFile {
+        UringFile { file, position: 0 }
+    }
+
+    /// Opens the file at the specified path for reading.
+    pub fn open(path: &Path) -> Result<UringFile, UnzipperError> {
+        Ok(UringFile::new(File::open(path)?))
+    }
+
+    /// Returns the file accessed.
+    pub fn get_ref(&self) -> &File {
+        &self.file
+    }
+
+    /// Returns the file accessed, consuming the UringFile.
+    pub fn into_inner(self) -> File {
+        self.file
+    }
+}
+
+impl Read for UringFile {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let length = buf.len().min(u32::MAX as usize) as u32;
+        let entry = opcode::Read::new(types::Fd(self.file.as_raw_fd()), buf.as_mut_ptr(), length)
+            .offset(self.position)
+            .build();
+        // SAFETY: the buffer outlives the operation, waited for by submit()
+        let length = unsafe { submit(entry)? };
+        self.position += length as u64;
+        Ok(length)
+    }
+}
+
+impl Write for UringFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let length = buf.len().min(u32::MAX as usize) as u32;
+        let entry = opcode::Write::new(types::Fd(self.file.as_raw_fd()), buf.as_ptr(), length)
+            .offset(self.position)
+            .build();
+        // SAFETY: the buffer outlives the operation, waited for by submit()
+        let length = unsafe { submit(entry)? };
+        self.position += length as u64;
+        Ok(length)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for UringFile {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(position) => Some(position),
+            SeekFrom::End(delta) => self.file.metadata()?.len().checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        };
+        self.position =
+            position.ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Invalid seek position"))?;
+        Ok(self.position)
+    }
+}
+
+/// Unzipper reading the zip file through io_uring.
+pub type UringUnzipper = Unzipper<UringFile>;
+
+impl Unzipper<UringFile> {
+    /// Creates a new Unzipper instance reading the zip file at the specified path through io_uring.
+    ///
+    /// The files extracted are written through io_uring as well with `UnzipperOptions::uring_output()`.
+    ///
+    /// # Arguments
+    /// * `filepath` - A reference to the path of the zip file to be opened.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper instance if successful, or an error if the file could not be opened.
+    pub fn open_uring(filepath: &Path) -> Result<UringUnzipper, UnzipperError> {
+        Unzipper::open_uring_with_options(filepath, UnzipperOptions::default())
+    }
+
+    /// Creates a new Unzipper instance reading the zip file at the specified path through io_uring,
+    /// using the given options. See `Unzipper::open_uring()`.
+    ///
+    /// # Arguments
+    /// * `filepath` - A reference to the path of the zip file to be opened.
+    /// * `options` - The options controlling how the zip file is read.
+    ///
+    /// # Returns
+    /// A Result containing the Unzipper instance if successful, or an error if the file could not be opened.
+    pub fn open_uring_with_options(
+        filepath: &Path,
+        options: UnzipperOptions,
+    ) -> Result<UringUnzipper, UnzipperError> {
+        let reader = UringFile::open(&filepath.canonicalize()?)?;
+        let mut unzipper = Unzipper::from_reader_with_options(reader, options)?;
+        unzipper.filepath = filepath.to_path_buf();
+        Ok(unzipper)
+    }
+}
+
+#[cfg(test)]
+mod uring_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use test_support::unit_test::UnitTest;
+
+    /// Returns true if io_uring is available, as it may be disabled in containers.
+    fn uring_available() -> bool {
+        IoUring::new(RING_ENTRIES).is_ok()
+    }
+
+    #[test]
+    fn test_uring_file() {
+        if !uring_available() {
+            return;
+        }
+        let path = std::env::temp_dir().join("unzipper_uring_file.bin");
+        let mut file = UringFile::new(File::create(&path).unwrap());
+        file.write_all(b"0123456789").unwrap();
+        file.seek(SeekFrom::Start(2)).unwrap();
+        file.write_all(b"ab").unwrap();
+        drop(file);
+
+        let mut file = UringFile::open(&path).unwrap();
+        let mut content = String::new();
+        file.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "01ab456789");
+        assert_eq!(file.seek(SeekFrom::End(-3)).unwrap(), 7);
+        let mut bytes = [0u8; 5];
+        assert_eq!(file.read(&mut bytes).unwrap(), 3);
+        assert_eq!(&bytes[..3], b"789");
+        assert!(file.seek(SeekFrom::Current(-20)).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_uring_extraction() {
+        if !uring_available() {
+            return;
+        }
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let dest = std::env::temp_dir().join("unzipper_uring_extraction");
+        let _ = std::fs::remove_dir_all(&dest);
+
+        let expected = Unzipper::new(&file).unwrap();
+        let options = UnzipperOptions::new().uring_output(true);
+        let unzipper = Unzipper::open_uring_with_options(&file, options).unwrap();
+        assert_eq!(
+            unzipper.get_file("content.opf").unwrap(),
+            expected.get_file("content.opf").unwrap()
+        );
+        unzipper.extract_all(&dest).unwrap();
+        assert!(expected.compare_dir(&dest).unwrap().is_up_to_date());
+
+        std::fs::remove_dir_all(&dest).unwrap();
+    }
+}
@@ -0,0 +1,403 @@
+//! Verify Module.
+//!
+//! This module provides the integrity check of a whole zip archive. Instead of failing on the first problem,
+//! every entry is checked and each problem found is recorded in an `IntegrityReport`, with the entry
+//! concerned and its severity. The structures crafted to confuse extractors, such as entries whose data
+//! overlap, are reported as security findings.
+
+use core::fmt;
+use std::io::{Read, Seek};
+use std::ops::Range;
+
+use crate::entry::{EntryInfo, EntryKind};
+use crate::error::UnzipperError;
+use crate::unzipper::{FileHeader, Unzipper};
+
+/// Enum that describes the severity of a problem found in a zip archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    /// An inconsistency that does not prevent the content from being read, or an entry that could not be checked.
+    Warning,
+    /// A problem that prevents the content of an entry from being read correctly.
+    Error,
+    /// A structure crafted to confuse extractors or to build decompression bombs, such as overlapping entries.
+    Security,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+            Severity::Security => write!(f, "security"),
+        }
+    }
+}
+
+/// Struct that describes a problem found in a zip archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Issue {
+    file_path: Option<String>,
+    severity: Severity,
+    message: String,
+}
+
+impl Issue {
+    /// Creates a new Issue.
+    ///
+    /// # Arguments
+    /// * `file_path` - The path of the entry concerned, or None if the problem concerns the whole archive.
+    /// * `severity` - The severity of the problem.
+    /// * `message` - The description of the problem.
+    fn new(file_path: Option<&str>, severity: Severity, message: &str) -> Issue {
+        Issue {
+            file_path: file_path.map(str::to_string),
+            severity,
+            message: message.to_string(),
+        }
+    }
+
+    /// Returns the path of the entry concerned, or None if the problem concerns the whole archive.
+    pub fn file_path(&self) -> Option<&str> {
+        self.file_path.as_deref()
+    }
+
+    /// Returns the severity of the problem.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns the description of the problem.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file_path {
+            Some(file_path) => write!(f, "{}: {}: {}", self.severity, file_path, self.message),
+            None => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
+
+/// Struct that describes the result of the integrity check of a zip archive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntegrityReport {
+    entries_checked: usize,
+    issues: Vec<Issue>,
+}
+
+impl IntegrityReport {
+    /// Returns the number of entries checked.
+    pub fn entries_checked(&self) -> usize {
+        self.entries_checked
+    }
+
+    /// Returns the problems found, the ones concerning the whole archive first, then the ones of the entries
+    /// in the order of the central directory, and last the security findings.
+    pub fn issues(&self) -> &[Issue] {
+        &self.issues
+    }
+
+    /// Returns an iterator over the problems with the `Error` or `Security` severity.
+    pub fn errors(&self) -> impl Iterator<Item = &Issue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity >= Severity::Error)
+    }
+
+    /// Returns an iterator over the problems with the `Security` severity.
+    pub fn security_findings(&self) -> impl Iterator<Item = &Issue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Security)
+    }
+
+    /// Returns true if no problem with the `Error` or `Security` severity was found.
+    pub fn is_ok(&self) -> bool {
+        self.errors().next().is_none()
+    }
+}
+
+impl<R: Read + Seek> Unzipper<R> {
+    /// Verifies the integrity of the zip archive.
+    ///
+    /// The inconsistencies of the end of central directory record and of the central directory, tolerated
+    /// when the zip archive was opened, are reported as warnings. Then, for each entry, the offset and
+    /// the signature of its local file header are checked, as well as the position of its data, and the local
+    /// file header is compared with the central directory record, a difference being a security finding. The content
+    /// of the files is decompressed to verify their CRC-32 and size. Encrypted entries and entries needing
+    /// unsupported features cannot be decompressed, and are reported as warnings. Last, the entries whose local
+    /// file header or data overlap the ones of another entry or the central directory, a trick used by zip bombs
+    /// and to make extractors disagree on the content, are reported as security findings.
+    ///
+    /// # Returns
+    /// A Result containing the IntegrityReport listing the problems found, or an error if the zip archive
+    /// is not open, the zip file cannot be read or the operation is cancelled by the progress observer.
+    /// The warnings recorded while reading the entries are part of the report, and are not kept by the Unzipper.
+    pub fn verify(&mut self) -> Result<IntegrityReport, UnzipperError> {
+        let length = self.zip_length()?;
+
+        let mut report = IntegrityReport::default();
+        for warning in self.warnings_mut().iter() {
+            report.issues.push(Issue::new(
+                warning.file_path(),
+                Severity::Warning,
+                warning.message(),
+            ));
+        }
+
+        let infos: Vec<EntryInfo> = self.entries().collect();
+        let mut ranges = Vec::with_capacity(infos.len());
+        for info in infos {
+            if let Some(range) = self.verify_entry(&info, length, &mut report.issues)? {
+                ranges.push((info.name().to_string(), range));
+            }
+            report.entries_checked += 1;
+        }
+        self.verify_overlaps(ranges, &mut report.issues);
+        Ok(report)
+    }
+
+    /// Verifies that the entries do not overlap each other or the central directory.
+    ///
+    /// # Arguments
+    /// * `ranges` - The paths of the entries and their range in the zip file, from the local file header
+    ///   to the end of the data.
+    /// * `issues` - Receives the problems found.
+    fn verify_overlaps(
+        &mut self,
+        mut ranges: Vec<(String, Range<usize>)>,
+        issues: &mut Vec<Issue>,
+    ) {
+        // The central directory is located again, without recording its warnings twice
+        let dir_range = self
+            .find_dir_end(&mut Vec::new())
+            .ok()
+            .map(|dir_end| dir_end.dir_offset..dir_end.dir_offset + dir_end.dir_size);
+
+        ranges.sort_by_key(|(_, range)| range.start);
+        let mut furthest: Option<(&str, usize)> = None; // entry reaching the furthest so far
+        for (file_path, range) in &ranges {
+            if let Some((other, end)) = furthest {
+                if range.start < end {
+                    issues.push(Issue::new(
+                        Some(file_path),
+                        Severity::Security,
+                        &format!("Entry overlaps the entry {other}"),
+                    ));
+                }
+            }
+            if let Some(dir_range) = &dir_range {
+                if range.start < dir_range.end && dir_range.start < range.end {
+                    issues.push(Issue::new(
+                        Some(file_path),
+                        Severity::Security,
+                        "Entry overlaps the central directory",
+                    ));
+                }
+            }
+            if furthest.is_none_or(|(_, end)| range.end > end) {
+                furthest = Some((file_path, range.end));
+            }
+        }
+    }
+
+    /// Verifies the integrity of an entry.
+    ///
+    /// # Arguments
+    /// * `info` - The description of the entry.
+    /// * `length` - The length of the zip file.
+    /// * `issues` - Receives the problems found.
+    ///
+    /// # Returns
+    /// A Result containing the range of the entry in the zip file, from its local file header to the end
+    /// of its data, if it could be located, or an error if the check cannot go on with the other entries.
+    fn verify_entry(
+        &mut self,
+        info: &EntryInfo,
+        length: usize,
+        issues: &mut Vec<Issue>,
+    ) -> Result<Option<Range<usize>>, UnzipperError> {
+        let file_path = info.name();
+        let mut report = |severity: Severity, message: &str| {
+            issues.push(Issue::new(Some(file_path), severity, message));
+        };
+
+        let start_pos = self.parsed_entries()[file_path].start_pos as usize;
+        if start_pos + FileHeader::SIZE > length {
+            report(
+                Severity::Error,
+                "Local file header beyond the end of the zip file",
+            );
+            return Ok(None);
+        }
+
+        let range = match self.locate_file_data(file_path) {
+            Ok((data_offset, file_entry)) => {
+                let data_end = data_offset + file_entry.compressed_size as usize;
+                if data_end > length {
+                    report(
+                        Severity::Error,
+                        "Entry data extends beyond the end of the zip file",
+                    );
+                    return Ok(None);
+                }
+
+                // Records that differ make the extractors reading one or the other disagree
+                let mut buffer = [0; FileHeader::SIZE];
+                if self.get_data(&mut buffer, start_pos, "file header").is_ok() {
+                    if let Ok(file_header) = FileHeader::parse(&buffer) {
+                        for message in file_entry.header_mismatches(&file_header) {
+                            report(Severity::Security, &message);
+                        }
+                    }
+                }
+                Some(start_pos..data_end)
+            }
+            Err(UnzipperError::Unsupported { features, .. }) => {
+                let features: Vec<String> = features.iter().map(ToString::to_string).collect();
+                report(
+                    Severity::Warning,
+                    &format!("Unsupported {}, content not verified", features.join(", ")),
+                );
+                return Ok(None);
+            }
+            Err(error) => {
+                report(Severity::Error, &error.to_string());
+                return Ok(None);
+            }
+        };
+
+        if info.kind() == EntryKind::Directory {
+            return Ok(range);
+        }
+        if info.is_encrypted() {
+            report(Severity::Warning, "Encrypted entry, content not verified");
+            return Ok(range);
+        }
+
+        let warnings = self.warnings_mut().len();
+        let result = self.get_file_to(file_path, &mut std::io::sink());
+        for warning in self.warnings_mut().drain(warnings..) {
+            report(Severity::Warning, warning.message());
+        }
+        match result {
+            Ok(_) => Ok(range),
+            Err(UnzipperError::Cancelled) => Err(UnzipperError::Cancelled),
+            Err(error) => {
+                report(Severity::Error, &error.to_string());
+                Ok(range)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod verify_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use crate::options::{ParseMode, UnzipperOptions};
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_verify_valid_archive() {
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let mut unzipper = Unzipper::new(&file).unwrap();
+
+        let report = unzipper.verify().unwrap();
+        assert!(report.is_ok());
+        assert!(report.issues().is_empty());
+        assert_eq!(report.entries_checked(), unzipper.entries().count());
+    }
+
+    #[test]
+    fn test_verify_broken_archive() {
+        let unit_test = UnitTest::new("unzipper_verify");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("broken.zip");
+        let options = UnzipperOptions::default().parse_mode(ParseMode::Lenient);
+        let mut unzipper = Unzipper::with_options(&file, options).unwrap();
+
+        // The entry beyond the central directory is reported when opening, then when checking its header
+        let report = unzipper.verify().unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(report.entries_checked(), 5);
+        let issues: Vec<(Option<&str>, Severity)> = report
+            .issues()
+            .iter()
+            .map(|issue| (issue.file_path(), issue.severity()))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (Some("beyond.txt"), Severity::Warning),
+                (Some("bad_crc.txt"), Severity::Security),
+                (Some("bad_crc.txt"), Severity::Error),
+                (Some("bad_size.txt"), Severity::Security),
+                (Some("bad_size.txt"), Severity::Warning),
+                (Some("bad_offset.txt"), Severity::Error),
+                (Some("beyond.txt"), Severity::Error),
+            ]
+        );
+        assert!(report.issues()[1]
+            .message()
+            .starts_with("Local file header gives the CRC-32"));
+        assert!(report.issues()[2].message().contains("CRC-32 mismatch"));
+        assert!(report.issues()[3]
+            .message()
+            .starts_with("Local file header gives the size"));
+        assert_eq!(
+            report.issues()[5].to_string(),
+            "error: bad_offset.txt: Invalid local file header signature"
+        );
+        assert_eq!(report.errors().count(), 5);
+        assert_eq!(report.security_findings().count(), 2);
+        assert_eq!(unzipper.warnings().len(), 1);
+
+        // Without the lenient mode, the zip archive is rejected when opening
+        assert!(matches!(
+            Unzipper::new(&file),
+            Err(UnzipperError::InvalidArchive(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_overlapping_entries() {
+        let unit_test = UnitTest::new("unzipper_verify");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("overlap.zip");
+        let options = UnzipperOptions::default().parse_mode(ParseMode::Lenient);
+        let mut unzipper = Unzipper::with_options(&file, options).unwrap();
+
+        let report = unzipper.verify().unwrap();
+        assert!(!report.is_ok());
+        let findings: Vec<String> = report
+            .security_findings()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(
+            findings,
+            [
+                "security: second.txt: Entry overlaps the entry first.txt",
+                "security: third.txt: Entry overlaps the central directory"
+            ]
+        );
+        assert_eq!(
+            report.issues().last().unwrap().severity(),
+            Severity::Security
+        );
+
+        // A valid archive has no finding
+        let unit_test = UnitTest::new("unzipper_open_epub_file");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("ebook.epub");
+        let mut unzipper = Unzipper::new(&file).unwrap();
+        assert_eq!(unzipper.verify().unwrap().security_findings().count(), 0);
+    }
+}
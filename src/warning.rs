@@ -0,0 +1,185 @@
+//! Warning Module.
+//!
+//! This module provides the description of the inconsistencies found in a zip archive.
+//! Depending on the parsing mode of the options, an inconsistency is either tolerated and recorded as a warning,
+//! or turned into an `UnzipperError::InvalidArchive` error.
+
+use core::fmt;
+use std::sync::Arc;
+
+use crate::error::UnzipperError;
+use crate::options::ParseMode;
+
+/// Struct that describes an inconsistency of a zip archive, tolerated by the Unzipper.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Warning {
+    file_path: Option<String>,
+    message: String,
+}
+
+impl Warning {
+    /// Creates a new Warning.
+    ///
+    /// # Arguments
+    /// * `file_path` - The path of the entry concerned, or None if the inconsistency concerns the whole archive.
+    /// * `message` - The description of the inconsistency.
+    pub(crate) fn new(file_path: Option<&str>, message: &str) -> Warning {
+        Warning {
+            file_path: file_path.map(str::to_string),
+            message: message.to_string(),
+        }
+    }
+
+    /// Returns the path of the entry concerned, or None if the inconsistency concerns the whole archive.
+    pub fn file_path(&self) -> Option<&str> {
+        self.file_path.as_deref()
+    }
+
+    /// Returns the description of the inconsistency.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.file_path {
+            Some(ref file_path) => write!(f, "{}: {file_path}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Struct that holds the function notified of each warning recorded, installed with `UnzipperOptions::on_warning()`.
+#[derive(Clone)]
+pub(crate) struct WarningHandler(Arc<dyn Fn(&Warning) + Send + Sync>);
+
+impl fmt::Debug for WarningHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("WarningHandler")
+    }
+}
+
+impl WarningHandler {
+    /// Creates a new WarningHandler calling the given function.
+    pub(crate) fn new(handler: impl Fn(&Warning) + Send + Sync + 'static) -> WarningHandler {
+        WarningHandler(Arc::new(handler))
+    }
+
+    /// Notifies the function of a warning.
+    pub(crate) fn notify(&self, warning: &Warning) {
+        (self.0)(warning)
+    }
+}
+
+/// Records an inconsistency of the zip archive as a warning, or turns it into an error, according to the parsing mode.
+///
+/// # Arguments
+/// * `mode` - The parsing mode of the options.
+/// * `warnings` - The warnings of the Unzipper, receiving the inconsistency when it is tolerated.
+/// * `warning` - The inconsistency found.
+/// * `tolerated_by` - The strictest parsing mode tolerating the inconsistency.
+///
+/// # Returns
+/// A Result indicating that the inconsistency is tolerated, or an InvalidArchive error otherwise.
+pub(crate) fn tolerate(
+    mode: ParseMode,
+    warnings: &mut Vec<Warning>,
+    warning: Warning,
+    tolerated_by: ParseMode,
+) -> Result<(), UnzipperError> {
+    if mode < tolerated_by {
+        return Err(UnzipperError::InvalidArchive(warning.to_string()));
+    }
+    warnings.push(warning);
+    Ok(())
+}
+
+#[cfg(test)]
+mod warning_tests {
+    use super::*;
+
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    use crate::options::UnzipperOptions;
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_warning_parse_modes() {
+        let unit_test = UnitTest::new("unzipper_quirks");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("quirks.zip");
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        let messages: Vec<String> = unzipper.warnings().iter().map(Warning::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "Archive comment extends beyond the end of the file",
+                "Malformed extra field: bad_extra.txt"
+            ]
+        );
+        assert_eq!(unzipper.warnings()[1].file_path(), Some("bad_extra.txt"));
+
+        // The entry is read in full, although its recorded size is wrong
+        assert_eq!(
+            unzipper.get_file("wrong_size.txt").unwrap(),
+            b"Twenty bytes of text".repeat(3)
+        );
+        assert_eq!(
+            unzipper.warnings().last().unwrap().message(),
+            "Decompressed size does not match the central directory"
+        );
+
+        let options = UnzipperOptions::new().parse_mode(ParseMode::Strict);
+        assert!(matches!(
+            Unzipper::with_options(&file, options),
+            Err(UnzipperError::InvalidArchive(_))
+        ));
+
+        // A truncated central directory is only tolerated by the lenient mode
+        let file = PathBuf::from(unit_test.test_case_folder()).join("truncated.zip");
+        assert!(Unzipper::new(&file).is_err());
+        let options = UnzipperOptions::new().parse_mode(ParseMode::Lenient);
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        assert_eq!(unzipper.entries().count(), 2);
+        assert_eq!(
+            unzipper.warnings()[0].message(),
+            "Central directory is truncated: 2 of 3 entries found"
+        );
+        assert_eq!(unzipper.get_file("two.txt").unwrap(), b"two\n");
+    }
+
+    #[test]
+    fn test_warning_handler() {
+        let unit_test = UnitTest::new("unzipper_quirks");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("quirks.zip");
+
+        let notified = Arc::new(Mutex::new(Vec::new()));
+        let handled = notified.clone();
+        let options = UnzipperOptions::new()
+            .on_warning(move |warning| handled.lock().unwrap().push(warning.to_string()));
+        let unzipper = Unzipper::with_options(&file, options).unwrap();
+        assert_eq!(notified.lock().unwrap().len(), 2);
+
+        // The warnings found while reading the entries are notified as they are recorded
+        unzipper.get_file("wrong_size.txt").unwrap();
+        let recorded: Vec<String> = unzipper.warnings().iter().map(Warning::to_string).collect();
+        assert_eq!(*notified.lock().unwrap(), recorded);
+        assert_eq!(
+            recorded.last().unwrap(),
+            "Decompressed size does not match the central directory: wrong_size.txt"
+        );
+
+        // The inconsistencies turned into errors are not notified
+        notified.lock().unwrap().clear();
+        let handled = notified.clone();
+        let options = UnzipperOptions::new()
+            .parse_mode(ParseMode::Strict)
+            .on_warning(move |warning| handled.lock().unwrap().push(warning.to_string()));
+        assert!(Unzipper::with_options(&file, options).is_err());
+        assert!(notified.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+//! WinZip AES Module.
+//!
+//! This module provides the decryption of entries encrypted with the WinZip AES scheme (AE-1 and AE-2).
+//!
+//! The encrypted data of an entry is preceded by a salt and a 2-byte password verifier, and followed by a
+//! 10-byte authentication code. The AES key, the authentication key and the password verifier are derived from
+//! the password and the salt with PBKDF2-HMAC-SHA1. The data is decrypted with AES in CTR mode, and authenticated
+//! with HMAC-SHA1 computed over the encrypted data.
+//!
+//! This module is available with the `crypto` feature.
+
+use aes::{Aes128, Aes192, Aes256};
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use ctr::Ctr128LE;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use crate::extra::AesExtra;
+use crate::secret::wipe;
+
+/// Size of the password verifier following the salt.
+pub(crate) const PASSWORD_VERIFIER_SIZE: usize = 2;
+
+/// Size of the authentication code following the encrypted data.
+pub(crate) const AUTHENTICATION_CODE_SIZE: usize = 10;
+
+const KEY_DERIVATION_ITERATIONS: u32 = 1000;
+
+/// AES cipher in CTR mode, with a little-endian counter starting at 1.
+enum AesCtr {
+    Aes128(Ctr128LE<Aes128>),
+    Aes192(Ctr128LE<Aes192>),
+    Aes256(Ctr128LE<Aes256>),
+}
+
+/// Struct that holds the state of the WinZip AES decryption of an entry.
+pub(crate) struct WinZipAes {
+    cipher: AesCtr,
+    hmac: Hmac<Sha1>,
+}
+
+impl WinZipAes {
+    /// Creates a new WinZipAes decryptor, deriving the keys from the password and the salt.
+    ///
+    /// # Arguments
+    /// * `password` - The password used to decrypt the entry.
+    /// * `aes_extra` - The encryption parameters of the entry.
+    /// * `salt` - The salt preceding the encrypted data.
+    /// * `verifier` - The password verifier following the salt.
+    ///
+    /// # Returns
+    /// The decryptor, or None if the password does not match the password verifier.
+    pub(crate) fn new(
+        password: &[u8],
+        aes_extra: &AesExtra,
+        salt: &[u8],
+        verifier: &[u8; PASSWORD_VERIFIER_SIZE],
+    ) -> Option<WinZipAes> {
+        let key_length = aes_extra.key_length();
+        let mut derived = [0u8; 2 * 32 + PASSWORD_VERIFIER_SIZE];
+        let derived = &mut derived[..2 * key_length + PASSWORD_VERIFIER_SIZE];
+        pbkdf2::pbkdf2_hmac::<Sha1>(password, salt, KEY_DERIVATION_ITERATIONS, derived);
+        let winzip_aes = Self::with_keys(derived, aes_extra, verifier);
+        wipe(derived);
+        winzip_aes
+    }
+
+    /// Creates a new WinZipAes decryptor from the keys derived from the password.
+    ///
+    /// # Arguments
+    /// * `derived` - The AES key, followed by the authentication key and the password verifier.
+    /// * `aes_extra` - The encryption parameters of the entry.
+    /// * `verifier` - The password verifier following the salt.
+    ///
+    /// # Returns
+    /// The decryptor, or None if the password does not match the password verifier.
+    fn with_keys(
+        derived: &[u8],
+        aes_extra: &AesExtra,
+        verifier: &[u8; PASSWORD_VERIFIER_SIZE],
+    ) -> Option<WinZipAes> {
+        let key_length = aes_extra.key_length();
+        let (key, rest) = derived.split_at(key_length);
+        let (hmac_key, derived_verifier) = rest.split_at(key_length);
+        if derived_verifier != verifier {
+            return None;
+        }
+
+        let mut iv = [0u8; 16];
+        iv[0] = 1;
+        let cipher = match aes_extra.strength {
+            1 => AesCtr::Aes128(Ctr128LE::new_from_slices(key, &iv).ok()?),
+            2 => AesCtr::Aes192(Ctr128LE::new_from_slices(key, &iv).ok()?),
+            _ => AesCtr::Aes256(Ctr128LE::new_from_slices(key, &iv).ok()?),
+        };
+
+        Some(WinZipAes {
+            cipher,
+            hmac: Hmac::new_from_slice(hmac_key).ok()?,
+        })
+    }
+
+    /// Decrypts the given bytes in place, updating the authentication code.
+    pub(crate) fn decrypt(&mut self, bytes: &mut [u8]) {
+        self.hmac.update(bytes);
+        match self.cipher {
+            AesCtr::Aes128(ref mut cipher) => cipher.apply_keystream(bytes),
+            AesCtr::Aes192(ref mut cipher) => cipher.apply_keystream(bytes),
+            AesCtr::Aes256(ref mut cipher) => cipher.apply_keystream(bytes),
+        }
+    }
+
+    /// Checks the authentication code of the entry, once all its encrypted data has been decrypted.
+    ///
+    /// # Arguments
+    /// * `code` - The 10-byte authentication code, as read from the zip file.
+    ///
+    /// # Returns
+    /// true if the encrypted data is authentic, false otherwise.
+    pub(crate) fn verify(self, code: &[u8; AUTHENTICATION_CODE_SIZE]) -> bool {
+        self.hmac.verify_truncated_left(code).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod winzip_aes_tests {
+    use std::io::Cursor;
+    use std::path::PathBuf;
+
+    use crate::error::UnzipperError;
+    use crate::options::UnzipperOptions;
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_winzip_aes_get_file_with_password() {
+        let unit_test = UnitTest::new("unzipper_winzip_aes");
+        let file = PathBuf::from(unit_test.test_case_folder()).join("winzip_aes.zip");
+
+        let unzipper = Unzipper::new(&file).unwrap();
+        assert!(unzipper.entries().all(|entry| entry.is_encrypted()));
+        assert!(matches!(
+            unzipper.get_file("aes256.txt"),
+            Err(UnzipperError::PasswordRequired(_))
+        ));
+        assert!(matches!(
+            unzipper.get_file_with_password("aes256.txt", b"wrong"),
+            Err(UnzipperError::InvalidPassword(_))
+        ));
+
+        // AE-2, AES-256, deflated
+        assert_eq!(
+            unzipper
+                .get_file_with_password("aes256.txt", b"secret")
+                .unwrap(),
+            "Deflated line of text encrypted with AES-256.\n"
+                .repeat(500)
+                .as_bytes()
+        );
+        // AE-1, AES-128, stored: the CRC-32 is verified as well
+        assert_eq!(
+            unzipper
+                .get_file_with_password("aes128.txt", b"secret")
+                .unwrap(),
+            b"Stored text encrypted with AES-128.\n"
+        );
+        // AE-2, AES-192, deflated
+        assert_eq!(
+            unzipper
+                .get_file_with_password("aes192.txt", b"secret")
+                .unwrap(),
+            "Deflated text encrypted with AES-192.\n"
+                .repeat(3)
+                .as_bytes()
+        );
+
+        // Tampered data is detected through the authentication code
+        let mut bytes = std::fs::read(&file).unwrap();
+        let entry = unzipper
+            .entries()
+            .find(|entry| entry.name() == "aes128.txt")
+            .unwrap();
+        let data_offset = entry.offset() as usize + 30 + "aes128.txt".len() + 11 + 8 + 2;
+        bytes[data_offset] ^= 0x01;
+        let options = UnzipperOptions::new().verify_crc(false);
+        let unzipper = Unzipper::from_reader_with_options(Cursor::new(bytes), options).unwrap();
+        assert!(matches!(
+            unzipper.get_file_with_password("aes128.txt", b"secret"),
+            Err(UnzipperError::AuthenticationFailed(_))
+        ));
+    }
+}
@@ -0,0 +1,129 @@
+//! ZipCrypto Module.
+//!
+//! This module provides the decryption of entries encrypted with the traditional PKWARE stream cipher
+//! (also known as ZipCrypto), as described in the section 6.1 of the PKWARE APPNOTE.
+//!
+//! Each encrypted entry starts with a 12-byte encryption header. Once decrypted, its last byte must match
+//! the high byte of the entry CRC-32 (or of the last modification time when the sizes and CRC-32 are stored
+//! in a data descriptor), which allows to detect a wrong password before decompressing anything.
+
+use crate::crc32::update_byte;
+use crate::secret::wipe;
+
+/// Size of the encryption header preceding the encrypted data of an entry.
+pub(crate) const ENCRYPTION_HEADER_SIZE: usize = 12;
+
+/// Struct that holds the state of the ZipCrypto stream cipher.
+#[derive(Debug, Clone)]
+pub(crate) struct ZipCrypto {
+    keys: [u32; 3],
+}
+
+impl ZipCrypto {
+    /// Creates a new ZipCrypto cipher initialized with the given password.
+    pub(crate) fn new(password: &[u8]) -> ZipCrypto {
+        let mut cipher = ZipCrypto {
+            keys: [0x12345678, 0x23456789, 0x34567890],
+        };
+        for &byte in password {
+            cipher.update_keys(byte);
+        }
+        cipher
+    }
+
+    /// Updates the cipher keys with a plain text byte.
+    fn update_keys(&mut self, byte: u8) {
+        self.keys[0] = update_byte(self.keys[0], byte);
+        self.keys[1] = self.keys[1]
+            .wrapping_add(self.keys[0] & 0xff)
+            .wrapping_mul(134775813)
+            .wrapping_add(1);
+        self.keys[2] = update_byte(self.keys[2], (self.keys[1] >> 24) as u8);
+    }
+
+    /// Returns the next byte of the key stream.
+    fn stream_byte(&self) -> u8 {
+        let temp = (self.keys[2] | 2) as u16;
+        (temp.wrapping_mul(temp ^ 1) >> 8) as u8
+    }
+
+    /// Decrypts the given bytes in place.
+    pub(crate) fn decrypt(&mut self, bytes: &mut [u8]) {
+        for byte in bytes.iter_mut() {
+            *byte ^= self.stream_byte();
+            self.update_keys(*byte);
+        }
+    }
+
+    /// Decrypts the encryption header of an entry and checks it against the expected check byte.
+    ///
+    /// # Arguments
+    /// * `header` - The 12-byte encryption header, as read from the zip file.
+    /// * `check_byte` - The expected value of the last decrypted byte of the header.
+    ///
+    /// # Returns
+    /// true if the password is (most likely) correct, false otherwise.
+    pub(crate) fn check_header(
+        &mut self,
+        header: &[u8; ENCRYPTION_HEADER_SIZE],
+        check_byte: u8,
+    ) -> bool {
+        let mut header = *header;
+        self.decrypt(&mut header);
+        let checked = header[ENCRYPTION_HEADER_SIZE - 1] == check_byte;
+        wipe(&mut header);
+        checked
+    }
+}
+
+impl Drop for ZipCrypto {
+    fn drop(&mut self) {
+        wipe(&mut self.keys);
+    }
+}
+
+#[cfg(test)]
+mod zipcrypto_tests {
+    use std::path::PathBuf;
+
+    use crate::error::UnzipperError;
+    use crate::unzipper::Unzipper;
+    use test_support::unit_test::UnitTest;
+
+    #[test]
+    fn test_zipcrypto_get_file_with_password() {
+        let unit_test = UnitTest::new("unzipper_zipcrypto");
+        let folder = PathBuf::from(unit_test.test_case_folder());
+        let secret = b"Secret message stored in a ZipCrypto encrypted entry.\n";
+
+        let unzipper = Unzipper::new(&folder.join("zipcrypto.zip")).unwrap();
+        assert!(unzipper.entries().all(|entry| entry.is_encrypted()));
+        assert!(matches!(
+            unzipper.get_file("secret.txt"),
+            Err(UnzipperError::PasswordRequired(_))
+        ));
+        assert!(matches!(
+            unzipper.get_file_with_password("secret.txt", b"wrong"),
+            Err(UnzipperError::InvalidPassword(_))
+        ));
+        assert_eq!(
+            unzipper
+                .get_file_with_password("secret.txt", b"secret")
+                .unwrap(),
+            secret
+        );
+        assert_eq!(
+            unzipper
+                .get_file_with_password("long.txt", b"secret")
+                .unwrap(),
+            "Compressible line of text.\n".repeat(200).as_bytes()
+        );
+
+        // Entry written with a data descriptor: the check byte comes from the modification time
+        let unzipper = Unzipper::new(&folder.join("zipcrypto_streamed.zip")).unwrap();
+        assert_eq!(
+            unzipper.get_file_with_password("-", b"secret").unwrap(),
+            secret
+        );
+    }
+}